@@ -0,0 +1,112 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmarks of type library compilation and type system search.
+
+#![recursion_limit = "256"]
+
+#[macro_use]
+extern crate strict_encoding;
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use strict_types::stl::{std_stl, strict_types_stl};
+use strict_types::typesys::SystemBuilder;
+use strict_types::{LibBuilder, SymbolicSys, TypeLib};
+
+const LIB: &str = "BenchLib";
+
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+#[derive(StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB)]
+pub struct T0 {
+    value: u64,
+}
+
+/// Defines a chain of structures, each of which contains the previous one.
+macro_rules! chain {
+    ($prev:ident;) => {};
+    ($prev:ident; $next:ident $($rest:ident)*) => {
+        #[derive(Clone, Eq, PartialEq, Debug, Default)]
+        #[derive(StrictType, StrictEncode, StrictDecode)]
+        #[strict_type(lib = LIB)]
+        pub struct $next {
+            prev: $prev,
+            value: u64,
+        }
+        chain!($next; $($rest)*);
+    };
+}
+
+chain!(T0;
+    T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11 T12 T13 T14 T15 T16
+    T17 T18 T19 T20 T21 T22 T23 T24 T25 T26 T27 T28 T29 T30 T31 T32
+    T33 T34 T35 T36 T37 T38 T39 T40 T41 T42 T43 T44 T45 T46 T47 T48
+    T49 T50 T51 T52 T53 T54 T55 T56 T57 T58 T59 T60 T61 T62 T63 T64
+    T65 T66 T67 T68 T69 T70 T71 T72 T73 T74 T75 T76 T77 T78 T79 T80
+    T81 T82 T83 T84 T85 T86 T87 T88 T89 T90 T91 T92 T93 T94 T95 T96
+    T97 T98 T99 T100 T101 T102 T103 T104 T105 T106 T107 T108 T109 T110 T111 T112
+    T113 T114 T115 T116 T117 T118 T119 T120 T121 T122 T123 T124 T125 T126 T127 T128
+    T129 T130 T131 T132 T133 T134 T135 T136 T137 T138 T139 T140 T141 T142 T143 T144
+    T145 T146 T147 T148 T149 T150 T151 T152 T153 T154 T155 T156 T157 T158 T159 T160
+    T161 T162 T163 T164 T165 T166 T167 T168 T169 T170 T171 T172 T173 T174 T175 T176
+    T177 T178 T179 T180 T181 T182 T183 T184 T185 T186 T187 T188 T189 T190 T191 T192
+    T193 T194 T195 T196 T197 T198 T199
+);
+
+fn builder_small() -> LibBuilder { LibBuilder::new(libname!(LIB), []).transpile::<T9>() }
+
+fn builder_large() -> LibBuilder { LibBuilder::new(libname!(LIB), []).transpile::<T49>() }
+
+fn compile(c: &mut Criterion) {
+    c.bench_function("compile_small", |b| {
+        b.iter_batched(builder_small, |builder| builder.compile().unwrap(), BatchSize::SmallInput)
+    });
+    c.bench_function("compile_large", |b| {
+        b.iter_batched(builder_large, |builder| builder.compile().unwrap(), BatchSize::SmallInput)
+    });
+}
+
+fn system(lib: TypeLib) -> SymbolicSys {
+    let std = std_stl();
+    let st = strict_types_stl();
+    SystemBuilder::new()
+        .import(lib)
+        .unwrap()
+        .import(std)
+        .unwrap()
+        .import(st)
+        .unwrap()
+        .finalize()
+        .unwrap()
+}
+
+fn search(c: &mut Criterion) {
+    let small = system(builder_small().compile().unwrap());
+    c.bench_function("search_small", |b| b.iter(|| small.search(black_box("Bench*.T1*"))));
+    let large = system(LibBuilder::new(libname!(LIB), []).transpile::<T199>().compile().unwrap());
+    c.bench_function("search_large", |b| b.iter(|| large.search(black_box("Bench*.T1*"))));
+}
+
+criterion_group!(benches, compile, search);
+criterion_main!(benches);
@@ -0,0 +1,220 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmarks of strict value decoding, type checking and canonical encoding.
+
+#[macro_use]
+extern crate amplify;
+#[macro_use]
+extern crate strict_encoding;
+
+use std::hint::black_box;
+
+use amplify::confinement::{LargeVec, SmallVec, TinyString};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use strict_encoding::{Ident, StrictSerialize};
+use strict_types::stl::{std_stl, strict_types_stl};
+use strict_types::typesys::SystemBuilder;
+use strict_types::{LibBuilder, StrictVal, SymbolicSys};
+
+const LIB: &str = "BenchLib";
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB, dumb = Self::with(strict_dumb!()))]
+pub struct Nominal {
+    ticker: Ident,
+    name: TinyString,
+    precision: u8,
+}
+
+impl StrictSerialize for Nominal {}
+
+impl Nominal {
+    fn with(no: u64) -> Self {
+        Nominal {
+            ticker: Ident::try_from(format!("T{no}")).unwrap(),
+            name: TinyString::try_from(format!("Asset number {no}")).unwrap(),
+            precision: (no % 18) as u8,
+        }
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+#[derive(StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB)]
+pub struct Wide {
+    a: u8,
+    b: u16,
+    c: u32,
+    d: u64,
+    e: i8,
+    f: i16,
+    g: i32,
+    h: i64,
+    i: TinyString,
+    j: Option<u64>,
+}
+
+impl StrictSerialize for Wide {}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB, tags = order, dumb = Self::Value(strict_dumb!()))]
+pub enum Inner {
+    Value(u64),
+    Name(TinyString),
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB, tags = order, dumb = Self::Inner(strict_dumb!()))]
+pub enum Middle {
+    Inner(Inner),
+    Nominal(Nominal),
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB, tags = order, dumb = Self::Middle(strict_dumb!()))]
+pub enum Outer {
+    Middle(Middle),
+    List(SmallVec<Middle>),
+}
+
+impl StrictSerialize for Outer {}
+
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+#[derive(StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB)]
+pub struct Registry {
+    items: LargeVec<Nominal>,
+}
+
+impl StrictSerialize for Registry {}
+
+fn registry(len: u64) -> Registry {
+    Registry {
+        items: LargeVec::try_from((0..len).map(Nominal::with).collect::<Vec<_>>()).unwrap(),
+    }
+}
+
+fn nested(len: u64) -> Outer {
+    let items = (0..len)
+        .map(|no| match no % 3 {
+            0 => Middle::Inner(Inner::Value(no)),
+            1 => Middle::Inner(Inner::Name(TinyString::try_from(format!("Name {no}")).unwrap())),
+            _ => Middle::Nominal(Nominal::with(no)),
+        })
+        .collect::<Vec<_>>();
+    Outer::List(SmallVec::try_from(items).unwrap())
+}
+
+fn system() -> SymbolicSys {
+    let std = std_stl();
+    let st = strict_types_stl();
+    let lib = LibBuilder::new(libname!(LIB), [std.to_dependency(), st.to_dependency()])
+        .transpile::<Registry>()
+        .transpile::<Wide>()
+        .transpile::<Outer>()
+        .compile()
+        .unwrap();
+    SystemBuilder::new()
+        .import(lib)
+        .unwrap()
+        .import(std)
+        .unwrap()
+        .import(st)
+        .unwrap()
+        .finalize()
+        .unwrap()
+}
+
+fn serialize(val: &impl StrictSerialize) -> Vec<u8> {
+    val.to_strict_serialized::<{ usize::MAX }>().unwrap().release()
+}
+
+fn bench_decode(c: &mut Criterion, sys: &SymbolicSys, name: &str, ty: &'static str, data: Vec<u8>) {
+    c.bench_function(name, |b| {
+        b.iter(|| sys.strict_deserialize_type(black_box(ty), black_box(&data)).unwrap())
+    });
+}
+
+fn bench_typify(c: &mut Criterion, sys: &SymbolicSys, name: &str, ty: &'static str, data: Vec<u8>) {
+    let val = sys.strict_deserialize_type(ty, &data).unwrap().unbox();
+    c.bench_function(name, |b| {
+        b.iter_batched(|| val.clone(), |val| sys.typify(val, ty).unwrap(), BatchSize::SmallInput)
+    });
+}
+
+fn decode(c: &mut Criterion) {
+    let sys = system();
+
+    bench_decode(c, &sys, "decode_struct_small", "BenchLib.Nominal", serialize(&Nominal::with(1)));
+    let wide = Wide {
+        i: TinyString::try_from(s!("Some text")).unwrap(),
+        j: Some(42),
+        ..default!()
+    };
+    bench_decode(c, &sys, "decode_struct_large", "BenchLib.Wide", serialize(&wide));
+
+    bench_decode(c, &sys, "decode_list_small", "BenchLib.Registry", serialize(&registry(10)));
+    bench_decode(c, &sys, "decode_list_large", "BenchLib.Registry", serialize(&registry(10_000)));
+
+    let single = Outer::Middle(Middle::Inner(Inner::Value(5)));
+    bench_decode(c, &sys, "decode_union_small", "BenchLib.Outer", serialize(&single));
+    bench_decode(c, &sys, "decode_union_large", "BenchLib.Outer", serialize(&nested(1000)));
+}
+
+fn typify(c: &mut Criterion) {
+    let sys = system();
+
+    bench_typify(c, &sys, "typify_struct_small", "BenchLib.Nominal", serialize(&Nominal::with(1)));
+    let wide = Wide {
+        i: TinyString::try_from(s!("Some text")).unwrap(),
+        j: Some(42),
+        ..default!()
+    };
+    bench_typify(c, &sys, "typify_struct_large", "BenchLib.Wide", serialize(&wide));
+
+    bench_typify(c, &sys, "typify_list_small", "BenchLib.Registry", serialize(&registry(10)));
+    bench_typify(c, &sys, "typify_list_large", "BenchLib.Registry", serialize(&registry(1000)));
+}
+
+/// Value with `depth` levels of nested unions, tuples and lists.
+fn deep(depth: usize) -> StrictVal {
+    (0..depth).fold(StrictVal::num(0u64), |val, level| match level % 3 {
+        0 => StrictVal::union("some", val),
+        1 => StrictVal::tuple([val, StrictVal::str("text")]),
+        _ => StrictVal::list([val.clone(), val]),
+    })
+}
+
+fn canonical(c: &mut Criterion) {
+    let small = deep(6);
+    c.bench_function("canonical_small", |b| b.iter(|| black_box(&small).to_canonical_bytes()));
+    let large = deep(30);
+    c.bench_function("canonical_large", |b| b.iter(|| black_box(&large).to_canonical_bytes()));
+}
+
+criterion_group!(benches, decode, typify, canonical);
+criterion_main!(benches);
@@ -0,0 +1,93 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![no_main]
+
+//! Fuzzes decoding of strict-encoded data according to a type from a type system.
+
+#[macro_use]
+extern crate strict_encoding;
+
+use std::sync::OnceLock;
+
+use amplify::confinement::NonEmptyString;
+use libfuzzer_sys::fuzz_target;
+use strict_encoding::{Ident, StreamReader};
+use strict_types::stl::{std_stl, strict_types_stl};
+use strict_types::{LibBuilder, SymbolicSys, SystemBuilder};
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = "TestLib", tags = repr, into_u8, try_from_u8)]
+#[repr(u8)]
+pub enum Precision {
+    #[strict_type(dumb)]
+    NoDecimals = 0,
+    OneDecimal = 1,
+    TwoDecimals = 2,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = "TestLib", dumb = Nominal::dumb())]
+pub struct Nominal {
+    pub ticker: Ident,
+    pub name: NonEmptyString<32>,
+    pub precision: Precision,
+}
+
+impl Nominal {
+    fn dumb() -> Self {
+        Nominal {
+            ticker: Ident::from("DUMB"),
+            name: NonEmptyString::try_from(String::from("Dumb")).expect("static name"),
+            precision: Precision::NoDecimals,
+        }
+    }
+}
+
+fn system() -> &'static SymbolicSys {
+    static SYS: OnceLock<SymbolicSys> = OnceLock::new();
+    SYS.get_or_init(|| {
+        let std = std_stl();
+        let st = strict_types_stl();
+        let lib = LibBuilder::new("TestLib", [std.to_dependency(), st.to_dependency()])
+            .transpile::<Nominal>()
+            .compile()
+            .expect("test library");
+        SystemBuilder::new()
+            .import(lib)
+            .and_then(|builder| builder.import(std))
+            .and_then(|builder| builder.import(st))
+            .expect("test type system")
+            .finalize()
+            .expect("test type system")
+    })
+}
+
+fuzz_target!(|data: &[u8]| {
+    let sys = system();
+    let mut reader = StreamReader::cursor::<{ u16::MAX as usize }>(data);
+    let _ = sys.strict_read_type("TestLib.Nominal", &mut reader);
+    // A type with deep nesting and all kinds of collections
+    let _ = sys.strict_deserialize_type("StrictTypes.TypeLib", data);
+});
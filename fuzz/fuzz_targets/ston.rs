@@ -0,0 +1,42 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![no_main]
+
+//! Fuzzes STON parser.
+
+use std::str::FromStr;
+
+use libfuzzer_sys::fuzz_target;
+use strict_types::StrictVal;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        if let Ok(val) = StrictVal::from_str(s) {
+            // Printed values must be parsed back; since STON shorthands are not unique (like `~`
+            // for both `none` and zero union tags) we compare the printed text instead
+            let printed = val.to_string();
+            let reparsed = StrictVal::from_str(&printed).expect("printed value must be parsed");
+            assert_eq!(reparsed.to_string(), printed);
+        }
+    }
+});
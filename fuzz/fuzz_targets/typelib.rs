@@ -0,0 +1,40 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![no_main]
+
+//! Fuzzes deserialization of type libraries.
+
+use amplify::confinement::Confined;
+use libfuzzer_sys::fuzz_target;
+use strict_encoding::StrictDeserialize;
+use strict_types::TypeLib;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(data) = Confined::try_from(data.to_vec()) else {
+        return;
+    };
+    if let Ok(lib) = TypeLib::from_strict_serialized::<{ usize::MAX }>(data) {
+        let _ = lib.id();
+        let _ = lib.to_string();
+    }
+});
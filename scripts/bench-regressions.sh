@@ -0,0 +1,21 @@
+#!/usr/bin/env bash
+
+# Fails if any criterion benchmark mean regressed against the saved baseline by more than the
+# given fraction (20% by default).
+
+THRESHOLD=${1:-0.20}
+FAILED=0
+
+# Benchmarks without a baseline have no `change` directory and are not checked.
+shopt -s nullglob
+
+for estimates in target/criterion/*/change/estimates.json; do
+    name=$(basename "$(dirname "$(dirname "$estimates")")")
+    change=$(jq '.mean.point_estimate' "$estimates")
+    if (( $(echo "$change > $THRESHOLD" | bc -l) )); then
+        echo "$name regressed by $(echo "$change * 100" | bc -l | xargs printf '%.1f')%"
+        FAILED=1
+    fi
+done
+
+exit $FAILED
@@ -66,16 +66,60 @@ impl FromStr for SemId {
     fn from_str(s: &str) -> Result<Self, Self::Err> { Self::from_baid64_str(s) }
 }
 impl Display for SemId {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { self.fmt_baid64(f) }
+    /// Formats the id as Baid64 string, like other ids: the alternate flag (`{:#}`) omits the
+    /// mnemonic checksum suffix, and the minus sign flag (`{:-}`) omits the `semid:` prefix.
+    ///
+    /// The plus sign flag (`{:+}`) formats the id in hex, truncated to the precision, if provided
+    /// (`{:+.8}`). With `bip39` feature, the plus sign together with the alternate flag (`{:+#}`)
+    /// produces the word fingerprint returned by [`SemId::to_mnemonic`].
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if !f.sign_plus() {
+            return self.fmt_baid64(f);
+        }
+        #[cfg(feature = "bip39")]
+        if f.alternate() {
+            return f.write_str(&self.to_mnemonic());
+        }
+        let hex = format!("{self:x}");
+        let len = f.precision().unwrap_or(hex.len()).min(hex.len());
+        f.write_str(&hex[..len])
+    }
 }
 
 pub const SEM_ID_TAG: [u8; 32] = *b"urn:ubideco:strict-types:typ:v01";
 
 impl SemId {
     pub fn unit() -> Self { SemId::default() }
+
+    /// Returns human-readable fingerprint of the semantic id, consisting of twelve space-separated
+    /// BIP-39 words encoding the first 16 bytes of the id.
+    #[cfg(feature = "bip39")]
+    pub fn to_mnemonic(&self) -> String {
+        bip39::Mnemonic::from_entropy(&self[..16])
+            .expect("16 bytes is a valid BIP-39 entropy length")
+            .to_string()
+    }
+
+    /// Checks that the provided words match the fingerprint returned by [`Self::to_mnemonic`].
+    ///
+    /// Words may be separated by any non-alphabetic characters; it is sufficient to provide only
+    /// the first words of the fingerprint. Returns `false` if no words are provided.
+    #[cfg(feature = "bip39")]
+    pub fn verify_mnemonic(&self, mnemonic: &str) -> bool {
+        let words = |s: &str| {
+            s.split(|c: char| !c.is_ascii_alphabetic())
+                .filter(|word| !word.is_empty())
+                .map(str::to_ascii_lowercase)
+                .collect::<Vec<_>>()
+        };
+        let expected = words(&self.to_mnemonic());
+        let provided = words(mnemonic);
+        !provided.is_empty() && expected.starts_with(&provided)
+    }
 }
 
 impl TypeRef for SemId {
+    fn sem_id(&self) -> Option<SemId> { Some(*self) }
     fn is_unicode_char(&self) -> bool { Self::unicode_char() == *self }
     fn is_byte(&self) -> bool { Self::byte() == *self || Ty::<Self>::U8.sem_id_unnamed() == *self }
 }
@@ -266,4 +310,35 @@ mod test {
     fn byte() {
         assert!(Ty::<SemId>::Primitive(Primitive::BYTE).is_byte());
     }
+
+    #[test]
+    fn display() {
+        let id = SemId::unit();
+        let hex = format!("{id:x}");
+        assert_eq!(hex.len(), 64);
+        assert_eq!(format!("{id:+}"), hex);
+        assert_eq!(format!("{id:+.8}"), hex[..8]);
+        let baid64 = id.to_string();
+        assert!(baid64.starts_with("semid:") && baid64.contains('#'));
+        assert_eq!(format!("{id:#}"), baid64.split('#').next().unwrap());
+        assert_eq!(format!("{id:-}"), baid64.trim_start_matches("semid:"));
+        assert_eq!(SemId::from_str(&baid64).unwrap(), id);
+        #[cfg(feature = "bip39")]
+        assert_eq!(format!("{id:+#}"), id.to_mnemonic());
+    }
+
+    #[test]
+    #[cfg(feature = "bip39")]
+    fn mnemonic() {
+        let id = SemId::unit();
+        let mnemonic = id.to_mnemonic();
+        assert_eq!(mnemonic.split(' ').count(), 12);
+        assert!(mnemonic.split(' ').all(|word| bip39::Language::English.find_word(word).is_some()));
+        assert!(id.verify_mnemonic(&mnemonic));
+        assert!(id.verify_mnemonic(&mnemonic.replace(' ', "-").to_uppercase()));
+        let prefix = mnemonic.split(' ').take(3).collect::<Vec<_>>().join(" ");
+        assert!(id.verify_mnemonic(&prefix));
+        assert!(!id.verify_mnemonic(""));
+        assert!(!id.verify_mnemonic(&Ty::<SemId>::U8.sem_id_unnamed().to_mnemonic()));
+    }
 }
@@ -22,7 +22,7 @@
 
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::{self, Debug, Display, Formatter};
-use std::ops::Deref;
+use std::ops::{Deref, RangeInclusive};
 
 use amplify::confinement::{Confined, NonEmptyOrdMap, NonEmptyOrdSet, NonEmptyVec};
 use amplify::{confinement, Wrapper};
@@ -33,6 +33,8 @@ use strict_encoding::{
 
 use super::id::SemCommit;
 use crate::ast::Iter;
+use crate::layout::{fixed, prefixed, sum, times, SizeRange};
+use crate::{SemId, TypeSystem};
 
 /// Glue for constructing ASTs.
 pub trait TypeRef:
@@ -44,6 +46,13 @@ pub trait TypeRef:
     fn is_compound(&self) -> bool { false }
     fn is_byte(&self) -> bool { false }
     fn is_unicode_char(&self) -> bool { false }
+
+    /// Semantic id of the referenced type, if it can be known without resolving the reference
+    /// within a type library.
+    fn sem_id(&self) -> Option<SemId> { None }
+
+    /// Nesting depth of the referenced type, if it is inlined; zero otherwise.
+    fn depth(&self) -> usize { 0 }
 }
 
 pub trait PrimitiveRef: TypeRef {
@@ -51,9 +60,10 @@ pub trait PrimitiveRef: TypeRef {
     fn unicode_char() -> Self;
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Display)]
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Display)]
 #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
 #[strict_type(lib = STRICT_TYPES_LIB, tags = repr, into_u8, try_from_u8)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
 #[display(lowercase)]
 #[repr(u8)]
 pub enum Cls {
@@ -194,15 +204,23 @@ impl<Ref: TypeRef> Ty<Ref> {
     pub fn set(ty: Ref, sizing: Sizing) -> Self { Ty::Set(ty, sizing) }
     pub fn map(key: Ref, val: Ref, sizing: Sizing) -> Self { Ty::Map(key, val, sizing) }
 
-    pub fn is_char_enum(&self) -> bool {
-        if let Ty::Tuple(fields) = self {
-            fields.first().and_then(Ref::as_ty).map(Self::is_char_enum).unwrap_or_default()
-        } else if let Ty::Enum(variants) = self {
-            variants.iter().all(|variant| (32..=127).contains(&variant.tag))
-        } else {
-            false
+    #[deprecated(since = "2.8.1", note = "use is_ascii_char instead")]
+    pub fn is_char_enum(&self) -> bool { self.is_ascii_char() }
+    /// Detects whether the type is an enum of printable ASCII characters, where variant tags are
+    /// the character codes, or a tuple starting with such an inlined enum.
+    pub fn is_ascii_char(&self) -> bool {
+        match self {
+            Ty::Tuple(fields) => fields.first().and_then(Ref::as_ty).is_some_and(Self::is_ascii_char),
+            Ty::Enum(variants) => {
+                variants.iter().all(|variant| (0x20..=0x7E).contains(&variant.tag))
+            }
+            _ => false,
         }
     }
+    /// Detects whether the type is an enum describing a set of bit flags.
+    pub fn is_bitfield(&self) -> bool {
+        matches!(self, Ty::Enum(variants) if variants.is_bitfield())
+    }
     pub fn is_compound(&self) -> bool {
         match self {
             Ty::Tuple(fields) if fields.len() > 1 => true,
@@ -244,6 +262,48 @@ impl<Ref: TypeRef> Ty<Ref> {
         }
         None
     }
+
+    /// Compares structure of two types using different kinds of type references, checking that
+    /// they have the same variants, fields and their names, collection sizes, and reference
+    /// types with the same semantic ids.
+    ///
+    /// This allows to match a compiled type against its library definition, since inline types
+    /// are compared by their semantic ids. Types with references which semantic ids can't be
+    /// known (like named references in not yet compiled libraries) are never equal.
+    pub fn struct_eq<Other: TypeRef>(&self, other: &Ty<Other>) -> bool {
+        fn ref_eq<A: TypeRef, B: TypeRef>(a: &A, b: &B) -> bool {
+            if let (Some(a), Some(b)) = (a.as_ty(), b.as_ty()) {
+                return a.struct_eq(b);
+            }
+            matches!((a.sem_id(), b.sem_id()), (Some(a), Some(b)) if a == b)
+        }
+
+        match (self, other) {
+            (Ty::Primitive(a), Ty::Primitive(b)) => a == b,
+            (Ty::UnicodeChar, Ty::UnicodeChar) => true,
+            (Ty::Enum(a), Ty::Enum(b)) => a == b,
+            (Ty::Union(a), Ty::Union(b)) => {
+                a.len() == b.len()
+                    && a.iter().zip(b.iter()).all(|((va, a), (vb, b))| {
+                        va.tag == vb.tag && va.name == vb.name && ref_eq(a, b)
+                    })
+            }
+            (Ty::Tuple(a), Ty::Tuple(b)) => {
+                a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| ref_eq(a, b))
+            }
+            (Ty::Struct(a), Ty::Struct(b)) => {
+                a.len() == b.len()
+                    && a.iter().zip(b.iter()).all(|(a, b)| a.name == b.name && ref_eq(&a.ty, &b.ty))
+            }
+            (Ty::Array(a, len_a), Ty::Array(b, len_b)) => len_a == len_b && ref_eq(a, b),
+            (Ty::List(a, sizing_a), Ty::List(b, sizing_b))
+            | (Ty::Set(a, sizing_a), Ty::Set(b, sizing_b)) => sizing_a == sizing_b && ref_eq(a, b),
+            (Ty::Map(ka, a, sizing_a), Ty::Map(kb, b, sizing_b)) => {
+                sizing_a == sizing_b && ref_eq(ka, kb) && ref_eq(a, b)
+            }
+            _ => false,
+        }
+    }
 }
 
 impl<Ref: TypeRef> Display for Ty<Ref>
@@ -289,6 +349,7 @@ where Ref: Display
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
 #[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
 #[strict_type(lib = STRICT_TYPES_LIB, tags = custom)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
 pub enum ItemCase {
     #[strict_type(tag = 0)]
     UnnamedField(u8),
@@ -309,6 +370,17 @@ pub enum ItemCase {
 }
 
 impl<Ref: TypeRef> Ty<Ref> {
+    /// Returns nesting depth of the type: zero for types without nested types, and one plus the
+    /// maximal depth of the nested types otherwise.
+    ///
+    /// References to named and external types are not followed and count as zero depth.
+    pub fn depth(&self) -> usize {
+        match self {
+            Ty::Primitive(_) | Ty::UnicodeChar | Ty::Enum(_) => 0,
+            _ => 1 + self.iter().map(|(ty, _)| ty.depth()).max().unwrap_or_default(),
+        }
+    }
+
     pub fn ty_at(&self, pos: u8) -> Option<&Ref> {
         match self {
             Ty::Union(fields) => fields.ty_by_pos(pos),
@@ -407,8 +479,29 @@ impl<'a, Ref: TypeRef> IntoIterator for &'a NamedFields<Ref> {
 }
 
 impl<Ref: TypeRef> NamedFields<Ref> {
+    /// Constructs fields ordered alphabetically by their names.
+    pub fn from_btreemap(map: BTreeMap<FieldName, Ref>) -> Result<Self, confinement::Error> {
+        let fields = map.into_iter().map(|(name, ty)| Field { name, ty }).collect::<Vec<_>>();
+        Self::try_from(fields)
+    }
+
     pub fn into_inner(self) -> Vec<Field<Ref>> { self.0.release() }
 
+    /// Reorders fields alphabetically by their names.
+    ///
+    /// NB: field order is a part of the type definition, thus the resulting type has a different
+    /// semantic id, unless the fields were already sorted.
+    pub fn sort_by_name(self) -> Self {
+        let mut fields = self.into_inner();
+        fields.sort_by(|a, b| a.name.cmp(&b.name));
+        Self::try_from(fields).expect("re-packing existing fields structure")
+    }
+
+    pub fn contains_name(&self, name: &FieldName) -> bool { self.0.iter().any(|f| &f.name == name) }
+    pub fn position_of(&self, name: &FieldName) -> Option<usize> {
+        self.0.iter().position(|f| &f.name == name)
+    }
+
     pub fn ty_by_pos(&self, pos: u8) -> Option<&Ref> { self.0.get(pos as usize).map(|f| &f.ty) }
     pub fn ty_by_name(&self, name: &FieldName) -> Option<&Ref> {
         self.0.iter().find(|f| &f.name == name).map(|f| &f.ty)
@@ -548,9 +641,36 @@ impl<'a, Ref: TypeRef> IntoIterator for &'a UnionVariants<Ref> {
     fn into_iter(self) -> Self::IntoIter { self.0.iter() }
 }
 
+/// Minimal number of contiguous unused tags which are considered to be a range reserved for future
+/// extensions.
+pub const RESERVED_TAGS: usize = 16;
+
+/// Computes ranges of tags not used by any of the variants.
+fn unused_tags<'a>(variants: impl IntoIterator<Item = &'a Variant>) -> Vec<RangeInclusive<u8>> {
+    let used = variants.into_iter().map(|v| v.tag).collect::<BTreeSet<_>>();
+    let mut ranges = vec![];
+    let mut start = 0u16;
+    for tag in used.into_iter().map(u16::from).chain([0x100]) {
+        if tag > start {
+            ranges.push(start as u8..=(tag - 1) as u8);
+        }
+        start = tag + 1;
+    }
+    ranges
+}
+
 impl<Ref: TypeRef> UnionVariants<Ref> {
     pub fn into_inner(self) -> BTreeMap<Variant, Ref> { self.0.release() }
 
+    /// Ranges of tags in `0..=255` which are not used by the union variants, in ascending order.
+    pub fn unused_tags(&self) -> Vec<RangeInclusive<u8>> { unused_tags(self.0.keys()) }
+
+    /// Detects whether the union has at least [`RESERVED_TAGS`] contiguous unused tags, which may
+    /// be used by the future versions of the type.
+    pub fn has_reserved_range(&self) -> bool {
+        self.unused_tags().iter().any(|range| range.len() >= RESERVED_TAGS)
+    }
+
     pub fn unwrap_first(&self) -> &Variant { self.0.first_key_value().unwrap().0 }
     pub fn unwrap_last(&self) -> &Variant { self.0.last_key_value().unwrap().0 }
 
@@ -666,9 +786,78 @@ impl<'a> IntoIterator for &'a EnumVariants {
     fn into_iter(self) -> Self::IntoIter { self.0.iter() }
 }
 
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum InvalidVariant {
+    /// enum has no variant named `{0}`.
+    UnknownName(VariantName),
+
+    /// bit flags {0:#010b} don't match enum variants.
+    UnknownBits(u8),
+}
+
 impl EnumVariants {
+    /// Constructs enum for a set of `max_bits` bit flags, named `bit0`, `bit1` etc., where each
+    /// variant tag is a power of two.
+    ///
+    /// # Panics
+    ///
+    /// If `max_bits` is zero or exceeds 8.
+    pub fn from_bitflags(max_bits: u8) -> EnumVariants {
+        let names = (0..max_bits).map(|n| vname!(format!("bit{n}"))).collect::<Vec<_>>();
+        Self::from_bitflag_names(&names)
+    }
+
+    /// Constructs enum for a set of bit flags with the provided names, where the tag of n-th
+    /// variant is `1 << n`.
+    ///
+    /// # Panics
+    ///
+    /// If no names or more than 8 names are provided, or if the names are repeated.
+    pub fn from_bitflag_names(names: &[VariantName]) -> EnumVariants {
+        assert!((1..=8).contains(&names.len()), "bit flag enum must have from 1 to 8 variants");
+        let variants = names
+            .iter()
+            .enumerate()
+            .map(|(n, name)| Variant::named(1 << n, name.clone()))
+            .collect::<BTreeSet<_>>();
+        assert!(
+            variants.iter().map(|v| &v.name).collect::<BTreeSet<_>>().len() == names.len(),
+            "repeated bit flag names"
+        );
+        EnumVariants::try_from(variants).expect("number of variants is checked")
+    }
+
+    /// Detects whether all variant tags are powers of two, i.e. whether the enum describes a set
+    /// of bit flags.
+    pub fn is_bitfield(&self) -> bool { self.0.iter().all(|v| v.tag.is_power_of_two()) }
+
+    /// Combines tags of the `selected` variants into a single bit field value.
+    pub fn to_bitfield(&self, selected: &[VariantName]) -> Result<u8, InvalidVariant> {
+        selected.iter().try_fold(0u8, |bits, name| {
+            self.tag_by_name(name)
+                .map(|tag| bits | tag)
+                .ok_or_else(|| InvalidVariant::UnknownName(name.clone()))
+        })
+    }
+
+    /// Lists names of the variants which tags are set in the `bits` field value, in the order of
+    /// their tags.
+    pub fn from_bitfield(&self, bits: u8) -> Result<Vec<VariantName>, InvalidVariant> {
+        let selected =
+            self.0.iter().filter(|v| v.tag != 0 && bits & v.tag == v.tag).collect::<Vec<_>>();
+        let known = selected.iter().fold(0u8, |acc, v| acc | v.tag);
+        if known != bits {
+            return Err(InvalidVariant::UnknownBits(bits & !known));
+        }
+        Ok(selected.into_iter().map(|v| v.name.clone()).collect())
+    }
+
     pub fn into_inner(self) -> BTreeSet<Variant> { self.0.release() }
 
+    /// Ranges of tags in `0..=255` which are not used by the enum variants, in ascending order.
+    pub fn unused_tags(&self) -> Vec<RangeInclusive<u8>> { unused_tags(self.0.iter()) }
+
     pub fn tag_by_name(&self, name: &VariantName) -> Option<u8> {
         self.0.iter().find(|v| &v.name == name).map(|v| v.tag)
     }
@@ -720,3 +909,320 @@ impl Display for EnumVariants {
         writeln!(f)
     }
 }
+
+impl Ty<SemId> {
+    /// Computes the range of byte sizes a value of the type takes when strict-encoded, resolving
+    /// referenced types with the type system `sys`.
+    ///
+    /// Recursive types and types referencing types absent in `sys` are treated as unbounded.
+    pub fn encoded_size_range(&self, sys: &TypeSystem) -> SizeRange {
+        self.size_range(sys, &mut vec![])
+    }
+
+    fn size_range(&self, sys: &TypeSystem, stack: &mut Vec<SemId>) -> SizeRange {
+        self.size_range_with(|sem_id| {
+            if stack.contains(sem_id) {
+                return (0, None);
+            }
+            let Some(ty) = sys.get(*sem_id) else {
+                return (0, None);
+            };
+            stack.push(*sem_id);
+            let range = ty.size_range(sys, stack);
+            stack.pop();
+            range
+        })
+    }
+
+    /// Computes the encoded size range of the type, taking size ranges of the referenced types
+    /// from `inner`, which is called in the order of the type references.
+    pub(crate) fn size_range_with(&self, mut inner: impl FnMut(&SemId) -> SizeRange) -> SizeRange {
+        match self {
+            Ty::Primitive(prim) => fixed(prim.byte_size() as u64),
+            Ty::UnicodeChar => (1, Some(4)),
+            Ty::Enum(_) => fixed(1),
+            Ty::Union(variants) => {
+                let mut ranges = variants.values().map(inner);
+                let first = ranges.next().unwrap_or(fixed(0));
+                let (min, max) = ranges.fold(first, |(min, max), (a, b)| {
+                    (min.min(a), max.zip(b).map(|(max, b)| max.max(b)))
+                });
+                sum(fixed(1), (min, max))
+            }
+            Ty::Tuple(fields) => fields.iter().map(&mut inner).fold(fixed(0), sum),
+            Ty::Struct(fields) => fields.iter().map(|field| inner(&field.ty)).fold(fixed(0), sum),
+            Ty::Array(ty, len) if ty.is_byte() => fixed(*len as u64),
+            Ty::Array(ty, len) => times(inner(ty), *len as u64, Some(*len as u64)),
+            // Unicode strings are length-prefixed with the number of bytes
+            Ty::List(ty, sizing) if ty.is_unicode_char() => prefixed(*sizing, fixed(1)),
+            Ty::List(ty, sizing) | Ty::Set(ty, sizing) => prefixed(*sizing, inner(ty)),
+            Ty::Map(key, ty, sizing) => {
+                let key = inner(key);
+                prefixed(*sizing, sum(key, inner(ty)))
+            }
+        }
+    }
+
+    /// Checks that all types referenced by this type are present in the type system `sys`,
+    /// returning ids of the missing types otherwise.
+    pub fn validate_refs(&self, sys: &TypeSystem) -> Result<(), Vec<SemId>> {
+        let missing = self
+            .iter()
+            .map(|(id, _)| *id)
+            .filter(|id| sys.get(*id).is_none())
+            .collect::<BTreeSet<_>>();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        Err(missing.into_iter().collect())
+    }
+
+    /// Detects whether the type references itself, directly or via other types from `sys`.
+    pub fn is_recursive(&self, sys: &TypeSystem) -> bool {
+        let mut visited = BTreeSet::new();
+        let mut stack = self.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+        while let Some(sem_id) = stack.pop() {
+            if !visited.insert(sem_id) {
+                continue;
+            }
+            let Some(ty) = sys.get(sem_id) else {
+                continue;
+            };
+            if ty == self {
+                return true;
+            }
+            stack.extend(ty.iter().map(|(id, _)| *id));
+        }
+        false
+    }
+
+    /// Creates a copy of the type where each reference to the `old` type is replaced with a
+    /// reference to `new_ref`.
+    ///
+    /// The semantic id of the resulting type differs from the original one if the type
+    /// references `old`.
+    pub fn substitute(&self, old: SemId, new_ref: SemId) -> Ty<SemId> {
+        self.map_refs(&|id| if id == old { new_ref } else { id })
+    }
+
+    pub(crate) fn map_refs(&self, f: &impl Fn(SemId) -> SemId) -> Ty<SemId> {
+        match self {
+            Ty::Union(variants) => {
+                let variants = variants
+                    .iter()
+                    .map(|(variant, id)| (variant.clone(), f(*id)))
+                    .collect::<BTreeMap<_, _>>();
+                Ty::Union(UnionVariants::from(Confined::from_checked(variants)))
+            }
+            Ty::Struct(fields) => {
+                let fields = fields
+                    .iter()
+                    .map(|field| Field {
+                        name: field.name.clone(),
+                        ty: f(field.ty),
+                    })
+                    .collect::<Vec<_>>();
+                Ty::Struct(NamedFields::try_from(fields).expect("re-packing existing fields"))
+            }
+            Ty::Tuple(fields) => {
+                let fields = fields.iter().copied().map(f).collect::<Vec<_>>();
+                Ty::Tuple(UnnamedFields::try_from(fields).expect("re-packing existing fields"))
+            }
+            Ty::Array(id, len) => Ty::Array(f(*id), *len),
+            Ty::List(id, sizing) => Ty::List(f(*id), *sizing),
+            Ty::Set(id, sizing) => Ty::Set(f(*id), *sizing),
+            Ty::Map(key, id, sizing) => Ty::Map(f(*key), f(*id), *sizing),
+            ty @ (Ty::Primitive(_) | Ty::Enum(_) | Ty::UnicodeChar) => ty.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::stl::{std_stl, strict_types_stl};
+    use crate::typelib::{InlineRef, InlineRef1, LibRef};
+    use crate::typesys::TypeFqn;
+    use crate::value::test_helpers::{test_system, Nominal};
+    use crate::LibBuilder;
+
+    fn record<Ref: TypeRef>(fields: impl IntoIterator<Item = (&'static str, Ref)>) -> Ty<Ref> {
+        let fields = fields.into_iter().map(|(name, ty)| Field {
+            name: fname!(name),
+            ty,
+        });
+        Ty::Struct(NamedFields::try_from(fields.collect::<Vec<_>>()).unwrap())
+    }
+
+    fn insert(sys: &mut TypeSystem, ty: Ty<SemId>) -> SemId {
+        let id = ty.sem_id_unnamed();
+        sys.insert_unchecked(id, ty).unwrap();
+        id
+    }
+
+    #[test]
+    fn depth() {
+        let int = Ty::<InlineRef1>::U32;
+        assert_eq!(int.depth(), 0);
+        let rec = record([("value", InlineRef::Inline(int))]);
+        assert_eq!(rec.depth(), 1);
+        let list = Ty::List(LibRef::Inline(rec), Sizing::U8);
+        assert_eq!(list.depth(), 2);
+        let named = Ty::List(LibRef::Named(SemId::unit()), Sizing::U8);
+        assert_eq!(named.depth(), 1);
+    }
+
+    #[test]
+    fn struct_eq() {
+        let std = std_stl();
+        let st = strict_types_stl();
+        let lib = LibBuilder::new("TestLib", [std.to_dependency(), st.to_dependency()])
+            .transpile::<Nominal>()
+            .compile()
+            .unwrap();
+        let sys = test_system();
+        for src in [&lib, &st, &std] {
+            for (name, ty) in &src.types {
+                let fqn = TypeFqn::with(src.name.clone(), name.clone());
+                let compiled = sys.get(fqn).unwrap();
+                assert!(ty.struct_eq(compiled), "{name}");
+                assert!(compiled.struct_eq(ty), "{name}");
+            }
+        }
+
+        let nominal = sys.get("TestLib.Nominal").unwrap();
+        assert!(!lib.types[&tn!("Precision")].struct_eq(nominal));
+        let renamed = record([("amount", SemId::unit())]);
+        assert!(!renamed.struct_eq(&record([("value", SemId::unit())])));
+        assert!(renamed.struct_eq(&record([("amount", LibRef::Named(SemId::unit()))])));
+        assert!(!renamed.struct_eq(&record([("amount", LibRef::Named(SemId::byte()))])));
+    }
+
+    #[test]
+    fn bitflags() {
+        let flags = EnumVariants::from_bitflags(4);
+        assert_eq!(flags.len(), 4);
+        assert_eq!(flags.tag_by_name(&vname!("bit3")), Some(8));
+        assert!(flags.is_bitfield());
+        assert!(Ty::<SemId>::Enum(flags.clone()).is_bitfield());
+        assert!(!Ty::<SemId>::Enum(variants!("a", "b")).is_bitfield());
+        assert!(!Ty::<SemId>::U8.is_bitfield());
+
+        let named = EnumVariants::from_bitflag_names(&[vname!("read"), vname!("write")]);
+        assert_eq!(named.tag_by_name(&vname!("write")), Some(2));
+
+        let selected = [vname!("bit2"), vname!("bit0")];
+        let bits = flags.to_bitfield(&selected).unwrap();
+        assert_eq!(bits, 0b0101);
+        assert_eq!(flags.from_bitfield(bits).unwrap(), vec![vname!("bit0"), vname!("bit2")]);
+        assert_eq!(flags.from_bitfield(0).unwrap(), Vec::<VariantName>::new());
+
+        assert_eq!(
+            flags.to_bitfield(&[vname!("bit4")]),
+            Err(InvalidVariant::UnknownName(vname!("bit4")))
+        );
+        assert_eq!(flags.from_bitfield(0b0001_0011), Err(InvalidVariant::UnknownBits(0b0001_0000)));
+        assert_eq!(
+            InvalidVariant::UnknownBits(0b0001_0000).to_string(),
+            "bit flags 0b00010000 don't match enum variants."
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "from 1 to 8 variants")]
+    fn bitflags_too_many() { EnumVariants::from_bitflags(9); }
+
+    #[test]
+    fn unused_tags() {
+        let enumer: EnumVariants = variants!("a", "b", "c");
+        assert_eq!(enumer.unused_tags(), vec![3..=255]);
+        let flags = EnumVariants::from_bitflags(8);
+        assert_eq!(flags.unused_tags()[..3], [0..=0, 3..=3, 5..=7]);
+        assert_eq!(flags.unused_tags().last(), Some(&(129..=255)));
+
+        let union = |tags: &[u8]| {
+            let variants = tags
+                .iter()
+                .map(|tag| (Variant::named(*tag, vname!(format!("v{tag}"))), SemId::unit()))
+                .collect::<BTreeMap<_, _>>();
+            UnionVariants::try_from(variants).unwrap()
+        };
+        assert_eq!(union(&[0, 1, 255]).unused_tags(), vec![2..=254]);
+        assert_eq!(union(&[1, 16, 254]).unused_tags(), vec![0..=0, 2..=15, 17..=253, 255..=255]);
+        assert!(union(&[0, 1, 255]).has_reserved_range());
+        let dense = union(&(0..=255).step_by(15).collect::<Vec<_>>());
+        assert!(dense.unused_tags().iter().all(|range| range.len() == 14));
+        assert!(!dense.has_reserved_range());
+    }
+
+    #[test]
+    fn field_order() {
+        let rec = record([("b", SemId::unit()), ("c", SemId::unit()), ("a", SemId::unit())]);
+        let Ty::Struct(fields) = rec else {
+            unreachable!()
+        };
+        assert!(fields.contains_name(&fname!("c")));
+        assert!(!fields.contains_name(&fname!("d")));
+        assert_eq!(fields.position_of(&fname!("a")), Some(2));
+        assert_eq!(fields.position_of(&fname!("d")), None);
+
+        let sorted = fields.clone().sort_by_name();
+        let names = sorted.iter().map(|f| f.name.to_string()).collect::<Vec<_>>();
+        assert_eq!(names, ["a", "b", "c"]);
+        assert_eq!(sorted.position_of(&fname!("a")), Some(0));
+        assert_eq!(sorted.clone().sort_by_name(), sorted);
+
+        let map = fields.into_iter().map(|f| (f.name, f.ty)).collect::<BTreeMap<_, _>>();
+        assert_eq!(NamedFields::from_btreemap(map).unwrap(), sorted);
+        assert!(NamedFields::<SemId>::from_btreemap(empty!()).is_err());
+    }
+
+    #[test]
+    fn size_range() {
+        let mut sys = TypeSystem::new();
+        let int = Ty::<SemId>::U32;
+        let int_id = insert(&mut sys, int.clone());
+        let rec = record([("a", int_id), ("b", int_id)]);
+        let rec_id = insert(&mut sys, rec.clone());
+
+        assert_eq!(int.encoded_size_range(&sys), (4, Some(4)));
+        assert_eq!(rec.encoded_size_range(&sys), (8, Some(8)));
+        assert_eq!(Ty::List(rec_id, Sizing::new(1, 10)).encoded_size_range(&sys), (9, Some(81)));
+        assert_eq!(Ty::Array(rec_id, 3).encoded_size_range(&sys), (24, Some(24)));
+        assert_eq!(
+            Ty::Map(int_id, rec_id, Sizing::new(0, u16::MAX as u64)).encoded_size_range(&sys),
+            (2, Some(2 + 12 * u16::MAX as u64))
+        );
+        assert_eq!(Ty::List(rec_id, Sizing::new(2, u64::MAX)).encoded_size_range(&sys), (24, None));
+        assert_eq!(Ty::List(SemId::unit(), Sizing::U8).encoded_size_range(&sys), (1, None));
+        assert!(!rec.is_recursive(&sys));
+    }
+
+    #[test]
+    fn validate_refs() {
+        let mut sys = TypeSystem::new();
+        let int_id = insert(&mut sys, Ty::U32);
+        let missing = Ty::<SemId>::U64.sem_id_unnamed();
+        assert_eq!(record([("a", int_id)]).validate_refs(&sys), Ok(()));
+        assert_eq!(
+            record([("a", int_id), ("b", missing), ("c", missing)]).validate_refs(&sys),
+            Err(vec![missing])
+        );
+    }
+
+    #[test]
+    fn recursive() {
+        let mut sys = TypeSystem::new();
+        let list_id = Ty::<InlineRef>::UNIT.sem_id_named(&tn!("List"));
+        let node_id = Ty::<InlineRef>::UNIT.sem_id_named(&tn!("Node"));
+        let node_ty = record([("children", list_id)]);
+        let list_ty = Ty::List(node_id, Sizing::U8);
+        sys.insert_unchecked(node_id, node_ty.clone()).unwrap();
+        sys.insert_unchecked(list_id, list_ty.clone()).unwrap();
+
+        assert!(node_ty.is_recursive(&sys));
+        assert!(list_ty.is_recursive(&sys));
+        assert_eq!(node_ty.encoded_size_range(&sys), (1, None));
+        assert!(!Ty::List(list_id, Sizing::U8).is_recursive(&TypeSystem::new()));
+    }
+}
@@ -21,21 +21,55 @@
 // limitations under the License.
 
 use std::fmt;
-use std::fmt::{Display, Formatter};
+use std::fmt::{Display, Formatter, Write};
 
 use amplify::confinement::LargeVec;
+use amplify::Wrapper;
+use encoding::{Sizing, StrictDeserialize, StrictSerialize};
 use strict_encoding::STRICT_TYPES_LIB;
 
 use super::vesper::TypeVesper;
-use crate::typesys::{TypeInfo, TypeTree};
+use crate::ast::ItemCase;
+use crate::typesys::{NestedCase, TypeInfo, TypeTree};
+use crate::value::SizingExt;
+use crate::Ty;
+
+/// Minimal and maximal number of bytes taken by a strict-encoded value; the maximum is `None` if
+/// the size is not bounded.
+pub type SizeRange = (u64, Option<u64>);
+
+pub(crate) fn fixed(size: u64) -> SizeRange { (size, Some(size)) }
+
+pub(crate) fn sum(a: SizeRange, b: SizeRange) -> SizeRange {
+    (a.0.saturating_add(b.0), a.1.zip(b.1).and_then(|(a, b)| a.checked_add(b)))
+}
+
+pub(crate) fn times(range: SizeRange, min: u64, max: Option<u64>) -> SizeRange {
+    let upper = match (range.1, max) {
+        (Some(0), _) => Some(0),
+        (Some(size), Some(max)) => size.checked_mul(max),
+        _ => None,
+    };
+    (range.0.saturating_mul(min), upper)
+}
+
+/// Size of a collection with a length prefix followed by elements of size `range`.
+pub(crate) fn prefixed(sizing: Sizing, range: SizeRange) -> SizeRange {
+    let max = if sizing.max == u64::MAX { None } else { Some(sizing.max) };
+    sum(fixed(sizing.byte_size() as u64), times(range, sizing.min, max))
+}
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 #[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
 #[strict_type(lib = STRICT_TYPES_LIB)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MemoryLayout {
     items: LargeVec<TypeInfo>,
 }
 
+impl StrictSerialize for MemoryLayout {}
+impl StrictDeserialize for MemoryLayout {}
+
 impl From<TypeTree<'_>> for MemoryLayout {
     fn from(tree: TypeTree) -> Self {
         let mut layout = MemoryLayout::new();
@@ -61,11 +95,82 @@ impl Display for MemoryLayout {
 impl MemoryLayout {
     fn new() -> Self { Self { items: empty!() } }
 
+    #[cfg(feature = "serde")]
+    pub(crate) fn items(&self) -> &[TypeInfo] { self.items.as_slice() }
+
+    /// Computes the range of byte sizes a value of this layout takes when strict-encoded.
+    pub fn encoded_size_range(&self) -> SizeRange { self.item_size_range(0, &self.children()) }
+
+    /// Returns list of child item positions for each of the layout items.
+    pub(crate) fn children(&self) -> Vec<Vec<usize>> {
+        let mut children = vec![vec![]; self.items.len()];
+        let mut stack: Vec<usize> = vec![];
+        for (pos, item) in self.items.iter().enumerate() {
+            while stack.last().is_some_and(|parent| self.items[*parent].depth >= item.depth) {
+                stack.pop();
+            }
+            if let Some(parent) = stack.last() {
+                children[*parent].push(pos);
+            }
+            stack.push(pos);
+        }
+        children
+    }
+
+    fn item_size_range(&self, pos: usize, children: &[Vec<usize>]) -> SizeRange {
+        let item = &self.items[pos];
+        let mut inner = children[pos].iter().map(|no| self.item_size_range(*no, children));
+
+        let rstr = item.nested.iter().find_map(|case| match case {
+            NestedCase::RStr(_, _, sizing) => Some(*sizing),
+            _ => None,
+        });
+        let mut range = match (&item.ty, rstr) {
+            (Ty::Tuple(_), Some(sizing)) => {
+                let rest = Sizing::new(sizing.min.saturating_sub(1), sizing.max - 1);
+                sum(fixed(1), prefixed(rest, fixed(1)))
+            }
+            // Leaf items are strings and byte collections, taking a byte per element
+            (ty, _) => ty.size_range_with(|_| inner.next().unwrap_or(fixed(1))),
+        };
+
+        for case in &item.nested {
+            if *case == NestedCase::Option {
+                range = (1, range.1.and_then(|max| max.checked_add(1)));
+            }
+        }
+        range
+    }
+
+    /// Generates graphviz `.dot` directed graph of the layout, where each of the layout items is
+    /// a vertex and each parent-child relation is an edge.
+    pub fn to_dot(&self) -> String {
+        let mut dot = s!("digraph layout {\n    node [shape=box];\n");
+        for (pos, item) in self.items.iter().enumerate() {
+            let expr = item.to_vesper();
+            writeln!(dot, "    n{pos} [label=\"{}\\n{}\"];", expr.subject, expr.predicate).ok();
+        }
+        for (parent, children) in self.children().into_iter().enumerate() {
+            for child in children {
+                let style = match &self.items[child].item {
+                    Some(ItemCase::NamedField(_, name)) => format!("label=\"{name}\""),
+                    Some(ItemCase::UnnamedField(pos)) => format!("label=\"{pos}\""),
+                    Some(ItemCase::UnionVariant(tag, _)) => format!("label=\"{tag}\", color=blue"),
+                    Some(_) => s!("style=dashed"),
+                    None => s!("style=solid"),
+                };
+                writeln!(dot, "    n{parent} -> n{child} [{style}];").ok();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
     pub fn to_vesper(&self) -> TypeVesper {
         let mut root = None;
         let mut path: Vec<usize> = vec![];
         for item in &self.items {
-            let expr = item.to_vesper();
+            let expr = item.to_vesper().into_inner();
             let depth = item.depth;
 
             if path.is_empty() && depth == 0 {
@@ -94,6 +199,42 @@ impl MemoryLayout {
                 .push(Box::new(expr))
                 .expect("invalid type layout containing too much items");
         }
-        root.expect("invalid type layout with zero items")
+        root.expect("invalid type layout with zero items").into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::value::test_helpers::{test_system, Nominal};
+
+    #[test]
+    fn size_range() {
+        let sys = test_system();
+        assert_eq!(sys.encoded_size_range("TestLib.Precision"), Some((1, Some(1))));
+        assert_eq!(sys.encoded_size_range("Std.Bool"), Some((1, Some(1))));
+        assert_eq!(sys.encoded_size_range("TestLib.Nominal"), Some((5, Some(135))));
+        assert_eq!(sys.encoded_size_range("TestLib.Missing"), None);
+
+        let (min, max) = sys.encoded_size_range("TestLib.Nominal").unwrap();
+        let len = Nominal::with("T", "N", 0).to_strict_serialized::<1000>().unwrap().len() as u64;
+        assert_eq!(len, min);
+        let ticker = "T".repeat(100);
+        let name = "N".repeat(32);
+        let nominal = Nominal::with(ticker.leak(), name.leak(), 2);
+        let len = nominal.to_strict_serialized::<1000>().unwrap().len() as u64;
+        assert_eq!(Some(len), max);
+    }
+
+    #[test]
+    fn dot() {
+        let sys = test_system();
+        let dot = sys.type_tree("TestLib.Nominal").unwrap().to_layout().to_dot();
+        assert!(dot.starts_with("digraph layout {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains("    n0 [label=\"Nominal\\nrec\"];\n"));
+        assert!(dot.contains("[label=\"precision\\nenum\"];\n"));
+        assert!(dot.contains("    n0 -> n1 [label=\"ticker\"];\n"));
+        assert!(dot.contains("[label=\"precision\"];\n"));
     }
 }
@@ -24,4 +24,5 @@ pub mod vesper;
 mod translate;
 mod memory;
 
-pub use memory::MemoryLayout;
+pub(crate) use memory::{fixed, prefixed, sum, times};
+pub use memory::{MemoryLayout, SizeRange};
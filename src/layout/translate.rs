@@ -22,6 +22,7 @@
 
 use amplify::confinement::Confined;
 use encoding::Primitive;
+use vesper::TExpr;
 
 use super::vesper::{Attr, Pred, TypeVesper};
 use crate::ast::ItemCase;
@@ -96,7 +97,7 @@ impl TypeInfo {
             }
             _ => {}
         }
-        if ty.is_char_enum() {
+        if ty.is_ascii_char() {
             predicate = Pred::Char;
         } else if ty.is_byte_array() {
             predicate = Pred::Bytes;
@@ -143,12 +144,12 @@ impl TypeInfo {
             attributes.push(Attr::Tag(*pos));
         }
 
-        TypeVesper {
+        TypeVesper::from(TExpr {
             subject,
             predicate,
             attributes: Confined::from_checked(attributes),
             content: none!(),
             comment,
-        }
+        })
     }
 }
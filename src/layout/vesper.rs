@@ -20,37 +20,137 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::fmt;
 use std::fmt::{Display, Formatter};
-use std::ops::Range;
+use std::str::FromStr;
+use std::{fmt, io};
 
+use amplify::confinement::{Confined, SmallString, SmallVec, TinyVec};
 use amplify::num::u24;
-use encoding::{Ident, Sizing};
+use encoding::{
+    DecodeError, Ident, InvalidRString, Sizing, StrictDecode, StrictDeserialize, StrictDumb,
+    StrictEncode, StrictSerialize, StrictType, TypeName, TypedRead, TypedWrite, STRICT_TYPES_LIB,
+};
 use vesper::{AttrVal, Attribute, Expression, Predicate, TExpr};
 
 use crate::Cls;
 
-pub type TypeVesper = TExpr<Pred>;
+/// Vesper expression describing type memory layout.
+///
+/// The expression can be strict-encoded, serialized with serde and parsed back from its
+/// [`Display`] representation.
+#[derive(Wrapper, WrapperMut, Clone, Eq, PartialEq, Debug, From)]
+#[wrapper(Deref)]
+#[wrapper_mut(DerefMut)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(into = "VesperRepr", from = "VesperRepr")
+)]
+pub struct TypeVesper(TExpr<Pred>);
 
-#[derive(Clone, Eq, PartialEq, Hash, Debug, Display)]
+impl Display for TypeVesper {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { Display::fmt(&self.0.display(), f) }
+}
+
+/// Strict-encodable representation of [`TypeVesper`], since [`TExpr`] is a foreign type.
+///
+/// Comments exceeding 64kiB are not preserved.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = STRICT_TYPES_LIB, rename = "TypeVesper")]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct VesperRepr {
+    subject: Ident,
+    predicate: Pred,
+    attributes: SmallVec<Attr>,
+    content: TinyVec<VesperRepr>,
+    comment: Option<SmallString>,
+}
+
+impl From<&TExpr<Pred>> for VesperRepr {
+    fn from(expr: &TExpr<Pred>) -> Self {
+        VesperRepr {
+            subject: expr.subject.clone(),
+            predicate: expr.predicate,
+            attributes: expr.attributes.clone(),
+            content: Confined::from_checked(
+                expr.content.iter().map(|expr| VesperRepr::from(expr.as_ref())).collect(),
+            ),
+            comment: expr.comment.clone().and_then(|comment| SmallString::try_from(comment).ok()),
+        }
+    }
+}
+
+impl From<TypeVesper> for VesperRepr {
+    fn from(vesper: TypeVesper) -> Self { VesperRepr::from(&vesper.0) }
+}
+
+impl From<VesperRepr> for TExpr<Pred> {
+    fn from(repr: VesperRepr) -> Self {
+        TExpr {
+            subject: repr.subject,
+            predicate: repr.predicate,
+            attributes: repr.attributes,
+            content: Confined::from_checked(
+                repr.content.into_iter().map(|repr| Box::new(TExpr::from(repr))).collect(),
+            ),
+            comment: repr.comment.map(SmallString::release),
+        }
+    }
+}
+
+impl From<VesperRepr> for TypeVesper {
+    fn from(repr: VesperRepr) -> Self { TypeVesper(repr.into()) }
+}
+
+impl StrictType for TypeVesper {
+    const STRICT_LIB_NAME: &'static str = STRICT_TYPES_LIB;
+    fn strict_name() -> Option<TypeName> { Some(tn!("TypeVesper")) }
+}
+
+impl StrictDumb for TypeVesper {
+    fn strict_dumb() -> Self { VesperRepr::strict_dumb().into() }
+}
+
+impl StrictEncode for TypeVesper {
+    fn strict_encode<W: TypedWrite>(&self, writer: W) -> io::Result<W> {
+        VesperRepr::from(&self.0).strict_encode(writer)
+    }
+}
+
+impl StrictDecode for TypeVesper {
+    fn strict_decode(reader: &mut impl TypedRead) -> Result<Self, DecodeError> {
+        VesperRepr::strict_decode(reader).map(TypeVesper::from)
+    }
+}
+
+impl StrictSerialize for TypeVesper {}
+impl StrictDeserialize for TypeVesper {}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Display)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = STRICT_TYPES_LIB, tags = repr, into_u8, try_from_u8)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
 #[display(lowercase)]
+#[repr(u8)]
 pub enum Pred {
     /// Type alias
-    Is,
-    Tuple,
-    Rec,
-    Enum,
-    Union,
+    #[strict_type(dumb)]
+    Is = 0,
+    Tuple = 1,
+    Rec = 2,
+    Enum = 3,
+    Union = 4,
     // Composites
-    Char,
-    Str,
-    Ascii,
-    Bytes,
+    Char = 0x10,
+    Str = 0x11,
+    Ascii = 0x12,
+    Bytes = 0x13,
     // Collections:
-    Array,
-    List,
-    Set,
-    Map,
+    Array = 0x20,
+    List = 0x21,
+    Set = 0x22,
+    Map = 0x23,
 }
 
 impl Predicate for Pred {
@@ -87,6 +187,9 @@ pub enum AttrExpr {
 impl Expression for AttrExpr {}
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = STRICT_TYPES_LIB, tags = order, dumb = Self::Option)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
 pub enum Attr {
     TypeName(Ident),
     Wrapped(Option<Ident>),
@@ -136,16 +239,19 @@ impl Attribute for Attr {
 }
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
-pub struct LenRange(Range<u64>);
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = STRICT_TYPES_LIB)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+pub struct LenRange(Sizing);
 
 impl From<Sizing> for LenRange {
     #[inline]
-    fn from(sizing: Sizing) -> Self { Self(sizing.min..sizing.max) }
+    fn from(sizing: Sizing) -> Self { Self(sizing) }
 }
 
 impl Display for LenRange {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        match (self.0.start, self.0.end) {
+        match (self.0.min, self.0.max) {
             (min, max) if max == u8::MAX as u64 => write!(f, "{min}..MAX8"),
             (min, max) if max == u16::MAX as u64 => write!(f, "{min}..MAX16"),
             (min, max) if max == u24::MAX.into_u64() => write!(f, "{min}..MAX24"),
@@ -156,3 +262,243 @@ impl Display for LenRange {
         }
     }
 }
+
+impl FromStr for LenRange {
+    type Err = VesperParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || VesperParseError::InvalidRange(s.to_owned());
+        let (min, max) = s.split_once("..").ok_or_else(err)?;
+        let max = match max {
+            "MAX8" => u8::MAX as u64,
+            "MAX16" => u16::MAX as u64,
+            "MAX24" => u24::MAX.into_u64(),
+            "MAX32" => u32::MAX as u64,
+            "MAX64" => u64::MAX,
+            max => max.parse().map_err(|_| err())?,
+        };
+        let min = min.parse().map_err(|_| err())?;
+        Ok(LenRange(Sizing::new(min, max)))
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum VesperParseError {
+    /// empty vesper expression.
+    Empty,
+
+    /// line {0} has invalid indentation.
+    Indentation(usize),
+
+    /// line {0} must contain a predicate and a subject.
+    NoSubject(usize),
+
+    /// unknown predicate `{0}`.
+    UnknownPredicate(String),
+
+    /// invalid attribute `{0}`.
+    InvalidAttr(String),
+
+    /// invalid length range `{0}`.
+    InvalidRange(String),
+
+    /// unterminated attribute block started at line {0}.
+    Unterminated(usize),
+
+    #[from]
+    #[display(inner)]
+    InvalidIdent(InvalidRString),
+}
+
+impl FromStr for Pred {
+    type Err = VesperParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "is" => Pred::Is,
+            "tuple" => Pred::Tuple,
+            "rec" => Pred::Rec,
+            "enum" => Pred::Enum,
+            "union" => Pred::Union,
+            "char" => Pred::Char,
+            "str" => Pred::Str,
+            "ascii" => Pred::Ascii,
+            "bytes" => Pred::Bytes,
+            "array" => Pred::Array,
+            "list" => Pred::List,
+            "set" => Pred::Set,
+            "map" => Pred::Map,
+            other => return Err(VesperParseError::UnknownPredicate(other.to_owned())),
+        })
+    }
+}
+
+impl FromStr for Attr {
+    type Err = VesperParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || VesperParseError::InvalidAttr(s.to_owned());
+        let Some((name, val)) = s.split_once(' ') else {
+            return Ok(match s {
+                "wrapped" => Attr::Wrapped(None),
+                "option" => Attr::Option,
+                name => Attr::TypeName(Ident::from_str(name)?),
+            });
+        };
+        Ok(match name {
+            "aka" => Attr::Wrapped(Some(Ident::from_str(val)?)),
+            "tag" => Attr::Tag(val.parse().map_err(|_| err())?),
+            "len" if val.contains("..") => Attr::LenRange(val.parse()?),
+            "len" => Attr::Len(val.parse().map_err(|_| err())?),
+            "charset" => Attr::AsciiEnum(Ident::from_str(val)?),
+            "first" => Attr::AsciiFirst(Ident::from_str(val)?),
+            "rest" => Attr::AsciiRest(Ident::from_str(val)?),
+            name => Attr::EnumVariant(val.parse().map_err(|_| err())?, Ident::from_str(name)?),
+        })
+    }
+}
+
+impl FromStr for TypeVesper {
+    type Err = VesperParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Joining attribute blocks spanning multiple lines into a single line
+        let mut lines = vec![];
+        let mut iter = s.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+        while let Some((no, line)) = iter.next() {
+            let Some(head) = line.strip_suffix(" {") else {
+                lines.push((no + 1, line.to_owned()));
+                continue;
+            };
+            let mut attrs = vec![];
+            let comment;
+            loop {
+                let (_, line) = iter.next().ok_or(VesperParseError::Unterminated(no + 1))?;
+                let line = line.trim();
+                if let Some(rest) = line.strip_prefix('}') {
+                    comment = rest.strip_prefix(" -- ");
+                    break;
+                }
+                attrs.extend(line.split(", "));
+            }
+            let mut line = format!("{head}, {}", attrs.join(", "));
+            if let Some(comment) = comment {
+                line.push_str(" -- ");
+                line.push_str(comment);
+            }
+            lines.push((no + 1, line));
+        }
+
+        let mut stack: Vec<TExpr<Pred>> = vec![];
+        for (no, line) in lines {
+            let body = line.trim_start_matches(' ');
+            let indent = line.len() - body.len();
+            if indent % 2 != 0 || indent / 2 > stack.len() || (indent == 0 && !stack.is_empty()) {
+                return Err(VesperParseError::Indentation(no));
+            }
+            while stack.len() > indent / 2 {
+                let expr = stack.pop().expect("stack is not empty");
+                push_child(&mut stack, expr);
+            }
+
+            let (body, comment) = match body.split_once(" -- ") {
+                Some((body, comment)) => (body, Some(comment.to_owned())),
+                None => (body, None),
+            };
+            let mut items = body.split(", ");
+            let head = items.next().unwrap_or_default();
+            let (predicate, subject) =
+                head.split_once(' ').ok_or(VesperParseError::NoSubject(no))?;
+            let attributes = items.map(Attr::from_str).collect::<Result<Vec<_>, _>>()?;
+            stack.push(TExpr {
+                subject: Ident::from_str(subject)?,
+                predicate: predicate.parse()?,
+                attributes: Confined::try_from(attributes)
+                    .map_err(|_| VesperParseError::InvalidAttr(body.to_owned()))?,
+                content: none!(),
+                comment,
+            });
+        }
+        while stack.len() > 1 {
+            let expr = stack.pop().expect("stack is not empty");
+            push_child(&mut stack, expr);
+        }
+        stack.pop().map(TypeVesper).ok_or(VesperParseError::Empty)
+    }
+}
+
+fn push_child(stack: &mut [TExpr<Pred>], expr: TExpr<Pred>) {
+    let parent = stack.last_mut().expect("indentation is checked");
+    parent.content.push(Box::new(expr)).expect("too many nested vesper expressions");
+}
+
+#[cfg(test)]
+mod test {
+    use encoding::{StrictDeserialize, StrictSerialize};
+
+    use super::*;
+    use crate::layout::MemoryLayout;
+    use crate::value::test_helpers::test_system;
+
+    #[test]
+    fn layout_roundtrip() {
+        let sys = test_system();
+        let layout = sys.type_tree("TestLib.Nominal").unwrap().to_layout();
+        let data = layout.to_strict_serialized::<{ usize::MAX }>().unwrap();
+        let decoded = MemoryLayout::from_strict_serialized::<{ usize::MAX }>(data).unwrap();
+        assert_eq!(decoded.to_string(), layout.to_string());
+
+        let vesper = layout.to_vesper();
+        let data = vesper.to_strict_serialized::<{ usize::MAX }>().unwrap();
+        let decoded = TypeVesper::from_strict_serialized::<{ usize::MAX }>(data).unwrap();
+        assert_eq!(decoded, vesper);
+        assert_eq!(decoded.to_string(), layout.to_string());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn layout_serde() {
+        let sys = test_system();
+        let layout = sys.type_tree("TestLib.Nominal").unwrap().to_layout();
+        let json = serde_json::to_string(&layout).unwrap();
+        let decoded: MemoryLayout = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, layout);
+
+        let vesper = layout.to_vesper();
+        let json = serde_json::to_string(&vesper).unwrap();
+        assert!(json.contains(r#""predicate":"rec""#));
+        let decoded: TypeVesper = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, vesper);
+    }
+
+    #[test]
+    fn parse() {
+        let sys = test_system();
+        let vesper = sys.type_tree("TestLib.Nominal").unwrap().to_layout().to_vesper();
+        assert_eq!(TypeVesper::from_str(&vesper.to_string()).unwrap(), vesper);
+
+        for fqn in sys.search("*") {
+            let vesper = sys.type_tree(fqn).unwrap().to_layout().to_vesper();
+            assert_eq!(TypeVesper::from_str(&vesper.to_string()).unwrap(), vesper);
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(TypeVesper::from_str(""), Err(VesperParseError::Empty));
+        assert_eq!(TypeVesper::from_str("rec"), Err(VesperParseError::NoSubject(1)));
+        assert_eq!(
+            TypeVesper::from_str("rec Some\n   is field, U8"),
+            Err(VesperParseError::Indentation(2))
+        );
+        assert_eq!(
+            TypeVesper::from_str("record Some"),
+            Err(VesperParseError::UnknownPredicate(s!("record")))
+        );
+        assert_eq!(
+            TypeVesper::from_str("list Some, len 1..MAX"),
+            Err(VesperParseError::InvalidRange(s!("1..MAX")))
+        );
+    }
+}
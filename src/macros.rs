@@ -95,3 +95,22 @@ macro_rules! variants {
         }
     };
 }
+
+/// Includes a type library previously serialized in binary form (for instance, with
+/// [`TypeLib::export_build`](crate::TypeLib::export_build)) into the crate.
+///
+/// # Panics
+///
+/// If the file doesn't contain a valid type library.
+#[macro_export]
+macro_rules! include_type_lib {
+    ($path:expr) => {
+        <$crate::TypeLib as ::strict_encoding::StrictDeserialize>::from_strict_serialized::<
+            { ::amplify::confinement::U24 },
+        >(
+            ::amplify::confinement::Confined::try_from(include_bytes!($path).to_vec())
+                .expect("type library data exceed maximal size"),
+        )
+        .expect("invalid type library data")
+    };
+}
@@ -0,0 +1,182 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Property-based testing strategies producing type libraries.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use amplify::num::u24;
+use encoding::{FieldName, Sizing, TypeName, Variant, VariantName};
+use proptest::collection::{btree_map, btree_set, vec};
+use proptest::prelude::*;
+use proptest::sample::select;
+
+use crate::ast::Field;
+use crate::typelib::TranspileRef;
+use crate::{LibBuilder, Ty, TypeLib};
+
+const MAX_TYPES: usize = 6;
+
+fn ident() -> impl Strategy<Value = String> { "[a-z][a-zA-Z0-9]{0,6}" }
+
+fn type_name(no: usize) -> TypeName { tn!(format!("Type{no}")) }
+
+fn sizing() -> impl Strategy<Value = Sizing> {
+    let max = [u8::MAX as u64, u16::MAX as u64, u24::MAX.into_u64(), u32::MAX as u64, u64::MAX];
+    (0..2u64, select(max.to_vec())).prop_map(|(min, max)| Sizing::new(min, max))
+}
+
+/// Strategy for references to primitive types or to the library types with names from `known`.
+fn type_ref(known: Vec<TypeName>) -> BoxedStrategy<TranspileRef> {
+    let prims = [Ty::U8, Ty::U16, Ty::U32, Ty::U64, Ty::I8, Ty::I64, Ty::UNIT, Ty::BYTE];
+    let prim = select(prims.to_vec()).prop_map(|ty| TranspileRef::Embedded(Box::new(ty)));
+    if known.is_empty() {
+        return prim.boxed();
+    }
+    prop_oneof![prim, select(known).prop_map(TranspileRef::Named)].boxed()
+}
+
+/// Strategy for types, which may refer to the library types with names from `known`.
+fn ty(known: Vec<TypeName>) -> BoxedStrategy<Ty<TranspileRef>> {
+    type_ref(known.clone())
+        .prop_map(|r| match r {
+            TranspileRef::Embedded(ty) => *ty,
+            named => Ty::Tuple(vec![named].try_into().unwrap()),
+        })
+        .prop_recursive(2, 16, 4, move |inner| {
+            let r = prop_oneof![
+                type_ref(known.clone()),
+                inner.prop_map(|ty| TranspileRef::Embedded(Box::new(ty)))
+            ];
+            prop_oneof![
+                btree_set(ident(), 1..6).prop_map(|names| {
+                    let variants = names
+                        .into_iter()
+                        .enumerate()
+                        .map(|(tag, name)| {
+                            Variant::named(tag as u8, VariantName::try_from(name).unwrap())
+                        })
+                        .collect::<BTreeSet<_>>();
+                    Ty::Enum(variants.try_into().unwrap())
+                }),
+                btree_map(ident(), r.clone(), 1..4).prop_map(|variants| {
+                    let variants = variants
+                        .into_iter()
+                        .enumerate()
+                        .map(|(tag, (name, ty))| {
+                            (Variant::named(tag as u8, VariantName::try_from(name).unwrap()), ty)
+                        })
+                        .collect::<BTreeMap<_, _>>();
+                    Ty::Union(variants.try_into().unwrap())
+                }),
+                btree_map(ident(), r.clone(), 1..4).prop_map(|fields| {
+                    let fields = fields
+                        .into_iter()
+                        .map(|(name, ty)| Field {
+                            name: FieldName::try_from(name).unwrap(),
+                            ty,
+                        })
+                        .collect::<Vec<_>>();
+                    Ty::Struct(fields.try_into().unwrap())
+                }),
+                vec(r.clone(), 1..4).prop_map(|fields| Ty::Tuple(fields.try_into().unwrap())),
+                (r.clone(), 1..8u16).prop_map(|(ty, len)| Ty::Array(ty, len)),
+                (r.clone(), sizing()).prop_map(|(ty, sizing)| Ty::List(ty, sizing)),
+                (r.clone(), sizing()).prop_map(|(ty, sizing)| Ty::Set(ty, sizing)),
+                (r.clone(), r, sizing()).prop_map(|(key, ty, sizing)| Ty::Map(key, ty, sizing)),
+            ]
+        })
+        .boxed()
+}
+
+/// Produces libraries without dependencies, having up to six types, each of which may refer to
+/// the types defined before it.
+impl Arbitrary for TypeLib {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (1..=MAX_TYPES)
+            .prop_flat_map(|count| {
+                (0..count).map(|no| ty((0..no).map(type_name).collect())).collect::<Vec<_>>()
+            })
+            .prop_map(|types| {
+                let mut builder = LibBuilder::new(libname!("TestLib"), []);
+                builder
+                    .types
+                    .extend(types.into_iter().enumerate().map(|(no, ty)| (type_name(no), ty)));
+                builder.compile().expect("invalid library generated")
+            })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use encoding::{StrictDeserialize, StrictSerialize};
+    use proptest::test_runner::TestCaseError;
+
+    use super::*;
+    use crate::typesys::SystemBuilder;
+
+    proptest! {
+        #[test]
+        fn serialize(lib in any::<TypeLib>()) {
+            let data = lib.to_strict_serialized::<{ usize::MAX }>().unwrap();
+            let decoded = TypeLib::from_strict_serialized::<{ usize::MAX }>(data).unwrap();
+            prop_assert_eq!(decoded.id(), lib.id());
+            prop_assert_eq!(decoded, lib);
+        }
+
+        #[test]
+        fn recompile(lib in any::<TypeLib>()) {
+            let recompiled = LibBuilder::from_type_lib(&lib, []).unwrap().compile().unwrap();
+            prop_assert_eq!(recompiled, lib);
+        }
+
+        #[test]
+        fn values(lib in any::<TypeLib>()) {
+            let sys = SystemBuilder::new().import(lib).unwrap().finalize().unwrap();
+            let types = sys.as_types();
+            for sem_id in types.as_unconfined().keys() {
+                let Some(strategy) = types.value_strategy(*sem_id) else {
+                    continue;
+                };
+                let mut runner = proptest::test_runner::TestRunner::new(ProptestConfig::with_cases(8));
+                runner.run(&strategy, |val| {
+                    let typed = types
+                        .typify(val.clone(), *sem_id)
+                        .map_err(|err| TestCaseError::fail(err.to_string()))?;
+                    let data = types
+                        .strict_serialize_value::<{ usize::MAX }>(&typed)
+                        .map_err(|err| TestCaseError::fail(err.to_string()))?;
+                    let decoded = types
+                        .strict_deserialize_type(*sem_id, &data)
+                        .map_err(|err| TestCaseError::fail(err.to_string()))?;
+                    prop_assert_eq!(decoded.unbox(), val);
+                    Ok(())
+                })
+                .map_err(|err| TestCaseError::fail(err.to_string()))?;
+            }
+        }
+    }
+}
@@ -0,0 +1,407 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Export of type libraries into Apache Avro schemata.
+//!
+//! Each library type becomes an Avro named type in the namespace matching the library name:
+//! structs and tuples are exported as records, enums as enums and byte arrays as fixed; other
+//! types are wrapped into a record with a single `value` field, which doesn't affect Avro binary
+//! encoding. Newtypes over anonymous types are exported as the wrapped type. Named types are
+//! defined at their first use and referenced by their full name afterwards; types from the
+//! dependencies are referenced by full name only, requiring the schemata of the dependencies to be
+//! parsed first.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use encoding::{LibName, NumCls, Primitive, TypeName};
+use serde_json::{json, Value};
+
+use crate::typelib::{ExternRef, ExternTypes, InlineRef, InlineRef1, InlineRef2, LibRef};
+use crate::{SemId, Ty, TypeLib, TypeLibId, TypeRef};
+
+struct AvroCtx<'lib> {
+    lib: &'lib LibName,
+    types: BTreeMap<SemId, (&'lib TypeName, &'lib Ty<LibRef>)>,
+    libs: BTreeMap<TypeLibId, &'lib LibName>,
+    extern_types: &'lib ExternTypes,
+    defined: BTreeSet<String>,
+    /// Name of the library type being defined, used for naming anonymous records.
+    scope: String,
+}
+
+impl AvroCtx<'_> {
+    fn full_name(&self, name: &str) -> String { format!("{}.{name}", self.lib) }
+
+    /// Returns a name which is not yet defined, registering it.
+    fn unique_name(&mut self, base: &str) -> String {
+        let mut name = base.to_owned();
+        let mut no = 1;
+        while self.defined.contains(&self.full_name(&name)) {
+            no += 1;
+            name = format!("{base}_{no}");
+        }
+        self.defined.insert(self.full_name(&name));
+        name
+    }
+
+    fn define(&mut self, name: &TypeName, ty: &Ty<LibRef>) -> Value {
+        let full_name = self.full_name(name.as_str());
+        if !self.defined.insert(full_name.clone()) {
+            return json!(full_name);
+        }
+        let scope = std::mem::replace(&mut self.scope, name.to_string());
+        let schema = named_avro(name.as_str(), ty, self);
+        self.scope = scope;
+        schema
+    }
+
+    fn named(&mut self, sem_id: &SemId) -> Value {
+        match self.types.get(sem_id) {
+            Some((name, ty)) => self.define(name, ty),
+            None => json!({ "type": "null", "doc": format!("unknown type {sem_id}") }),
+        }
+    }
+
+    fn external(&self, ext: &ExternRef) -> Value {
+        let Some(lib) = self.libs.get(&ext.lib_id) else {
+            return json!({ "type": "null", "doc": format!("unknown library {}", ext.lib_id) });
+        };
+        match self.extern_types.get(*lib).and_then(|index| index.get(&ext.sem_id)) {
+            Some(name) => json!(format!("{lib}.{name}")),
+            None => json!({ "type": "null", "doc": format!("unknown type {lib}.{}", ext.sem_id) }),
+        }
+    }
+
+    fn fixed(&mut self, name: &str, size: impl Into<Value>) -> Value {
+        let full_name = self.full_name(name);
+        if !self.defined.insert(full_name.clone()) {
+            return json!(full_name);
+        }
+        json!({ "type": "fixed", "name": name, "namespace": self.lib.to_string(), "size": size.into() })
+    }
+}
+
+trait AvroRef: TypeRef {
+    fn avro(&self, ctx: &mut AvroCtx) -> Value;
+    /// Produces definition of the named type `name` if the referenced type is inlined.
+    fn avro_named(&self, _name: &str, _ctx: &mut AvroCtx) -> Option<Value> { None }
+}
+
+macro_rules! impl_avro_ref {
+    ($ty:ident) => {
+        impl AvroRef for $ty {
+            fn avro(&self, ctx: &mut AvroCtx) -> Value {
+                match self {
+                    $ty::Inline(ty) => ty_avro(ty, ctx),
+                    $ty::Named(sem_id) => ctx.named(sem_id),
+                    $ty::Extern(ext) => ctx.external(ext),
+                }
+            }
+
+            fn avro_named(&self, name: &str, ctx: &mut AvroCtx) -> Option<Value> {
+                match self {
+                    $ty::Inline(ty) => Some(named_avro(name, ty, ctx)),
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+impl_avro_ref!(LibRef);
+impl_avro_ref!(InlineRef);
+impl_avro_ref!(InlineRef1);
+
+impl AvroRef for InlineRef2 {
+    fn avro(&self, ctx: &mut AvroCtx) -> Value {
+        match self {
+            InlineRef2::Named(sem_id) => ctx.named(sem_id),
+            InlineRef2::Extern(ext) => ctx.external(ext),
+        }
+    }
+}
+
+/// Avro has no unsigned integers, thus they are exported as `long`, documenting the actual type.
+fn primitive_avro(prim: Primitive, ctx: &mut AvroCtx) -> Value {
+    if prim == Primitive::UNIT {
+        return json!("null");
+    }
+    if prim == Primitive::BYTE {
+        return json!({ "type": "int", "doc": prim.to_string() });
+    }
+    let info = prim.info();
+    let bits = info.byte_size() as u32 * 8;
+    match info.ty {
+        NumCls::Float if prim == Primitive::F32 => json!("float"),
+        NumCls::Float if prim == Primitive::F64 => json!("double"),
+        NumCls::Unsigned | NumCls::NonZero if bits <= 64 => {
+            json!({ "type": "long", "doc": prim.to_string() })
+        }
+        NumCls::Signed if bits < 32 => json!({ "type": "int", "doc": prim.to_string() }),
+        NumCls::Signed if bits == 32 => json!("int"),
+        NumCls::Signed if bits == 64 => json!("long"),
+        _ => ctx.fixed(&prim.to_string(), info.byte_size()),
+    }
+}
+
+/// Returns the key identifying an Avro union branch; Avro unions can't have two branches with
+/// the same key or directly nested unions.
+fn branch_key(schema: &Value) -> Option<String> {
+    match schema {
+        Value::String(name) => Some(name.clone()),
+        Value::Object(obj) => match (obj.get("name"), obj.get("namespace"), obj.get("type")) {
+            (Some(name), Some(ns), _) => Some(format!("{}.{}", ns.as_str()?, name.as_str()?)),
+            (_, _, Some(Value::String(ty))) => Some(ty.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn record(name: &str, fields: Vec<Value>, ctx: &AvroCtx) -> Value {
+    json!({ "type": "record", "name": name, "namespace": ctx.lib.to_string(), "fields": fields })
+}
+
+fn field(name: impl ToString, schema: Value) -> Value {
+    json!({ "name": name.to_string(), "type": schema })
+}
+
+/// Produces definition of the named type `name`. Newtypes over inlined types are defined as
+/// the wrapped type.
+fn named_avro<Ref: AvroRef>(name: &str, ty: &Ty<Ref>, ctx: &mut AvroCtx) -> Value {
+    if let Ty::Tuple(fields) = ty {
+        if let (true, Some(inner)) = (ty.is_newtype(), fields.iter().next()) {
+            if let Some(schema) = inner.avro_named(name, ctx) {
+                return schema;
+            }
+        }
+    }
+    match ty {
+        Ty::Enum(variants) => json!({
+            "type": "enum",
+            "name": name,
+            "namespace": ctx.lib.to_string(),
+            "symbols": variants.iter().map(|v| v.name.to_string()).collect::<Vec<_>>(),
+        }),
+        Ty::Struct(fields) => {
+            let fields = fields.iter().map(|f| field(&f.name, f.ty.avro(ctx))).collect::<Vec<_>>();
+            record(name, fields, ctx)
+        }
+        Ty::Tuple(fields) => {
+            let fields = fields
+                .iter()
+                .enumerate()
+                .map(|(no, ty)| field(format!("_{no}"), ty.avro(ctx)))
+                .collect::<Vec<_>>();
+            record(name, fields, ctx)
+        }
+        Ty::Array(_, len) if ty.is_byte_array() => json!({
+            "type": "fixed",
+            "name": name,
+            "namespace": ctx.lib.to_string(),
+            "size": len,
+        }),
+        _ => {
+            let schema = ty_avro(ty, ctx);
+            record(name, vec![field("value", schema)], ctx)
+        }
+    }
+}
+
+fn ty_avro<Ref: AvroRef>(ty: &Ty<Ref>, ctx: &mut AvroCtx) -> Value {
+    match ty {
+        Ty::Primitive(prim) => primitive_avro(*prim, ctx),
+        Ty::UnicodeChar => json!("string"),
+        Ty::Enum(_) | Ty::Struct(_) | Ty::Tuple(_) => {
+            let name = ctx.unique_name(&ctx.scope.clone());
+            named_avro(&name, ty, ctx)
+        }
+        Ty::Array(_, _) if ty.is_byte_array() => {
+            let name = ctx.unique_name(&ctx.scope.clone());
+            named_avro(&name, ty, ctx)
+        }
+        Ty::Union(variants) => {
+            let branches = variants
+                .into_iter()
+                .map(|(variant, ty)| (variant.name.to_string(), ty.avro(ctx)))
+                .collect::<Vec<_>>();
+            let mut keys = BTreeSet::new();
+            let distinct = branches
+                .iter()
+                .all(|(_, schema)| branch_key(schema).is_some_and(|key| keys.insert(key)));
+            if distinct {
+                return Value::Array(branches.into_iter().map(|(_, schema)| schema).collect());
+            }
+            let scope = ctx.scope.clone();
+            Value::Array(
+                branches
+                    .into_iter()
+                    .map(|(variant, schema)| {
+                        let name = ctx.unique_name(&format!("{scope}_{variant}"));
+                        record(&name, vec![field("value", schema)], ctx)
+                    })
+                    .collect(),
+            )
+        }
+        Ty::Array(ty, _) | Ty::Set(ty, _) => json!({ "type": "array", "items": ty.avro(ctx) }),
+        Ty::List(ty, _) if ty.is_unicode_char() => json!("string"),
+        Ty::List(ty, _) if ty.is_byte() => json!("bytes"),
+        Ty::List(ty, _) => json!({ "type": "array", "items": ty.avro(ctx) }),
+        Ty::Map(key, val, _) => {
+            let key = key.avro(ctx);
+            let val = val.avro(ctx);
+            if key == json!("string") {
+                return json!({ "type": "map", "values": val });
+            }
+            let name = ctx.unique_name(&format!("{}_Entry", ctx.scope));
+            let entry = record(&name, vec![field("key", key), field("value", val)], ctx);
+            json!({ "type": "array", "items": entry })
+        }
+    }
+}
+
+impl TypeLib {
+    /// Converts the library into an Apache Avro schema, which is a union of all the library
+    /// types.
+    ///
+    /// Strict unions are exported as Avro unions; if some of the variants can't form a valid
+    /// Avro union, each variant is wrapped into a record. Maps with non-string keys are exported
+    /// as arrays of key-value records.
+    pub fn to_avro_schema(&self) -> Value {
+        let mut ctx = AvroCtx {
+            lib: &self.name,
+            types: self
+                .types
+                .iter()
+                .map(|(name, ty)| (ty.sem_id_named(name), (name, ty)))
+                .collect(),
+            libs: self.dependencies.iter().map(|dep| (dep.id, &dep.name)).collect(),
+            extern_types: &self.extern_types,
+            defined: BTreeSet::new(),
+            scope: String::new(),
+        };
+        Value::Array(
+            self.types
+                .iter()
+                .map(|(name, ty)| ctx.define(name, ty))
+                .filter(|schema| !schema.is_string())
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::stl::{std_stl, strict_types_stl};
+    use crate::value::test_helpers::Nominal;
+    use crate::LibBuilder;
+
+    /// Checks that named types are defined once and before being referenced, and that union
+    /// branches are distinct, collecting the named type definitions.
+    fn check(schema: &Value, defined: &mut BTreeMap<String, Value>) {
+        const PRIMITIVES: [&str; 8] =
+            ["null", "boolean", "int", "long", "float", "double", "bytes", "string"];
+        match schema {
+            Value::String(name) => {
+                assert!(PRIMITIVES.contains(&name.as_str()) || defined.contains_key(name), "{name}")
+            }
+            Value::Array(branches) => {
+                let mut keys = BTreeSet::new();
+                for branch in branches {
+                    assert!(!branch.is_array());
+                    assert!(keys.insert(branch_key(branch).unwrap()), "{branch}");
+                    check(branch, defined);
+                }
+            }
+            Value::Object(obj) => {
+                if let Some(Value::String(name)) = obj.get("name") {
+                    let full_name = format!("{}.{name}", obj["namespace"].as_str().unwrap());
+                    assert!(defined.insert(full_name, schema.clone()).is_none(), "{name}");
+                }
+                match obj["type"].as_str().unwrap() {
+                    "record" => obj["fields"]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .for_each(|field| check(&field["type"], defined)),
+                    "array" => check(&obj["items"], defined),
+                    "map" => check(&obj["values"], defined),
+                    _ => {}
+                }
+            }
+            _ => panic!("invalid schema {schema}"),
+        }
+    }
+
+    #[test]
+    fn nominal() {
+        let std = std_stl();
+        let st = strict_types_stl();
+        let lib = LibBuilder::new("TestLib", [std.to_dependency(), st.to_dependency()])
+            .transpile::<Nominal>()
+            .compile()
+            .unwrap();
+        let schema = lib.to_avro_schema();
+        assert_eq!(
+            schema,
+            json!([
+                {
+                    "type": "record",
+                    "name": "Nominal",
+                    "namespace": "TestLib",
+                    "fields": [
+                        { "name": "ticker", "type": "StrictTypes.Ident" },
+                        { "name": "name", "type": "string" },
+                        {
+                            "name": "precision",
+                            "type": {
+                                "type": "enum",
+                                "name": "Precision",
+                                "namespace": "TestLib",
+                                "symbols": ["noDecimals", "oneDecimal", "twoDecimals"],
+                            },
+                        },
+                    ],
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn stl() {
+        let mut defined = BTreeMap::new();
+        check(&std_stl().to_avro_schema(), &mut defined);
+        check(&strict_types_stl().to_avro_schema(), &mut defined);
+
+        let find = |name: &str| &defined[&format!("StrictTypes.{name}")];
+        assert_eq!(
+            find("SemId"),
+            &json!({ "type": "fixed", "name": "SemId", "namespace": "StrictTypes", "size": 32 })
+        );
+        assert_eq!(
+            find("Sizing")["fields"][0],
+            json!({ "name": "min", "type": { "type": "long", "doc": "U64" } })
+        );
+        assert!(find("InlineRef2")["fields"][0]["type"].is_array());
+    }
+}
@@ -0,0 +1,379 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+
+use encoding::{FieldName, TypeName, VariantName};
+
+use crate::{LibRef, SemId, Ty, TypeLib};
+
+/// Change in a type library which breaks decoding of the data created with its previous version.
+#[derive(Clone, Eq, PartialEq, Debug, Display)]
+#[display(doc_comments)]
+pub enum BreakingChange {
+    /// type `{0}` was removed.
+    TypeRemoved(TypeName),
+
+    /// type `{0}` was changed.
+    TypeChanged(TypeName),
+
+    /// field `{ty}.{from}` was renamed into `{to}`.
+    FieldRenamed {
+        ty: TypeName,
+        from: FieldName,
+        to: FieldName,
+    },
+
+    /// type of the field `{ty}.{field}` was changed.
+    FieldTypeChanged { ty: TypeName, field: FieldName },
+
+    /// field `{ty}.{field}` was removed.
+    FieldRemoved { ty: TypeName, field: FieldName },
+
+    /// non-optional field `{ty}.{field}` was added.
+    FieldAdded { ty: TypeName, field: FieldName },
+
+    /// variant `{ty}.{variant}` was removed.
+    VariantRemoved { ty: TypeName, variant: VariantName },
+
+    /// tag or type of the variant `{ty}.{variant}` was changed.
+    VariantChanged { ty: TypeName, variant: VariantName },
+
+    /// variant `{ty}.{variant}` was added with a tag below the tags of the existing variants.
+    VariantInserted { ty: TypeName, variant: VariantName },
+}
+
+/// Additive change in a type library which doesn't break decoding of the existing data.
+#[derive(Clone, Eq, PartialEq, Debug, Display)]
+#[display(doc_comments)]
+pub enum AddedItem {
+    /// new type `{0}`.
+    Type(TypeName),
+
+    /// new optional field `{ty}.{field}`.
+    Field { ty: TypeName, field: FieldName },
+
+    /// new variant `{ty}.{variant}`.
+    Variant { ty: TypeName, variant: VariantName },
+}
+
+/// Result of the library compatibility check performed by [`TypeLib::is_compatible_with`].
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum CompatResult {
+    /// All types of both libraries are the same.
+    Compatible,
+
+    /// The new library version breaks backward compatibility.
+    BackwardIncompatible(Vec<BreakingChange>),
+
+    /// The new library version contains only additive changes.
+    ForwardOnly(Vec<AddedItem>),
+}
+
+impl CompatResult {
+    pub fn is_compatible(&self) -> bool { !matches!(self, CompatResult::BackwardIncompatible(_)) }
+}
+
+struct CompatCtx<'lib> {
+    old_names: BTreeMap<SemId, &'lib TypeName>,
+    new_names: BTreeMap<SemId, &'lib TypeName>,
+    breaking: Vec<BreakingChange>,
+    added: Vec<AddedItem>,
+}
+
+impl CompatCtx<'_> {
+    /// Compares type references, considering references to the named types equal if the types
+    /// have the same name (the named types themselves are compared separately).
+    fn same_ref(&self, old: &LibRef, new: &LibRef) -> bool {
+        match (old, new) {
+            (LibRef::Named(old), LibRef::Named(new)) => {
+                let old = self.old_names.get(old);
+                old.is_some() && old == self.new_names.get(new)
+            }
+            (LibRef::Extern(old), LibRef::Extern(new)) => old == new,
+            (LibRef::Inline(old), LibRef::Inline(new)) => old == new,
+            _ => false,
+        }
+    }
+
+    fn compare(&mut self, name: &TypeName, old: &Ty<LibRef>, new: &Ty<LibRef>) {
+        match (old, new) {
+            (Ty::Struct(old_fields), Ty::Struct(new_fields)) => {
+                for (pos, old) in old_fields.iter().enumerate() {
+                    let Some(new) = new_fields.get(pos) else {
+                        self.breaking.push(BreakingChange::FieldRemoved {
+                            ty: name.clone(),
+                            field: old.name.clone(),
+                        });
+                        continue;
+                    };
+                    if old.name != new.name {
+                        self.breaking.push(BreakingChange::FieldRenamed {
+                            ty: name.clone(),
+                            from: old.name.clone(),
+                            to: new.name.clone(),
+                        });
+                    }
+                    if !self.same_ref(&old.ty, &new.ty) {
+                        self.breaking.push(BreakingChange::FieldTypeChanged {
+                            ty: name.clone(),
+                            field: new.name.clone(),
+                        });
+                    }
+                }
+                for new in new_fields.iter().skip(old_fields.len()) {
+                    let optional = matches!(&new.ty, LibRef::Inline(ty) if ty.is_option());
+                    let (ty, field) = (name.clone(), new.name.clone());
+                    if optional {
+                        self.added.push(AddedItem::Field { ty, field });
+                    } else {
+                        self.breaking.push(BreakingChange::FieldAdded { ty, field });
+                    }
+                }
+            }
+            (Ty::Enum(old_variants), Ty::Enum(new_variants)) => {
+                let old = old_variants.iter().map(|v| (&v.name, (v.tag, None))).collect();
+                let new = new_variants.iter().map(|v| (&v.name, (v.tag, None))).collect();
+                self.compare_variants(name, old, new);
+            }
+            (Ty::Union(old_variants), Ty::Union(new_variants)) => {
+                let old = old_variants.iter().map(|(v, ty)| (&v.name, (v.tag, Some(ty)))).collect();
+                let new = new_variants.iter().map(|(v, ty)| (&v.name, (v.tag, Some(ty)))).collect();
+                self.compare_variants(name, old, new);
+            }
+            _ => self.breaking.push(BreakingChange::TypeChanged(name.clone())),
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn compare_variants(
+        &mut self,
+        name: &TypeName,
+        old: BTreeMap<&VariantName, (u8, Option<&LibRef>)>,
+        new: BTreeMap<&VariantName, (u8, Option<&LibRef>)>,
+    ) {
+        let max_tag = old.values().map(|(tag, _)| *tag).max().unwrap_or_default();
+        for (variant, (old_tag, old_ty)) in &old {
+            let (ty, variant) = (name.clone(), (*variant).clone());
+            let Some((new_tag, new_ty)) = new.get(&variant) else {
+                self.breaking.push(BreakingChange::VariantRemoved { ty, variant });
+                continue;
+            };
+            let same_ty = match (old_ty, new_ty) {
+                (None, None) => true,
+                (Some(old), Some(new)) => self.same_ref(old, new),
+                _ => false,
+            };
+            if new_tag != old_tag || !same_ty {
+                self.breaking.push(BreakingChange::VariantChanged { ty, variant });
+            }
+        }
+        for (variant, (tag, _)) in new {
+            if old.contains_key(variant) {
+                continue;
+            }
+            let (ty, variant) = (name.clone(), variant.clone());
+            if tag > max_tag {
+                self.added.push(AddedItem::Variant { ty, variant });
+            } else {
+                self.breaking.push(BreakingChange::VariantInserted { ty, variant });
+            }
+        }
+    }
+}
+
+fn type_names(lib: &TypeLib) -> BTreeMap<SemId, &TypeName> {
+    lib.types.iter().map(|(name, ty)| (ty.sem_id_named(name), name)).collect()
+}
+
+impl TypeLib {
+    /// Checks whether `other` library is a backward-compatible evolution of this library.
+    ///
+    /// Types are matched by their names. Struct types may only get new optional fields appended
+    /// at the end, and enums and unions may only get new variants with tags above the existing
+    /// ones; any other change in a type definition is considered breaking.
+    pub fn is_compatible_with(&self, other: &TypeLib) -> CompatResult {
+        let mut ctx = CompatCtx {
+            old_names: type_names(self),
+            new_names: type_names(other),
+            breaking: vec![],
+            added: vec![],
+        };
+
+        for (name, old) in &self.types {
+            match other.types.get(name) {
+                None => ctx.breaking.push(BreakingChange::TypeRemoved(name.clone())),
+                Some(new) if new.sem_id_named(name) == old.sem_id_named(name) => {}
+                Some(new) => ctx.compare(name, old, new),
+            }
+        }
+        for name in other.types.keys() {
+            if !self.types.contains_key(name) {
+                ctx.added.push(AddedItem::Type(name.clone()));
+            }
+        }
+
+        if !ctx.breaking.is_empty() {
+            CompatResult::BackwardIncompatible(ctx.breaking)
+        } else if !ctx.added.is_empty() {
+            CompatResult::ForwardOnly(ctx.added)
+        } else {
+            CompatResult::Compatible
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::confinement::NonEmptyString;
+    use encoding::Ident;
+
+    use super::*;
+    use crate::stl::{std_stl, strict_types_stl};
+    use crate::value::test_helpers::Nominal;
+    use crate::LibBuilder;
+
+    mod v2 {
+        use super::*;
+
+        #[derive(Copy, Clone, Eq, PartialEq, Debug)]
+        #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+        #[strict_type(lib = "TestLib", tags = repr, into_u8, try_from_u8)]
+        #[repr(u8)]
+        pub enum Precision {
+            #[strict_type(dumb)]
+            NoDecimals = 0,
+            OneDecimal = 1,
+            TwoDecimals = 2,
+            ThreeDecimals = 3,
+        }
+
+        #[derive(Clone, Eq, PartialEq, Debug)]
+        #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+        #[strict_type(lib = "TestLib", dumb = { Nominal {
+            ticker: strict_dumb!(),
+            name: NonEmptyString::try_from(s!("Dumb")).unwrap(),
+            precision: strict_dumb!(),
+            memo: None,
+        } })]
+        pub struct Nominal {
+            pub ticker: Ident,
+            pub name: NonEmptyString<32>,
+            pub precision: Precision,
+            pub memo: Option<u8>,
+        }
+    }
+
+    mod v3 {
+        use super::*;
+
+        #[derive(Copy, Clone, Eq, PartialEq, Debug)]
+        #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+        #[strict_type(lib = "TestLib", tags = repr, into_u8, try_from_u8)]
+        #[repr(u8)]
+        pub enum Precision {
+            #[strict_type(dumb)]
+            NoDecimals = 0,
+            OneDecimal = 1,
+        }
+
+        #[derive(Clone, Eq, PartialEq, Debug)]
+        #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+        #[strict_type(lib = "TestLib", dumb = { Nominal {
+            ticker: strict_dumb!(),
+            title: NonEmptyString::try_from(s!("Dumb")).unwrap(),
+            precision: strict_dumb!(),
+        } })]
+        pub struct Nominal {
+            pub ticker: Ident,
+            pub title: NonEmptyString<32>,
+            pub precision: Precision,
+        }
+    }
+
+    fn lib<T: encoding::StrictEncode + encoding::StrictDumb>() -> TypeLib {
+        let std = std_stl();
+        let st = strict_types_stl();
+        LibBuilder::new("TestLib", [std.to_dependency(), st.to_dependency()])
+            .transpile::<T>()
+            .compile()
+            .unwrap()
+    }
+
+    #[test]
+    fn compatible() {
+        let v1 = lib::<Nominal>();
+        assert_eq!(v1.is_compatible_with(&v1), CompatResult::Compatible);
+    }
+
+    #[test]
+    fn additive() {
+        let v1 = lib::<Nominal>();
+        let v2 = lib::<v2::Nominal>();
+        assert_eq!(
+            v1.is_compatible_with(&v2),
+            CompatResult::ForwardOnly(vec![
+                AddedItem::Field {
+                    ty: tn!("Nominal"),
+                    field: fname!("memo")
+                },
+                AddedItem::Variant {
+                    ty: tn!("Precision"),
+                    variant: vname!("threeDecimals")
+                },
+            ])
+        );
+        assert!(matches!(v2.is_compatible_with(&v1), CompatResult::BackwardIncompatible(_)));
+    }
+
+    #[test]
+    fn breaking() {
+        let v1 = lib::<Nominal>();
+        let v3 = lib::<v3::Nominal>();
+        assert_eq!(
+            v1.is_compatible_with(&v3),
+            CompatResult::BackwardIncompatible(vec![
+                BreakingChange::FieldRenamed {
+                    ty: tn!("Nominal"),
+                    from: fname!("name"),
+                    to: fname!("title")
+                },
+                BreakingChange::VariantRemoved {
+                    ty: tn!("Precision"),
+                    variant: vname!("twoDecimals")
+                },
+            ])
+        );
+
+        let precision = lib::<v3::Precision>();
+        assert_eq!(
+            v1.is_compatible_with(&precision),
+            CompatResult::BackwardIncompatible(vec![
+                BreakingChange::TypeRemoved(tn!("Nominal")),
+                BreakingChange::VariantRemoved {
+                    ty: tn!("Precision"),
+                    variant: vname!("twoDecimals")
+                },
+            ])
+        );
+    }
+}
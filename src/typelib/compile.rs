@@ -21,11 +21,14 @@
 // limitations under the License.
 
 use std::collections::BTreeMap;
+use std::sync::Arc;
 
 use encoding::LibName;
 use strict_encoding::TypeName;
 
-use crate::typelib::{Dependency, ExternTypes, InlineRef, InlineRef1, InlineRef2, LibRef};
+use crate::typelib::{
+    BuildWarning, Dependency, ExternRef, InlineRef, InlineRef1, InlineRef2, LibRef, SymbolError,
+};
 use crate::{SemId, Translate, TranspileError, TranspileRef, Ty};
 
 pub type TypeIndex = BTreeMap<TypeName, SemId>;
@@ -39,9 +42,10 @@ pub enum CompileError {
     /// a different type with name `{0}` is already present
     DuplicateName(TypeName),
 
-    /// type `{unknown}` referenced inside `{within}` is not known
+    /// type `{unknown}` referenced by `{within_type}` inside `{within}` is not known
     UnknownType {
         unknown: TypeName,
+        within_type: TypeName,
         within: Ty<TranspileRef>,
     },
 
@@ -51,6 +55,12 @@ pub enum CompileError {
     /// dependency {0} is already present in the library
     DuplicatedDependency(Dependency),
 
+    /// library already depends on {present}, which has the same name as {new}.
+    ConflictingDependency {
+        present: Dependency,
+        new: Dependency,
+    },
+
     /// too deep type nesting for type {2} inside {0}, path {1}
     NestedInline(TypeName, String, String),
 
@@ -65,26 +75,115 @@ pub enum CompileError {
 
     /// library `{0}` contains too many types.
     LibTooLarge(LibName),
+
+    /// types {0:?} reference each other in a cycle.
+    TypeCycle(Vec<TypeName>),
+
+    /// library doesn't use dependencies {0:?}.
+    UnusedDependencies(Vec<Dependency>),
+
+    /// types reference libraries which are not library dependencies: {0:?}.
+    UndeclaredExternRefs(Vec<(TypeName, ExternRef)>),
+
+    #[display(inner)]
+    Symbol(SymbolError),
+
+    /// library validation has failed: {0:?}.
+    ValidationFailed(Vec<BuildWarning>),
+}
+
+impl CompileError {
+    /// Returns short machine-readable code of the error.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CompileError::DuplicateName(_) => "duplicate_name",
+            CompileError::UnknownType { .. } => "unknown_type",
+            CompileError::Continue => "continue",
+            CompileError::DuplicatedDependency(_) => "duplicated_dependency",
+            CompileError::ConflictingDependency { .. } => "conflicting_dependency",
+            CompileError::NestedInline(..) => "nested_inline",
+            CompileError::UnknownLib(_) => "unknown_lib",
+            CompileError::TooManyDependencies => "too_many_dependencies",
+            CompileError::TooManyTypes => "too_many_types",
+            CompileError::LibTooLarge(_) => "lib_too_large",
+            CompileError::TypeCycle(_) => "type_cycle",
+            CompileError::UnusedDependencies(_) => "unused_dependencies",
+            CompileError::UndeclaredExternRefs(_) => "undeclared_extern_refs",
+            CompileError::Symbol(_) => "symbol",
+            CompileError::ValidationFailed(_) => "validation_failed",
+        }
+    }
+
+    /// Pairs the error with a suggestion on how it can be fixed, if there is one.
+    pub fn suggest(self) -> (CompileError, Option<String>) {
+        let suggestion = match &self {
+            CompileError::DuplicateName(name) => Some(format!(
+                "check for type name collisions: two different types are named `{name}`, probably \
+                 coming from different modules"
+            )),
+            CompileError::UnknownType { unknown, .. } => {
+                Some(format!("add type `{unknown}` to the library or import it from a dependency"))
+            }
+            CompileError::DuplicatedDependency(dep) => {
+                Some(format!("remove repeated dependency on `{}`", dep.name))
+            }
+            CompileError::ConflictingDependency { present, .. } => {
+                Some(format!("use a single version of library `{}`", present.name))
+            }
+            CompileError::NestedInline(name, ..) => {
+                Some(format!("extract some of the types inlined into `{name}` as named types"))
+            }
+            CompileError::UnknownLib(lib) => {
+                Some(format!("check that library `{lib}` is present in the `known_libs` argument"))
+            }
+            CompileError::TooManyDependencies => {
+                Some(s!("split the library or merge some of its dependencies"))
+            }
+            CompileError::TooManyTypes | CompileError::LibTooLarge(_) => {
+                Some(s!("split the library into several smaller libraries"))
+            }
+            CompileError::TypeCycle(_) => {
+                Some(s!("restructure the types so they don't reference each other"))
+            }
+            CompileError::UnusedDependencies(_) => {
+                Some(s!("remove the dependencies which are not used by the library types"))
+            }
+            CompileError::UndeclaredExternRefs(_) => {
+                Some(s!("add the referenced libraries to the library dependencies"))
+            }
+            CompileError::Continue
+            | CompileError::Symbol(_)
+            | CompileError::ValidationFailed(_) => None,
+        };
+        (self, suggestion)
+    }
 }
 
 impl From<TranspileError> for CompileError {
     fn from(err: TranspileError) -> Self {
         match err {
-            TranspileError::UnknownType { unknown, within } => {
-                Self::UnknownType { unknown, within }
-            }
+            TranspileError::UnknownType {
+                unknown,
+                within_type,
+                within,
+            } => Self::UnknownType {
+                unknown,
+                within_type,
+                within,
+            },
             TranspileError::UnknownLib(lib) => Self::UnknownLib(lib),
             TranspileError::TooManyDependencies => Self::TooManyDependencies,
             TranspileError::TooManyTypes => Self::TooManyTypes,
             TranspileError::LibTooLarge(lib) => Self::LibTooLarge(lib),
+            TranspileError::RepeatedType(name) => Self::DuplicateName(name),
+            TranspileError::Symbol(err) => Self::Symbol(err),
         }
     }
 }
 
 pub struct NestedContext {
     pub top_name: TypeName,
-    pub index: TypeIndex,
-    pub extern_types: ExternTypes,
+    pub index: Arc<TypeIndex>,
     pub stack: Vec<String>,
 }
 
@@ -190,3 +289,28 @@ impl Translate<InlineRef2> for TranspileRef {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn suggest() {
+        let (err, suggestion) = CompileError::UnknownLib(libname!("Absent")).suggest();
+        assert_eq!(err, CompileError::UnknownLib(libname!("Absent")));
+        assert_eq!(err.kind(), "unknown_lib");
+        assert!(suggestion.unwrap().contains("`known_libs`"));
+
+        let (err, suggestion) = CompileError::DuplicateName(tn!("Dup")).suggest();
+        assert_eq!(err.kind(), "duplicate_name");
+        assert!(suggestion.unwrap().contains("`Dup`"));
+
+        let (err, suggestion) = CompileError::TooManyTypes.suggest();
+        assert_eq!(err.kind(), "too_many_types");
+        assert!(suggestion.unwrap().contains("split"));
+
+        let (err, suggestion) = CompileError::Continue.suggest();
+        assert_eq!(err.kind(), "continue");
+        assert_eq!(suggestion, None);
+    }
+}
@@ -0,0 +1,173 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Comparison of types and dependencies between two versions of a type library.
+
+use std::fmt::{self, Display, Formatter};
+
+use encoding::TypeName;
+
+use crate::{Dependency, LibRef, Ty, TypeLib};
+
+/// Differences between two versions of a type library, produced by [`TypeLib::diff`].
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct TypeLibDiff {
+    /// Types present only in the new library.
+    pub added_types: Vec<TypeName>,
+    /// Types present only in the old library.
+    pub removed_types: Vec<TypeName>,
+    /// Types present in both libraries under the same name, but with different definitions,
+    /// with the old and new definitions.
+    pub changed_types: Vec<(TypeName, Ty<LibRef>, Ty<LibRef>)>,
+    /// Dependencies present only in the new library.
+    pub added_deps: Vec<Dependency>,
+    /// Dependencies present only in the old library.
+    pub removed_deps: Vec<Dependency>,
+}
+
+impl TypeLibDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_types.is_empty()
+            && self.removed_types.is_empty()
+            && self.changed_types.is_empty()
+            && self.added_deps.is_empty()
+            && self.removed_deps.is_empty()
+    }
+}
+
+/// Lists removed and added dependencies and type definitions in the manner of a unified diff,
+/// with changed types shown as a removal of the old definition followed by the new one.
+impl Display for TypeLibDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for dep in &self.removed_deps {
+            writeln!(f, "-import {dep}")?;
+        }
+        for dep in &self.added_deps {
+            writeln!(f, "+import {dep}")?;
+        }
+        for name in &self.removed_types {
+            writeln!(f, "-data {name}")?;
+        }
+        for (name, old, new) in &self.changed_types {
+            writeln!(f, "-data {name}: {old}")?;
+            writeln!(f, "+data {name}: {new}")?;
+        }
+        for name in &self.added_types {
+            writeln!(f, "+data {name}")?;
+        }
+        Ok(())
+    }
+}
+
+impl TypeLib {
+    /// Compares types and dependencies of this library with the `other` (newer) version.
+    ///
+    /// Types are matched by their names, so a type which definition has changed is reported as
+    /// changed and not as removed and added. Dependencies are matched by their ids.
+    pub fn diff(&self, other: &TypeLib) -> TypeLibDiff {
+        let mut diff = TypeLibDiff::default();
+        for (name, old) in &self.types {
+            match other.types.get(name) {
+                None => diff.removed_types.push(name.clone()),
+                Some(new) if new != old => {
+                    diff.changed_types.push((name.clone(), old.clone(), new.clone()))
+                }
+                Some(_) => {}
+            }
+        }
+        diff.added_types =
+            other.types.keys().filter(|name| !self.types.contains_key(*name)).cloned().collect();
+
+        let has_dep =
+            |lib: &TypeLib, dep: &Dependency| lib.dependencies.iter().any(|d| d.id == dep.id);
+        diff.removed_deps =
+            self.dependencies.iter().filter(|dep| !has_dep(other, dep)).cloned().collect();
+        diff.added_deps =
+            other.dependencies.iter().filter(|dep| !has_dep(self, dep)).cloned().collect();
+        diff
+    }
+
+    /// Checks whether the library contains all types of the `other` library with the same
+    /// definitions.
+    pub fn is_superset_of(&self, other: &TypeLib) -> bool {
+        other.types.iter().all(|(name, ty)| self.types.get(name) == Some(ty))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use encoding::Sizing;
+
+    use super::*;
+    use crate::stl::std_stl;
+    use crate::typelib::TypeMap;
+
+    fn lib(types: impl IntoIterator<Item = (&'static str, Ty<LibRef>)>) -> TypeLib {
+        TypeLib {
+            name: libname!("TestLib"),
+            dependencies: none!(),
+            extern_types: none!(),
+            types: TypeMap::try_from_iter(types.into_iter().map(|(name, ty)| (tn!(name), ty)))
+                .unwrap(),
+            type_docs: none!(),
+            field_docs: none!(),
+        }
+    }
+
+    #[test]
+    fn diff() {
+        let old = lib([("Byte", Ty::U8), ("Word", Ty::U16), ("Removed", Ty::I8)]);
+        let mut new = lib([
+            ("Byte", Ty::U8),
+            ("Word", Ty::U32),
+            ("Added", Ty::List(LibRef::Inline(Ty::U8), Sizing::U8)),
+        ]);
+        new.dependencies.push(std_stl().to_dependency()).unwrap();
+
+        assert!(old.diff(&old).is_empty());
+        assert_eq!(old.diff(&old).to_string(), "");
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.added_types, vec![tn!("Added")]);
+        assert_eq!(diff.removed_types, vec![tn!("Removed")]);
+        assert_eq!(diff.changed_types, vec![(tn!("Word"), Ty::U16, Ty::U32)]);
+        assert_eq!(diff.added_deps, vec![std_stl().to_dependency()]);
+        assert!(diff.removed_deps.is_empty());
+        assert_eq!(
+            diff.to_string(),
+            format!(
+                "+import {}\n-data Removed\n-data Word: U16\n+data Word: U32\n+data Added\n",
+                std_stl().to_dependency()
+            )
+        );
+
+        let reverse = new.diff(&old);
+        assert_eq!(reverse.added_types, diff.removed_types);
+        assert_eq!(reverse.removed_deps, diff.added_deps);
+
+        assert!(old.is_superset_of(&old));
+        assert!(!new.is_superset_of(&old));
+        assert!(old.is_superset_of(&lib([("Byte", Ty::U8)])));
+        assert!(!old.is_superset_of(&lib([("Byte", Ty::U16)])));
+    }
+}
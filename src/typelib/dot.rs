@@ -0,0 +1,214 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generation of graphviz `.dot` graphs of library dependencies and type references.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use encoding::TypeName;
+
+use crate::typelib::type_lib::collect_ty_refs;
+use crate::{SemId, TypeLib};
+
+/// Vertex of the type reference graph.
+struct Node {
+    lib: String,
+    name: String,
+    resolved: bool,
+}
+
+impl TypeLib {
+    /// Generates graphviz `.dot` directed graph of the library dependencies, with an edge from
+    /// the library to each of the libraries it imports. Unused imports are drawn dashed, and
+    /// libraries referenced by the types without being imported are drawn red.
+    pub fn to_dependency_dot(&self) -> String {
+        let mut dot = s!("digraph dependencies {\n    node [shape=box];\n");
+        writeln!(dot, "    \"{}\" [style=bold];", self.name).ok();
+        let unused = self.unused_dependencies();
+        for dep in &self.dependencies {
+            let style = if unused.contains(dep) { "dashed" } else { "solid" };
+            writeln!(dot, "    \"{}\" -> \"{}\" [style={style}];", self.name, dep.name).ok();
+        }
+        let mut ext = bset![];
+        for ty in self.types.values() {
+            collect_ty_refs(ty, &mut bset![], &mut ext);
+        }
+        let missing = ext
+            .into_iter()
+            .map(|r| r.lib_id)
+            .filter(|id| !self.dependencies.iter().any(|dep| dep.id == *id))
+            .collect::<BTreeSet<_>>();
+        for id in missing {
+            writeln!(dot, "    \"{id}\" [color=red];").ok();
+            writeln!(dot, "    \"{}\" -> \"{id}\" [color=red];", self.name).ok();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Generates graphviz `.dot` directed graph of references between the library types; see
+    /// [`TypeLib::to_type_reference_dot_with`].
+    pub fn to_type_reference_dot(&self) -> String { self.to_type_reference_dot_with([], false) }
+
+    /// Generates graphviz `.dot` directed graph with a vertex per library type or type imported
+    /// from other library, and an edge per named or external type reference. External
+    /// references are drawn dashed, and references which can't be resolved are drawn red.
+    ///
+    /// Types which neither reference nor are referenced by other types are omitted, unless they
+    /// are listed in `roots`. If `clusters` is set, types are grouped by their library.
+    pub fn to_type_reference_dot_with(
+        &self,
+        roots: impl IntoIterator<Item = TypeName>,
+        clusters: bool,
+    ) -> String {
+        let lib = self.name.to_string();
+        let mut nodes = BTreeMap::<String, Node>::new();
+        let mut edges = BTreeSet::<(String, String, bool)>::new();
+
+        let index = self
+            .types
+            .iter()
+            .map(|(name, ty)| (ty.sem_id_named(name), name))
+            .collect::<BTreeMap<SemId, _>>();
+        for (name, ty) in &self.types {
+            let from = format!("{lib}.{name}");
+            nodes.insert(from.clone(), Node {
+                lib: lib.clone(),
+                name: name.to_string(),
+                resolved: true,
+            });
+
+            let mut named = bset![];
+            let mut ext = bset![];
+            collect_ty_refs(ty, &mut named, &mut ext);
+            for sem_id in named {
+                let (to, node) = match index.get(&sem_id) {
+                    Some(name) => (format!("{lib}.{name}"), Node {
+                        lib: lib.clone(),
+                        name: name.to_string(),
+                        resolved: true,
+                    }),
+                    None => (format!("{lib}.{sem_id}"), Node {
+                        lib: lib.clone(),
+                        name: sem_id.to_string(),
+                        resolved: false,
+                    }),
+                };
+                nodes.entry(to.clone()).or_insert(node);
+                edges.insert((from.clone(), to, false));
+            }
+            for r in ext {
+                let dep = self.dependencies.iter().find(|dep| dep.id == r.lib_id);
+                let ext_lib = dep.map(|dep| dep.name.to_string()).unwrap_or(r.lib_id.to_string());
+                let ext_name = dep
+                    .and_then(|dep| self.extern_types.get(&dep.name))
+                    .and_then(|index| index.get(&r.sem_id));
+                let node = Node {
+                    lib: ext_lib.clone(),
+                    name: ext_name.map(TypeName::to_string).unwrap_or(r.sem_id.to_string()),
+                    resolved: ext_name.is_some(),
+                };
+                let to = format!("{ext_lib}.{}", node.name);
+                nodes.entry(to.clone()).or_insert(node);
+                edges.insert((from.clone(), to, true));
+            }
+        }
+
+        let roots = roots.into_iter().map(|name| format!("{lib}.{name}")).collect::<BTreeSet<_>>();
+        let linked = edges.iter().flat_map(|(from, to, _)| [from, to]).collect::<BTreeSet<_>>();
+        nodes.retain(|id, _| linked.contains(id) || roots.contains(id));
+
+        let mut dot = s!("digraph types {\n    node [shape=box];\n");
+        if clusters {
+            let mut groups = BTreeMap::<&str, Vec<(&String, &Node)>>::new();
+            for (id, node) in &nodes {
+                groups.entry(node.lib.as_str()).or_default().push((id, node));
+            }
+            for (group, nodes) in groups {
+                writeln!(dot, "    subgraph \"cluster_{group}\" {{").ok();
+                writeln!(dot, "        label=\"{group}\";").ok();
+                for (id, node) in nodes {
+                    writeln!(dot, "        \"{id}\" [{}];", node_attrs(node, &node.name)).ok();
+                }
+                dot.push_str("    }\n");
+            }
+        } else {
+            for (id, node) in &nodes {
+                writeln!(dot, "    \"{id}\" [{}];", node_attrs(node, id)).ok();
+            }
+        }
+        for (from, to, ext) in &edges {
+            let attrs = match (nodes[to].resolved, ext) {
+                (false, _) => " [color=red]",
+                (true, true) => " [style=dashed]",
+                (true, false) => "",
+            };
+            writeln!(dot, "    \"{from}\" -> \"{to}\"{attrs};").ok();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn node_attrs(node: &Node, label: &str) -> String {
+    if node.resolved {
+        format!("label=\"{label}\"")
+    } else {
+        format!("label=\"{label}\", color=red")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::stl::{std_stl, strict_types_stl};
+    use crate::value::test_helpers::Nominal;
+    use crate::{LibBuilder, Ty};
+
+    #[test]
+    fn dot() {
+        let std = std_stl();
+        let st = strict_types_stl();
+        let mut lib = LibBuilder::new("TestLib", [std.to_dependency(), st.to_dependency()])
+            .transpile::<Nominal>()
+            .compile()
+            .unwrap();
+
+        let dot = lib.to_dependency_dot();
+        assert!(dot.starts_with("digraph dependencies {"));
+        assert!(dot.contains("\"TestLib\" -> \"Std\" [style=dashed];"));
+        assert!(dot.contains("\"TestLib\" -> \"StrictTypes\" [style=solid];"));
+
+        lib.populate(tn!("Isolated"), Ty::U8).unwrap();
+        let dot = lib.to_type_reference_dot();
+        assert!(dot.contains("\"TestLib.Nominal\" -> \"StrictTypes.Ident\" [style=dashed];"));
+        assert!(!dot.contains("color=red"));
+        assert!(dot.contains("\"TestLib.Nominal\" -> \"TestLib.Precision\";"));
+        assert!(!dot.contains("subgraph"));
+        assert!(!dot.contains("Isolated"));
+
+        let dot = lib.to_type_reference_dot_with([tn!("Isolated")], true);
+        assert!(dot.contains("\"TestLib.Isolated\" [label=\"Isolated\"];"));
+        assert!(dot.contains("subgraph \"cluster_StrictTypes\" {"));
+        assert!(dot.contains("\"StrictTypes.Ident\" [label=\"Ident\"];"));
+    }
+}
@@ -0,0 +1,312 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use amplify::confinement::Confined;
+use encoding::TypeName;
+
+use crate::ast::{Field, NamedFields, UnionVariants, UnnamedFields};
+use crate::typelib::{InlineRef, InlineRef1, InlineRef2, LibRef, TypeMap};
+use crate::{Ty, TypeLib, TypeLibId, TypeRef};
+
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum FlattenError {
+    /// dependency library {0} can't be resolved.
+    MissingDependency(TypeLibId),
+
+    /// library {found} was resolved instead of the dependency {expected}.
+    WrongDependency {
+        expected: TypeLibId,
+        found: TypeLibId,
+    },
+
+    /// type `{0}` has different definitions in the library and its dependencies.
+    NameConflict(TypeName),
+
+    /// flattened library contains too many types.
+    TooManyTypes,
+}
+
+/// Replaces references to the types of other libraries with the references to the types embedded
+/// into the library itself.
+trait Embed: TypeRef {
+    fn embed(self) -> Self;
+}
+
+impl Embed for LibRef {
+    fn embed(self) -> Self {
+        match self {
+            LibRef::Inline(ty) => LibRef::Inline(embed_ty(ty)),
+            LibRef::Named(sem_id) => LibRef::Named(sem_id),
+            LibRef::Extern(ext) => LibRef::Named(ext.sem_id),
+        }
+    }
+}
+
+impl Embed for InlineRef {
+    fn embed(self) -> Self {
+        match self {
+            InlineRef::Inline(ty) => InlineRef::Inline(embed_ty(ty)),
+            InlineRef::Named(sem_id) => InlineRef::Named(sem_id),
+            InlineRef::Extern(ext) => InlineRef::Named(ext.sem_id),
+        }
+    }
+}
+
+impl Embed for InlineRef1 {
+    fn embed(self) -> Self {
+        match self {
+            InlineRef1::Inline(ty) => InlineRef1::Inline(embed_ty(ty)),
+            InlineRef1::Named(sem_id) => InlineRef1::Named(sem_id),
+            InlineRef1::Extern(ext) => InlineRef1::Named(ext.sem_id),
+        }
+    }
+}
+
+impl Embed for InlineRef2 {
+    fn embed(self) -> Self {
+        match self {
+            InlineRef2::Named(sem_id) => InlineRef2::Named(sem_id),
+            InlineRef2::Extern(ext) => InlineRef2::Named(ext.sem_id),
+        }
+    }
+}
+
+fn embed_ty<Ref: Embed>(ty: Ty<Ref>) -> Ty<Ref> {
+    match ty {
+        Ty::Union(variants) => {
+            let variants = variants
+                .into_iter()
+                .map(|(variant, ty)| (variant, ty.embed()))
+                .collect::<BTreeMap<_, _>>();
+            Ty::Union(UnionVariants::from(Confined::from_checked(variants)))
+        }
+        Ty::Struct(fields) => {
+            let fields = fields
+                .into_iter()
+                .map(|field| Field {
+                    name: field.name,
+                    ty: field.ty.embed(),
+                })
+                .collect::<Vec<_>>();
+            Ty::Struct(NamedFields::try_from(fields).expect("re-packing existing fields structure"))
+        }
+        Ty::Tuple(fields) => {
+            let fields = fields.into_iter().map(Ref::embed).collect::<Vec<_>>();
+            Ty::Tuple(
+                UnnamedFields::try_from(fields).expect("re-packing existing fields structure"),
+            )
+        }
+        Ty::Array(ty, len) => Ty::Array(ty.embed(), len),
+        Ty::List(ty, sizing) => Ty::List(ty.embed(), sizing),
+        Ty::Set(ty, sizing) => Ty::Set(ty.embed(), sizing),
+        Ty::Map(key, ty, sizing) => Ty::Map(key.embed(), ty.embed(), sizing),
+        ty @ (Ty::Primitive(_) | Ty::Enum(_) | Ty::UnicodeChar) => ty,
+    }
+}
+
+impl TypeLib {
+    /// Produces a self-contained library without dependencies, which embeds all types of the
+    /// (direct and indirect) dependency libraries provided by the `resolver`.
+    ///
+    /// References to the external types are replaced with references to the embedded types, thus
+    /// semantic ids of all types are preserved; however the library id changes since it commits
+    /// to the list of dependencies. Flattening a library without dependencies returns it
+    /// unchanged.
+    pub fn flatten(
+        &self,
+        resolver: impl Fn(TypeLibId) -> Option<TypeLib>,
+    ) -> Result<TypeLib, FlattenError> {
+        let mut types = BTreeMap::new();
+        let mut add_types = |lib: TypeLib| -> Result<(), FlattenError> {
+            for (name, ty) in lib.types {
+                let ty = embed_ty(ty);
+                match types.get(&name) {
+                    Some(present) if present != &ty => {
+                        return Err(FlattenError::NameConflict(name));
+                    }
+                    Some(_) => {}
+                    None => {
+                        types.insert(name, ty);
+                    }
+                }
+            }
+            Ok(())
+        };
+
+        let mut type_docs = self.type_docs.clone();
+        let mut field_docs = self.field_docs.clone();
+        add_types(self.clone())?;
+
+        let mut libs = self.with_dependencies(resolver)?;
+        libs.pop();
+        for lib in libs.into_iter().rev() {
+            for (name, doc) in &lib.type_docs {
+                type_docs.entry(name.clone()).or_insert_with(|| doc.clone());
+            }
+            for (name, docs) in &lib.field_docs {
+                field_docs.entry(name.clone()).or_insert_with(|| docs.clone());
+            }
+            add_types(lib)?;
+        }
+
+        Ok(TypeLib {
+            name: self.name.clone(),
+            dependencies: none!(),
+            extern_types: none!(),
+            types: TypeMap::try_from(types).map_err(|_| FlattenError::TooManyTypes)?,
+            type_docs,
+            field_docs,
+        })
+    }
+
+    /// Collects the library together with all its (direct and indirect) dependency libraries
+    /// provided by the `resolver`, allowing to distribute them as a self-contained set.
+    ///
+    /// Dependencies go before the libraries which use them, and the library itself is the last
+    /// one; thus the libraries may be imported one by one in the returned order.
+    pub fn with_dependencies(
+        &self,
+        resolver: impl Fn(TypeLibId) -> Option<TypeLib>,
+    ) -> Result<Vec<TypeLib>, FlattenError> {
+        fn visit(
+            lib: TypeLib,
+            resolver: &impl Fn(TypeLibId) -> Option<TypeLib>,
+            visited: &mut BTreeSet<TypeLibId>,
+            libs: &mut Vec<TypeLib>,
+        ) -> Result<(), FlattenError> {
+            for dep in &lib.dependencies {
+                if !visited.insert(dep.id) {
+                    continue;
+                }
+                let dep_lib = resolver(dep.id).ok_or(FlattenError::MissingDependency(dep.id))?;
+                if dep_lib.id() != dep.id {
+                    return Err(FlattenError::WrongDependency {
+                        expected: dep.id,
+                        found: dep_lib.id(),
+                    });
+                }
+                visit(dep_lib, resolver, visited, libs)?;
+            }
+            libs.push(lib);
+            Ok(())
+        }
+
+        let mut libs = vec![];
+        visit(self.clone(), &resolver, &mut BTreeSet::new(), &mut libs)?;
+        Ok(libs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::stl::{std_stl, strict_types_stl};
+    use crate::typesys::SystemBuilder;
+    use crate::value::test_helpers::Nominal;
+    use crate::LibBuilder;
+
+    #[test]
+    fn flatten() {
+        let std = std_stl();
+        let st = strict_types_stl();
+        let lib = LibBuilder::new(libname!("TestLib"), [std.to_dependency(), st.to_dependency()])
+            .transpile::<Nominal>()
+            .compile()
+            .unwrap();
+        assert!(st.dependencies.iter().any(|dep| dep.id == std.id()));
+
+        let libs = BTreeMap::from([(std.id(), std.clone()), (st.id(), st.clone())]);
+        let flat = lib.flatten(|id| libs.get(&id).cloned()).unwrap();
+        assert!(flat.dependencies.is_empty());
+        assert!(flat.extern_types.is_empty());
+        assert!(flat.validate_extern_refs().is_ok());
+        for src in [&lib, &st, &std] {
+            for (name, ty) in &src.types {
+                assert_eq!(flat.types[name].sem_id_named(name), ty.sem_id_named(name));
+            }
+        }
+        assert_eq!(flat.types.len(), lib.types.len() + st.types.len() + std.types.len());
+        assert_eq!(flat.flatten(|_| None).unwrap(), flat);
+
+        let sys = SystemBuilder::new()
+            .import(lib.clone())
+            .unwrap()
+            .import(st.clone())
+            .unwrap()
+            .import(std.clone())
+            .unwrap()
+            .finalize()
+            .unwrap();
+        let flat_sys = SystemBuilder::new().import(flat).unwrap().finalize().unwrap();
+        assert_eq!(flat_sys.as_types(), sys.as_types());
+
+        let libs = BTreeMap::from([(st.id(), st.clone())]);
+        assert_eq!(
+            lib.flatten(|id| libs.get(&id).cloned()),
+            Err(FlattenError::MissingDependency(std.id()))
+        );
+        assert!(matches!(
+            lib.flatten(|_| Some(lib.clone())),
+            Err(FlattenError::WrongDependency { found, .. }) if found == lib.id()
+        ));
+    }
+
+    #[test]
+    fn with_dependencies() {
+        let std = std_stl();
+        let st = strict_types_stl();
+        let lib = LibBuilder::new(libname!("TestLib"), [std.to_dependency(), st.to_dependency()])
+            .transpile::<Nominal>()
+            .compile()
+            .unwrap();
+
+        let resolved = BTreeMap::from([(std.id(), std.clone()), (st.id(), st.clone())]);
+        let libs = lib.with_dependencies(|id| resolved.get(&id).cloned()).unwrap();
+        assert_eq!(libs.len(), 3);
+        assert_eq!(libs[2], lib);
+        let pos = |id| libs.iter().position(|lib| lib.id() == id).unwrap();
+        assert!(pos(std.id()) < pos(st.id()));
+        assert_eq!(std.with_dependencies(|_| None).unwrap(), vec![std.clone()]);
+
+        let sys = SystemBuilder::new().import_all(libs).unwrap().finalize().unwrap();
+        let expected = SystemBuilder::new()
+            .import(lib.clone())
+            .unwrap()
+            .import(st.clone())
+            .unwrap()
+            .import(std.clone())
+            .unwrap()
+            .finalize()
+            .unwrap();
+        assert_eq!(sys, expected);
+
+        assert_eq!(
+            lib.with_dependencies(|id| if id == st.id() { Some(st.clone()) } else { None }),
+            Err(FlattenError::MissingDependency(std.id()))
+        );
+    }
+}
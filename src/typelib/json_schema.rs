@@ -0,0 +1,511 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Export of type libraries into JSON Schema (draft-07).
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+
+use encoding::{LibName, NumCls, Primitive, Sizing, TypeName};
+use serde_json::{json, Map, Value};
+
+use crate::layout::MemoryLayout;
+use crate::typelib::{ExternRef, ExternTypes, LibRef, LibSubref, LibSubrefView};
+use crate::typesys::{NestedCase, TypeFqn};
+use crate::{SemId, Ty, TypeLib, TypeLibId, TypeRef};
+
+pub const JSON_SCHEMA_DRAFT07: &str = "http://json-schema.org/draft-07/schema#";
+
+/// Name of the JSON object field holding union variant name.
+pub const JSON_SCHEMA_UNION_TAG: &str = "_tag";
+
+struct SchemaCtx<'lib> {
+    lib: &'lib LibName,
+    names: BTreeMap<SemId, &'lib TypeName>,
+    libs: BTreeMap<TypeLibId, &'lib LibName>,
+    extern_types: &'lib ExternTypes,
+    /// Whether all types are put into a single `$defs` map under fully qualified names.
+    shared: bool,
+    /// Library types referenced by the produced schemas.
+    used: RefCell<BTreeSet<&'lib TypeName>>,
+}
+
+impl<'lib> SchemaCtx<'lib> {
+    fn with(lib: &'lib TypeLib, shared: bool) -> Self {
+        SchemaCtx {
+            lib: &lib.name,
+            names: lib.types.iter().map(|(name, ty)| (ty.sem_id_named(name), name)).collect(),
+            libs: lib.dependencies.iter().map(|dep| (dep.id, &dep.name)).collect(),
+            extern_types: &lib.extern_types,
+            shared,
+            used: empty!(),
+        }
+    }
+
+    fn named(&self, sem_id: &SemId) -> Value {
+        match self.names.get(sem_id) {
+            Some(name) if self.shared => {
+                self.used.borrow_mut().insert(name);
+                json!({ "$ref": format!("#/$defs/{}.{name}", self.lib) })
+            }
+            Some(name) => json!({ "$ref": format!("#/$defs/{name}") }),
+            None => json!({ "$comment": format!("unknown type {sem_id}") }),
+        }
+    }
+
+    fn external(&self, ext: &ExternRef) -> Value {
+        let Some(lib) = self.libs.get(&ext.lib_id) else {
+            return json!({ "$comment": format!("unknown library {}", ext.lib_id) });
+        };
+        match self.extern_types.get(*lib).and_then(|index| index.get(&ext.sem_id)) {
+            Some(name) if self.shared => json!({ "$ref": format!("#/$defs/{lib}.{name}") }),
+            Some(name) => json!({ "$ref": format!("{lib}.json#/$defs/{name}") }),
+            None => json!({ "$comment": format!("unknown type {lib}.{}", ext.sem_id) }),
+        }
+    }
+}
+
+trait SchemaRef: LibSubref {
+    fn json_schema(&self, ctx: &SchemaCtx) -> Value {
+        match self.view() {
+            LibSubrefView::Inline(ty) => ty_schema(ty, &mut |r| r.json_schema(ctx)),
+            LibSubrefView::Named(sem_id) => ctx.named(sem_id),
+            LibSubrefView::Extern(ext) => ctx.external(ext),
+        }
+    }
+}
+
+impl<Ref: LibSubref> SchemaRef for Ref {}
+
+fn primitive_schema(prim: Primitive) -> Value {
+    if prim == Primitive::UNIT {
+        return json!({ "type": "null" });
+    }
+    if prim == Primitive::BYTE {
+        return json!({ "type": "integer", "minimum": 0, "maximum": u8::MAX });
+    }
+    let info = prim.info();
+    let bits = info.byte_size() as u32 * 8;
+    match info.ty {
+        NumCls::Float => json!({ "type": "number" }),
+        NumCls::Unsigned if bits <= 64 => int_schema(0, (u64::MAX >> (64 - bits)) as i128),
+        NumCls::NonZero if bits <= 64 => int_schema(1, (u64::MAX >> (64 - bits)) as i128),
+        NumCls::Signed if bits <= 64 => {
+            int_schema((i64::MIN >> (64 - bits)) as i128, (i64::MAX >> (64 - bits)) as i128)
+        }
+        NumCls::Unsigned => int_schema(0, i128::MAX),
+        NumCls::NonZero => int_schema(1, i128::MAX),
+        NumCls::Signed => int_schema(i128::MIN, i128::MAX),
+    }
+}
+
+/// Largest integer which JSON consumers using double-precision numbers represent exactly.
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+fn int_schema(min: i128, max: i128) -> Value {
+    let mut schema = json!({ "type": "integer" });
+    // Bounds outside of the safe range can't be represented exactly, so they are omitted
+    if min >= -(MAX_SAFE_INTEGER as i128) {
+        schema["minimum"] = json!(min as i64);
+    }
+    if max <= MAX_SAFE_INTEGER as i128 {
+        schema["maximum"] = json!(max as i64);
+    }
+    schema
+}
+
+fn sizing(schema: &mut Value, sizing: Sizing, min: &str, max: &str) {
+    schema[min] = json!(sizing.min);
+    // Larger limits can't be represented exactly and don't restrict any real data
+    if sizing.max <= MAX_SAFE_INTEGER {
+        schema[max] = json!(sizing.max);
+    }
+}
+
+fn variant_schema(name: &str, value: Value) -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            JSON_SCHEMA_UNION_TAG: { "const": name },
+            "value": value,
+        },
+        "required": [JSON_SCHEMA_UNION_TAG, "value"],
+        "additionalProperties": false,
+    })
+}
+
+fn tuple_schema(items: Vec<Value>) -> Value {
+    json!({
+        "type": "array",
+        "minItems": items.len(),
+        "maxItems": items.len(),
+        "items": items,
+        "additionalItems": false,
+    })
+}
+
+/// Produces schema of the type, using `child` to produce schemas of the referenced types.
+fn ty_schema<Ref: TypeRef>(ty: &Ty<Ref>, child: &mut impl FnMut(&Ref) -> Value) -> Value {
+    match ty {
+        Ty::Primitive(prim) => primitive_schema(*prim),
+        Ty::UnicodeChar => json!({ "type": "string", "minLength": 1, "maxLength": 1 }),
+        Ty::Enum(variants) => json!({
+            "type": "string",
+            "enum": variants.iter().map(|v| v.name.to_string()).collect::<Vec<_>>(),
+        }),
+        Ty::Union(variants) => {
+            let one_of = variants
+                .into_iter()
+                .map(|(variant, ty)| variant_schema(variant.name.as_str(), child(ty)))
+                .collect::<Vec<_>>();
+            json!({ "oneOf": one_of })
+        }
+        Ty::Tuple(fields) => tuple_schema(fields.iter().map(child).collect()),
+        Ty::Struct(fields) => {
+            let properties = fields
+                .iter()
+                .map(|field| (field.name.to_string(), child(&field.ty)))
+                .collect::<Map<_, _>>();
+            let required = fields.iter().map(|field| field.name.to_string()).collect::<Vec<_>>();
+            json!({
+                "type": "object",
+                "properties": properties,
+                "required": required,
+                "additionalProperties": false,
+            })
+        }
+        Ty::Array(ty, len) => json!({
+            "type": "array",
+            "items": child(ty),
+            "minItems": len,
+            "maxItems": len,
+        }),
+        Ty::List(ty, size) if ty.is_unicode_char() => {
+            let mut schema = json!({ "type": "string" });
+            sizing(&mut schema, *size, "minLength", "maxLength");
+            schema
+        }
+        Ty::List(ty, size) => {
+            let mut schema = json!({ "type": "array", "items": child(ty) });
+            sizing(&mut schema, *size, "minItems", "maxItems");
+            schema
+        }
+        Ty::Set(ty, size) => {
+            let mut schema = json!({ "type": "array", "items": child(ty), "uniqueItems": true });
+            sizing(&mut schema, *size, "minItems", "maxItems");
+            schema
+        }
+        Ty::Map(_, ty, size) => {
+            let mut schema = json!({ "type": "object", "additionalProperties": child(ty) });
+            sizing(&mut schema, *size, "minProperties", "maxProperties");
+            schema
+        }
+    }
+}
+
+impl TypeLib {
+    /// Converts the library into a JSON Schema document, putting each of the library types into
+    /// `$defs`. Types from the dependencies are referenced as `<LibName>.json#/$defs/<TypeName>`.
+    /// Type and field documentation is exported as `description`.
+    pub fn to_json_schema(&self) -> Value {
+        let ctx = SchemaCtx::with(self, false);
+        let defs = self
+            .types
+            .iter()
+            .map(|(name, ty)| (name.to_string(), self.type_schema(name, ty, &ctx)))
+            .collect::<Map<_, _>>();
+        json!({
+            "$schema": JSON_SCHEMA_DRAFT07,
+            "$id": format!("{}.json", self.name),
+            "title": self.name.to_string(),
+            "$comment": format!("strict type library {}", self.id()),
+            "$defs": defs,
+        })
+    }
+
+    /// Puts JSON Schema of the library type `name` into the `$defs` map shared between multiple
+    /// libraries, returning a `$ref` pointing to it, or `None` if the library has no such type.
+    ///
+    /// Types are put into `defs` under `<LibName>.<TypeName>` keys, together with all types of
+    /// this library which they reference. Types from the dependencies are referenced under the
+    /// same keys, and must be added to `defs` by calling this method on the dependency libraries.
+    /// Types already present in `defs` are not generated again. To produce schema of a type
+    /// together with all its dependencies at once, use [`MemoryLayout::to_json_schema_fragment`].
+    pub fn to_json_schema_fragment(
+        &self,
+        name: &TypeName,
+        defs: &mut Map<String, Value>,
+    ) -> Option<Value> {
+        let (name, _) = self.types.get_key_value(name)?;
+        let ctx = SchemaCtx::with(self, true);
+        let mut queue = vec![name];
+        while let Some(name) = queue.pop() {
+            let key = format!("{}.{name}", self.name);
+            if defs.contains_key(&key) {
+                continue;
+            }
+            let schema = self.type_schema(name, &self.types[name], &ctx);
+            defs.insert(key, schema);
+            queue.extend(ctx.used.take());
+        }
+        Some(json!({ "$ref": format!("#/$defs/{}.{name}", self.name) }))
+    }
+
+    fn type_schema(&self, name: &TypeName, ty: &Ty<LibRef>, ctx: &SchemaCtx) -> Value {
+        let mut schema = ty_schema(ty, &mut |r| r.json_schema(ctx));
+        if let Some(doc) = self.type_doc(name) {
+            schema["description"] = json!(doc.as_str());
+        }
+        for (field, doc) in self.field_docs.get(name).into_iter().flatten() {
+            if let Some(property) = schema["properties"].get_mut(field.as_str()) {
+                property["description"] = json!(doc.as_str());
+            }
+        }
+        schema
+    }
+}
+
+/// Puts schema produced by `schema` into `defs` under the name of the type, unless it is already
+/// present there, returning a `$ref` pointing to it.
+fn define(
+    defs: &mut Map<String, Value>,
+    fqn: &TypeFqn,
+    schema: impl FnOnce(&mut Map<String, Value>) -> Value,
+) -> Value {
+    let key = fqn.to_string();
+    if !defs.contains_key(&key) {
+        let schema = schema(defs);
+        defs.insert(key.clone(), schema);
+    }
+    json!({ "$ref": format!("#/$defs/{key}") })
+}
+
+impl MemoryLayout {
+    /// Puts JSON Schema of the type with this layout into the `$defs` map shared between
+    /// multiple types and libraries, returning the schema of the type, which is a `$ref` if the
+    /// type is named.
+    ///
+    /// Named types are put into `defs` under `<LibName>.<TypeName>` keys, like by
+    /// [`TypeLib::to_json_schema_fragment`], together with all named types they reference, such
+    /// that the layouts of types from different libraries may share the same `defs`. Types
+    /// already present in `defs` are not generated again.
+    pub fn to_json_schema_fragment(&self, defs: &mut Map<String, Value>) -> Value {
+        self.item_schema(0, &self.children(), defs)
+    }
+
+    fn item_schema(
+        &self,
+        pos: usize,
+        children: &[Vec<usize>],
+        defs: &mut Map<String, Value>,
+    ) -> Value {
+        let item = &self.items()[pos];
+        let mut schema = match &item.fqn {
+            Some(fqn) => define(defs, fqn, |defs| self.item_ty_schema(pos, children, defs)),
+            None => self.item_ty_schema(pos, children, defs),
+        };
+        // Wrappers are listed from the outermost one
+        for case in item.nested.iter().rev() {
+            schema = match case {
+                NestedCase::Option => json!({ "oneOf": [
+                    variant_schema("none", primitive_schema(Primitive::UNIT)),
+                    variant_schema("some", schema),
+                ] }),
+                NestedCase::NewType(Some(fqn)) => define(defs, fqn, |_| tuple_schema(vec![schema])),
+                NestedCase::NewType(None) => tuple_schema(vec![schema]),
+                NestedCase::ByteStr
+                | NestedCase::AsciiStr(_)
+                | NestedCase::UniStr
+                | NestedCase::RStr(..) => schema,
+            };
+        }
+        schema
+    }
+
+    fn item_ty_schema(
+        &self,
+        pos: usize,
+        children: &[Vec<usize>],
+        defs: &mut Map<String, Value>,
+    ) -> Value {
+        let item = &self.items()[pos];
+        let string = item.nested.iter().find_map(|case| match (case, &item.ty) {
+            (NestedCase::RStr(_, _, sizing), _) => Some(*sizing),
+            (NestedCase::AsciiStr(_) | NestedCase::UniStr, Ty::List(_, sizing)) => Some(*sizing),
+            _ => None,
+        });
+        if let Some(size) = string {
+            let mut schema = json!({ "type": "string" });
+            sizing(&mut schema, size, "minLength", "maxLength");
+            return schema;
+        }
+        // Children follow the order of the type references; byte strings and arrays have none
+        let refs = item.ty.iter().map(|(id, _)| *id).zip(children[pos].iter().copied());
+        let refs = refs.collect::<BTreeMap<_, _>>();
+        ty_schema(&item.ty, &mut |id: &SemId| match refs.get(id) {
+            Some(child) => self.item_schema(*child, children, defs),
+            None if id.is_byte() => primitive_schema(Primitive::BYTE),
+            None => json!({ "$comment": format!("unknown type {id}") }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::stl::{std_stl, strict_types_stl};
+    use crate::value::test_helpers::{test_system, Nominal};
+    use crate::LibBuilder;
+
+    #[test]
+    fn nominal() {
+        let std = std_stl();
+        let st = strict_types_stl();
+        let lib = LibBuilder::new("TestLib", [std.to_dependency(), st.to_dependency()])
+            .transpile::<Nominal>()
+            .compile()
+            .unwrap();
+        let schema = lib.to_json_schema();
+
+        let json = serde_json::to_string_pretty(&schema).unwrap();
+        let parsed: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, schema);
+
+        assert_eq!(schema["$schema"], JSON_SCHEMA_DRAFT07);
+        assert_eq!(schema["$id"], "TestLib.json");
+        let nominal = &schema["$defs"]["Nominal"];
+        assert_eq!(nominal["type"], "object");
+        assert_eq!(nominal["required"], json!(["ticker", "name", "precision"]));
+        assert_eq!(nominal["properties"]["precision"], json!({ "$ref": "#/$defs/Precision" }));
+        assert_eq!(
+            nominal["properties"]["ticker"],
+            json!({ "$ref": "StrictTypes.json#/$defs/Ident" })
+        );
+        assert_eq!(
+            nominal["properties"]["name"],
+            json!({ "type": "string", "minLength": 1, "maxLength": 32 })
+        );
+        assert_eq!(
+            schema["$defs"]["Precision"],
+            json!({ "type": "string", "enum": ["noDecimals", "oneDecimal", "twoDecimals"] })
+        );
+    }
+
+    #[test]
+    fn fragment() {
+        let std = std_stl();
+        let st = strict_types_stl();
+        let lib = LibBuilder::new("TestLib", [std.to_dependency(), st.to_dependency()])
+            .transpile::<Nominal>()
+            .compile()
+            .unwrap();
+
+        let mut defs = Map::new();
+        let root = lib.to_json_schema_fragment(&tn!("Nominal"), &mut defs).unwrap();
+        assert_eq!(root, json!({ "$ref": "#/$defs/TestLib.Nominal" }));
+        assert_eq!(defs.keys().collect::<Vec<_>>(), vec!["TestLib.Nominal", "TestLib.Precision"]);
+        let nominal = &defs["TestLib.Nominal"];
+        assert_eq!(
+            nominal["properties"]["precision"],
+            json!({ "$ref": "#/$defs/TestLib.Precision" })
+        );
+        assert_eq!(nominal["properties"]["ticker"], json!({ "$ref": "#/$defs/StrictTypes.Ident" }));
+        assert_eq!(defs["TestLib.Precision"], lib.to_json_schema()["$defs"]["Precision"]);
+
+        st.to_json_schema_fragment(&tn!("Ident"), &mut defs).unwrap();
+        assert!(defs.contains_key("StrictTypes.Ident"));
+        let len = defs.len();
+        st.to_json_schema_fragment(&tn!("Ident"), &mut defs).unwrap();
+        assert_eq!(defs.len(), len);
+        assert_eq!(lib.to_json_schema_fragment(&tn!("Unknown"), &mut defs), None);
+    }
+
+    #[test]
+    fn layout_fragment() {
+        let sys = test_system();
+        let layout = sys.type_layout("TestLib.Nominal").unwrap();
+        let mut defs = Map::new();
+        let root = layout.to_json_schema_fragment(&mut defs);
+        assert_eq!(root, json!({ "$ref": "#/$defs/TestLib.Nominal" }));
+        assert_eq!(defs.keys().collect::<Vec<_>>(), vec![
+            "StrictTypes.Ident",
+            "TestLib.Nominal",
+            "TestLib.Precision"
+        ]);
+        // Newtype wrapping ASCII string with restricted first character
+        let ident = json!({ "type": "string", "minLength": 1, "maxLength": 100 });
+        assert_eq!(
+            defs["StrictTypes.Ident"],
+            json!({
+                "type": "array",
+                "items": [ident],
+                "minItems": 1,
+                "maxItems": 1,
+                "additionalItems": false,
+            })
+        );
+
+        let std = std_stl();
+        let st = strict_types_stl();
+        let lib = LibBuilder::new("TestLib", [std.to_dependency(), st.to_dependency()])
+            .transpile::<Nominal>()
+            .compile()
+            .unwrap();
+        let mut lib_defs = Map::new();
+        lib.to_json_schema_fragment(&tn!("Nominal"), &mut lib_defs).unwrap();
+        assert_eq!(defs["TestLib.Nominal"], lib_defs["TestLib.Nominal"]);
+        assert_eq!(defs["TestLib.Precision"], lib_defs["TestLib.Precision"]);
+
+        let len = defs.len();
+        assert_eq!(layout.to_json_schema_fragment(&mut defs), root);
+        assert_eq!(defs.len(), len);
+    }
+
+    #[test]
+    fn unbounded() {
+        let std = std_stl();
+        let st = strict_types_stl();
+        let mut lib = LibBuilder::new("TestLib", [std.to_dependency(), st.to_dependency()])
+            .transpile::<Nominal>()
+            .compile()
+            .unwrap();
+        let list = Ty::List(LibRef::Inline(Ty::U8), Sizing::new(1, u64::MAX));
+        lib.populate(tn!("Unbounded"), list).unwrap();
+        let schema = lib.to_json_schema();
+        assert_eq!(
+            schema["$defs"]["Unbounded"],
+            json!({ "type": "array", "items": { "type": "integer", "minimum": 0, "maximum": 255 }, "minItems": 1 })
+        );
+    }
+
+    #[test]
+    fn wide_integers() {
+        assert_eq!(
+            primitive_schema(Primitive::U32),
+            json!({ "type": "integer", "minimum": 0, "maximum": u32::MAX })
+        );
+        assert_eq!(primitive_schema(Primitive::U64), json!({ "type": "integer", "minimum": 0 }));
+        assert_eq!(primitive_schema(Primitive::N64), json!({ "type": "integer", "minimum": 1 }));
+        assert_eq!(primitive_schema(Primitive::I64), json!({ "type": "integer" }));
+        assert_eq!(primitive_schema(Primitive::U128), json!({ "type": "integer", "minimum": 0 }));
+    }
+}
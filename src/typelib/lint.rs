@@ -0,0 +1,304 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use encoding::TypeName;
+
+use crate::ast::RESERVED_TAGS;
+use crate::typelib::{InlineRef, InlineRef1, InlineRef2, LibSubref};
+use crate::{Dependency, LibRef, SemId, Ty, TypeLib, TypeRef};
+
+/// Structures with more fields than this are reported by [`TypeLib::lint`].
+pub const LINT_MAX_FIELDS: usize = 32;
+/// Types nested deeper than this are reported by [`TypeLib::lint`].
+pub const LINT_MAX_DEPTH: usize = 8;
+
+/// Schema design issue found by [`TypeLib::lint`]. None of them prevents the library from being
+/// used.
+#[derive(Clone, Eq, PartialEq, Debug, Display)]
+#[display(doc_comments)]
+pub enum LintWarning {
+    /// union `{0}` has a single variant; it should probably be a newtype instead.
+    SingleVariantUnion(TypeName),
+
+    /// string `{0}` has no minimal length and thus allows empty strings.
+    EmptyStringAllowed(TypeName),
+
+    /// union `{ty}` doesn't use tags {start}..={end}, which may indicate an accidental tag
+    /// collision.
+    TagGapInUnion { ty: TypeName, start: u8, end: u8 },
+
+    /// structure `{ty}` has {fields} fields; consider splitting it into smaller structures.
+    OverlyLargeStruct { ty: TypeName, fields: usize },
+
+    /// type `{ty}` has nesting depth {depth}, which makes its data hard to read and process.
+    DeepNesting { ty: TypeName, depth: usize },
+
+    /// dependency `{0}` is not referenced by any of the library types.
+    UnusedDependency(Dependency),
+
+    /// types `{ty}` and `{other}` have the same definition with semantic id {sem_id}; one of them
+    /// is probably redundant.
+    DuplicateSemanticId {
+        ty: TypeName,
+        other: TypeName,
+        sem_id: SemId,
+    },
+}
+
+impl LintWarning {
+    /// Returns name of the type the issue is found in, if the issue is specific to a type.
+    pub fn type_name(&self) -> Option<&TypeName> {
+        match self {
+            LintWarning::SingleVariantUnion(ty)
+            | LintWarning::EmptyStringAllowed(ty)
+            | LintWarning::TagGapInUnion { ty, .. }
+            | LintWarning::OverlyLargeStruct { ty, .. }
+            | LintWarning::DeepNesting { ty, .. }
+            | LintWarning::DuplicateSemanticId { ty, .. } => Some(ty),
+            LintWarning::UnusedDependency(_) => None,
+        }
+    }
+}
+
+impl TypeLib {
+    /// Checks the library types for common schema design issues.
+    ///
+    /// Nesting depth follows references to the other types of the same library; all other checks
+    /// are applied to the library types without looking into the types inlined into them.
+    pub fn lint(&self) -> Vec<LintWarning> {
+        let mut warnings = vec![];
+        let mut depths = Depths {
+            index: self.types.iter().map(|(name, ty)| (ty.sem_id_named(name), ty)).collect(),
+            cache: empty!(),
+            visiting: empty!(),
+        };
+        let mut structures = BTreeMap::<SemId, &TypeName>::new();
+        for (name, ty) in &self.types {
+            match ty {
+                Ty::Union(variants) if variants.len() == 1 => {
+                    warnings.push(LintWarning::SingleVariantUnion(name.clone()));
+                }
+                Ty::Union(variants) => {
+                    let last = variants.keys().map(|v| v.tag).max().unwrap_or_default();
+                    for gap in variants.unused_tags() {
+                        if *gap.end() < last && gap.len() < RESERVED_TAGS {
+                            warnings.push(LintWarning::TagGapInUnion {
+                                ty: name.clone(),
+                                start: *gap.start(),
+                                end: *gap.end(),
+                            });
+                        }
+                    }
+                }
+                Ty::Struct(fields) if fields.len() > LINT_MAX_FIELDS => {
+                    warnings.push(LintWarning::OverlyLargeStruct {
+                        ty: name.clone(),
+                        fields: fields.len(),
+                    });
+                }
+                _ => {}
+            }
+            let string = match ty {
+                Ty::Tuple(fields) if fields.len() == 1 => match &fields[0] {
+                    LibRef::Inline(ty) => allows_empty_string(ty),
+                    _ => false,
+                },
+                ty => allows_empty_string(ty),
+            };
+            if string {
+                warnings.push(LintWarning::EmptyStringAllowed(name.clone()));
+            }
+            let depth = depths.ty_depth(ty);
+            if depth > LINT_MAX_DEPTH {
+                warnings.push(LintWarning::DeepNesting {
+                    ty: name.clone(),
+                    depth,
+                });
+            }
+            let sem_id = ty.sem_id_unnamed();
+            if let Some(other) = structures.insert(sem_id, name) {
+                warnings.push(LintWarning::DuplicateSemanticId {
+                    ty: name.clone(),
+                    other: other.clone(),
+                    sem_id,
+                });
+            }
+        }
+        warnings.extend(self.unused_dependencies().into_iter().map(LintWarning::UnusedDependency));
+        warnings
+    }
+
+    /// Checks the library with [`TypeLib::lint`], failing if any issue is found.
+    pub fn lint_strict(&self) -> Result<(), Vec<LintWarning>> {
+        let warnings = self.lint();
+        if !warnings.is_empty() {
+            return Err(warnings);
+        }
+        Ok(())
+    }
+}
+
+fn allows_empty_string<Ref: TypeRef>(ty: &Ty<Ref>) -> bool {
+    matches!(ty, Ty::List(item, sizing) if item.is_unicode_char() && sizing.min == 0)
+}
+
+/// Nesting depths of the library types, computed following named references.
+struct Depths<'lib> {
+    index: BTreeMap<SemId, &'lib Ty<LibRef>>,
+    cache: BTreeMap<SemId, usize>,
+    visiting: BTreeSet<SemId>,
+}
+
+impl Depths<'_> {
+    fn ty_depth<Ref: NestedDepth>(&mut self, ty: &Ty<Ref>) -> usize {
+        match ty {
+            Ty::Primitive(_) | Ty::UnicodeChar | Ty::Enum(_) => 0,
+            _ => 1 + ty.type_refs().map(|(r, _)| r.nested_depth(self)).max().unwrap_or_default(),
+        }
+    }
+
+    fn named_depth(&mut self, sem_id: SemId) -> usize {
+        if let Some(depth) = self.cache.get(&sem_id) {
+            return *depth;
+        }
+        // Recursive types and types from other libraries are not followed
+        let Some(ty) = self.index.get(&sem_id).copied() else {
+            return 0;
+        };
+        if !self.visiting.insert(sem_id) {
+            return 0;
+        }
+        let depth = self.ty_depth(ty);
+        self.visiting.remove(&sem_id);
+        self.cache.insert(sem_id, depth);
+        depth
+    }
+}
+
+trait NestedDepth: LibSubref {
+    fn nested_depth(&self, depths: &mut Depths) -> usize;
+}
+
+macro_rules! impl_nested_depth {
+    ($ty:ident) => {
+        impl NestedDepth for $ty {
+            fn nested_depth(&self, depths: &mut Depths) -> usize {
+                match self {
+                    $ty::Inline(ty) => depths.ty_depth(ty),
+                    $ty::Named(sem_id) => depths.named_depth(*sem_id),
+                    $ty::Extern(_) => 0,
+                }
+            }
+        }
+    };
+}
+
+impl_nested_depth!(LibRef);
+impl_nested_depth!(InlineRef);
+impl_nested_depth!(InlineRef1);
+
+impl NestedDepth for InlineRef2 {
+    fn nested_depth(&self, depths: &mut Depths) -> usize {
+        match self {
+            InlineRef2::Named(sem_id) => depths.named_depth(*sem_id),
+            InlineRef2::Extern(_) => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use encoding::Sizing;
+
+    use super::*;
+    use crate::stl::{std_stl, strict_types_stl};
+    use crate::value::test_helpers::Nominal;
+    use crate::LibBuilder;
+
+    #[test]
+    fn lint() {
+        let std = std_stl();
+        let st = strict_types_stl();
+        let mut lib = LibBuilder::new("TestLib", [std.to_dependency(), st.to_dependency()])
+            .transpile::<Nominal>()
+            .compile()
+            .unwrap();
+        assert_eq!(lib.lint(), vec![LintWarning::UnusedDependency(std.to_dependency())]);
+        assert!(lib.lint_strict().is_err());
+
+        let named =
+            |lib: &TypeLib, name: &TypeName| LibRef::Named(lib.types[name].sem_id_named(name));
+        lib.populate(tn!("Single"), Ty::Union(variants!("some" => Ty::<InlineRef>::U8))).unwrap();
+        lib.populate(
+            tn!("Gapped"),
+            Ty::Union(variants!("a" => 0 => Ty::<InlineRef>::U8, "b" => 5 => Ty::<InlineRef>::U16)),
+        )
+        .unwrap();
+        lib.populate(
+            tn!("Text"),
+            Ty::Tuple(fields!(LibRef::Inline(Ty::List(
+                InlineRef::Inline(Ty::UnicodeChar),
+                Sizing::U8
+            )))),
+        )
+        .unwrap();
+        let fields = (0..=LINT_MAX_FIELDS)
+            .map(|no| crate::ast::Field {
+                name: fname!(format!("f{no}")),
+                ty: LibRef::Inline(Ty::U8),
+            })
+            .collect::<Vec<_>>();
+        lib.populate(tn!("Large"), Ty::Struct(fields.try_into().unwrap())).unwrap();
+        lib.populate(tn!("Level0"), Ty::List(LibRef::Inline(Ty::U8), Sizing::U8)).unwrap();
+        for level in 1..=LINT_MAX_DEPTH {
+            let inner = named(&lib, &tn!(format!("Level{}", level - 1)));
+            lib.populate(tn!(format!("Level{level}")), Ty::List(inner, Sizing::U8)).unwrap();
+        }
+        let precision = lib.types[&tn!("Precision")].clone();
+        lib.populate(tn!("Precision2"), precision).unwrap();
+
+        let warnings = lib.lint();
+        assert!(warnings.contains(&LintWarning::SingleVariantUnion(tn!("Single"))));
+        assert!(warnings.contains(&LintWarning::TagGapInUnion {
+            ty: tn!("Gapped"),
+            start: 1,
+            end: 4
+        }));
+        assert!(warnings.contains(&LintWarning::EmptyStringAllowed(tn!("Text"))));
+        assert!(warnings.contains(&LintWarning::OverlyLargeStruct {
+            ty: tn!("Large"),
+            fields: LINT_MAX_FIELDS + 1
+        }));
+        assert!(warnings.contains(&LintWarning::DeepNesting {
+            ty: tn!(format!("Level{LINT_MAX_DEPTH}")),
+            depth: LINT_MAX_DEPTH + 1
+        }));
+        assert!(!warnings.iter().any(|w| w.type_name() == Some(&tn!("Level7"))));
+        assert!(warnings.contains(&LintWarning::DuplicateSemanticId {
+            ty: tn!("Precision2"),
+            other: tn!("Precision"),
+            sem_id: lib.types[&tn!("Precision")].sem_id_unnamed(),
+        }));
+    }
+}
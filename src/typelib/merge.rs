@@ -0,0 +1,344 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use amplify::confinement::Confined;
+use encoding::{LibName, TypeName};
+
+use crate::typelib::type_lib::collect_ty_refs;
+use crate::typelib::TypeMap;
+use crate::{LibBuilder, SemId, TypeLib, TypeLibId};
+
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum MergeError {
+    /// can't merge library `{0}` into a library with a different name `{1}`.
+    LibNameMismatch(LibName, LibName),
+
+    /// type `{name}` has different definitions in the merged libraries ({left} and {right}).
+    NameConflict {
+        name: TypeName,
+        left: SemId,
+        right: SemId,
+    },
+
+    /// dependency `{name}` has different versions in the merged libraries ({left} and {right}).
+    DependencyConflict {
+        name: LibName,
+        left: TypeLibId,
+        right: TypeLibId,
+    },
+
+    /// type `{0}` has different definitions in the merged library builders.
+    TypeConflict(TypeName),
+
+    /// type `{name}` references type {sem_id} which was discarded during the merge.
+    DanglingRef { name: TypeName, sem_id: SemId },
+
+    /// merged library contains too many types.
+    TooManyTypes,
+
+    /// merged library contains too many dependencies.
+    TooManyDependencies,
+
+    /// merged library contains too many types imported from `{0}`.
+    LibTooLarge(LibName),
+}
+
+/// Strategy for resolving type name conflicts during library merge.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum MergeStrategy {
+    /// Fail with [`MergeError::NameConflict`].
+    #[default]
+    Fail,
+
+    /// Keep the type definition from the left (`self`) library.
+    KeepLeft,
+}
+
+impl TypeLib {
+    /// Merges types of two libraries having the same name, failing on any type name conflict.
+    pub fn merge(self, other: TypeLib) -> Result<TypeLib, MergeError> {
+        self.merge_with(other, MergeStrategy::Fail)
+    }
+
+    /// Merges types of two libraries having the same name, resolving type name conflicts
+    /// according to the provided `strategy`.
+    ///
+    /// The resulting library id is computed from the combined set of types.
+    pub fn merge_with(
+        self,
+        other: TypeLib,
+        strategy: MergeStrategy,
+    ) -> Result<TypeLib, MergeError> {
+        if self.name != other.name {
+            return Err(MergeError::LibNameMismatch(other.name, self.name));
+        }
+
+        let mut dependencies = self.dependencies.release();
+        for dep in other.dependencies {
+            match dependencies.iter().find(|d| d.name == dep.name) {
+                Some(d) if d.id != dep.id => {
+                    return Err(MergeError::DependencyConflict {
+                        name: dep.name,
+                        left: d.id,
+                        right: dep.id,
+                    });
+                }
+                Some(_) => {}
+                None => {
+                    dependencies.insert(dep);
+                }
+            }
+        }
+
+        let mut extern_types = self
+            .extern_types
+            .into_iter()
+            .map(|(lib, index)| (lib, index.release()))
+            .collect::<BTreeMap<_, _>>();
+        for (lib, index) in other.extern_types {
+            extern_types.entry(lib).or_default().extend(index);
+        }
+
+        let mut types = self.types.release();
+        for (name, ty) in other.types {
+            match types.get(&name) {
+                Some(left) if left != &ty => {
+                    if strategy == MergeStrategy::KeepLeft {
+                        continue;
+                    }
+                    return Err(MergeError::NameConflict {
+                        left: left.sem_id_named(&name),
+                        right: ty.sem_id_named(&name),
+                        name,
+                    });
+                }
+                Some(_) => {}
+                None => {
+                    types.insert(name, ty);
+                }
+            }
+        }
+
+        let known = types.iter().map(|(name, ty)| ty.sem_id_named(name)).collect::<BTreeSet<_>>();
+        for (name, ty) in &types {
+            let mut named = bset![];
+            collect_ty_refs(ty, &mut named, &mut bset![]);
+            if let Some(sem_id) = named.into_iter().find(|id| !known.contains(id)) {
+                return Err(MergeError::DanglingRef {
+                    name: name.clone(),
+                    sem_id,
+                });
+            }
+        }
+
+        let extern_types = Confined::try_from(
+            extern_types
+                .into_iter()
+                .map(|(lib, index)| {
+                    let index = Confined::try_from(index)
+                        .map_err(|_| MergeError::LibTooLarge(lib.clone()))?;
+                    Ok((lib, index))
+                })
+                .collect::<Result<BTreeMap<_, _>, _>>()?,
+        )
+        .map_err(|_| MergeError::TooManyDependencies)?;
+
+        // Documentation from the left library takes precedence
+        let mut type_docs = other.type_docs;
+        type_docs.extend(self.type_docs);
+        type_docs.retain(|name, _| types.contains_key(name));
+        let mut field_docs = other.field_docs;
+        for (name, docs) in self.field_docs {
+            field_docs.entry(name).or_default().extend(docs);
+        }
+        field_docs.retain(|name, _| types.contains_key(name));
+
+        Ok(TypeLib {
+            name: self.name,
+            dependencies: Confined::try_from(dependencies)
+                .map_err(|_| MergeError::TooManyDependencies)?,
+            extern_types,
+            types: TypeMap::try_from(types).map_err(|_| MergeError::TooManyTypes)?,
+            type_docs,
+            field_docs,
+        })
+    }
+}
+
+impl LibBuilder {
+    /// Combines types registered in two library builders, keeping the library name of `self`.
+    ///
+    /// Types with the same name and definition are deduplicated; documentation from `self` takes
+    /// precedence.
+    pub fn merge(mut self, other: LibBuilder) -> Result<LibBuilder, MergeError> {
+        for dep in other.known_libs {
+            match self.known_libs.iter().find(|d| d.name == dep.name) {
+                Some(d) if d.id != dep.id => {
+                    return Err(MergeError::DependencyConflict {
+                        name: dep.name,
+                        left: d.id,
+                        right: dep.id,
+                    });
+                }
+                Some(_) => {}
+                None => {
+                    self.known_libs.insert(dep);
+                }
+            }
+        }
+
+        for (lib, index) in other.extern_types {
+            self.extern_types.entry(lib).or_default().extend(index);
+        }
+
+        for (name, ty) in other.types {
+            match self.types.get(&name) {
+                Some(left) if left != &ty => return Err(MergeError::TypeConflict(name)),
+                Some(_) => {}
+                None => {
+                    self.types.insert(name, ty);
+                }
+            }
+        }
+
+        for (name, doc) in other.type_docs {
+            self.type_docs.entry(name).or_insert(doc);
+        }
+        for (name, docs) in other.field_docs {
+            let field_docs = self.field_docs.entry(name).or_default();
+            for (field, doc) in docs {
+                field_docs.entry(field).or_insert(doc);
+            }
+        }
+        self.conflicts.extend(other.conflicts);
+        self.cache_hits += other.cache_hits;
+
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::stl::{std_stl, strict_types_stl};
+    use crate::value::test_helpers::{Nominal, Precision};
+    use crate::LibBuilder;
+
+    #[derive(Clone, Eq, PartialEq, Debug, Default)]
+    #[derive(StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Amount(u64);
+
+    #[derive(Clone, Eq, PartialEq, Debug, Default)]
+    #[derive(StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Fee(u64);
+
+    mod other {
+        #[derive(Copy, Clone, Eq, PartialEq, Debug)]
+        #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+        #[strict_type(lib = "TestLib", tags = repr, into_u8, try_from_u8)]
+        #[repr(u8)]
+        pub enum Precision {
+            #[strict_type(dumb)]
+            Indivisible = 0,
+            Centi = 2,
+        }
+    }
+
+    fn builder() -> LibBuilder {
+        LibBuilder::new("TestLib", [std_stl().to_dependency(), strict_types_stl().to_dependency()])
+    }
+
+    #[test]
+    fn merge() {
+        let left = builder().transpile::<Nominal>().compile().unwrap();
+        let amount = builder().transpile::<Amount>().compile().unwrap();
+        let precision = builder().transpile::<Precision>().compile().unwrap();
+        let right = amount.merge(precision).unwrap();
+        let merged = left.clone().merge(right.clone()).unwrap();
+
+        let names = merged.types.keys().map(TypeName::to_string).collect::<Vec<_>>();
+        assert_eq!(names, ["Amount", "Nominal", "Precision"]);
+        assert_eq!(merged.dependencies, left.dependencies);
+        assert_ne!(merged.id(), left.id());
+        assert_eq!(merged, right.merge(left).unwrap());
+    }
+
+    #[test]
+    fn renamed() {
+        let amount = builder().transpile::<Amount>().compile().unwrap();
+        let fee = builder().transpile::<Fee>().compile().unwrap();
+        let merged = amount.merge(fee).unwrap();
+        assert_eq!(merged.types[&tn!("Amount")], merged.types[&tn!("Fee")]);
+        assert_ne!(
+            merged.types[&tn!("Amount")].sem_id_named(&tn!("Amount")),
+            merged.types[&tn!("Fee")].sem_id_named(&tn!("Fee"))
+        );
+    }
+
+    #[test]
+    fn conflict() {
+        let left = builder().transpile::<Nominal>().compile().unwrap();
+        let right = builder().transpile::<other::Precision>().compile().unwrap();
+        let err = left.clone().merge(right.clone()).unwrap_err();
+        assert_eq!(err, MergeError::NameConflict {
+            name: tn!("Precision"),
+            left: left.types[&tn!("Precision")].sem_id_named(&tn!("Precision")),
+            right: right.types[&tn!("Precision")].sem_id_named(&tn!("Precision")),
+        });
+
+        let merged = left.clone().merge_with(right.clone(), MergeStrategy::KeepLeft).unwrap();
+        assert_eq!(merged.types, left.types);
+
+        let err = right.merge_with(left, MergeStrategy::KeepLeft).unwrap_err();
+        assert!(matches!(err, MergeError::DanglingRef { name, .. } if name == tn!("Nominal")));
+    }
+
+    #[test]
+    fn lib_mismatch() {
+        let lib = builder().transpile::<Nominal>().compile().unwrap();
+        let err = lib.merge(strict_types_stl()).unwrap_err();
+        assert_eq!(err, MergeError::LibNameMismatch(libname!("StrictTypes"), libname!("TestLib")));
+    }
+
+    #[test]
+    fn merge_builders() {
+        let merged = builder()
+            .transpile::<Nominal>()
+            .merge(builder().transpile::<Amount>().transpile::<Precision>())
+            .unwrap();
+        let nominal = builder().transpile::<Nominal>().compile().unwrap();
+        let amount = builder().transpile::<Amount>().compile().unwrap();
+        assert_eq!(merged.lib_name, libname!("TestLib"));
+        assert_eq!(merged.compile().unwrap(), nominal.merge(amount).unwrap());
+
+        let err = builder()
+            .transpile::<Nominal>()
+            .merge(builder().transpile::<other::Precision>())
+            .unwrap_err();
+        assert_eq!(err, MergeError::TypeConflict(tn!("Precision")));
+    }
+}
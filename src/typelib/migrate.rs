@@ -0,0 +1,587 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rules upgrading data created with one version of a library to the next library version.
+
+use std::collections::BTreeMap;
+use std::mem;
+
+use encoding::{FieldName, TypeName, VariantName};
+
+use crate::typelib::{InlineRef, InlineRef1, InlineRef2, LibRef};
+use crate::value::EnumTag;
+use crate::{SemId, StrictVal, Ty, TypeLib, TypeRef};
+
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum MigrationError {
+    /// type `{0}` is not known to the library.
+    UnknownType(TypeName),
+
+    /// migration of type `{0}` requires it to be a structure.
+    NotStruct(TypeName),
+
+    /// migration of type `{0}` requires it to be an enum or a union.
+    NotEnum(TypeName),
+
+    /// field `{type_name}.{field_name}` is absent.
+    NoField {
+        type_name: TypeName,
+        field_name: FieldName,
+    },
+
+    /// field `{type_name}.{field_name}` is already present.
+    FieldExists {
+        type_name: TypeName,
+        field_name: FieldName,
+    },
+
+    /// field `{type_name}.{field_name}` has type {found} instead of {expected}.
+    FieldTypeMismatch {
+        type_name: TypeName,
+        field_name: FieldName,
+        expected: SemId,
+        found: SemId,
+    },
+}
+
+/// Single change in a library type, which has to be reflected in the data.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "camelCase", rename_all_fields = "camelCase", tag = "rule")
+)]
+pub enum MigrationRule {
+    RenameField {
+        type_name: TypeName,
+        old_name: FieldName,
+        new_name: FieldName,
+    },
+
+    AddField {
+        type_name: TypeName,
+        field_name: FieldName,
+        default_value: StrictVal,
+    },
+
+    RemoveField {
+        type_name: TypeName,
+        field_name: FieldName,
+    },
+
+    /// Renames enum or union variant. Variants referenced by their tag are kept as is.
+    RenameVariant {
+        type_name: TypeName,
+        old_name: VariantName,
+        new_name: VariantName,
+    },
+
+    /// Changes type of a structure field, converting the field value with the `coerce`
+    /// function; if no function is provided, the value is kept as is.
+    ///
+    /// The `coerce` function is not serialized.
+    ChangeFieldType {
+        type_name: TypeName,
+        field_name: FieldName,
+        old_sem_id: SemId,
+        new_sem_id: SemId,
+        #[cfg_attr(feature = "serde", serde(skip))]
+        coerce: Option<fn(StrictVal) -> StrictVal>,
+    },
+}
+
+impl MigrationRule {
+    pub fn type_name(&self) -> &TypeName {
+        match self {
+            MigrationRule::RenameField { type_name, .. }
+            | MigrationRule::AddField { type_name, .. }
+            | MigrationRule::RemoveField { type_name, .. }
+            | MigrationRule::RenameVariant { type_name, .. }
+            | MigrationRule::ChangeFieldType { type_name, .. } => type_name,
+        }
+    }
+}
+
+/// Sequence of rules upgrading data to a new version of a library.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct Migration {
+    pub rules: Vec<MigrationRule>,
+}
+
+impl Migration {
+    pub fn new(rules: impl IntoIterator<Item = MigrationRule>) -> Self {
+        Migration {
+            rules: rules.into_iter().collect(),
+        }
+    }
+}
+
+struct MigrationCtx<'lib> {
+    named: BTreeMap<SemId, (&'lib TypeName, &'lib Ty<LibRef>)>,
+    migration: &'lib Migration,
+}
+
+trait MigrateRef: TypeRef {
+    fn migrate(&self, ctx: &MigrationCtx, val: StrictVal) -> Result<StrictVal, MigrationError>;
+}
+
+macro_rules! impl_migrate_ref {
+    ($ty:ident) => {
+        impl MigrateRef for $ty {
+            fn migrate(
+                &self,
+                ctx: &MigrationCtx,
+                val: StrictVal,
+            ) -> Result<StrictVal, MigrationError> {
+                match self {
+                    $ty::Inline(ty) => ctx.migrate(None, ty, val),
+                    $ty::Named(sem_id) => ctx.migrate_named(*sem_id, val),
+                    // Types from other libraries are not affected by the migration
+                    $ty::Extern(_) => Ok(val),
+                }
+            }
+        }
+    };
+}
+
+impl_migrate_ref!(LibRef);
+impl_migrate_ref!(InlineRef);
+impl_migrate_ref!(InlineRef1);
+
+impl MigrateRef for InlineRef2 {
+    fn migrate(&self, ctx: &MigrationCtx, val: StrictVal) -> Result<StrictVal, MigrationError> {
+        match self {
+            InlineRef2::Named(sem_id) => ctx.migrate_named(*sem_id, val),
+            InlineRef2::Extern(_) => Ok(val),
+        }
+    }
+}
+
+impl MigrationCtx<'_> {
+    fn migrate_named(&self, sem_id: SemId, val: StrictVal) -> Result<StrictVal, MigrationError> {
+        match self.named.get(&sem_id) {
+            Some((name, ty)) => self.migrate(Some(name), ty, val),
+            None => Ok(val),
+        }
+    }
+
+    /// Migrates nested values according to the type definition (which is the definition from the
+    /// previous library version), and then applies the rules for the type itself.
+    fn migrate<Ref: MigrateRef>(
+        &self,
+        name: Option<&TypeName>,
+        ty: &Ty<Ref>,
+        val: StrictVal,
+    ) -> Result<StrictVal, MigrationError> {
+        let val = match (ty, val) {
+            (Ty::Struct(fields), StrictVal::Struct(mut vals)) => {
+                for field in fields {
+                    if let Some(val) = vals.get_mut(&field.name) {
+                        *val = field.ty.migrate(self, mem::replace(val, StrictVal::Unit))?;
+                    }
+                }
+                StrictVal::Struct(vals)
+            }
+            (Ty::Tuple(fields), StrictVal::Tuple(vals)) => StrictVal::Tuple(
+                fields
+                    .iter()
+                    .zip(vals)
+                    .map(|(ty, val)| ty.migrate(self, val))
+                    .collect::<Result<_, _>>()?,
+            ),
+            (Ty::Union(variants), StrictVal::Union(tag, val)) => {
+                let variant = match &tag {
+                    EnumTag::Name(name) => variants.by_name(name),
+                    EnumTag::Ord(tag) => variants.by_tag(*tag),
+                };
+                let val = match variant {
+                    Some((_, ty)) => ty.migrate(self, *val)?,
+                    None => *val,
+                };
+                StrictVal::Union(tag, Box::new(val))
+            }
+            (Ty::Array(ty, _) | Ty::List(ty, _) | Ty::Set(ty, _), StrictVal::List(vals)) => {
+                StrictVal::List(self.migrate_items(ty, vals)?)
+            }
+            (Ty::Array(ty, _) | Ty::List(ty, _) | Ty::Set(ty, _), StrictVal::Set(vals)) => {
+                StrictVal::Set(self.migrate_items(ty, vals)?)
+            }
+            (Ty::Map(key_ty, ty, _), StrictVal::Map(items)) => StrictVal::Map(
+                items
+                    .into_iter()
+                    .map(|(key, val)| Ok((key_ty.migrate(self, key)?, ty.migrate(self, val)?)))
+                    .collect::<Result<_, _>>()?,
+            ),
+            (_, val) => val,
+        };
+        match name {
+            Some(name) => self.apply(name, ty, val),
+            None => Ok(val),
+        }
+    }
+
+    fn migrate_items<Ref: MigrateRef>(
+        &self,
+        ty: &Ref,
+        vals: Vec<StrictVal>,
+    ) -> Result<Vec<StrictVal>, MigrationError> {
+        vals.into_iter().map(|val| ty.migrate(self, val)).collect()
+    }
+
+    fn apply<Ref: MigrateRef>(
+        &self,
+        name: &TypeName,
+        ty: &Ty<Ref>,
+        mut val: StrictVal,
+    ) -> Result<StrictVal, MigrationError> {
+        for rule in self.migration.rules.iter().filter(|rule| rule.type_name() == name) {
+            let no_field = |field_name: &FieldName| MigrationError::NoField {
+                type_name: name.clone(),
+                field_name: field_name.clone(),
+            };
+            let field_exists = |field_name: &FieldName| MigrationError::FieldExists {
+                type_name: name.clone(),
+                field_name: field_name.clone(),
+            };
+            val = match (rule, val) {
+                (
+                    MigrationRule::RenameField {
+                        old_name, new_name, ..
+                    },
+                    StrictVal::Struct(vals),
+                ) => {
+                    if !vals.contains_key(old_name) {
+                        return Err(no_field(old_name));
+                    }
+                    if vals.contains_key(new_name) {
+                        return Err(field_exists(new_name));
+                    }
+                    StrictVal::Struct(
+                        vals.into_iter()
+                            .map(|(field, val)| {
+                                (if &field == old_name { new_name.clone() } else { field }, val)
+                            })
+                            .collect(),
+                    )
+                }
+                (
+                    MigrationRule::AddField {
+                        field_name,
+                        default_value,
+                        ..
+                    },
+                    StrictVal::Struct(mut vals),
+                ) => {
+                    if vals.contains_key(field_name) {
+                        return Err(field_exists(field_name));
+                    }
+                    vals.insert(field_name.clone(), default_value.clone());
+                    StrictVal::Struct(vals)
+                }
+                (MigrationRule::RemoveField { field_name, .. }, StrictVal::Struct(mut vals)) => {
+                    vals.shift_remove(field_name).ok_or_else(|| no_field(field_name))?;
+                    StrictVal::Struct(vals)
+                }
+                (
+                    MigrationRule::ChangeFieldType {
+                        field_name,
+                        old_sem_id,
+                        coerce,
+                        ..
+                    },
+                    StrictVal::Struct(mut vals),
+                ) => {
+                    if let Ty::Struct(fields) = ty {
+                        let found = fields.ty_by_name(field_name).and_then(Ref::sem_id);
+                        if let Some(found) = found.filter(|found| found != old_sem_id) {
+                            return Err(MigrationError::FieldTypeMismatch {
+                                type_name: name.clone(),
+                                field_name: field_name.clone(),
+                                expected: *old_sem_id,
+                                found,
+                            });
+                        }
+                    }
+                    let val = vals.get_mut(field_name).ok_or_else(|| no_field(field_name))?;
+                    if let Some(coerce) = coerce {
+                        *val = coerce(mem::replace(val, StrictVal::Unit));
+                    }
+                    StrictVal::Struct(vals)
+                }
+                (
+                    MigrationRule::RenameField { .. }
+                    | MigrationRule::AddField { .. }
+                    | MigrationRule::RemoveField { .. }
+                    | MigrationRule::ChangeFieldType { .. },
+                    _,
+                ) => return Err(MigrationError::NotStruct(name.clone())),
+                (
+                    MigrationRule::RenameVariant {
+                        old_name, new_name, ..
+                    },
+                    val,
+                ) => {
+                    let rename = |tag: EnumTag| match tag {
+                        EnumTag::Name(name) if &name == old_name => EnumTag::Name(new_name.clone()),
+                        tag => tag,
+                    };
+                    match val {
+                        StrictVal::Enum(tag) => StrictVal::Enum(rename(tag)),
+                        StrictVal::Union(tag, val) => StrictVal::Union(rename(tag), val),
+                        _ => return Err(MigrationError::NotEnum(name.clone())),
+                    }
+                }
+            };
+        }
+        Ok(val)
+    }
+}
+
+impl TypeLib {
+    /// Upgrades `data`, which is a value of the library type `ty`, applying the `migration`
+    /// rules to it and all of its nested values. The library must be the version which was used
+    /// to create the data.
+    ///
+    /// The rules are applied sequentially, after the nested values are migrated. Values of types
+    /// from other libraries are not changed.
+    ///
+    /// For [`MigrationRule::ChangeFieldType`], the previous field type is checked against the
+    /// library, if the library type has the field with the same name.
+    pub fn migrate(
+        &self,
+        ty: &TypeName,
+        data: StrictVal,
+        migration: &Migration,
+    ) -> Result<StrictVal, MigrationError> {
+        let root = self.types.get(ty).ok_or_else(|| MigrationError::UnknownType(ty.clone()))?;
+        let ctx = MigrationCtx {
+            named: self
+                .types
+                .iter()
+                .map(|(name, ty)| (ty.sem_id_named(name), (name, ty)))
+                .collect(),
+            migration,
+        };
+        ctx.migrate(Some(ty), root, data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use encoding::Sizing;
+
+    use super::*;
+    use crate::ast::{EnumVariants, Field};
+    use crate::typelib::TranspileRef;
+    use crate::value::StrictNum;
+    use crate::{LibBuilder, SystemBuilder};
+
+    fn named(name: &'static str) -> TranspileRef { TranspileRef::Named(tn!(name)) }
+
+    fn lib(
+        colors: EnumVariants,
+        item: &[(&'static str, TranspileRef)],
+        total: Ty<TranspileRef>,
+    ) -> TypeLib {
+        let item = item.iter().cloned().map(|(name, ty)| Field {
+            name: fname!(name),
+            ty,
+        });
+        let mut builder = LibBuilder::new(libname!("TestLib"), []);
+        builder.types.insert(tn!("Color"), Ty::Enum(colors));
+        builder.types.insert(tn!("Item"), Ty::Struct(item.collect::<Vec<_>>().try_into().unwrap()));
+        builder.types.insert(
+            tn!("Order"),
+            Ty::Struct(fields!(
+                fname!("items") => TranspileRef::from(Ty::List(named("Item"), Sizing::U8)),
+                fname!("total") => TranspileRef::from(total)
+            )),
+        );
+        builder.compile().unwrap()
+    }
+
+    fn cents(val: StrictVal) -> StrictVal {
+        match val {
+            StrictVal::Number(StrictNum::Uint(n)) => StrictVal::num(n as u32 * 100),
+            val => val,
+        }
+    }
+
+    #[test]
+    fn migrate() {
+        let v1 = lib(
+            variants!("red", "green"),
+            &[("id", Ty::U32.into()), ("colour", named("Color")), ("legacy", Ty::U8.into())],
+            Ty::U16,
+        );
+        let v2 = lib(
+            variants!("red", "lime"),
+            &[("id", Ty::U32.into()), ("color", named("Color")), ("qty", Ty::U8.into())],
+            Ty::U32,
+        );
+
+        let migration = Migration::new([
+            MigrationRule::RenameField {
+                type_name: tn!("Item"),
+                old_name: fname!("colour"),
+                new_name: fname!("color"),
+            },
+            MigrationRule::RemoveField {
+                type_name: tn!("Item"),
+                field_name: fname!("legacy"),
+            },
+            MigrationRule::AddField {
+                type_name: tn!("Item"),
+                field_name: fname!("qty"),
+                default_value: StrictVal::num(1u8),
+            },
+            MigrationRule::RenameVariant {
+                type_name: tn!("Color"),
+                old_name: vname!("green"),
+                new_name: vname!("lime"),
+            },
+            MigrationRule::ChangeFieldType {
+                type_name: tn!("Order"),
+                field_name: fname!("total"),
+                old_sem_id: Ty::<SemId>::U16.sem_id_unnamed(),
+                new_sem_id: Ty::<SemId>::U32.sem_id_unnamed(),
+                coerce: Some(cents),
+            },
+        ]);
+
+        let item = |id: u32, color: &'static str| {
+            StrictVal::struc([
+                ("id", StrictVal::num(id)),
+                ("colour", StrictVal::enumer(color)),
+                ("legacy", StrictVal::num(0u8)),
+            ])
+        };
+        let order = StrictVal::struc([
+            ("items", StrictVal::list([item(1, "green"), item(2, "red")])),
+            ("total", StrictVal::num(5u16)),
+        ]);
+        let migrated = v1.migrate(&tn!("Order"), order, &migration).unwrap();
+
+        let item = |id: u32, color: &'static str| {
+            StrictVal::struc([
+                ("id", StrictVal::num(id)),
+                ("color", StrictVal::enumer(color)),
+                ("qty", StrictVal::num(1u8)),
+            ])
+        };
+        let expected = StrictVal::struc([
+            ("items", StrictVal::list([item(1, "lime"), item(2, "red")])),
+            ("total", StrictVal::num(500u32)),
+        ]);
+        assert_eq!(migrated, expected);
+
+        let sys = SystemBuilder::new().import(v2).unwrap().finalize().unwrap();
+        sys.typify(migrated, "TestLib.Order").unwrap();
+    }
+
+    #[test]
+    fn errors() {
+        let v1 = lib(variants!("red"), &[("id", Ty::U32.into())], Ty::U16);
+        let rule = |rule| Migration::new([rule]);
+        let item = StrictVal::struc([("id", StrictVal::num(1u32))]);
+        let order = StrictVal::struc([("items", StrictVal::list([item])), ("total", 5u16.into())]);
+
+        let err = v1.migrate(&tn!("Unknown"), order.clone(), &Migration::default()).unwrap_err();
+        assert_eq!(err, MigrationError::UnknownType(tn!("Unknown")));
+
+        let remove = rule(MigrationRule::RemoveField {
+            type_name: tn!("Item"),
+            field_name: fname!("name"),
+        });
+        assert_eq!(
+            v1.migrate(&tn!("Order"), order.clone(), &remove).unwrap_err(),
+            MigrationError::NoField {
+                type_name: tn!("Item"),
+                field_name: fname!("name")
+            }
+        );
+
+        let add = rule(MigrationRule::AddField {
+            type_name: tn!("Order"),
+            field_name: fname!("total"),
+            default_value: StrictVal::num(0u16),
+        });
+        assert_eq!(
+            v1.migrate(&tn!("Order"), order.clone(), &add).unwrap_err(),
+            MigrationError::FieldExists {
+                type_name: tn!("Order"),
+                field_name: fname!("total")
+            }
+        );
+
+        let change = rule(MigrationRule::ChangeFieldType {
+            type_name: tn!("Order"),
+            field_name: fname!("total"),
+            old_sem_id: Ty::<SemId>::U8.sem_id_unnamed(),
+            new_sem_id: Ty::<SemId>::U32.sem_id_unnamed(),
+            coerce: None,
+        });
+        assert!(matches!(
+            v1.migrate(&tn!("Order"), order.clone(), &change).unwrap_err(),
+            MigrationError::FieldTypeMismatch { .. }
+        ));
+
+        let rename = rule(MigrationRule::RenameVariant {
+            type_name: tn!("Item"),
+            old_name: vname!("red"),
+            new_name: vname!("blue"),
+        });
+        assert_eq!(
+            v1.migrate(&tn!("Order"), order, &rename).unwrap_err(),
+            MigrationError::NotEnum(tn!("Item"))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        let migration = Migration::new([
+            MigrationRule::RenameField {
+                type_name: tn!("Item"),
+                old_name: fname!("colour"),
+                new_name: fname!("color"),
+            },
+            MigrationRule::ChangeFieldType {
+                type_name: tn!("Order"),
+                field_name: fname!("total"),
+                old_sem_id: Ty::<SemId>::U16.sem_id_unnamed(),
+                new_sem_id: Ty::<SemId>::U32.sem_id_unnamed(),
+                coerce: Some(|val| val),
+            },
+        ]);
+        let json = serde_json::to_string(&migration).unwrap();
+        assert!(json.contains(r#""rule":"renameField","typeName":"Item","oldName":"colour""#));
+        let decoded = serde_json::from_str::<Migration>(&json).unwrap();
+        assert_eq!(decoded.rules.len(), 2);
+        assert!(matches!(
+            &decoded.rules[1],
+            MigrationRule::ChangeFieldType { coerce: None, field_name, .. } if field_name == &fname!("total")
+        ));
+    }
+}
@@ -24,23 +24,54 @@ mod id;
 mod type_lib;
 mod compile;
 mod serialize;
+mod stl_text;
+mod lint;
 mod transpile;
 mod symbolic;
 mod translate;
+mod merge;
+mod flatten;
+mod migrate;
+mod compat;
+mod diff;
+mod stats;
+mod proto;
+mod typescript;
+mod dot;
+#[cfg(feature = "serde")]
+mod json_schema;
+#[cfg(feature = "serde")]
+mod avro;
+#[cfg(test)]
+mod arbitrary;
 
+pub use compat::{AddedItem, BreakingChange, CompatResult};
 pub(crate) use compile::NestedContext;
 #[allow(deprecated)]
 pub use compile::TranslateError;
 pub use compile::{CompileError, TypeIndex};
+pub use diff::TypeLibDiff;
+pub use flatten::FlattenError;
 pub use id::TypeLibId;
-pub use symbolic::{ExternTypes, SymbolRef, SymbolicLib, TranspileError, TranspileRef};
+#[cfg(feature = "serde")]
+pub use json_schema::{JSON_SCHEMA_DRAFT07, JSON_SCHEMA_UNION_TAG};
+pub use lint::{LintWarning, LINT_MAX_DEPTH, LINT_MAX_FIELDS};
+pub use merge::{MergeError, MergeStrategy};
+pub use migrate::{Migration, MigrationError, MigrationRule};
+pub use stats::BuildStats;
+pub use stl_text::{ParseError, ParseErrorKind};
+pub use symbolic::{
+    BuildWarning, ExternTypes, RemoveError, SymbolRef, SymbolicLib, TranspileError, TranspileRef,
+};
 use translate::SymbolContext;
 pub use translate::SymbolError;
 pub use transpile::LibBuilder;
 pub(crate) use type_lib::TypeMap;
 pub use type_lib::{
-    Dependency, ExternRef, InlineRef, InlineRef1, InlineRef2, LibRef, LibSubref, TypeLib,
+    Dependency, Doc, ExternRef, InlineRef, InlineRef1, InlineRef2, LibRef, LibSubref,
+    LibSubrefView, TypeLib,
 };
+pub use typescript::TS_UNION_TAG;
 
 #[deprecated(since = "1.3.0", note = "import from the crate root")]
 pub use super::parse_args;
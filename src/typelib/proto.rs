@@ -0,0 +1,540 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generation of proto3 `.proto` files from type libraries.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use encoding::{LibName, NumCls, Primitive, TypeName};
+
+use crate::typelib::{ExternRef, ExternTypes, LibSubref, LibSubrefView};
+use crate::{SemId, Ty, TypeLib, TypeLibId};
+
+const PROTO_EMPTY: &str = "google.protobuf.Empty";
+const PROTO_EMPTY_IMPORT: &str = "google/protobuf/empty.proto";
+
+struct ProtoCtx<'lib> {
+    names: BTreeMap<SemId, &'lib TypeName>,
+    libs: BTreeMap<TypeLibId, &'lib LibName>,
+    extern_types: &'lib ExternTypes,
+    imports: BTreeSet<String>,
+}
+
+impl ProtoCtx<'_> {
+    fn named(&self, sem_id: &SemId) -> Result<String, String> {
+        self.names.get(sem_id).map(|name| name.to_string()).ok_or(format!("unknown type {sem_id}"))
+    }
+
+    fn external(&mut self, ext: &ExternRef) -> Result<String, String> {
+        let lib = self.libs.get(&ext.lib_id).ok_or(format!("unknown library {}", ext.lib_id))?;
+        let name = self
+            .extern_types
+            .get(*lib)
+            .and_then(|index| index.get(&ext.sem_id))
+            .ok_or(format!("unknown type {lib}.{}", ext.sem_id))?;
+        self.imports.insert(format!("{lib}.proto"));
+        Ok(format!(".{lib}.{name}"))
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Label {
+    Single,
+    Repeated,
+    Map,
+}
+
+struct ProtoField {
+    label: Label,
+    ty: String,
+    comment: Option<String>,
+}
+
+impl ProtoField {
+    fn single(ty: impl ToString) -> Self {
+        ProtoField {
+            label: Label::Single,
+            ty: ty.to_string(),
+            comment: None,
+        }
+    }
+
+    fn repeated(ty: impl ToString) -> Self {
+        ProtoField {
+            label: Label::Repeated,
+            ty: ty.to_string(),
+            comment: None,
+        }
+    }
+
+    fn with_comment(mut self, comment: impl ToString) -> Self {
+        self.comment = Some(comment.to_string());
+        self
+    }
+}
+
+trait ProtoRef: LibSubref {
+    /// Resolves the type of a single (non-repeated) proto value.
+    fn proto_elem(
+        &self,
+        ctx: &mut ProtoCtx,
+        hint: &str,
+        nested: &mut Vec<String>,
+        indent: usize,
+    ) -> Result<String, String> {
+        match self.view() {
+            LibSubrefView::Inline(ty) => inline_elem(ty, ctx, hint, nested, indent),
+            LibSubrefView::Named(sem_id) => ctx.named(sem_id),
+            LibSubrefView::Extern(ext) => ctx.external(ext),
+        }
+    }
+
+    /// Resolves the type of a proto message field.
+    fn proto_field(
+        &self,
+        ctx: &mut ProtoCtx,
+        hint: &str,
+        nested: &mut Vec<String>,
+        indent: usize,
+    ) -> Result<ProtoField, String> {
+        match self.view() {
+            LibSubrefView::Inline(ty) => field_ty(ty, ctx, hint, nested, indent),
+            _ => self.proto_elem(ctx, hint, nested, indent).map(ProtoField::single),
+        }
+    }
+
+    /// Resolves the type of a proto map key.
+    fn proto_key(&self) -> Result<String, String> {
+        match self.view() {
+            LibSubrefView::Inline(ty) => key_ty(ty),
+            _ => Err(s!("maps with non-scalar keys")),
+        }
+    }
+}
+
+impl<Ref: LibSubref> ProtoRef for Ref {}
+
+fn pascal_case(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+fn screaming_case(name: &str) -> String {
+    let mut s = String::with_capacity(name.len() + 4);
+    for (pos, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() && pos > 0 {
+            s.push('_');
+        }
+        s.push(c.to_ascii_uppercase());
+    }
+    s
+}
+
+fn scalar(prim: Primitive, ctx: &mut ProtoCtx) -> ProtoField {
+    if prim == Primitive::UNIT {
+        ctx.imports.insert(PROTO_EMPTY_IMPORT.to_owned());
+        return ProtoField::single(PROTO_EMPTY);
+    }
+    if prim == Primitive::BYTE {
+        return ProtoField::single("uint32").with_comment("byte");
+    }
+    let info = prim.info();
+    let ty = match (info.ty, info.byte_size()) {
+        (NumCls::Unsigned | NumCls::NonZero, 1..=4) => "uint32",
+        (NumCls::Unsigned | NumCls::NonZero, 5..=8) => "uint64",
+        (NumCls::Signed, 1..=4) => "int32",
+        (NumCls::Signed, 5..=8) => "int64",
+        (NumCls::Float, 4) => "float",
+        (NumCls::Float, 8) => "double",
+        _ => return ProtoField::single("bytes").with_comment(format!("{prim}, little-endian")),
+    };
+    let field = ProtoField::single(ty);
+    if info.byte_size() < 4 {
+        field.with_comment(prim)
+    } else {
+        field
+    }
+}
+
+fn key_ty<Ref: ProtoRef>(ty: &Ty<Ref>) -> Result<String, String> {
+    match ty {
+        Ty::Primitive(prim) if *prim != Primitive::UNIT => {
+            let info = prim.info();
+            match (info.ty, info.byte_size()) {
+                (NumCls::Unsigned | NumCls::NonZero, 1..=4) => Ok(s!("uint32")),
+                (NumCls::Unsigned | NumCls::NonZero, 5..=8) => Ok(s!("uint64")),
+                (NumCls::Signed, 1..=4) => Ok(s!("int32")),
+                (NumCls::Signed, 5..=8) => Ok(s!("int64")),
+                _ => Err(format!("maps with {prim} keys")),
+            }
+        }
+        Ty::List(ty, _) if ty.is_unicode_char() => Ok(s!("string")),
+        _ => Err(s!("maps with non-scalar keys")),
+    }
+}
+
+fn inline_elem<Ref: ProtoRef>(
+    ty: &Ty<Ref>,
+    ctx: &mut ProtoCtx,
+    hint: &str,
+    nested: &mut Vec<String>,
+    indent: usize,
+) -> Result<String, String> {
+    let field = field_ty(ty, ctx, hint, nested, indent)?;
+    match field.label {
+        Label::Single => Ok(field.ty),
+        Label::Repeated | Label::Map => Err(s!("nested collections")),
+    }
+}
+
+fn field_ty<Ref: ProtoRef>(
+    ty: &Ty<Ref>,
+    ctx: &mut ProtoCtx,
+    hint: &str,
+    nested: &mut Vec<String>,
+    indent: usize,
+) -> Result<ProtoField, String> {
+    Ok(match ty {
+        Ty::Primitive(prim) => scalar(*prim, ctx),
+        Ty::UnicodeChar => ProtoField::single("string").with_comment("single character"),
+        Ty::Enum(_) | Ty::Union(_) | Ty::Struct(_) | Ty::Tuple(_) => {
+            nested.push(definition(hint, ty, ctx, indent)?);
+            ProtoField::single(hint)
+        }
+        Ty::Array(ty, len) if ty.is_byte() => {
+            ProtoField::single("bytes").with_comment(format!("fixed length of {len} bytes"))
+        }
+        Ty::Array(ty, len) => ProtoField::repeated(ty.proto_elem(ctx, hint, nested, indent)?)
+            .with_comment(format!("fixed length of {len} items")),
+        Ty::List(ty, _) if ty.is_unicode_char() => ProtoField::single("string"),
+        Ty::List(ty, _) if ty.is_byte() => ProtoField::single("bytes"),
+        Ty::List(ty, _) => ProtoField::repeated(ty.proto_elem(ctx, hint, nested, indent)?),
+        Ty::Set(ty, _) => ProtoField::repeated(ty.proto_elem(ctx, hint, nested, indent)?)
+            .with_comment("unique items"),
+        Ty::Map(key, val, _) => {
+            let key = key.proto_key()?;
+            let val = val.proto_elem(ctx, hint, nested, indent)?;
+            ProtoField {
+                label: Label::Map,
+                ty: format!("map<{key}, {val}>"),
+                comment: None,
+            }
+        }
+    })
+}
+
+fn write_field(
+    body: &mut String,
+    field: Result<ProtoField, String>,
+    name: &str,
+    no: usize,
+    indent: usize,
+) {
+    let pad = "  ".repeat(indent);
+    match field {
+        Ok(ProtoField { label, ty, comment }) => {
+            let label = if label == Label::Repeated { "repeated " } else { "" };
+            write!(body, "{pad}{label}{ty} {name} = {no};").ok();
+            if let Some(comment) = comment {
+                write!(body, " // {comment}").ok();
+            }
+            writeln!(body).ok();
+        }
+        Err(reason) => {
+            writeln!(body, "{pad}// TODO: not supported: {name} = {no} ({reason})").ok();
+        }
+    }
+}
+
+fn definition<Ref: ProtoRef>(
+    name: &str,
+    ty: &Ty<Ref>,
+    ctx: &mut ProtoCtx,
+    indent: usize,
+) -> Result<String, String> {
+    let pad = "  ".repeat(indent);
+    let mut nested = vec![];
+    let mut body = String::new();
+
+    if let Ty::Enum(variants) = ty {
+        let prefix = screaming_case(name);
+        writeln!(body, "{pad}enum {name} {{").ok();
+        if variants.name_by_tag(0).is_none() {
+            writeln!(body, "{pad}  {prefix}_UNSPECIFIED = 0;").ok();
+        }
+        // protoc rejects value names which differ only in case and underscores
+        let mut seen = BTreeSet::new();
+        for variant in variants.iter() {
+            let mut variant_name = screaming_case(variant.name.as_str());
+            if !seen.insert(variant_name.replace('_', "")) {
+                variant_name = format!("{variant_name}_{}", variant.tag);
+            }
+            writeln!(body, "{pad}  {prefix}_{variant_name} = {};", variant.tag).ok();
+        }
+        writeln!(body, "{pad}}}").ok();
+        return Ok(body);
+    }
+
+    match ty {
+        Ty::Union(variants) => {
+            writeln!(body, "{pad}  oneof value {{").ok();
+            for (variant, ty) in variants {
+                let hint = pascal_case(variant.name.as_str());
+                let field =
+                    ty.proto_elem(ctx, &hint, &mut nested, indent + 1).map(ProtoField::single);
+                write_field(
+                    &mut body,
+                    field,
+                    variant.name.as_str(),
+                    variant.tag as usize + 1,
+                    indent + 2,
+                );
+            }
+            writeln!(body, "{pad}  }}").ok();
+        }
+        Ty::Struct(fields) => {
+            for (no, field) in fields.iter().enumerate() {
+                let hint = pascal_case(field.name.as_str());
+                let proto = field.ty.proto_field(ctx, &hint, &mut nested, indent + 1);
+                write_field(&mut body, proto, field.name.as_str(), no + 1, indent + 1);
+            }
+        }
+        Ty::Tuple(fields) => {
+            for (no, ty) in fields.iter().enumerate() {
+                let hint = format!("F{no}");
+                let proto = ty.proto_field(ctx, &hint, &mut nested, indent + 1);
+                write_field(&mut body, proto, &format!("f{no}"), no + 1, indent + 1);
+            }
+        }
+        _ => {
+            let proto = field_ty(ty, ctx, "Value", &mut nested, indent + 1);
+            write_field(&mut body, proto, "value", 1, indent + 1);
+        }
+    }
+
+    let mut def = format!("{pad}message {name} {{\n");
+    for nested in nested {
+        def.push_str(&nested);
+    }
+    def.push_str(&body);
+    writeln!(def, "{pad}}}").ok();
+    Ok(def)
+}
+
+impl TypeLib {
+    /// Generates proto3 `.proto` file describing the library types. Types from the library
+    /// dependencies are imported from `<LibName>.proto` files.
+    ///
+    /// Type constructs which can't be represented in protobuf are emitted as `TODO` comments.
+    pub fn to_proto_file(&self) -> String {
+        let mut ctx = ProtoCtx {
+            names: self.types.iter().map(|(name, ty)| (ty.sem_id_named(name), name)).collect(),
+            libs: self.dependencies.iter().map(|dep| (dep.id, &dep.name)).collect(),
+            extern_types: &self.extern_types,
+            imports: empty!(),
+        };
+
+        let mut body = String::new();
+        for (name, ty) in &self.types {
+            writeln!(body).ok();
+            match definition(name.as_str(), ty, &mut ctx, 0) {
+                Ok(def) => body.push_str(&def),
+                Err(reason) => {
+                    writeln!(body, "// TODO: not supported: {name} ({reason})").ok();
+                }
+            }
+        }
+
+        let mut proto = format!("// Generated from strict type library {}\n\n", self.id());
+        proto.push_str("syntax = \"proto3\";\n\n");
+        writeln!(proto, "package {};", self.name).ok();
+        if !ctx.imports.is_empty() {
+            writeln!(proto).ok();
+        }
+        for import in ctx.imports {
+            writeln!(proto, "import \"{import}\";").ok();
+        }
+        proto.push_str(&body);
+        proto
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use encoding::Sizing;
+
+    use super::*;
+    use crate::stl::{std_stl, strict_types_stl};
+    use crate::typelib::LibRef;
+    use crate::value::test_helpers::Nominal;
+    use crate::LibBuilder;
+
+    #[test]
+    fn nominal() {
+        let std = std_stl();
+        let st = strict_types_stl();
+        let lib = LibBuilder::new("TestLib", [std.to_dependency(), st.to_dependency()])
+            .transpile::<Nominal>()
+            .compile()
+            .unwrap();
+        let proto = lib.to_proto_file();
+        let body = proto.split_once("\n\n").unwrap().1;
+        assert_eq!(
+            body,
+            r#"syntax = "proto3";
+
+package TestLib;
+
+import "StrictTypes.proto";
+
+message Nominal {
+  .StrictTypes.Ident ticker = 1;
+  string name = 2;
+  Precision precision = 3;
+}
+
+enum Precision {
+  PRECISION_NO_DECIMALS = 0;
+  PRECISION_ONE_DECIMAL = 1;
+  PRECISION_TWO_DECIMALS = 2;
+}
+"#
+        );
+    }
+
+    /// Mirrors of the messages generated by prost from `TestLib.proto`.
+    #[cfg(not(target_arch = "wasm32"))]
+    mod prost_mirror {
+        #[derive(Clone, PartialEq, prost::Message)]
+        pub struct Nominal {
+            #[prost(message, optional, tag = "1")]
+            pub ticker: Option<Ident>,
+            #[prost(string, tag = "2")]
+            pub name: String,
+            #[prost(int32, tag = "3")]
+            pub precision: i32,
+        }
+
+        #[derive(Clone, PartialEq, prost::Message)]
+        pub struct Ident {
+            #[prost(message, optional, tag = "1")]
+            pub f0: Option<IdentF0>,
+        }
+
+        #[derive(Clone, PartialEq, prost::Message)]
+        pub struct IdentF0 {
+            #[prost(int32, tag = "1")]
+            pub f0: i32,
+            #[prost(int32, repeated, tag = "2")]
+            pub f1: Vec<i32>,
+        }
+    }
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn prost_compile() {
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+
+        use prost::Message;
+
+        let std = std_stl();
+        let st = strict_types_stl();
+        let lib = LibBuilder::new("TestLib", [std.to_dependency(), st.to_dependency()])
+            .transpile::<Nominal>()
+            .compile()
+            .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        for lib in [&std, &st, &lib] {
+            std::fs::write(dir.join(format!("{}.proto", lib.name)), lib.to_proto_file()).unwrap();
+        }
+        let protoc = protoc_bin_vendored::protoc_bin_path().unwrap();
+        let include = protoc_bin_vendored::include_path().unwrap();
+        prost_build::Config::new()
+            .protoc_executable(&protoc)
+            .out_dir(dir)
+            .compile_protos(&[dir.join("TestLib.proto")], &[dir])
+            .unwrap();
+        let rust = std::fs::read_to_string(dir.join("test_lib.rs")).unwrap();
+        assert!(rust.contains("pub struct Nominal"));
+
+        let protoc = |mode: &str, input: &[u8]| {
+            let mut child = Command::new(&protoc)
+                .arg(format!("--{mode}=TestLib.Nominal"))
+                .arg("-I")
+                .arg(dir)
+                .arg("-I")
+                .arg(&include)
+                .arg("TestLib.proto")
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .spawn()
+                .unwrap();
+            child.stdin.take().unwrap().write_all(input).unwrap();
+            let output = child.wait_with_output().unwrap();
+            assert!(output.status.success());
+            output.stdout
+        };
+
+        let nominal = prost_mirror::Nominal {
+            ticker: Some(prost_mirror::Ident {
+                f0: Some(prost_mirror::IdentF0 {
+                    f0: 20,
+                    f1: vec![30, 1, 2],
+                }),
+            }),
+            name: s!("Some name"),
+            precision: 2,
+        };
+        let text = protoc("decode", &nominal.encode_to_vec());
+        let text = String::from_utf8(text).unwrap();
+        assert!(text.contains(r#"name: "Some name""#), "{text}");
+        assert!(text.contains("precision: PRECISION_TWO_DECIMALS"), "{text}");
+        let data = protoc("encode", text.as_bytes());
+        assert_eq!(prost_mirror::Nominal::decode(data.as_slice()).unwrap(), nominal);
+    }
+
+    #[test]
+    fn unsupported() {
+        let mut ctx = ProtoCtx {
+            names: empty!(),
+            libs: empty!(),
+            extern_types: &ExternTypes::default(),
+            imports: empty!(),
+        };
+        let ty = Ty::<LibRef>::Map(
+            LibRef::Named(Ty::<LibRef>::UNIT.sem_id_unnamed()),
+            LibRef::Inline(Ty::U8),
+            Sizing::U8,
+        );
+        let def = definition("Index", &ty, &mut ctx, 0).unwrap();
+        assert_eq!(
+            def,
+            "message Index {\n  // TODO: not supported: value = 1 (maps with non-scalar keys)\n}\n"
+        );
+    }
+}
@@ -21,8 +21,8 @@
 // limitations under the License.
 
 use std::fmt::{Display, Formatter};
-use std::path::Path;
-use std::{fmt, io};
+use std::path::{Path, PathBuf};
+use std::{env, fmt, fs, io};
 
 use amplify::confinement::U24 as U24MAX;
 use baid64::DisplayBaid64;
@@ -76,6 +76,35 @@ impl TypeLib {
 
         Ok(())
     }
+
+    /// Writes the library in binary form to `strict-types/<name>.stl` inside the `dir`, returning
+    /// the path to the file. The file content depends only on the library types.
+    pub fn export_to(&self, dir: impl AsRef<Path>) -> io::Result<PathBuf> {
+        let mut path = dir.as_ref().join("strict-types");
+        fs::create_dir_all(&path)?;
+        path.push(format!("{}.stl", self.name));
+        let data = self
+            .to_strict_serialized::<U24MAX>()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        fs::write(&path, data.as_slice())?;
+        Ok(path)
+    }
+
+    /// Exports the library from a build script into its `OUT_DIR` (see [`Self::export_to`]).
+    ///
+    /// The exported library can be embedded into the crate with
+    /// [`include_type_lib!`](crate::include_type_lib), which avoids transpiling the library at
+    /// runtime:
+    ///
+    /// ```ignore
+    /// let lib = include_type_lib!(concat!(env!("OUT_DIR"), "/strict-types/MyLib.stl"));
+    /// ```
+    pub fn export_build(&self) -> io::Result<PathBuf> {
+        let dir = env::var_os("OUT_DIR").ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "OUT_DIR is not set outside of a build script")
+        })?;
+        self.export_to(dir)
+    }
 }
 
 impl StrictSerialize for SymbolicLib {}
@@ -187,3 +216,23 @@ impl armor::StrictArmor for TypeLib {
         headers
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::stl::std_stl;
+
+    #[test]
+    fn export() {
+        let std = std_stl();
+        let dir = env::temp_dir().join(format!("strict-types-export-{}", std::process::id()));
+        let path = std.export_to(&dir).unwrap();
+        assert_eq!(path, dir.join("strict-types").join("Std.stl"));
+        let data = fs::read(&path).unwrap();
+        assert_eq!(data, std.export_to(&dir).map(fs::read).unwrap().unwrap());
+        fs::remove_dir_all(dir).unwrap();
+
+        assert_eq!(include_type_lib!("../../stl/Std@0.1.0.stl"), std);
+        assert_eq!(data, include_bytes!("../../stl/Std@0.1.0.stl"));
+    }
+}
@@ -0,0 +1,186 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Diagnostics of library construction with [`LibBuilder`].
+
+use std::fmt::{self, Display, Formatter};
+use std::time::{Duration, Instant};
+
+use encoding::TypeName;
+
+use crate::typesys::SystemBuilder;
+use crate::{LibBuilder, TranspileRef, Ty, TypeLib};
+
+/// Statistics of a library under construction, returned by [`LibBuilder::statistics`].
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct BuildStats {
+    /// Number of named types registered in the library.
+    pub named_types: usize,
+    /// Number of references to inline (embedded) types, including the nested ones.
+    pub inline_refs: usize,
+    /// Number of references to types from other libraries.
+    pub extern_refs: usize,
+    /// Number of distinct semantic ids of named and inline types of the compiled library.
+    pub sem_ids: usize,
+    /// Number of references to named types which were already registered in the library and
+    /// thus didn't require transpiling the type again.
+    pub cache_hits: usize,
+    /// Time taken by the library compilation; `None` if the library can't be compiled.
+    pub compile_time: Option<Duration>,
+}
+
+impl Display for BuildStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "named types: {}", self.named_types)?;
+        writeln!(f, "inline references: {}", self.inline_refs)?;
+        writeln!(f, "extern references: {}", self.extern_refs)?;
+        writeln!(f, "semantic ids: {}", self.sem_ids)?;
+        writeln!(f, "cache hits: {}", self.cache_hits)?;
+        match self.compile_time {
+            Some(time) => writeln!(f, "compile time: {time:?}"),
+            None => writeln!(f, "compile time: n/a"),
+        }
+    }
+}
+
+fn count_refs(ty: &Ty<TranspileRef>, stats: &mut BuildStats) {
+    for (r, _) in ty.type_refs() {
+        match r {
+            TranspileRef::Embedded(ty) => {
+                stats.inline_refs += 1;
+                count_refs(ty, stats);
+            }
+            TranspileRef::Extern(_) => stats.extern_refs += 1,
+            TranspileRef::Named(_) => {}
+        }
+    }
+}
+
+impl LibBuilder {
+    /// Counts types and type references registered in the builder so far.
+    pub(super) fn ref_stats(&self) -> BuildStats {
+        let mut stats = BuildStats {
+            named_types: self.types.len(),
+            cache_hits: self.cache_hits,
+            ..default!()
+        };
+        for ty in self.types.values() {
+            count_refs(ty, &mut stats);
+        }
+        stats
+    }
+
+    /// Compiles a copy of the library, leaving the builder intact.
+    fn trial_compile(&self) -> Option<(TypeLib, Duration)> {
+        if self.types.is_empty() {
+            return None;
+        }
+        let mut builder = LibBuilder::new(self.lib_name.clone(), self.known_libs.clone());
+        builder.extern_types = self.extern_types.clone();
+        builder.types = self.types.clone();
+        builder.conflicts = self.conflicts.clone();
+        let start = Instant::now();
+        let lib = builder.compile_symbols().ok()?.compile().ok()?;
+        Some((lib, start.elapsed()))
+    }
+
+    /// Returns statistics of the types registered in the builder.
+    ///
+    /// Since [`LibBuilder::compile`] consumes the builder, the compilation time and the number
+    /// of semantic ids are measured by compiling a copy of the library.
+    pub fn statistics(&self) -> BuildStats {
+        let mut stats = self.ref_stats();
+        if let Some((lib, elapsed)) = self.trial_compile() {
+            stats.compile_time = Some(elapsed);
+            stats.sem_ids = SystemBuilder::new()
+                .import(lib)
+                .map(|builder| builder.into_type_system().len())
+                .unwrap_or_default();
+        }
+        #[cfg(feature = "tracing")]
+        tracing::info!(lib = %self.lib_name, ?stats, "library build statistics");
+        stats
+    }
+
+    /// Lists the named types of the library with the maximal number of bytes their values take
+    /// when strict-encoded (see [`Ty::encoded_size_range`]). For types of unbounded size the
+    /// minimal size is reported.
+    ///
+    /// Types from other libraries are not known to the builder and are treated as unbounded.
+    /// Returns an empty list if the library can't be compiled.
+    pub fn type_size_report(&self) -> Vec<(TypeName, usize)> {
+        let Some((lib, _)) = self.trial_compile() else {
+            return vec![];
+        };
+        let named = lib
+            .types
+            .iter()
+            .map(|(name, ty)| (name.clone(), ty.sem_id_named(name)))
+            .collect::<Vec<_>>();
+        let Ok(builder) = SystemBuilder::new().import(lib) else {
+            return vec![];
+        };
+        let sys = builder.into_type_system();
+        named
+            .into_iter()
+            .map(|(name, id)| {
+                let (min, max) = sys[id].encoded_size_range(&sys);
+                (name, usize::try_from(max.unwrap_or(min)).unwrap_or(usize::MAX))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::stl::{std_stl, strict_types_stl};
+    use crate::value::test_helpers::Nominal;
+
+    #[test]
+    fn statistics() {
+        let std = std_stl();
+        let st = strict_types_stl();
+        let builder =
+            LibBuilder::new(libname!("TestLib"), [std.to_dependency(), st.to_dependency()])
+                .transpile::<Nominal>()
+                .transpile::<Nominal>();
+
+        let stats = builder.statistics();
+        assert_eq!(stats.named_types, builder.types.len());
+        assert!(stats.cache_hits > 0);
+        assert!(stats.extern_refs > 0);
+        assert!(stats.compile_time.is_some());
+        assert!(stats.sem_ids >= stats.named_types);
+
+        let report = builder.type_size_report();
+        assert_eq!(report.len(), stats.named_types);
+        assert!(report.contains(&(tn!("Precision"), 1)));
+
+        let lib = builder.compile().unwrap();
+        assert_eq!(stats.named_types, lib.types.len());
+
+        let stats = LibBuilder::new(libname!("Empty"), []).statistics();
+        assert_eq!(stats, BuildStats::default());
+    }
+}
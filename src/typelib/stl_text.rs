@@ -0,0 +1,930 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Strict type library (`.stl`) text notation of [`SymbolicLib`], which, unlike its `Display`
+//! output, can be parsed back.
+//!
+//! ```text
+//! typemod LibName
+//!
+//! import StdLib#stl:...
+//!   use Baz#semid:...
+//!
+//! /// Type documentation
+//! data Foo :: bar Bar, baz StdLib.Baz
+//!   /// bar: field documentation
+//! data Bar :: none#0 () | some#1 U8
+//! ```
+//!
+//! Each type is defined on a single line. Compound types nested into other types are put in
+//! parentheses; single-element tuples of inline types are marked with a trailing comma, and union
+//! and enum variants always carry their tags.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::str::FromStr;
+
+use amplify::confinement::Confined;
+use encoding::{FieldName, LibName, Primitive, Sizing, TypeName, Variant, VariantName};
+
+use super::symbolic::{SymbolRef, SymbolicLib, TranspileRef};
+use crate::ast::{Field, NamedFields, UnionVariants, UnnamedFields};
+use crate::typelib::Doc;
+use crate::{Dependency, SemId, Ty, TypeLibId};
+
+/// Error parsing `.stl` text, providing the position at which it has happened.
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display("line {line}, column {column}: {kind}")]
+pub struct ParseError {
+    /// Line number, starting from 1.
+    pub line: usize,
+    /// Column number in characters, starting from 1.
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Display)]
+#[display(doc_comments)]
+pub enum ParseErrorKind {
+    /// unexpected end of the line; {0} was expected.
+    UnexpectedEnd(&'static str),
+
+    /// unexpected character `{0}`; {1} was expected.
+    UnexpectedChar(char, &'static str),
+
+    /// unknown statement `{0}`.
+    UnknownStatement(String),
+
+    /// the text doesn't start with `typemod` statement.
+    NoTypemod,
+
+    /// invalid number `{0}`.
+    InvalidNumber(String),
+
+    /// invalid identifier `{0}`.
+    InvalidIdent(String),
+
+    /// invalid library or semantic id `{0}`.
+    InvalidId(String),
+
+    /// documentation `{0}` is longer than 255 bytes.
+    InvalidDoc(String),
+
+    /// documentation is not followed by a type definition.
+    DanglingDoc,
+
+    /// `use` statement doesn't follow a library import.
+    DanglingUse,
+
+    /// library `{0}` is imported more than once.
+    RepeatedImport(LibName),
+
+    /// type `{0}` is defined more than once.
+    RepeatedType(TypeName),
+
+    /// repeated field name `{0}`.
+    RepeatedField(FieldName),
+
+    /// repeated variant name or tag `{0}`.
+    RepeatedVariant(VariantName),
+
+    /// library `{0}` is not imported.
+    UnknownLib(LibName),
+
+    /// type `{1}` is not listed among the types used from library `{0}`.
+    UnknownExtern(LibName, TypeName),
+
+    /// fields, variants and types can't be mixed in the same type definition.
+    MixedItems,
+
+    /// {0} exceeds the allowed number of items.
+    TooMany(&'static str),
+}
+
+impl SymbolicLib {
+    /// Returns text representation of the library in the strict type library (`.stl`)
+    /// notation, which can be read back with [`SymbolicLib::from_stl_text`].
+    pub fn to_stl_text(&self) -> String {
+        let mut s = format!("typemod {}\n\n", self.name());
+        for dep in self.dependencies() {
+            writeln!(s, "import {}#{}", dep.name, dep.id).expect("writing to string");
+            for (sem_id, name) in self.extern_types().get(&dep.name).into_iter().flatten() {
+                writeln!(s, "  use {name}#{sem_id}").expect("writing to string");
+            }
+            s.push('\n');
+        }
+        if self.dependencies().is_empty() {
+            s.push_str("-- no dependencies\n\n");
+        }
+        for (name, ty) in self.types() {
+            if let Some(doc) = self.type_docs().get(name) {
+                push_doc(&mut s, "", doc);
+            }
+            write!(s, "data {name} :: ").expect("writing to string");
+            match ty {
+                Ty::Tuple(fields)
+                    if fields.len() == 1 && matches!(fields[0], TranspileRef::Embedded(_)) =>
+                {
+                    push_ref(&mut s, &fields[0]);
+                    s.push(',');
+                }
+                ty => push_ty(&mut s, ty),
+            }
+            s.push('\n');
+            for (field, doc) in self.field_docs().get(name).into_iter().flatten() {
+                push_doc(&mut s, &format!("  /// {field}:"), doc);
+            }
+        }
+        s
+    }
+
+    /// Parses library from the strict type library (`.stl`) text notation produced by
+    /// [`SymbolicLib::to_stl_text`].
+    pub fn from_stl_text(s: &str) -> Result<SymbolicLib, ParseError> {
+        let declared = s
+            .lines()
+            .filter_map(|line| line.trim_start().strip_prefix("data "))
+            .filter_map(|rest| rest.split("::").next())
+            .map(str::trim)
+            .collect::<BTreeSet<_>>();
+
+        let mut name = None;
+        let mut dependencies = BTreeSet::<Dependency>::new();
+        let mut extern_types = BTreeMap::<LibName, BTreeMap<SemId, TypeName>>::new();
+        let mut types = BTreeMap::<TypeName, Ty<TranspileRef>>::new();
+        let mut type_docs = BTreeMap::<TypeName, Doc>::new();
+        let mut field_docs = BTreeMap::<TypeName, BTreeMap<FieldName, Doc>>::new();
+
+        let mut last_import = None::<LibName>;
+        let mut last_type = None::<TypeName>;
+        let mut last_field = None::<FieldName>;
+        let mut pending_doc = None::<String>;
+
+        for (no, line) in s.lines().enumerate() {
+            let mut parser = Parser {
+                src: line,
+                pos: 0,
+                line: no + 1,
+                declared: &declared,
+            };
+            parser.skip_ws();
+            let indented = parser.pos > 0;
+            let rest = parser.rest();
+            if rest.is_empty() || rest.starts_with("--") {
+                continue;
+            }
+            if name.is_none() {
+                if !rest.starts_with("typemod ") {
+                    return Err(parser.err(ParseErrorKind::NoTypemod));
+                }
+                parser.pos += "typemod ".len();
+                name = Some(parser.parse_ident::<LibName>()?);
+                parser.expect_end()?;
+                continue;
+            }
+
+            if let Some(doc) = rest.strip_prefix("///") {
+                let doc = doc.strip_prefix(' ').unwrap_or(doc);
+                match (indented, &last_type) {
+                    (true, Some(ty_name)) => {
+                        let Some((field, doc)) = doc.split_once(':') else {
+                            return Err(parser.unexpected("`:` after documented field name"));
+                        };
+                        let doc = doc.strip_prefix(' ').unwrap_or(doc);
+                        let field = FieldName::from_str(field)
+                            .map_err(|_| parser.err(ParseErrorKind::InvalidIdent(field.into())))?;
+                        let docs = field_docs.entry(ty_name.clone()).or_default();
+                        let text = match docs.get(&field) {
+                            Some(prev) if last_field.as_ref() == Some(&field) => {
+                                format!("{prev}\n{doc}")
+                            }
+                            _ => doc.to_owned(),
+                        };
+                        docs.insert(field.clone(), parser.doc(text)?);
+                        last_field = Some(field);
+                    }
+                    _ => {
+                        pending_doc = Some(match pending_doc {
+                            Some(prev) => format!("{prev}\n{doc}"),
+                            None => doc.to_owned(),
+                        });
+                    }
+                }
+                continue;
+            }
+            last_field = None;
+
+            if rest.starts_with("import ") {
+                parser.pos += "import ".len();
+                let lib = parser.parse_ident::<LibName>()?;
+                parser.expect('#', "`#` followed by library id")?;
+                let id = parser.parse_id::<TypeLibId>()?;
+                if !dependencies.insert(Dependency::with(id, lib.clone())) {
+                    return Err(parser.err(ParseErrorKind::RepeatedImport(lib)));
+                }
+                last_import = Some(lib);
+                last_type = None;
+            } else if rest.starts_with("use ") {
+                parser.pos += "use ".len();
+                let ty_name = parser.parse_ident::<TypeName>()?;
+                parser.expect('#', "`#` followed by semantic id")?;
+                let sem_id = parser.parse_id::<SemId>()?;
+                let Some(lib) = &last_import else {
+                    return Err(parser.err(ParseErrorKind::DanglingUse));
+                };
+                extern_types.entry(lib.clone()).or_default().insert(sem_id, ty_name);
+            } else if rest.starts_with("data ") {
+                parser.pos += "data ".len();
+                let ty_name = parser.parse_ident::<TypeName>()?;
+                parser.skip_ws();
+                if !parser.rest().starts_with("::") {
+                    return Err(parser.unexpected("`::`"));
+                }
+                parser.pos += 2;
+                let ty = parser.parse_body(None)?;
+                parser.expect_end()?;
+                if types.contains_key(&ty_name) {
+                    return Err(parser.err(ParseErrorKind::RepeatedType(ty_name)));
+                }
+                if let Some(doc) = pending_doc.take() {
+                    type_docs.insert(ty_name.clone(), parser.doc(doc)?);
+                }
+                types.insert(ty_name.clone(), ty);
+                last_import = None;
+                last_type = Some(ty_name);
+            } else {
+                let stmt = rest.split_whitespace().next().unwrap_or_default();
+                return Err(parser.err(ParseErrorKind::UnknownStatement(stmt.to_owned())));
+            }
+            if pending_doc.is_some() {
+                return Err(parser.err(ParseErrorKind::DanglingDoc));
+            }
+        }
+
+        let end = ParseError {
+            line: s.lines().count().max(1),
+            column: 1,
+            kind: ParseErrorKind::NoTypemod,
+        };
+        let Some(name) = name else {
+            return Err(end);
+        };
+        if pending_doc.is_some() {
+            return Err(ParseError {
+                kind: ParseErrorKind::DanglingDoc,
+                ..end
+            });
+        }
+        let too_many = |what| ParseError {
+            kind: ParseErrorKind::TooMany(what),
+            ..end.clone()
+        };
+
+        let mut resolved = BTreeMap::new();
+        for (ty_name, ty) in types {
+            let ty = resolve_externs(ty, &dependencies, &extern_types).map_err(|kind| {
+                let line = s
+                    .lines()
+                    .position(|line| {
+                        line.trim_start()
+                            .strip_prefix("data ")
+                            .and_then(|rest| rest.split("::").next())
+                            .map(str::trim)
+                            == Some(ty_name.as_str())
+                    })
+                    .map(|pos| pos + 1)
+                    .unwrap_or(end.line);
+                ParseError {
+                    line,
+                    column: 1,
+                    kind,
+                }
+            })?;
+            resolved.insert(ty_name, ty);
+        }
+
+        let extern_types = extern_types
+            .into_iter()
+            .map(|(lib, index)| {
+                Confined::try_from(index).map(|index| (lib, index)).map_err(|_| too_many("library"))
+            })
+            .collect::<Result<BTreeMap<_, _>, _>>()?;
+        Ok(SymbolicLib::with_parts(
+            name,
+            Confined::try_from(dependencies).map_err(|_| too_many("list of dependencies"))?,
+            Confined::try_from(extern_types).map_err(|_| too_many("list of used libraries"))?,
+            Confined::try_from(resolved).map_err(|_| too_many("library"))?,
+            type_docs,
+            field_docs,
+        ))
+    }
+}
+
+fn push_doc(s: &mut String, prefix: &str, doc: &Doc) {
+    let prefix = if prefix.is_empty() { "///" } else { prefix };
+    for line in doc.split('\n') {
+        s.push_str(prefix);
+        if !line.is_empty() {
+            s.push(' ');
+            s.push_str(line);
+        }
+        s.push('\n');
+    }
+}
+
+fn push_sizing(s: &mut String, sizing: Sizing) {
+    match (sizing.min, sizing.max) {
+        (0, 0xFFFF) => {}
+        (0, max) => write!(s, " ^ ..{max:#x}").expect("writing to string"),
+        (min, 0xFFFF) => write!(s, " ^ {min}..").expect("writing to string"),
+        (min, max) => write!(s, " ^ {min}..{max:#x}").expect("writing to string"),
+    }
+}
+
+fn push_variants(s: &mut String, variants: impl Iterator<Item = (Variant, Option<TranspileRef>)>) {
+    for (no, (variant, ty)) in variants.enumerate() {
+        if no > 0 {
+            s.push_str(" | ");
+        }
+        write!(s, "{}#{}", variant.name, variant.tag).expect("writing to string");
+        if let Some(ty) = ty {
+            s.push(' ');
+            push_ref(s, &ty);
+        }
+    }
+}
+
+fn push_ty(s: &mut String, ty: &Ty<TranspileRef>) {
+    match ty {
+        Ty::Primitive(prim) => write!(s, "{prim}").expect("writing to string"),
+        Ty::UnicodeChar => s.push_str("Unicode"),
+        Ty::Enum(variants) => push_variants(s, variants.iter().map(|v| (v.clone(), None))),
+        Ty::Union(variants) => {
+            push_variants(s, variants.iter().map(|(v, ty)| (v.clone(), Some(ty.clone()))))
+        }
+        Ty::Tuple(fields) => {
+            for (no, ty) in fields.iter().enumerate() {
+                if no > 0 {
+                    s.push_str(", ");
+                }
+                push_ref(s, ty);
+            }
+        }
+        Ty::Struct(fields) => {
+            for (no, field) in fields.iter().enumerate() {
+                if no > 0 {
+                    s.push_str(", ");
+                }
+                write!(s, "{} ", field.name).expect("writing to string");
+                push_ref(s, &field.ty);
+            }
+        }
+        Ty::Array(ty, len) => {
+            s.push('[');
+            push_ref(s, ty);
+            write!(s, " ^ {len}]").expect("writing to string");
+        }
+        Ty::List(ty, sizing) => {
+            s.push('[');
+            push_ref(s, ty);
+            push_sizing(s, *sizing);
+            s.push(']');
+        }
+        Ty::Set(ty, sizing) => {
+            s.push('{');
+            push_ref(s, ty);
+            push_sizing(s, *sizing);
+            s.push('}');
+        }
+        Ty::Map(key, ty, sizing) => {
+            s.push('{');
+            push_ref(s, key);
+            s.push_str(" ->");
+            push_sizing(s, *sizing);
+            s.push(' ');
+            push_ref(s, ty);
+            s.push('}');
+        }
+    }
+}
+
+fn push_ref(s: &mut String, r: &TranspileRef) {
+    match r {
+        TranspileRef::Embedded(ty)
+            if matches!(**ty, Ty::Enum(_) | Ty::Union(_) | Ty::Tuple(_) | Ty::Struct(_)) =>
+        {
+            s.push('(');
+            push_ty(s, ty);
+            s.push(')');
+        }
+        TranspileRef::Embedded(ty) => push_ty(s, ty),
+        TranspileRef::Named(name) => s.push_str(name.as_str()),
+        TranspileRef::Extern(sym) => write!(s, "{sym}").expect("writing to string"),
+    }
+}
+
+fn resolve_externs(
+    ty: Ty<TranspileRef>,
+    dependencies: &BTreeSet<Dependency>,
+    extern_types: &BTreeMap<LibName, BTreeMap<SemId, TypeName>>,
+) -> Result<Ty<TranspileRef>, ParseErrorKind> {
+    let resolve = |r: TranspileRef| -> Result<TranspileRef, ParseErrorKind> {
+        Ok(match r {
+            TranspileRef::Embedded(ty) => {
+                TranspileRef::Embedded(Box::new(resolve_externs(*ty, dependencies, extern_types)?))
+            }
+            TranspileRef::Extern(mut sym) => {
+                let dep = dependencies
+                    .iter()
+                    .find(|dep| dep.name == sym.lib_name)
+                    .ok_or_else(|| ParseErrorKind::UnknownLib(sym.lib_name.clone()))?;
+                sym.lib_id = dep.id;
+                sym.sem_id = extern_types
+                    .get(&sym.lib_name)
+                    .and_then(|index| index.iter().find(|(_, name)| **name == sym.ty_name))
+                    .map(|(sem_id, _)| *sem_id)
+                    .ok_or_else(|| {
+                        ParseErrorKind::UnknownExtern(sym.lib_name.clone(), sym.ty_name.clone())
+                    })?;
+                TranspileRef::Extern(sym)
+            }
+            named @ TranspileRef::Named(_) => named,
+        })
+    };
+    Ok(match ty {
+        Ty::Union(variants) => {
+            let variants = variants
+                .into_inner()
+                .into_iter()
+                .map(|(variant, ty)| Ok((variant, resolve(ty)?)))
+                .collect::<Result<BTreeMap<_, _>, _>>()?;
+            Ty::Union(UnionVariants::try_from(variants).expect("same collection size"))
+        }
+        Ty::Tuple(fields) => {
+            let fields = fields.into_iter().map(resolve).collect::<Result<Vec<_>, _>>()?;
+            Ty::Tuple(UnnamedFields::try_from(fields).expect("same collection size"))
+        }
+        Ty::Struct(fields) => {
+            let fields = fields
+                .into_inner()
+                .into_iter()
+                .map(|field| {
+                    Ok(Field {
+                        name: field.name,
+                        ty: resolve(field.ty)?,
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ty::Struct(NamedFields::try_from(fields).expect("same collection size"))
+        }
+        Ty::Array(ty, len) => Ty::Array(resolve(ty)?, len),
+        Ty::List(ty, sizing) => Ty::List(resolve(ty)?, sizing),
+        Ty::Set(ty, sizing) => Ty::Set(resolve(ty)?, sizing),
+        Ty::Map(key, ty, sizing) => Ty::Map(resolve(key)?, resolve(ty)?, sizing),
+        ty @ (Ty::Primitive(_) | Ty::UnicodeChar | Ty::Enum(_)) => ty,
+    })
+}
+
+enum Bounds {
+    Len(u64),
+    Range(Sizing),
+}
+
+enum Item {
+    Field(FieldName, TranspileRef),
+    Variant(VariantName, u8, Option<TranspileRef>),
+    Type(TranspileRef),
+}
+
+struct Parser<'s> {
+    src: &'s str,
+    pos: usize,
+    line: usize,
+    declared: &'s BTreeSet<&'s str>,
+}
+
+impl Parser<'_> {
+    fn rest(&self) -> &str { &self.src[self.pos..] }
+
+    fn peek(&self) -> Option<char> { self.rest().chars().next() }
+
+    fn bump(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.pos += ch.len_utf8();
+        Some(ch)
+    }
+
+    fn skip_ws(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    fn err(&self, kind: ParseErrorKind) -> ParseError {
+        let column = self.src[..self.pos].chars().count() + 1;
+        ParseError {
+            line: self.line,
+            column,
+            kind,
+        }
+    }
+
+    fn unexpected(&self, expected: &'static str) -> ParseError {
+        match self.peek() {
+            None => self.err(ParseErrorKind::UnexpectedEnd(expected)),
+            Some(ch) => self.err(ParseErrorKind::UnexpectedChar(ch, expected)),
+        }
+    }
+
+    fn expect(&mut self, ch: char, expected: &'static str) -> Result<(), ParseError> {
+        self.skip_ws();
+        if self.peek() != Some(ch) {
+            return Err(self.unexpected(expected));
+        }
+        self.bump();
+        Ok(())
+    }
+
+    fn expect_end(&mut self) -> Result<(), ParseError> {
+        self.skip_ws();
+        match self.peek() {
+            None => Ok(()),
+            Some(_) => Err(self.unexpected("end of the line")),
+        }
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &str {
+        let start = self.pos;
+        while self.peek().is_some_and(&f) {
+            self.bump();
+        }
+        &self.src[start..self.pos]
+    }
+
+    fn doc(&self, doc: String) -> Result<Doc, ParseError> {
+        Doc::from_str(&doc).map_err(|_| self.err(ParseErrorKind::InvalidDoc(doc)))
+    }
+
+    fn take_ident(&mut self) -> Result<&str, ParseError> {
+        self.skip_ws();
+        if !self.peek().is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_') {
+            return Err(self.unexpected("identifier"));
+        }
+        Ok(self.take_while(|ch| ch.is_ascii_alphanumeric() || ch == '_'))
+    }
+
+    fn parse_ident<T: FromStr>(&mut self) -> Result<T, ParseError> {
+        let start = self.pos;
+        let ident = self.take_ident()?.to_owned();
+        T::from_str(&ident).map_err(|_| {
+            self.pos = start;
+            self.skip_ws();
+            self.err(ParseErrorKind::InvalidIdent(ident))
+        })
+    }
+
+    fn parse_id<T: FromStr>(&mut self) -> Result<T, ParseError> {
+        let start = self.pos;
+        let id = self.take_while(|ch| !ch.is_whitespace()).to_owned();
+        T::from_str(&id).map_err(|_| {
+            self.pos = start;
+            self.err(ParseErrorKind::InvalidId(id))
+        })
+    }
+
+    fn parse_number<T: TryFrom<u64>>(&mut self) -> Result<T, ParseError> {
+        self.skip_ws();
+        let start = self.pos;
+        let s = self.take_while(|ch| ch.is_ascii_alphanumeric()).to_owned();
+        let val = match s.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16).ok(),
+            None => u64::from_str(&s).ok(),
+        };
+        val.and_then(|val| T::try_from(val).ok()).ok_or_else(|| {
+            self.pos = start;
+            self.err(ParseErrorKind::InvalidNumber(s))
+        })
+    }
+
+    /// Parses collection size following `^`. Returns `None` if no `^` is present.
+    fn parse_bounds(&mut self) -> Result<Option<Bounds>, ParseError> {
+        self.skip_ws();
+        if self.peek() != Some('^') {
+            return Ok(None);
+        }
+        self.bump();
+        self.skip_ws();
+        let min = if self.peek().is_some_and(|ch| ch.is_ascii_digit()) {
+            Some(self.parse_number()?)
+        } else {
+            None
+        };
+        self.skip_ws();
+        if !self.rest().starts_with("..") {
+            return match min {
+                Some(len) => Ok(Some(Bounds::Len(len))),
+                None => Err(self.unexpected("collection size")),
+            };
+        }
+        self.pos += 2;
+        let max = if self.peek().is_some_and(|ch| ch.is_ascii_digit()) {
+            Some(self.parse_number()?)
+        } else {
+            None
+        };
+        Ok(Some(Bounds::Range(Sizing::new(min.unwrap_or_default(), max.unwrap_or(0xFFFF)))))
+    }
+
+    /// Parses a sequence of items separated with commas or pipes, ending with the end of the line
+    /// or the `closing` character, which is not consumed.
+    fn parse_body(&mut self, closing: Option<char>) -> Result<Ty<TranspileRef>, ParseError> {
+        let start = self.pos;
+        let mut items = vec![];
+        let mut separator = None;
+        let mut trailing = false;
+        loop {
+            self.skip_ws();
+            let pos = self.pos;
+            items.push((self.parse_item()?, pos));
+            self.skip_ws();
+            match self.peek() {
+                Some(ch @ (',' | '|')) if separator.is_none() || separator == Some(ch) => {
+                    separator = Some(ch);
+                    self.bump();
+                    self.skip_ws();
+                    if self.peek() == closing {
+                        trailing = true;
+                        break;
+                    }
+                }
+                Some(ch) if Some(ch) != closing => {
+                    return Err(self.unexpected("`,`, `|` or the end of the type"));
+                }
+                _ => break,
+            }
+        }
+        let end = self.pos;
+        let mixed = |parser: &mut Self, pos| {
+            parser.pos = pos;
+            parser.err(ParseErrorKind::MixedItems)
+        };
+        self.pos = start;
+        let ty = match &items[0].0 {
+            Item::Type(_) if items.len() == 1 && !trailing && closing.is_none() => {
+                match items.remove(0).0 {
+                    Item::Type(TranspileRef::Embedded(ty)) => *ty,
+                    Item::Type(r) => Ty::Tuple(UnnamedFields::try_from(vec![r]).expect("one item")),
+                    _ => unreachable!(),
+                }
+            }
+            Item::Type(_) => {
+                let mut fields = vec![];
+                for (item, pos) in items {
+                    let Item::Type(ty) = item else {
+                        return Err(mixed(self, pos));
+                    };
+                    fields.push(ty);
+                }
+                Ty::Tuple(
+                    UnnamedFields::try_from(fields)
+                        .map_err(|_| self.err(ParseErrorKind::TooMany("tuple")))?,
+                )
+            }
+            Item::Field(..) => {
+                let mut fields = Vec::<Field<TranspileRef>>::new();
+                for (item, pos) in items {
+                    let Item::Field(name, ty) = item else {
+                        return Err(mixed(self, pos));
+                    };
+                    if fields.iter().any(|field| field.name == name) {
+                        self.pos = pos;
+                        return Err(self.err(ParseErrorKind::RepeatedField(name)));
+                    }
+                    fields.push(Field { name, ty });
+                }
+                Ty::Struct(
+                    NamedFields::try_from(fields)
+                        .map_err(|_| self.err(ParseErrorKind::TooMany("structure")))?,
+                )
+            }
+            Item::Variant(_, _, None) => {
+                let mut variants = BTreeSet::new();
+                for (item, pos) in items {
+                    let Item::Variant(name, tag, None) = item else {
+                        return Err(mixed(self, pos));
+                    };
+                    if !variants.insert(Variant::named(tag, name.clone())) {
+                        self.pos = pos;
+                        return Err(self.err(ParseErrorKind::RepeatedVariant(name)));
+                    }
+                }
+                Ty::Enum(
+                    Confined::try_from(variants)
+                        .map_err(|_| self.err(ParseErrorKind::TooMany("enum")))?
+                        .into(),
+                )
+            }
+            Item::Variant(_, _, Some(_)) => {
+                let mut variants = BTreeMap::new();
+                for (item, pos) in items {
+                    let Item::Variant(name, tag, Some(ty)) = item else {
+                        return Err(mixed(self, pos));
+                    };
+                    let variant = Variant::named(tag, name.clone());
+                    if variants.contains_key(&variant) {
+                        self.pos = pos;
+                        return Err(self.err(ParseErrorKind::RepeatedVariant(name)));
+                    }
+                    variants.insert(variant, ty);
+                }
+                Ty::Union(
+                    UnionVariants::try_from(variants)
+                        .map_err(|_| self.err(ParseErrorKind::TooMany("union")))?,
+                )
+            }
+        };
+        self.pos = end;
+        Ok(ty)
+    }
+
+    fn parse_item(&mut self) -> Result<Item, ParseError> {
+        self.skip_ws();
+        if !self.peek().is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_') {
+            return self.parse_ref().map(Item::Type);
+        }
+        let start = self.pos;
+        let ident = self.take_ident()?.to_owned();
+        if self.peek() == Some('#') {
+            self.bump();
+            let tag = self.parse_number()?;
+            let name = self.ident_at::<VariantName>(start, ident)?;
+            self.skip_ws();
+            let ty = if self.is_ref_start() { Some(self.parse_ref()?) } else { None };
+            return Ok(Item::Variant(name, tag, ty));
+        }
+        if self.peek() != Some('.') {
+            self.skip_ws();
+            if self.is_ref_start() {
+                let name = self.ident_at::<FieldName>(start, ident)?;
+                return Ok(Item::Field(name, self.parse_ref()?));
+            }
+        }
+        self.pos = start;
+        self.parse_ref().map(Item::Type)
+    }
+
+    fn ident_at<T: FromStr>(&mut self, pos: usize, ident: String) -> Result<T, ParseError> {
+        T::from_str(&ident).map_err(|_| {
+            self.pos = pos;
+            self.err(ParseErrorKind::InvalidIdent(ident))
+        })
+    }
+
+    fn is_ref_start(&self) -> bool {
+        self.peek().is_some_and(|ch| {
+            matches!(ch, '(' | '[' | '{') || ch.is_ascii_alphabetic() || ch == '_'
+        })
+    }
+
+    fn parse_ref(&mut self) -> Result<TranspileRef, ParseError> {
+        self.skip_ws();
+        let ty = match self.peek() {
+            Some('(') if self.rest().starts_with("()") => {
+                self.pos += 2;
+                Ty::UNIT
+            }
+            Some('(') => {
+                self.bump();
+                let ty = self.parse_body(Some(')'))?;
+                self.expect(')', "`)`")?;
+                ty
+            }
+            Some('[') => {
+                self.bump();
+                let ty = self.parse_ref()?;
+                let ty = match self.parse_bounds()? {
+                    None => Ty::List(ty, Sizing::U16),
+                    Some(Bounds::Len(len)) => {
+                        let len = u16::try_from(len).map_err(|_| {
+                            self.err(ParseErrorKind::InvalidNumber(len.to_string()))
+                        })?;
+                        Ty::Array(ty, len)
+                    }
+                    Some(Bounds::Range(sizing)) => Ty::List(ty, sizing),
+                };
+                self.expect(']', "`]`")?;
+                ty
+            }
+            Some('{') => {
+                self.bump();
+                let key = self.parse_ref()?;
+                self.skip_ws();
+                let ty = if self.rest().starts_with("->") {
+                    self.pos += 2;
+                    let sizing = self.parse_set_sizing()?;
+                    Ty::Map(key, self.parse_ref()?, sizing)
+                } else {
+                    Ty::Set(key, self.parse_set_sizing()?)
+                };
+                self.expect('}', "`}`")?;
+                ty
+            }
+            Some(ch) if ch.is_ascii_alphabetic() || ch == '_' => {
+                let start = self.pos;
+                let ident = self.take_ident()?.to_owned();
+                if self.peek() == Some('.') {
+                    self.bump();
+                    let lib_name = self.ident_at::<LibName>(start, ident)?;
+                    let ty_name = self.parse_ident::<TypeName>()?;
+                    // Library and semantic ids are resolved once all imports are known
+                    return Ok(TranspileRef::Extern(SymbolRef::with(
+                        lib_name,
+                        ty_name,
+                        TypeLibId::from([0u8; 32]),
+                        SemId::from([0u8; 32]),
+                    )));
+                }
+                if self.declared.contains(ident.as_str()) {
+                    return self.ident_at::<TypeName>(start, ident).map(TranspileRef::Named);
+                }
+                if ident == "Unicode" {
+                    Ty::UnicodeChar
+                } else if let Some(prim) = (0..=u8::MAX)
+                    .map(Primitive::from_code)
+                    .filter(|prim| *prim != Primitive::RESERVED)
+                    .find(|prim| prim.to_string() == ident)
+                {
+                    Ty::Primitive(prim)
+                } else {
+                    return self.ident_at::<TypeName>(start, ident).map(TranspileRef::Named);
+                }
+            }
+            _ => return Err(self.unexpected("type")),
+        };
+        Ok(TranspileRef::Embedded(Box::new(ty)))
+    }
+
+    fn parse_set_sizing(&mut self) -> Result<Sizing, ParseError> {
+        Ok(match self.parse_bounds()? {
+            None => Sizing::U16,
+            Some(Bounds::Len(len)) => Sizing::fixed(len),
+            Some(Bounds::Range(sizing)) => sizing,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::stl::{std_sym, strict_types_sym};
+    use crate::value::test_helpers::Nominal;
+    use crate::LibBuilder;
+
+    #[test]
+    fn round_trip() {
+        let std = crate::stl::std_stl();
+        let st = crate::stl::strict_types_stl();
+        let nominal =
+            LibBuilder::new(libname!("TestLib"), [std.to_dependency(), st.to_dependency()])
+                .transpile_documented::<Nominal>("Asset nomination.\n\nDetails.", &[
+                    ("ticker", "Asset ticker"),
+                    ("name", "Asset name\nin a free form"),
+                ])
+                .compile_symbols()
+                .unwrap();
+        for lib in [std_sym(), strict_types_sym(), nominal] {
+            let text = lib.to_stl_text();
+            assert_eq!(SymbolicLib::from_stl_text(&text), Ok(lib), "{text}");
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let err = SymbolicLib::from_stl_text("data Foo :: U8").unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::NoTypemod);
+        let err =
+            SymbolicLib::from_stl_text("typemod Test\n\ndata Foo :: a U8, b#1 U16").unwrap_err();
+        assert_eq!((err.line, err.column, err.kind), (3, 19, ParseErrorKind::MixedItems));
+        let err = SymbolicLib::from_stl_text("typemod Test\ndata Foo :: Std.Bool").unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::UnknownLib(libname!("Std")));
+    }
+}
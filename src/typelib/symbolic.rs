@@ -22,16 +22,21 @@
 
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::{self, Display, Formatter};
+use std::sync::Arc;
 
 use amplify::confinement::{Confined, SmallOrdMap, TinyOrdMap, TinyOrdSet};
 use amplify::ByteArray;
-use encoding::{LibName, LIB_EMBEDDED};
+use encoding::{FieldName, LibName, LIB_EMBEDDED};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 use sha2::Digest;
 use strict_encoding::{StrictDumb, TypeName, STRICT_TYPES_LIB};
 
 use super::{LibBuilder, SymbolContext};
-use crate::ast::{PrimitiveRef, SemCommit, SEM_ID_TAG};
-use crate::typelib::{CompileError, ExternRef, NestedContext, SymbolError, TypeIndex, TypeMap};
+use crate::ast::{PrimitiveRef, SemCommit, RESERVED_TAGS, SEM_ID_TAG};
+use crate::typelib::{
+    CompileError, Doc, ExternRef, NestedContext, SymbolError, TypeIndex, TypeMap,
+};
 use crate::{Dependency, LibRef, SemId, Translate, Ty, TypeLib, TypeLibId, TypeRef};
 
 pub type ExternTypes = TinyOrdMap<LibName, SmallOrdMap<SemId, TypeName>>;
@@ -45,6 +50,12 @@ pub struct SymbolicLib {
     dependencies: TinyOrdSet<Dependency>,
     extern_types: ExternTypes,
     types: SmallOrdMap<TypeName, Ty<TranspileRef>>,
+    #[strict_type(skip, dumb = empty!())]
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "BTreeMap::is_empty"))]
+    type_docs: BTreeMap<TypeName, Doc>,
+    #[strict_type(skip, dumb = empty!())]
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "BTreeMap::is_empty"))]
+    field_docs: BTreeMap<TypeName, BTreeMap<FieldName, Doc>>,
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Display)]
@@ -98,6 +109,27 @@ impl StrictDumb for Box<Ty<TranspileRef>> {
 impl TranspileRef {
     pub fn unit() -> Self { Ty::UNIT.into() }
 
+    /// Computes semantic id of the referenced type using types already added to the `builder`,
+    /// without compiling the library.
+    ///
+    /// Returns `None` if the referenced type depends on types not yet known to the builder, or if
+    /// it can't be compiled.
+    pub fn sem_id_in(&self, builder: &LibBuilder) -> Option<SemId> {
+        let lookup = |name: &TypeName| builder.types.get(name);
+        match self {
+            TranspileRef::Named(name) => {
+                resolve_sem_id(name, &lookup, &mut empty!(), &mut empty!())
+            }
+            TranspileRef::Embedded(ty) => {
+                // The name is used only for error reporting, which is not needed here
+                let top_name = tn!(builder.lib_name.to_string());
+                resolve_ty(&top_name, ty, &lookup, &mut empty!(), &mut empty!())
+                    .map(|ty| ty.sem_id_unnamed())
+            }
+            TranspileRef::Extern(r) => Some(r.sem_id),
+        }
+    }
+
     pub fn id(&self) -> SemId {
         if let TranspileRef::Extern(r) = self {
             r.sem_id
@@ -113,6 +145,13 @@ impl TranspileRef {
 }
 
 impl TypeRef for TranspileRef {
+    fn sem_id(&self) -> Option<SemId> {
+        match self {
+            TranspileRef::Extern(sym) => Some(sym.sem_id),
+            TranspileRef::Embedded(_) | TranspileRef::Named(_) => None,
+        }
+    }
+
     fn as_ty(&self) -> Option<&Ty<Self>> {
         match self {
             TranspileRef::Embedded(ty) => Some(ty),
@@ -158,9 +197,10 @@ impl Display for TranspileRef {
 #[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
 #[display(doc_comments)]
 pub enum TranspileError {
-    /// type `{unknown}` referenced inside `{within}` is not known.
+    /// type `{unknown}` referenced by `{within_type}` inside `{within}` is not known.
     UnknownType {
         unknown: TypeName,
+        within_type: TypeName,
         within: Ty<TranspileRef>,
     },
 
@@ -175,19 +215,207 @@ pub enum TranspileError {
 
     /// library `{0}` contains too many types.
     LibTooLarge(LibName),
+
+    /// type `{0}` is already present in the library.
+    RepeatedType(TypeName),
+
+    #[from]
+    #[display(inner)]
+    Symbol(SymbolError),
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum RemoveError {
+    /// type `{0}` is not a part of the library.
+    UnknownType(TypeName),
+
+    /// type `{referenced}` can't be removed since it is used by type `{referrer}`.
+    Referenced {
+        referenced: TypeName,
+        referrer: TypeName,
+    },
+}
+
+/// Issue found by [`LibBuilder::validate`] before the library compilation.
+#[derive(Clone, Eq, PartialEq, Debug, Display)]
+#[display(doc_comments)]
+pub enum BuildWarning {
+    /// type `{unknown}` referenced inside `{within}` is not defined.
+    UndefinedType { unknown: TypeName, within: TypeName },
+
+    /// type name `{name}` is registered for two different types `{first}` and `{second}`.
+    DuplicateType {
+        name: TypeName,
+        first: Box<Ty<TranspileRef>>,
+        second: Box<Ty<TranspileRef>>,
+    },
+
+    /// dependency `{0}` is never used.
+    UnusedDependency(Dependency),
+
+    /// type name `{0}` is the same as the library name.
+    LibNameConflict(TypeName),
+
+    /// type `{ty}` references library `{lib}` which is not a known dependency.
+    UnknownLib { lib: LibName, ty: TypeName },
+
+    /// union `{0}` has no range of unused tags reserved for future extensions.
+    NoReservedTags(TypeName),
+
+    /// union `{ty}` doesn't use tags {start}..={end}, which may be accidental.
+    TagGap { ty: TypeName, start: u8, end: u8 },
+}
+
+impl BuildWarning {
+    /// Detects whether the issue prevents the library from being compiled.
+    pub fn is_critical(&self) -> bool {
+        match self {
+            BuildWarning::UndefinedType { .. }
+            | BuildWarning::DuplicateType { .. }
+            | BuildWarning::UnknownLib { .. } => true,
+            BuildWarning::UnusedDependency(_)
+            | BuildWarning::LibNameConflict(_)
+            | BuildWarning::NoReservedTags(_)
+            | BuildWarning::TagGap { .. } => false,
+        }
+    }
 }
 
 impl LibBuilder {
+    /// Checks the library under construction for issues without compiling it.
+    pub fn validate(&self) -> Vec<BuildWarning> {
+        fn collect<'ty>(
+            ty: &'ty Ty<TranspileRef>,
+            named: &mut BTreeSet<&'ty TypeName>,
+            libs: &mut BTreeSet<&'ty LibName>,
+        ) {
+            for (subty, _) in ty.type_refs() {
+                match subty {
+                    TranspileRef::Embedded(ty) => collect(ty, named, libs),
+                    TranspileRef::Named(name) => {
+                        named.insert(name);
+                    }
+                    TranspileRef::Extern(r) => {
+                        libs.insert(&r.lib_name);
+                    }
+                }
+            }
+        }
+
+        let mut warnings = vec![];
+        let is_known = |lib: &LibName| {
+            lib == &libname!(LIB_EMBEDDED) || self.known_libs.iter().any(|dep| &dep.name == lib)
+        };
+        let mut used_libs = bset![];
+        for (name, ty) in &self.types {
+            if name.as_str() == self.lib_name.as_str() {
+                warnings.push(BuildWarning::LibNameConflict(name.clone()));
+            }
+            let (mut named, mut libs) = (bset![], bset![]);
+            collect(ty, &mut named, &mut libs);
+            for unknown in named.into_iter().filter(|n| !self.types.contains_key(*n)) {
+                warnings.push(BuildWarning::UndefinedType {
+                    unknown: unknown.clone(),
+                    within: name.clone(),
+                });
+            }
+            for lib in libs.iter().filter(|lib| !is_known(lib)) {
+                warnings.push(BuildWarning::UnknownLib {
+                    lib: (*lib).clone(),
+                    ty: name.clone(),
+                });
+            }
+            used_libs.extend(libs);
+            if let Ty::Union(variants) = ty {
+                if !variants.has_reserved_range() {
+                    warnings.push(BuildWarning::NoReservedTags(name.clone()));
+                }
+                let last = variants.keys().map(|v| v.tag).max().unwrap_or_default();
+                for gap in variants.unused_tags() {
+                    if *gap.end() < last && gap.len() < RESERVED_TAGS {
+                        warnings.push(BuildWarning::TagGap {
+                            ty: name.clone(),
+                            start: *gap.start(),
+                            end: *gap.end(),
+                        });
+                    }
+                }
+            }
+        }
+        for (name, first, second) in &self.conflicts {
+            warnings.push(BuildWarning::DuplicateType {
+                name: name.clone(),
+                first: Box::new(first.clone()),
+                second: Box::new(second.clone()),
+            });
+        }
+        for (lib, types) in &self.extern_types {
+            if is_known(lib) {
+                continue;
+            }
+            for ty in types.values() {
+                let warning = BuildWarning::UnknownLib {
+                    lib: lib.clone(),
+                    ty: ty.clone(),
+                };
+                if !warnings.contains(&warning) {
+                    warnings.push(warning);
+                }
+            }
+        }
+        for dep in &self.known_libs {
+            // Types of the dependencies may be registered in `extern_types` even if they are used
+            // only by other dependencies, thus we check the actual type references
+            if !used_libs.contains(&dep.name) {
+                warnings.push(BuildWarning::UnusedDependency(dep.clone()));
+            }
+        }
+        warnings
+    }
+
+    fn check(&self) -> Result<(), CompileError> {
+        let critical =
+            self.validate().into_iter().filter(BuildWarning::is_critical).collect::<Vec<_>>();
+        if !critical.is_empty() {
+            return Err(CompileError::ValidationFailed(critical));
+        }
+        Ok(())
+    }
+
+    /// Reconstructs library builder from a compiled library, such that more types can be added
+    /// to it. The library dependencies are added to the `known_libs`.
+    pub fn from_type_lib(
+        lib: &TypeLib,
+        known_libs: impl IntoIterator<Item = Dependency>,
+    ) -> Result<LibBuilder, TranspileError> {
+        let symbolic = lib.to_symbolic()?;
+        let mut builder =
+            LibBuilder::new(symbolic.name, known_libs.into_iter().chain(symbolic.dependencies));
+        builder.extern_types =
+            symbolic.extern_types.into_iter().map(|(lib, types)| (lib, types.release())).collect();
+        builder.types = symbolic.types.release();
+        builder.type_docs = symbolic.type_docs;
+        builder.field_docs = symbolic.field_docs;
+        Ok(builder)
+    }
+
     pub fn compile_symbols(self) -> Result<SymbolicLib, TranspileError> {
         let (name, known_libs, extern_types, types) =
             (self.lib_name, self.known_libs, self.extern_types, self.types);
+        // Types documented with `transpile_documented` may belong to other libraries
+        let type_docs =
+            self.type_docs.into_iter().filter(|(name, _)| types.contains_key(name)).collect();
+        let field_docs =
+            self.field_docs.into_iter().filter(|(name, _)| types.contains_key(name)).collect();
 
-        for ty in types.values() {
+        for (ty_name, ty) in &types {
             for (subty, _) in ty.type_refs() {
                 if let TranspileRef::Named(name) = subty {
                     if !types.contains_key(name) {
                         return Err(TranspileError::UnknownType {
                             unknown: name.clone(),
+                            within_type: ty_name.clone(),
                             within: ty.clone(),
                         });
                     }
@@ -222,7 +450,7 @@ impl LibBuilder {
                         .map_err(|_| TranspileError::LibTooLarge(k.clone()))?;
                     Ok((k, v))
                 })
-                .collect::<Result<_, _>>()?,
+                .collect::<Result<_, TranspileError>>()?,
         )
         .map_err(|_| TranspileError::TooManyDependencies)?;
         Ok(SymbolicLib {
@@ -230,54 +458,376 @@ impl LibBuilder {
             extern_types,
             dependencies,
             types,
+            type_docs,
+            field_docs,
         })
     }
 
-    pub fn compile(self) -> Result<TypeLib, CompileError> { self.compile_symbols()?.compile() }
+    /// Compiles the library. Fails with [`CompileError::ValidationFailed`] if
+    /// [`LibBuilder::validate`] has found critical issues.
+    ///
+    /// Unused dependencies don't prevent compilation; they are reported by
+    /// [`LibBuilder::validate`] as [`BuildWarning::UnusedDependency`].
+    pub fn compile(self) -> Result<TypeLib, CompileError> {
+        self.check()?;
+        #[cfg(feature = "tracing")]
+        let (stats, start) = (self.ref_stats(), std::time::Instant::now());
+        let lib = self.compile_symbols()?.compile()?;
+        #[cfg(feature = "tracing")]
+        tracing::info!(lib = %lib.name, ?stats, elapsed = ?start.elapsed(), "library compiled");
+        #[cfg(feature = "tracing")]
+        for dep in lib.unused_dependencies() {
+            tracing::warn!(lib = %lib.name, dep = %dep.name, "unused library dependency");
+        }
+        Ok(lib)
+    }
+
+    /// Compiles the library, failing if some of the library dependencies are not used.
+    pub fn compile_strict(self) -> Result<TypeLib, CompileError> {
+        self.check()?;
+        self.compile_symbols()?.compile_strict()
+    }
+}
+
+/// Compiles a type, computing semantic ids of all library types it references (and caching them in
+/// the `index`) without compiling the rest of the library.
+fn resolve_ty<'lib>(
+    name: &TypeName,
+    ty: &Ty<TranspileRef>,
+    lookup: &impl Fn(&TypeName) -> Option<&'lib Ty<TranspileRef>>,
+    index: &mut TypeIndex,
+    visiting: &mut BTreeSet<TypeName>,
+) -> Option<Ty<LibRef>> {
+    for subty in named_refs(ty) {
+        resolve_sem_id(subty, lookup, index, visiting)?;
+    }
+    let mut ctx = NestedContext {
+        top_name: name.clone(),
+        index: Arc::new(index.clone()),
+        stack: empty!(),
+    };
+    ty.clone().translate(&mut ctx, &()).ok()
+}
+
+/// Computes semantic id of a named library type, returning `None` if it or one of the types it
+/// references is not known, can't be compiled, or references itself.
+fn resolve_sem_id<'lib>(
+    name: &TypeName,
+    lookup: &impl Fn(&TypeName) -> Option<&'lib Ty<TranspileRef>>,
+    index: &mut TypeIndex,
+    visiting: &mut BTreeSet<TypeName>,
+) -> Option<SemId> {
+    if let Some(id) = index.get(name) {
+        return Some(*id);
+    }
+    let ty = lookup(name)?;
+    if !visiting.insert(name.clone()) {
+        return None;
+    }
+    let id = resolve_ty(name, ty, lookup, index, visiting)?.sem_id_named(name);
+    visiting.remove(name);
+    index.insert(name.clone(), id);
+    Some(id)
+}
+
+/// Collects names of the library types referenced by a type, including its inline types.
+fn named_refs(ty: &Ty<TranspileRef>) -> BTreeSet<&TypeName> {
+    fn collect<'lib>(ty: &'lib Ty<TranspileRef>, refs: &mut BTreeSet<&'lib TypeName>) {
+        for (subty, _) in ty.type_refs() {
+            match subty {
+                TranspileRef::Embedded(ty) => collect(ty, refs),
+                TranspileRef::Named(name) => {
+                    refs.insert(name);
+                }
+                TranspileRef::Extern(_) => {}
+            }
+        }
+    }
+
+    let mut refs = bset![];
+    collect(ty, &mut refs);
+    refs
+}
+
+/// Tarjan's algorithm for finding strongly connected components in the graph of named type
+/// references.
+struct TypeCycles<'lib> {
+    graph: BTreeMap<&'lib TypeName, BTreeSet<&'lib TypeName>>,
+    index: BTreeMap<&'lib TypeName, usize>,
+    lowlink: BTreeMap<&'lib TypeName, usize>,
+    stack: Vec<&'lib TypeName>,
+    on_stack: BTreeSet<&'lib TypeName>,
+}
+
+impl<'lib> TypeCycles<'lib> {
+    fn new(types: &'lib SmallOrdMap<TypeName, Ty<TranspileRef>>) -> Self {
+        let graph = types.iter().map(|(name, ty)| (name, named_refs(ty))).collect();
+        TypeCycles {
+            graph,
+            index: empty!(),
+            lowlink: empty!(),
+            stack: empty!(),
+            on_stack: empty!(),
+        }
+    }
+
+    /// Returns the first found cycle, consisting either of several types or of a single type
+    /// referencing itself.
+    fn find(mut self) -> Option<Vec<TypeName>> {
+        let nodes = self.graph.keys().copied().collect::<Vec<_>>();
+        for node in nodes {
+            if self.index.contains_key(node) {
+                continue;
+            }
+            if let Some(cycle) = self.connect(node) {
+                return Some(cycle);
+            }
+        }
+        None
+    }
+
+    fn connect(&mut self, node: &'lib TypeName) -> Option<Vec<TypeName>> {
+        let index = self.index.len();
+        self.index.insert(node, index);
+        self.lowlink.insert(node, index);
+        self.stack.push(node);
+        self.on_stack.insert(node);
+
+        let refs = self.graph.get(node).cloned().unwrap_or_default();
+        for next in &refs {
+            if !self.index.contains_key(next) {
+                if let Some(cycle) = self.connect(next) {
+                    return Some(cycle);
+                }
+                let low = self.lowlink[node].min(self.lowlink[next]);
+                self.lowlink.insert(node, low);
+            } else if self.on_stack.contains(next) {
+                let low = self.lowlink[node].min(self.index[next]);
+                self.lowlink.insert(node, low);
+            }
+        }
+
+        if self.lowlink[node] != self.index[node] {
+            return None;
+        }
+        let mut component = vec![];
+        while let Some(member) = self.stack.pop() {
+            self.on_stack.remove(member);
+            component.push(member.clone());
+            if member == node {
+                break;
+            }
+        }
+        if component.len() > 1 || refs.contains(node) {
+            component.sort();
+            return Some(component);
+        }
+        None
+    }
+}
+
+fn collect_symbol_refs<'ty>(ty: &'ty Ty<TranspileRef>, refs: &mut Vec<&'ty SymbolRef>) {
+    for (r, _) in ty.type_refs() {
+        match r {
+            TranspileRef::Embedded(ty) => collect_symbol_refs(ty, refs),
+            TranspileRef::Named(_) => {}
+            TranspileRef::Extern(r) => refs.push(r),
+        }
+    }
+}
+
+fn collect_extern_refs(ty: &Ty<TranspileRef>, ext: &mut BTreeSet<ExternRef>) {
+    for (r, _) in ty.type_refs() {
+        match r {
+            TranspileRef::Embedded(ty) => collect_extern_refs(ty, ext),
+            TranspileRef::Named(_) => {}
+            TranspileRef::Extern(r) => {
+                ext.insert(r.clone().into());
+            }
+        }
+    }
 }
 
 impl SymbolicLib {
-    pub fn compile(self) -> Result<TypeLib, CompileError> {
-        let name = self.name;
-        let dependencies = self.dependencies;
-        let mut extern_types = self.extern_types;
-        let mut old_types = self.types.release();
-        let mut index = TypeIndex::new();
+    pub(super) fn with_parts(
+        name: LibName,
+        dependencies: TinyOrdSet<Dependency>,
+        extern_types: ExternTypes,
+        types: SmallOrdMap<TypeName, Ty<TranspileRef>>,
+        type_docs: BTreeMap<TypeName, Doc>,
+        field_docs: BTreeMap<TypeName, BTreeMap<FieldName, Doc>>,
+    ) -> Self {
+        SymbolicLib {
+            name,
+            dependencies,
+            extern_types,
+            types,
+            type_docs,
+            field_docs,
+        }
+    }
+
+    /// Computes semantic id of the library type without compiling the library. Returns `None` if
+    /// the type is not known or can't be compiled.
+    pub fn sem_id_of(&self, name: &TypeName) -> Option<SemId> {
+        resolve_sem_id(name, &|name| self.types.get(name), &mut empty!(), &mut empty!())
+    }
+
+    /// Checks that all external types referenced by the library types belong to the libraries
+    /// from the library dependencies, returning the violating references otherwise.
+    pub fn validate_extern_refs(&self) -> Result<(), Vec<(TypeName, ExternRef)>> {
+        let mut undeclared = vec![];
+        for (name, ty) in &self.types {
+            let mut ext = bset![];
+            collect_extern_refs(ty, &mut ext);
+            undeclared.extend(
+                ext.into_iter()
+                    .filter(|r| !self.dependencies.iter().any(|dep| dep.id == r.lib_id))
+                    .map(|r| (name.clone(), r)),
+            );
+        }
+        if !undeclared.is_empty() {
+            return Err(undeclared);
+        }
+        Ok(())
+    }
+
+    /// Adds a new type to the library.
+    ///
+    /// Fails if the library already has a type with the same name, or if the type references
+    /// named types absent from the library or types from libraries which are not the library
+    /// dependencies.
+    pub fn add_type(&mut self, name: TypeName, ty: Ty<TranspileRef>) -> Result<(), TranspileError> {
+        if self.types.contains_key(&name) {
+            return Err(TranspileError::RepeatedType(name));
+        }
+        if let Some(unknown) =
+            named_refs(&ty).into_iter().find(|r| **r != name && !self.types.contains_key(*r))
+        {
+            return Err(TranspileError::UnknownType {
+                unknown: unknown.clone(),
+                within_type: name.clone(),
+                within: ty.clone(),
+            });
+        }
+
+        let mut refs = vec![];
+        collect_symbol_refs(&ty, &mut refs);
+        let mut extern_types = self.extern_types.clone();
+        for r in refs {
+            if !self.dependencies.iter().any(|dep| dep.id == r.lib_id) {
+                return Err(TranspileError::UnknownLib(r.lib_name.clone()));
+            }
+            let mut lib_types = extern_types.get(&r.lib_name).cloned().unwrap_or_default();
+            lib_types
+                .insert(r.sem_id, r.ty_name.clone())
+                .map_err(|_| TranspileError::LibTooLarge(r.lib_name.clone()))?;
+            extern_types
+                .insert(r.lib_name.clone(), lib_types)
+                .map_err(|_| TranspileError::TooManyDependencies)?;
+        }
+
+        self.types.insert(name, ty).map_err(|_| TranspileError::TooManyTypes)?;
+        self.extern_types = extern_types;
+        Ok(())
+    }
+
+    /// Removes type from the library together with its documentation, unless the type is used
+    /// by other library types.
+    pub fn remove_type(&mut self, name: &TypeName) -> Result<(), RemoveError> {
+        if !self.types.contains_key(name) {
+            return Err(RemoveError::UnknownType(name.clone()));
+        }
+        if let Some(referrer) = self
+            .types
+            .iter()
+            .find(|(other, ty)| *other != name && named_refs(ty).contains(name))
+            .map(|(referrer, _)| referrer)
+        {
+            return Err(RemoveError::Referenced {
+                referenced: name.clone(),
+                referrer: referrer.clone(),
+            });
+        }
+        self.types.remove(name).expect("library has no minimal number of types");
+        self.type_docs.remove(name);
+        self.field_docs.remove(name);
+        Ok(())
+    }
+
+    pub fn compile(self) -> Result<TypeLib, CompileError> { self.compile_with(false) }
+
+    /// Compiles the library, failing with [`CompileError::UnusedDependencies`] if some of the
+    /// library dependencies are not used by its types.
+    pub fn compile_strict(self) -> Result<TypeLib, CompileError> { self.compile_with(true) }
+
+    fn compile_with(self, strict: bool) -> Result<TypeLib, CompileError> {
+        self.validate_extern_refs().map_err(CompileError::UndeclaredExternRefs)?;
+        if let Some(cycle) = TypeCycles::new(&self.types).find() {
+            return Err(CompileError::TypeCycle(cycle));
+        }
+
+        // Types are compiled in passes: each pass compiles all types which have all the types
+        // they reference compiled by the previous passes.
+        let mut pending =
+            self.types.iter().map(|(name, ty)| (name, named_refs(ty))).collect::<BTreeMap<_, _>>();
+        let mut index = Arc::new(TypeIndex::new());
         let mut new_types = BTreeMap::<TypeName, Ty<LibRef>>::new();
-        let names = old_types.keys().cloned().collect::<BTreeSet<_>>();
+        while !pending.is_empty() {
+            let ready = pending
+                .iter()
+                .filter(|(_, refs)| refs.iter().all(|name| index.contains_key(*name)))
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>();
+            if ready.is_empty() {
+                // Without cycles, this happens only if some of the types are not defined
+                let (unknown, within_type) = pending
+                    .iter()
+                    .find_map(|(name, refs)| {
+                        let unknown = refs.iter().find(|r| !self.types.contains_key(**r))?;
+                        Some(((*unknown).clone(), (*name).clone()))
+                    })
+                    .expect("acyclic library always has a type ready for compilation");
+                let within = self.types[&within_type].clone();
+                return Err(CompileError::UnknownType {
+                    unknown,
+                    within_type,
+                    within,
+                });
+            }
 
-        while !old_types.is_empty() {
-            let mut found = false;
-            for name in &names {
-                let Some(ty) = old_types.get(name) else {
-                    continue;
-                };
+            let compile = |name: &&TypeName| -> Result<_, CompileError> {
                 let mut ctx = NestedContext {
-                    top_name: name.clone(),
-                    index,
-                    extern_types,
+                    top_name: (*name).clone(),
+                    index: index.clone(),
                     stack: empty!(),
                 };
-                let ty: Ty<LibRef> = match ty.clone().translate(&mut ctx, &()) {
-                    Ok(ty) => ty,
-                    Err(CompileError::Continue) => {
-                        index = ctx.index;
-                        extern_types = ctx.extern_types;
-                        continue;
-                    }
-                    Err(err) => return Err(err),
-                };
-                index = ctx.index;
-                extern_types = ctx.extern_types;
-                found = true;
-                let id = ty.sem_id_named(name);
-                index.insert(name.clone(), id);
-                new_types.insert(name.clone(), ty);
-                old_types.remove(name);
+                let ty: Ty<LibRef> = self.types[*name].clone().translate(&mut ctx, &())?;
+                Ok(((*name).clone(), ty))
+            };
+            #[cfg(feature = "rayon")]
+            let compiled = ready.par_iter().map(compile).collect::<Vec<_>>();
+            #[cfg(not(feature = "rayon"))]
+            let compiled = ready.iter().map(compile).collect::<Vec<_>>();
+
+            let index = Arc::make_mut(&mut index);
+            for res in compiled {
+                let (name, ty) = res?;
+                let sem_id = ty.sem_id_named(&name);
+                #[cfg(feature = "tracing")]
+                tracing::info!(lib = %self.name, %name, %sem_id, "compiled type");
+                index.insert(name.clone(), sem_id);
+                new_types.insert(name, ty);
+            }
+            for name in ready {
+                pending.remove(name);
             }
-            debug_assert!(found, "incomplete type definition found in the library");
         }
 
+        let name = self.name;
+        let dependencies = self.dependencies;
+        let extern_types = self.extern_types;
+
         let mut used_dependencies = BTreeSet::<Dependency>::new();
         for lib in extern_types.keys() {
             if lib == &libname!(LIB_EMBEDDED) {
@@ -297,20 +847,39 @@ impl SymbolicLib {
         let types = TypeMap::from_checked(new_types);
         let dependencies = Confined::from_checked(used_dependencies);
 
-        Ok(TypeLib {
+        let lib = TypeLib {
             name,
             dependencies,
             extern_types,
             types,
-        })
+            type_docs: self.type_docs,
+            field_docs: self.field_docs,
+        };
+        lib.validate_extern_refs().map_err(CompileError::UndeclaredExternRefs)?;
+        if strict {
+            let unused = lib.unused_dependencies();
+            if !unused.is_empty() {
+                return Err(CompileError::UnusedDependencies(unused));
+            }
+        }
+        Ok(lib)
     }
 }
 
 impl TypeLib {
     pub fn to_symbolic(&self) -> Result<SymbolicLib, SymbolError> {
         let lib_index = self.dependencies.iter().map(|dep| (dep.id, dep.name.clone())).collect();
-        let reverse_index =
-            self.types.iter().map(|(name, ty)| (ty.sem_id_named(name), name.clone())).collect();
+        let mut reverse_index = BTreeMap::<SemId, TypeName>::new();
+        for (name, ty) in &self.types {
+            let sem_id = ty.sem_id_named(name);
+            if let Some(prev) = reverse_index.insert(sem_id, name.clone()) {
+                return Err(SymbolError::AmbiguousExternType {
+                    lib: self.name.clone(),
+                    sem_id,
+                    names: vec![prev, name.clone()],
+                });
+            }
+        }
         let ctx = SymbolContext {
             reverse_index,
             lib_index,
@@ -330,6 +899,398 @@ impl TypeLib {
             dependencies: self.dependencies.clone(),
             extern_types,
             types,
+            type_docs: self.type_docs.clone(),
+            field_docs: self.field_docs.clone(),
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn refer(field: &'static str, ty: &str) -> Ty<TranspileRef> {
+        Ty::Struct(fields!(fname!(field) => TranspileRef::Named(tn!(ty.to_owned()))))
+    }
+
+    #[test]
+    fn type_cycle() {
+        let mut builder = LibBuilder::new(libname!("TestLib"), []);
+        builder.types.insert(tn!("Alpha"), refer("beta", "Beta"));
+        builder.types.insert(tn!("Beta"), refer("alpha", "Alpha"));
+        builder.types.insert(tn!("Gamma"), refer("alpha", "Alpha"));
+        assert_eq!(
+            builder.compile().unwrap_err(),
+            CompileError::TypeCycle(vec![tn!("Alpha"), tn!("Beta")])
+        );
+    }
+
+    #[test]
+    fn unused_dependencies() {
+        let std = crate::stl::std_stl();
+        let st = crate::stl::strict_types_stl();
+        let builder =
+            LibBuilder::new(libname!("TestLib"), [std.to_dependency(), st.to_dependency()])
+                .transpile::<crate::value::test_helpers::Nominal>();
+        assert!(matches!(
+            builder.compile_strict(),
+            Err(CompileError::UnusedDependencies(deps)) if deps == vec![std.to_dependency()]
+        ));
+    }
+
+    #[test]
+    fn symbolic_roundtrip() {
+        let std = crate::stl::std_stl();
+        let st = crate::stl::strict_types_stl();
+        let nominal =
+            LibBuilder::new(libname!("TestLib"), [std.to_dependency(), st.to_dependency()])
+                .transpile::<crate::value::test_helpers::Nominal>()
+                .compile()
+                .unwrap();
+        for lib in [std, st, nominal] {
+            let compiled = lib.to_symbolic().unwrap().compile().unwrap();
+            assert_eq!(compiled.id(), lib.id(), "library {}", lib.name);
+            assert_eq!(compiled, lib);
+        }
+    }
+
+    #[test]
+    fn type_index() {
+        let std = crate::stl::std_stl();
+        let st = crate::stl::strict_types_stl();
+        let plain = LibBuilder::new(libname!("TestLib"), [std.to_dependency(), st.to_dependency()])
+            .transpile::<crate::value::test_helpers::Nominal>()
+            .compile()
+            .unwrap();
+        // Types of the StrictTypes library are not transpiled, thus the library of their inner
+        // types is not required
+        let indexed = LibBuilder::new(libname!("TestLib"), [])
+            .with_type_index(st.to_dependency(), &st)
+            .transpile::<crate::value::test_helpers::Nominal>()
+            .compile()
+            .unwrap();
+        assert_eq!(indexed.types, plain.types);
+        assert_eq!(indexed.dependencies.iter().collect::<Vec<_>>(), vec![&st.to_dependency()]);
+        assert!(indexed.unused_dependencies().is_empty());
+        assert_eq!(indexed.extern_types[&st.name].len(), st.types.len());
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't match library")]
+    fn type_index_mismatch() {
+        let std = crate::stl::std_stl();
+        let st = crate::stl::strict_types_stl();
+        let _ = LibBuilder::new(libname!("TestLib"), []).with_type_index(std.to_dependency(), &st);
+    }
+
+    #[test]
+    fn extern_refs() {
+        let std = crate::stl::std_stl();
+        let st = crate::stl::strict_types_stl();
+        let nominal =
+            LibBuilder::new(libname!("TestLib"), [std.to_dependency(), st.to_dependency()])
+                .transpile::<crate::value::test_helpers::Nominal>()
+                .compile()
+                .unwrap();
+        let mut lib = nominal.to_symbolic().unwrap();
+        assert_eq!(lib.validate_extern_refs(), Ok(()));
+
+        lib.dependencies = empty!();
+        let undeclared = lib.validate_extern_refs().unwrap_err();
+        assert!(!undeclared.is_empty());
+        assert!(undeclared.iter().all(|(name, r)| name == &tn!("Nominal") && r.lib_id == st.id()));
+        assert_eq!(lib.compile(), Err(CompileError::UndeclaredExternRefs(undeclared)));
+    }
+
+    #[test]
+    fn from_type_lib() {
+        #[derive(Clone, Eq, PartialEq, Debug)]
+        #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+        #[strict_type(lib = "TestLib")]
+        struct Asset {
+            nominal: crate::value::test_helpers::Nominal,
+            supply: u64,
+        }
+
+        let std = crate::stl::std_stl();
+        let st = crate::stl::strict_types_stl();
+        let deps = [std.to_dependency(), st.to_dependency()];
+        let nominal = LibBuilder::new(libname!("TestLib"), deps.clone())
+            .transpile::<crate::value::test_helpers::Nominal>()
+            .compile()
+            .unwrap();
+        for lib in [std, st.clone(), nominal.clone()] {
+            let builder = LibBuilder::from_type_lib(&lib, []).unwrap();
+            assert_eq!(builder.compile().unwrap(), lib);
+        }
+
+        let extended = LibBuilder::from_type_lib(&nominal, deps.clone())
+            .unwrap()
+            .transpile::<Asset>()
+            .compile()
+            .unwrap();
+        for name in nominal.types.keys() {
+            assert_eq!(extended.types.get(name), nominal.types.get(name));
+        }
+        assert!(extended.types.contains_key(&tn!("Asset")));
+        assert_eq!(extended.types.len(), nominal.types.len() + 1);
+    }
+
+    #[test]
+    fn sem_id() {
+        use encoding::Sizing;
+
+        use crate::value::test_helpers::Nominal;
+
+        let std = crate::stl::std_stl();
+        let st = crate::stl::strict_types_stl();
+        let builder = || {
+            LibBuilder::new(libname!("TestLib"), [std.to_dependency(), st.to_dependency()])
+                .transpile::<Nominal>()
+        };
+        let lib = builder().compile().unwrap();
+        let symbolic = builder().compile_symbols().unwrap();
+        let builder = builder();
+        for (name, ty) in &lib.types {
+            let sem_id = ty.sem_id_named(name);
+            assert_eq!(TranspileRef::Named(name.clone()).sem_id_in(&builder), Some(sem_id));
+            assert_eq!(symbolic.sem_id_of(name), Some(sem_id));
+        }
+
+        let unknown = tn!("Unknown");
+        assert_eq!(TranspileRef::Named(unknown.clone()).sem_id_in(&builder), None);
+        assert_eq!(symbolic.sem_id_of(&unknown), None);
+        assert_eq!(
+            TranspileRef::unit().sem_id_in(&builder),
+            Some(Ty::<SemId>::UNIT.sem_id_unnamed())
+        );
+        let list = Ty::<TranspileRef>::List(tn!("Nominal").into(), Sizing::U8);
+        assert_eq!(
+            TranspileRef::from(list).sem_id_in(&builder),
+            Some(
+                Ty::List(lib.types[&tn!("Nominal")].sem_id_named(&tn!("Nominal")), Sizing::U8)
+                    .sem_id_unnamed()
+            )
+        );
+
+        let mut builder = LibBuilder::new(libname!("TestLib"), []);
+        builder.types.insert(tn!("Alpha"), refer("beta", "Beta"));
+        builder.types.insert(tn!("Beta"), refer("alpha", "Alpha"));
+        assert_eq!(TranspileRef::Named(tn!("Alpha")).sem_id_in(&builder), None);
+    }
+
+    #[test]
+    fn validate() {
+        use crate::typelib::transpile::BuilderParent;
+
+        let std = crate::stl::std_stl();
+        let st = crate::stl::strict_types_stl();
+        let builder =
+            LibBuilder::new(libname!("TestLib"), [std.to_dependency(), st.to_dependency()])
+                .transpile::<crate::value::test_helpers::Nominal>();
+        assert_eq!(builder.validate(), vec![BuildWarning::UnusedDependency(std.to_dependency())]);
+
+        let mut builder = LibBuilder::new(libname!("TestLib"), []);
+        builder.types.insert(tn!("TestLib"), refer("alpha", "Alpha"));
+        builder.types.insert(tn!("Beta"), Ty::UNIT);
+        let builder = builder.report_compiled(libname!("TestLib"), Some(tn!("Beta")), Ty::BYTE);
+        let warnings = builder.validate();
+        assert_eq!(warnings, vec![
+            BuildWarning::LibNameConflict(tn!("TestLib")),
+            BuildWarning::UndefinedType {
+                unknown: tn!("Alpha"),
+                within: tn!("TestLib"),
+            },
+            BuildWarning::DuplicateType {
+                name: tn!("Beta"),
+                first: Box::new(Ty::UNIT),
+                second: Box::new(Ty::BYTE),
+            },
+        ]);
+        assert_eq!(
+            builder.compile().unwrap_err(),
+            CompileError::ValidationFailed(warnings[1..].to_vec())
+        );
+
+        let union = |tags: &[u8]| {
+            let variants = tags
+                .iter()
+                .map(|tag| {
+                    (
+                        encoding::Variant::named(*tag, vname!(format!("v{tag}"))),
+                        TranspileRef::unit(),
+                    )
+                })
+                .collect::<BTreeMap<_, _>>();
+            Ty::Union(variants.try_into().unwrap())
+        };
+        let mut builder = LibBuilder::new(libname!("TestLib"), []);
+        builder.types.insert(tn!("Reserved"), union(&[0, 1, 32]));
+        builder.types.insert(tn!("Gapped"), union(&[0, 1, 5, 6]));
+        builder.types.insert(tn!("Full"), union(&(0..250).collect::<Vec<_>>()));
+        assert_eq!(builder.validate(), vec![
+            BuildWarning::NoReservedTags(tn!("Full")),
+            BuildWarning::TagGap {
+                ty: tn!("Gapped"),
+                start: 2,
+                end: 4
+            },
+        ]);
+        assert!(builder.compile().is_ok());
+    }
+
+    #[test]
+    fn docs() {
+        use encoding::{StrictDeserialize, StrictSerialize};
+
+        use crate::value::test_helpers::Nominal;
+        use crate::SystemBuilder;
+
+        let std = crate::stl::std_stl();
+        let st = crate::stl::strict_types_stl();
+        let deps = [std.to_dependency(), st.to_dependency()];
+        let plain =
+            LibBuilder::new(libname!("TestLib"), deps.clone()).transpile::<Nominal>().compile();
+        let lib = LibBuilder::new(libname!("TestLib"), deps)
+            .transpile_documented::<Nominal>("Nominal parameters of an asset", &[
+                ("ticker", "Asset ticker"),
+                ("precision", "Number of decimal digits"),
+            ])
+            .compile()
+            .unwrap();
+        assert_eq!(lib.id(), plain.unwrap().id());
+        assert_eq!(
+            lib.type_doc(&tn!("Nominal")).unwrap().as_str(),
+            "Nominal parameters of an asset"
+        );
+        assert_eq!(
+            lib.field_doc(&tn!("Nominal"), &fname!("ticker")).unwrap().as_str(),
+            "Asset ticker"
+        );
+        assert_eq!(lib.field_doc(&tn!("Nominal"), &fname!("name")), None);
+        assert_eq!(lib.type_doc(&tn!("Precision")), None);
+
+        let data = lib.to_strict_serialized::<{ usize::MAX }>().unwrap();
+        let decoded = TypeLib::from_strict_serialized::<{ usize::MAX }>(data).unwrap();
+        assert!(decoded.type_docs.is_empty());
+        assert_eq!(decoded.id(), lib.id());
+        assert_eq!(LibBuilder::from_type_lib(&lib, []).unwrap().compile().unwrap(), lib);
+
+        #[cfg(feature = "serde")]
+        {
+            let schema = lib.to_json_schema();
+            let nominal = &schema["$defs"]["Nominal"];
+            assert_eq!(nominal["description"], "Nominal parameters of an asset");
+            assert_eq!(nominal["properties"]["ticker"]["description"], "Asset ticker");
+        }
+        let dts = lib.to_typescript_dts();
+        assert!(dts.contains("/** Nominal parameters of an asset */\nexport interface Nominal {\n"));
+        assert!(dts.contains("    /** Asset ticker */\n    ticker: StrictTypes.Ident;\n"));
+
+        let sys = SystemBuilder::new()
+            .import(lib)
+            .unwrap()
+            .import(std)
+            .unwrap()
+            .import(st)
+            .unwrap()
+            .finalize()
+            .unwrap();
+        assert_eq!(
+            sys.type_doc("TestLib.Nominal").unwrap().as_str(),
+            "Nominal parameters of an asset"
+        );
+        assert_eq!(
+            sys.field_doc("TestLib.Nominal", &fname!("precision")).unwrap().as_str(),
+            "Number of decimal digits"
+        );
+        assert_eq!(sys.type_doc("TestLib.Precision"), None);
+    }
+
+    #[test]
+    fn many_types() {
+        let mut builder = LibBuilder::new(libname!("TestLib"), []);
+        for no in 0..200 {
+            let ty = match no % 4 {
+                0 => Ty::Struct(
+                    fields!(fname!("value") => TranspileRef::Embedded(Box::new(Ty::U64))),
+                ),
+                _ => refer("prev", &format!("Type{}", no - 1)),
+            };
+            builder.types.insert(tn!(format!("Type{no}")), ty);
+        }
+        let lib = builder.compile().unwrap();
+        assert_eq!(lib.types.len(), 200);
+        for no in (1..200).filter(|no| no % 4 != 0) {
+            let prev = tn!(format!("Type{}", no - 1));
+            let prev_id = lib.types[&prev].sem_id_named(&prev);
+            assert_eq!(
+                lib.types[&tn!(format!("Type{no}"))],
+                Ty::Struct(fields!(fname!("prev") => LibRef::Named(prev_id)))
+            );
+        }
+    }
+
+    #[test]
+    fn self_reference() {
+        let mut builder = LibBuilder::new(libname!("TestLib"), []);
+        builder.types.insert(tn!("Alpha"), refer("alpha", "Alpha"));
+        assert_eq!(builder.compile().unwrap_err(), CompileError::TypeCycle(vec![tn!("Alpha")]));
+    }
+
+    #[test]
+    fn add_remove_type() {
+        let std = crate::stl::std_stl();
+        let st = crate::stl::strict_types_stl();
+        let mut lib =
+            LibBuilder::new(libname!("TestLib"), [std.to_dependency(), st.to_dependency()])
+                .transpile::<crate::value::test_helpers::Nominal>()
+                .compile_symbols()
+                .unwrap();
+        let precision = lib.types[&tn!("Precision")].clone();
+
+        assert_eq!(
+            lib.add_type(tn!("Precision"), precision.clone()),
+            Err(TranspileError::RepeatedType(tn!("Precision")))
+        );
+        assert_eq!(
+            lib.add_type(tn!("Wrapper"), refer("inner", "Missing")),
+            Err(TranspileError::UnknownType {
+                unknown: tn!("Missing"),
+                within_type: tn!("Wrapper"),
+                within: refer("inner", "Missing"),
+            })
+        );
+        let mut foreign = SymbolRef::with(
+            libname!("Foreign"),
+            tn!("Type"),
+            TypeLibId::from_byte_array([0; 32]),
+            SemId::unit(),
+        );
+        let ext = Ty::Struct(fields!(fname!("ext") => TranspileRef::Extern(foreign.clone())));
+        assert_eq!(
+            lib.add_type(tn!("Ext"), ext),
+            Err(TranspileError::UnknownLib(libname!("Foreign")))
+        );
+
+        lib.add_type(tn!("Wrapper"), refer("inner", "Precision")).unwrap();
+        foreign.lib_name = std.name.clone();
+        foreign.lib_id = std.id();
+        let ext = Ty::Struct(fields!(fname!("ext") => TranspileRef::Extern(foreign)));
+        lib.add_type(tn!("Ext"), ext).unwrap();
+        assert!(lib.extern_types[&std.name].contains_key(&SemId::unit()));
+        assert!(lib.clone().compile_strict().is_ok());
+
+        assert_eq!(
+            lib.remove_type(&tn!("Precision")),
+            Err(RemoveError::Referenced {
+                referenced: tn!("Precision"),
+                referrer: tn!("Nominal"),
+            })
+        );
+        assert_eq!(lib.remove_type(&tn!("Absent")), Err(RemoveError::UnknownType(tn!("Absent"))));
+        lib.remove_type(&tn!("Wrapper")).unwrap();
+        assert!(!lib.types.contains_key(&tn!("Wrapper")));
+        assert!(lib.compile().is_ok());
+    }
+}
@@ -41,6 +41,13 @@ pub enum SymbolError {
 
     /// too many dependencies.
     TooManyDependencies,
+
+    /// type with semantic id `{sem_id}` from library `{lib}` has multiple names: {names:?}.
+    AmbiguousExternType {
+        lib: LibName,
+        sem_id: SemId,
+        names: Vec<TypeName>,
+    },
 }
 
 pub struct SymbolContext {
@@ -23,6 +23,7 @@
 use std::collections::{BTreeMap, BTreeSet};
 use std::io;
 use std::io::Sink;
+use std::str::FromStr;
 
 use amplify::confinement::{Confined, NonEmptyVec, U64 as U64MAX};
 use amplify::Wrapper;
@@ -35,7 +36,8 @@ use strict_encoding::{
 };
 
 use crate::ast::{EnumVariants, Field, NamedFields, UnionVariants, UnnamedFields};
-use crate::{Dependency, SemId, SymbolRef, TranspileRef, Ty, TypeLibId};
+use crate::typelib::Doc;
+use crate::{Dependency, SemId, SymbolRef, TranspileRef, Ty, TypeLib, TypeLibId};
 
 const MAX_WRITE_COUNT: usize = U64MAX;
 
@@ -54,6 +56,10 @@ pub struct LibBuilder {
     pub(super) known_libs: BTreeSet<Dependency>,
     pub(super) extern_types: BTreeMap<LibName, BTreeMap<SemId, TypeName>>,
     pub(super) types: BTreeMap<TypeName, Ty<TranspileRef>>,
+    pub(super) conflicts: Vec<(TypeName, Ty<TranspileRef>, Ty<TranspileRef>)>,
+    pub(super) type_docs: BTreeMap<TypeName, Doc>,
+    pub(super) field_docs: BTreeMap<TypeName, BTreeMap<FieldName, Doc>>,
+    pub(super) cache_hits: usize,
     sink: StreamWriter<Sink>,
     last_compiled: Option<TranspileRef>,
 }
@@ -68,6 +74,10 @@ impl LibBuilder {
             known_libs: known_libs.into_iter().collect(),
             extern_types: empty!(),
             types: empty!(),
+            conflicts: empty!(),
+            type_docs: empty!(),
+            field_docs: empty!(),
+            cache_hits: 0,
             sink: StreamWriter::sink::<MAX_WRITE_COUNT>(),
             last_compiled: None,
         }
@@ -77,6 +87,54 @@ impl LibBuilder {
         T::strict_dumb().strict_encode(self).expect("memory encoding doesn't error")
     }
 
+    /// Transpiles type `T`, attaching documentation to it and to its fields. Empty `type_doc` is
+    /// ignored.
+    ///
+    /// # Panics
+    ///
+    /// If `T` has no name, any of the field names is invalid or any of the documentation strings
+    /// exceeds 255 bytes.
+    pub fn transpile_documented<T: StrictEncode + StrictDumb>(
+        mut self,
+        type_doc: &str,
+        field_docs: &[(&str, &str)],
+    ) -> Self {
+        let name = T::strict_name().expect("documented type must be named");
+        let doc = |s: &str| Doc::from_str(s).expect("documentation exceeding 255 bytes");
+        if !type_doc.is_empty() {
+            self.type_docs.insert(name.clone(), doc(type_doc));
+        }
+        if !field_docs.is_empty() {
+            let docs = field_docs.iter().map(|(field, field_doc)| {
+                let field = FieldName::try_from(field.to_string())
+                    .unwrap_or_else(|_| panic!("invalid field name '{field}'"));
+                (field, doc(field_doc))
+            });
+            self.field_docs.entry(name).or_default().extend(docs);
+        }
+        self.transpile::<T>()
+    }
+
+    /// Adds `dep` to the known libraries and registers all types from its library `lib` as
+    /// external types, such that references to them are resolved without transpiling the
+    /// external types.
+    ///
+    /// # Panics
+    ///
+    /// If `dep` doesn't have the id of the `lib`.
+    pub fn with_type_index(mut self, dep: Dependency, lib: &TypeLib) -> Self {
+        assert_eq!(dep.id, lib.id(), "dependency {dep} doesn't match library {}", lib.name);
+        let index = lib.types.iter().map(|(name, ty)| (ty.sem_id_named(name), name.clone()));
+        self.extern_types.entry(dep.name.clone()).or_default().extend(index);
+        self.known_libs.insert(dep);
+        self
+    }
+
+    /// Looks up the semantic id of a type registered as an external type of the `lib` library.
+    fn extern_id(&self, lib: &LibName, name: &TypeName) -> Option<SemId> {
+        self.extern_types.get(lib)?.iter().find(|(_, ty_name)| *ty_name == name).map(|(id, _)| *id)
+    }
+
     fn dependency_id(&self, lib_name: &LibName) -> TypeLibId {
         self.known_libs
             .iter()
@@ -220,12 +278,22 @@ impl BuilderParent for LibBuilder {
         match (T::STRICT_LIB_NAME, T::strict_name()) {
             (LIB_EMBEDDED, _) | (_, None) => _compile(self),
             (lib, Some(name)) if lib != self.lib_name.as_str() => {
-                let (me, r) = _compile(self);
                 let lib_name = libname!(lib);
+                let (me, sem_id) = match self.extern_id(&lib_name, &name) {
+                    Some(sem_id) => (self, sem_id),
+                    None => {
+                        let (me, r) = _compile(self);
+                        (me, r.id())
+                    }
+                };
                 let lib_id = me.dependency_id(&lib_name);
-                (me, TranspileRef::Extern(SymbolRef::with(lib_name, name, lib_id, r.id())))
+                (me, TranspileRef::Extern(SymbolRef::with(lib_name, name, lib_id, sem_id)))
+            }
+            (_, Some(name)) if self.types.contains_key(&name) => {
+                let mut me = self;
+                me.cache_hits += 1;
+                (me, TranspileRef::Named(name))
             }
-            (_, Some(name)) if self.types.contains_key(&name) => (self, TranspileRef::Named(name)),
             (_, Some(_)) => _compile(self),
         }
     }
@@ -238,13 +306,18 @@ impl BuilderParent for LibBuilder {
     ) -> Self {
         let r = match (lib, name) {
             (lib, Some(name)) if lib == self.lib_name => {
-                if let Some(old_ty) = self.types.get(&name) {
-                    assert_eq!(
-                        old_ty, &ty,
-                        "repeated type name '{name}' for two different types '{old_ty}' and '{ty}'",
-                    );
+                match self.types.get(&name) {
+                    // Conflicting types are reported by `LibBuilder::validate`
+                    Some(old_ty) if old_ty != &ty => {
+                        let conflict = (name.clone(), old_ty.clone(), ty);
+                        if !self.conflicts.contains(&conflict) {
+                            self.conflicts.push(conflict);
+                        }
+                    }
+                    _ => {
+                        self.types.insert(name.clone(), ty);
+                    }
                 }
-                self.types.insert(name.clone(), ty);
                 TranspileRef::Named(name)
             }
             (lib, Some(name)) => {
@@ -21,11 +21,13 @@
 // limitations under the License.
 
 use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
 
-use amplify::confinement::{NonEmptyOrdMap, TinyOrdSet};
+use amplify::confinement::{self, Confined, NonEmptyOrdMap, TinyOrdSet, TinyString};
 use baid64::DisplayBaid64;
-use encoding::StrictDumb;
+use encoding::{FieldName, StrictDumb};
 use strict_encoding::{LibName, TypeName, STRICT_TYPES_LIB};
 
 use crate::typelib::compile::CompileError;
@@ -33,13 +35,85 @@ use crate::typelib::id::TypeLibId;
 use crate::typelib::ExternTypes;
 use crate::{SemId, Ty, TypeRef};
 
-pub trait LibSubref: TypeRef {}
-impl LibSubref for LibRef {}
-impl LibSubref for InlineRef {}
-impl LibSubref for InlineRef1 {}
-impl LibSubref for InlineRef2 {}
+/// Borrowed view of a type library reference, which allows walking library types without
+/// matching on each of the reference types separately.
+#[derive(Debug)]
+pub enum LibSubrefView<'r, Ref: TypeRef> {
+    Inline(&'r Ty<Ref>),
+    Named(&'r SemId),
+    Extern(&'r ExternRef),
+}
+
+pub trait LibSubref: TypeRef {
+    /// Reference type used by the inline types of this reference.
+    type Inner: LibSubref;
+
+    fn view(&self) -> LibSubrefView<'_, Self::Inner>;
+}
 
-#[derive(Clone, Eq, PartialEq, Debug, Display)]
+macro_rules! impl_lib_subref {
+    ($ty:ident, $inner:ident) => {
+        impl LibSubref for $ty {
+            type Inner = $inner;
+
+            fn view(&self) -> LibSubrefView<'_, $inner> {
+                match self {
+                    $ty::Inline(ty) => LibSubrefView::Inline(ty),
+                    $ty::Named(sem_id) => LibSubrefView::Named(sem_id),
+                    $ty::Extern(ext) => LibSubrefView::Extern(ext),
+                }
+            }
+        }
+    };
+}
+
+impl_lib_subref!(LibRef, InlineRef);
+impl_lib_subref!(InlineRef, InlineRef1);
+impl_lib_subref!(InlineRef1, InlineRef2);
+
+impl LibSubref for InlineRef2 {
+    // Inline types are not allowed at this nesting level
+    type Inner = InlineRef2;
+
+    fn view(&self) -> LibSubrefView<'_, InlineRef2> {
+        match self {
+            InlineRef2::Named(sem_id) => LibSubrefView::Named(sem_id),
+            InlineRef2::Extern(ext) => LibSubrefView::Extern(ext),
+        }
+    }
+}
+
+/// Collects named and external type references from a type, including references from all of its
+/// inline types.
+fn collect_refs<Ref: LibSubref>(
+    r: &Ref,
+    named: &mut BTreeSet<SemId>,
+    ext: &mut BTreeSet<ExternRef>,
+) {
+    match r.view() {
+        LibSubrefView::Inline(ty) => collect_ty_refs(ty, named, ext),
+        LibSubrefView::Named(sem_id) => {
+            named.insert(*sem_id);
+        }
+        LibSubrefView::Extern(r) => {
+            ext.insert(r.clone());
+        }
+    }
+}
+
+/// Collects named and external type references from a type, including references from all of its
+/// inline types.
+pub(crate) fn collect_ty_refs<Ref: LibSubref>(
+    ty: &Ty<Ref>,
+    named: &mut BTreeSet<SemId>,
+    ext: &mut BTreeSet<ExternRef>,
+) {
+    for (r, _) in ty.type_refs() {
+        collect_refs(r, named, ext);
+    }
+}
+
+#[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Debug, Display)]
 #[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
 #[strict_type(lib = STRICT_TYPES_LIB)]
 #[display("{lib_id}.{sem_id}")]
@@ -65,6 +139,13 @@ pub enum InlineRef {
 }
 
 impl TypeRef for InlineRef {
+    fn sem_id(&self) -> Option<SemId> {
+        match self {
+            InlineRef::Inline(ty) => Some(ty.sem_id_unnamed()),
+            InlineRef::Named(sem_id) => Some(*sem_id),
+            InlineRef::Extern(ext) => Some(ext.sem_id),
+        }
+    }
     fn is_compound(&self) -> bool {
         match self {
             InlineRef::Inline(ty) => ty.is_compound(),
@@ -83,6 +164,12 @@ impl TypeRef for InlineRef {
             _ => false,
         }
     }
+    fn depth(&self) -> usize {
+        match self {
+            InlineRef::Inline(ty) => ty.depth(),
+            _ => 0,
+        }
+    }
 }
 
 impl Display for InlineRef {
@@ -107,6 +194,13 @@ pub enum InlineRef1 {
 }
 
 impl TypeRef for InlineRef1 {
+    fn sem_id(&self) -> Option<SemId> {
+        match self {
+            InlineRef1::Inline(ty) => Some(ty.sem_id_unnamed()),
+            InlineRef1::Named(sem_id) => Some(*sem_id),
+            InlineRef1::Extern(ext) => Some(ext.sem_id),
+        }
+    }
     fn is_compound(&self) -> bool {
         match self {
             InlineRef1::Inline(ty) => ty.is_compound(),
@@ -125,6 +219,12 @@ impl TypeRef for InlineRef1 {
             _ => false,
         }
     }
+    fn depth(&self) -> usize {
+        match self {
+            InlineRef1::Inline(ty) => ty.depth(),
+            _ => 0,
+        }
+    }
 }
 
 impl Display for InlineRef1 {
@@ -147,6 +247,12 @@ pub enum InlineRef2 {
 }
 
 impl TypeRef for InlineRef2 {
+    fn sem_id(&self) -> Option<SemId> {
+        match self {
+            InlineRef2::Named(sem_id) => Some(*sem_id),
+            InlineRef2::Extern(ext) => Some(ext.sem_id),
+        }
+    }
     fn is_compound(&self) -> bool { false }
     fn is_byte(&self) -> bool { false }
     fn is_unicode_char(&self) -> bool { false }
@@ -173,6 +279,13 @@ pub enum LibRef {
 }
 
 impl TypeRef for LibRef {
+    fn sem_id(&self) -> Option<SemId> {
+        match self {
+            LibRef::Inline(ty) => Some(ty.sem_id_unnamed()),
+            LibRef::Named(sem_id) => Some(*sem_id),
+            LibRef::Extern(ext) => Some(ext.sem_id),
+        }
+    }
     fn is_compound(&self) -> bool {
         match self {
             LibRef::Inline(ty) => ty.is_compound(),
@@ -191,6 +304,12 @@ impl TypeRef for LibRef {
             _ => false,
         }
     }
+    fn depth(&self) -> usize {
+        match self {
+            LibRef::Inline(ty) => ty.depth(),
+            _ => 0,
+        }
+    }
 }
 
 impl Display for LibRef {
@@ -245,6 +364,23 @@ impl Display for Dependency {
 
 pub type TypeMap = NonEmptyOrdMap<TypeName, Ty<LibRef>, { u16::MAX as usize }>;
 
+/// Documentation of a type or a field, up to 255 bytes long.
+#[derive(Wrapper, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Display, From)]
+#[wrapper(Deref)]
+#[display(inner)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+pub struct Doc(TinyString);
+
+impl FromStr for Doc {
+    type Err = confinement::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> { TinyString::try_from(s.to_owned()).map(Doc) }
+}
+
+/// Type library.
+///
+/// Type and field documentation is library metadata: it is neither strict-encoded nor affects
+/// the library id.
 #[derive(Clone, Eq, PartialEq, Debug)]
 #[derive(StrictType, StrictEncode, StrictDecode)]
 #[strict_type(lib = STRICT_TYPES_LIB)]
@@ -254,6 +390,12 @@ pub struct TypeLib {
     pub dependencies: TinyOrdSet<Dependency>,
     pub extern_types: ExternTypes,
     pub types: TypeMap,
+    #[strict_type(skip)]
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "BTreeMap::is_empty"))]
+    pub type_docs: BTreeMap<TypeName, Doc>,
+    #[strict_type(skip)]
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "BTreeMap::is_empty"))]
+    pub field_docs: BTreeMap<TypeName, BTreeMap<FieldName, Doc>>,
 }
 
 impl StrictDumb for TypeLib {
@@ -263,6 +405,8 @@ impl StrictDumb for TypeLib {
             dependencies: default!(),
             extern_types: default!(),
             types: TypeMap::with_key_value(tn!("DumbType"), Ty::strict_dumb()),
+            type_docs: empty!(),
+            field_docs: empty!(),
         }
     }
 }
@@ -274,17 +418,258 @@ impl TypeLib {
         if self.dependencies.contains(&dependency) {
             return Err(CompileError::DuplicatedDependency(dependency));
         }
+        if let Some(present) = self.dependencies.iter().find(|dep| dep.name == dependency.name) {
+            return Err(CompileError::ConflictingDependency {
+                present: present.clone(),
+                new: dependency,
+            });
+        }
         self.dependencies.push(dependency).map_err(|_| CompileError::TooManyDependencies)?;
         Ok(())
     }
 
+    pub fn type_doc(&self, name: &TypeName) -> Option<&Doc> { self.type_docs.get(name) }
+
+    pub fn field_doc(&self, name: &TypeName, field: &FieldName) -> Option<&Doc> {
+        self.field_docs.get(name).and_then(|docs| docs.get(field))
+    }
+
+    /// Adds a type to the library, failing if the type references libraries which are not
+    /// imported as the library dependencies.
     pub fn populate(&mut self, name: TypeName, ty: Ty<LibRef>) -> Result<(), CompileError> {
         if self.types.contains_key(&name) {
             return Err(CompileError::DuplicateName(name));
         }
+        let undeclared = self.undeclared_extern_refs(&name, &ty);
+        if !undeclared.is_empty() {
+            return Err(CompileError::UndeclaredExternRefs(undeclared));
+        }
         self.types.insert(name, ty).map_err(|_| CompileError::TooManyTypes)?;
         Ok(())
     }
 
-    // TODO: Check that all dependencies are used
+    /// Returns names of the library types which are not referenced by any other library type.
+    pub fn unused_types(&self) -> Vec<TypeName> {
+        let mut referenced = bset![];
+        for (name, ty) in &self.types {
+            let mut named = bset![];
+            collect_ty_refs(ty, &mut named, &mut bset![]);
+            named.remove(&ty.sem_id_named(name));
+            referenced.extend(named);
+        }
+        self.types
+            .iter()
+            .filter(|(name, ty)| !referenced.contains(&ty.sem_id_named(name)))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Constructs a new library containing only the `roots` types and types which are
+    /// transitively referenced by them. External types and dependencies which are not used by
+    /// the remaining types are removed as well.
+    ///
+    /// Semantic ids of the retained types are not changed, while the library id may change.
+    ///
+    /// # Panics
+    ///
+    /// If none of the `roots` is present in the library.
+    pub fn prune_unreferenced(&self, roots: &[TypeName]) -> TypeLib {
+        let index = self
+            .types
+            .iter()
+            .map(|(name, ty)| (ty.sem_id_named(name), name))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut retained = BTreeSet::new();
+        let mut ext = BTreeSet::new();
+        let mut queue =
+            roots.iter().filter(|name| self.types.contains_key(*name)).collect::<Vec<_>>();
+        while let Some(name) = queue.pop() {
+            if !retained.insert(name) {
+                continue;
+            }
+            let mut named = bset![];
+            collect_ty_refs(&self.types[name], &mut named, &mut ext);
+            queue.extend(named.iter().filter_map(|id| index.get(id).copied()));
+        }
+
+        let types = self
+            .types
+            .iter()
+            .filter(|(name, _)| retained.contains(name))
+            .map(|(name, ty)| (name.clone(), ty.clone()))
+            .collect::<BTreeMap<_, _>>();
+        let types =
+            TypeMap::try_from(types).expect("none of the root types is present in the library");
+
+        let dependencies = self
+            .dependencies
+            .iter()
+            .filter(|dep| ext.iter().any(|r| r.lib_id == dep.id))
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        let extern_types = self
+            .extern_types
+            .iter()
+            .filter_map(|(lib, index)| {
+                let dep = dependencies.iter().find(|dep| &dep.name == lib)?;
+                let index = index
+                    .iter()
+                    .filter(|(id, _)| ext.contains(&ExternRef::with(dep.id, **id)))
+                    .map(|(id, name)| (*id, name.clone()))
+                    .collect::<BTreeMap<_, _>>();
+                Some((lib.clone(), Confined::from_checked(index)))
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        let type_docs = self
+            .type_docs
+            .iter()
+            .filter(|(name, _)| types.contains_key(*name))
+            .map(|(name, doc)| (name.clone(), doc.clone()))
+            .collect();
+        let field_docs = self
+            .field_docs
+            .iter()
+            .filter(|(name, _)| types.contains_key(*name))
+            .map(|(name, docs)| (name.clone(), docs.clone()))
+            .collect();
+
+        TypeLib {
+            name: self.name.clone(),
+            dependencies: Confined::from_checked(dependencies),
+            extern_types: Confined::from_checked(extern_types),
+            types,
+            type_docs,
+            field_docs,
+        }
+    }
+
+    /// Checks that all external types referenced by the library types belong to the libraries
+    /// from the library dependencies, returning the violating references otherwise.
+    pub fn validate_extern_refs(&self) -> Result<(), Vec<(TypeName, ExternRef)>> {
+        let undeclared = self
+            .types
+            .iter()
+            .flat_map(|(name, ty)| self.undeclared_extern_refs(name, ty))
+            .collect::<Vec<_>>();
+        if !undeclared.is_empty() {
+            return Err(undeclared);
+        }
+        Ok(())
+    }
+
+    /// Returns library dependencies which are not referenced by any of the library types.
+    pub fn unused_dependencies(&self) -> Vec<Dependency> {
+        let mut ext = bset![];
+        for ty in self.types.values() {
+            collect_ty_refs(ty, &mut bset![], &mut ext);
+        }
+        self.dependencies
+            .iter()
+            .filter(|dep| !ext.iter().any(|r| r.lib_id == dep.id))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns external references of type `name` to libraries absent from the library
+    /// dependencies.
+    fn undeclared_extern_refs(
+        &self,
+        name: &TypeName,
+        ty: &Ty<LibRef>,
+    ) -> Vec<(TypeName, ExternRef)> {
+        let mut ext = bset![];
+        collect_ty_refs(ty, &mut bset![], &mut ext);
+        ext.into_iter()
+            .filter(|r| !self.dependencies.iter().any(|dep| dep.id == r.lib_id))
+            .map(|r| (name.clone(), r))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use encoding::Sizing;
+
+    use super::*;
+    use crate::stl::{std_stl, strict_types_stl};
+    use crate::value::test_helpers::Nominal;
+    use crate::LibBuilder;
+
+    fn test_lib() -> TypeLib {
+        let std = std_stl();
+        let st = strict_types_stl();
+        LibBuilder::new("TestLib", [std.to_dependency(), st.to_dependency()])
+            .transpile::<Nominal>()
+            .compile()
+            .unwrap()
+    }
+
+    #[test]
+    fn unused_dependencies() {
+        // Std library is required only by the types from StrictTypes library, but not by TestLib
+        let mut lib = test_lib();
+        assert_eq!(lib.unused_dependencies(), vec![std_stl().to_dependency()]);
+
+        let dep = Dependency::with(lib.id(), libname!("Unused"));
+        lib.import(dep.clone()).unwrap();
+        let unused = lib.unused_dependencies();
+        assert_eq!(unused.len(), 2);
+        assert!(unused.contains(&dep));
+    }
+
+    #[test]
+    fn extern_refs() {
+        let st = strict_types_stl();
+        let mut lib = test_lib();
+        assert_eq!(lib.validate_extern_refs(), Ok(()));
+
+        let conflicting = Dependency::with(lib.id(), st.name.clone());
+        assert_eq!(
+            lib.import(conflicting.clone()),
+            Err(CompileError::ConflictingDependency {
+                present: st.to_dependency(),
+                new: conflicting
+            })
+        );
+
+        let ident = ExternRef::with(st.id(), st.types[&tn!("Ident")].sem_id_named(&tn!("Ident")));
+        let ty = Ty::List(LibRef::Extern(ident.clone()), Sizing::U8);
+        let mut populated = lib.clone();
+        populated.populate(tn!("Idents"), ty.clone()).unwrap();
+
+        lib.dependencies = empty!();
+        let undeclared = lib.validate_extern_refs().unwrap_err();
+        assert!(undeclared.contains(&(tn!("Nominal"), ident.clone())));
+        assert_eq!(
+            lib.populate(tn!("Idents"), ty),
+            Err(CompileError::UndeclaredExternRefs(vec![(tn!("Idents"), ident)]))
+        );
+    }
+
+    #[test]
+    fn unused_types() {
+        assert_eq!(test_lib().unused_types(), vec![tn!("Nominal")]);
+    }
+
+    #[test]
+    fn prune() {
+        let lib = test_lib();
+        let full = lib.prune_unreferenced(&[tn!("Nominal")]);
+        assert_eq!(full.types, lib.types);
+        assert_eq!(full.dependencies.len(), 1);
+        assert!(full.dependencies.contains(&strict_types_stl().to_dependency()));
+
+        let pruned = lib.prune_unreferenced(&[tn!("Precision")]);
+        assert_ne!(pruned.id(), lib.id());
+        assert_eq!(pruned.types.keys().collect::<Vec<_>>(), vec![&tn!("Precision")]);
+        assert!(pruned.dependencies.is_empty());
+        assert!(pruned.extern_types.is_empty());
+        assert_eq!(
+            pruned.types[&tn!("Precision")].sem_id_named(&tn!("Precision")),
+            lib.types[&tn!("Precision")].sem_id_named(&tn!("Precision"))
+        );
+        assert_eq!(pruned.unused_types(), vec![tn!("Precision")]);
+    }
 }
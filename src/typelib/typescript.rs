@@ -0,0 +1,314 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generation of TypeScript declaration (`.d.ts`) files from type libraries.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use encoding::{LibName, NumCls, Primitive, TypeName};
+
+use crate::typelib::{Doc, ExternRef, ExternTypes, LibSubref, LibSubrefView};
+use crate::{SemId, Ty, TypeLib, TypeLibId};
+
+/// Name of the TypeScript object field holding union variant name.
+pub const TS_UNION_TAG: &str = "_tag";
+
+struct TsCtx<'lib> {
+    names: BTreeMap<SemId, &'lib TypeName>,
+    libs: BTreeMap<TypeLibId, &'lib LibName>,
+    extern_types: &'lib ExternTypes,
+    imports: BTreeSet<String>,
+}
+
+impl TsCtx<'_> {
+    fn named(&self, sem_id: &SemId) -> String {
+        match self.names.get(sem_id) {
+            Some(name) => name.to_string(),
+            None => format!("unknown /* {sem_id} */"),
+        }
+    }
+
+    fn external(&mut self, ext: &ExternRef) -> String {
+        let Some(lib) = self.libs.get(&ext.lib_id) else {
+            return format!("unknown /* {} */", ext.lib_id);
+        };
+        match self.extern_types.get(*lib).and_then(|index| index.get(&ext.sem_id)) {
+            Some(name) => {
+                self.imports.insert(lib.to_string());
+                format!("{lib}.{name}")
+            }
+            None => format!("unknown /* {lib}.{} */", ext.sem_id),
+        }
+    }
+}
+
+trait TsRef: LibSubref {
+    fn ts_type(&self, ctx: &mut TsCtx) -> String {
+        match self.view() {
+            LibSubrefView::Inline(ty) => ty_ts(ty, ctx),
+            LibSubrefView::Named(sem_id) => ctx.named(sem_id),
+            LibSubrefView::Extern(ext) => ctx.external(ext),
+        }
+    }
+
+    fn ts_range(&self) -> Option<String> {
+        match self.view() {
+            LibSubrefView::Inline(ty) => ty_range(ty),
+            _ => None,
+        }
+    }
+}
+
+impl<Ref: LibSubref> TsRef for Ref {}
+
+/// Returns JSDoc `@range` tag value for integer primitives.
+fn range(prim: Primitive) -> Option<String> {
+    if prim == Primitive::UNIT {
+        return None;
+    }
+    if prim == Primitive::BYTE {
+        return Some(format!("0..{}", u8::MAX));
+    }
+    let info = prim.info();
+    let bits = info.byte_size() as u32 * 8;
+    Some(match info.ty {
+        NumCls::Float => return None,
+        NumCls::Unsigned if bits <= 64 => format!("0..{}", u64::MAX >> (64 - bits)),
+        NumCls::NonZero if bits <= 64 => format!("1..{}", u64::MAX >> (64 - bits)),
+        NumCls::Signed if bits <= 64 => {
+            format!("{}..{}", i64::MIN >> (64 - bits), i64::MAX >> (64 - bits))
+        }
+        NumCls::Unsigned => format!("0..2^{bits}-1"),
+        NumCls::NonZero => format!("1..2^{bits}-1"),
+        NumCls::Signed => format!("-2^{}..2^{}-1", bits - 1, bits - 1),
+    })
+}
+
+fn ty_range<Ref: TsRef>(ty: &Ty<Ref>) -> Option<String> {
+    match ty {
+        Ty::Primitive(prim) => range(*prim),
+        Ty::Tuple(fields) if fields.len() == 1 => fields.first().and_then(Ref::ts_range),
+        _ => None,
+    }
+}
+
+fn ty_ts<Ref: TsRef>(ty: &Ty<Ref>, ctx: &mut TsCtx) -> String {
+    match ty {
+        Ty::Primitive(prim) if *prim == Primitive::UNIT => s!("null"),
+        Ty::Primitive(_) => s!("number"),
+        Ty::UnicodeChar => s!("string"),
+        Ty::Enum(variants) => variants
+            .iter()
+            .map(|variant| format!("\"{}\"", variant.name))
+            .collect::<Vec<_>>()
+            .join(" | "),
+        Ty::Union(variants) => variants
+            .into_iter()
+            .map(|(variant, ty)| {
+                format!("{{ {TS_UNION_TAG}: \"{}\"; value: {} }}", variant.name, ty.ts_type(ctx))
+            })
+            .collect::<Vec<_>>()
+            .join(" | "),
+        Ty::Tuple(fields) if fields.len() == 1 => {
+            fields.first().map(|ty| ty.ts_type(ctx)).unwrap_or_default()
+        }
+        Ty::Tuple(fields) => {
+            let fields = fields.iter().map(|ty| ty.ts_type(ctx)).collect::<Vec<_>>();
+            format!("[{}]", fields.join(", "))
+        }
+        Ty::Struct(fields) => {
+            let fields = fields
+                .iter()
+                .map(|field| format!("{}: {}", field.name, field.ty.ts_type(ctx)))
+                .collect::<Vec<_>>();
+            format!("{{ {} }}", fields.join("; "))
+        }
+        Ty::Array(ty, _) | Ty::List(ty, _) if ty.is_byte() => s!("Uint8Array"),
+        Ty::List(ty, _) if ty.is_unicode_char() => s!("string"),
+        Ty::Array(ty, _) | Ty::List(ty, _) | Ty::Set(ty, _) => {
+            format!("Array<{}>", ty.ts_type(ctx))
+        }
+        Ty::Map(key, val, _) => {
+            let key = key.ts_type(ctx);
+            let val = val.ts_type(ctx);
+            if key == "string" || key == "number" {
+                format!("Record<{key}, {val}>")
+            } else {
+                format!("Map<{key}, {val}>")
+            }
+        }
+    }
+}
+
+/// Writes JSDoc comment with the documentation and `@range` tag, if any of them is present.
+fn jsdoc(def: &mut String, indent: &str, doc: Option<&Doc>, range: Option<String>) {
+    let doc = doc.map(|doc| doc.replace("*/", "*\\/"));
+    match (doc, range) {
+        (None, None) => {}
+        (Some(doc), None) => {
+            writeln!(def, "{indent}/** {doc} */").ok();
+        }
+        (None, Some(range)) => {
+            writeln!(def, "{indent}/** @range {range} */").ok();
+        }
+        (Some(doc), Some(range)) => {
+            writeln!(def, "{indent}/**\n{indent} * {doc}\n{indent} * @range {range}\n{indent} */")
+                .ok();
+        }
+    }
+}
+
+fn definition<Ref: TsRef>(name: &TypeName, ty: &Ty<Ref>, lib: &TypeLib, ctx: &mut TsCtx) -> String {
+    let mut def = String::new();
+    match ty {
+        Ty::Struct(fields) => {
+            jsdoc(&mut def, "", lib.type_doc(name), None);
+            writeln!(def, "export interface {name} {{").ok();
+            for field in fields {
+                let ts = field.ty.ts_type(ctx);
+                jsdoc(&mut def, "    ", lib.field_doc(name, &field.name), field.ty.ts_range());
+                writeln!(def, "    {}: {ts};", field.name).ok();
+            }
+            writeln!(def, "}}").ok();
+        }
+        Ty::Union(variants) => {
+            jsdoc(&mut def, "", lib.type_doc(name), None);
+            writeln!(def, "export type {name} =").ok();
+            let count = variants.len();
+            for (no, (variant, ty)) in variants.into_iter().enumerate() {
+                let sep = if no + 1 == count { ";" } else { "" };
+                writeln!(
+                    def,
+                    "    | {{ {TS_UNION_TAG}: \"{}\"; value: {} }}{sep}",
+                    variant.name,
+                    ty.ts_type(ctx)
+                )
+                .ok();
+            }
+        }
+        _ => {
+            jsdoc(&mut def, "", lib.type_doc(name), ty_range(ty));
+            writeln!(def, "export type {name} = {};", ty_ts(ty, ctx)).ok();
+        }
+    }
+    def
+}
+
+impl TypeLib {
+    /// Generates TypeScript declaration file describing the library types. Types from the library
+    /// dependencies are imported as modules named after the library (`./<LibName>`).
+    pub fn to_typescript_dts(&self) -> String {
+        let mut ctx = TsCtx {
+            names: self.types.iter().map(|(name, ty)| (ty.sem_id_named(name), name)).collect(),
+            libs: self.dependencies.iter().map(|dep| (dep.id, &dep.name)).collect(),
+            extern_types: &self.extern_types,
+            imports: empty!(),
+        };
+
+        let mut body = String::new();
+        for (name, ty) in &self.types {
+            writeln!(body).ok();
+            body.push_str(&definition(name, ty, self, &mut ctx));
+        }
+
+        let mut dts = format!("// Generated from strict type library {}\n", self.id());
+        if !ctx.imports.is_empty() {
+            writeln!(dts).ok();
+        }
+        for lib in ctx.imports {
+            writeln!(dts, "import type * as {lib} from \"./{lib}\";").ok();
+        }
+        dts.push_str(&body);
+        dts
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::stl::{std_stl, strict_types_stl};
+    use crate::value::test_helpers::Nominal;
+    use crate::LibBuilder;
+
+    #[test]
+    fn nominal() {
+        let std = std_stl();
+        let st = strict_types_stl();
+        let lib = LibBuilder::new("TestLib", [std.to_dependency(), st.to_dependency()])
+            .transpile::<Nominal>()
+            .compile()
+            .unwrap();
+        let dts = lib.to_typescript_dts();
+        let body = dts.split_once('\n').unwrap().1;
+        assert_eq!(
+            body,
+            r#"
+import type * as StrictTypes from "./StrictTypes";
+
+export interface Nominal {
+    ticker: StrictTypes.Ident;
+    name: string;
+    precision: Precision;
+}
+
+export type Precision = "noDecimals" | "oneDecimal" | "twoDecimals";
+"#
+        );
+    }
+
+    #[test]
+    #[ignore = "requires TypeScript compiler `tsc` in PATH"]
+    fn tsc_compile() {
+        let std = std_stl();
+        let st = strict_types_stl();
+        let lib = LibBuilder::new("TestLib", [std.to_dependency(), st.to_dependency()])
+            .transpile::<Nominal>()
+            .compile()
+            .unwrap();
+        let dir = std::env::temp_dir().join("strict-types-dts");
+        std::fs::create_dir_all(&dir).unwrap();
+        let files = [&std, &st, &lib].map(|lib| {
+            let path = dir.join(format!("{}.d.ts", lib.name));
+            std::fs::write(&path, lib.to_typescript_dts()).unwrap();
+            path
+        });
+        let output = std::process::Command::new("tsc")
+            .args(["--noEmit", "--strict"])
+            .args(files)
+            .output()
+            .expect("tsc is not available");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    }
+
+    #[test]
+    fn stl() {
+        let dts = strict_types_stl().to_typescript_dts();
+        assert!(dts.contains("import type * as Std from \"./Std\";\n"));
+        assert!(dts.contains("/** @range 0..255 */\nexport type Primitive = number;\n"));
+        assert!(dts.contains("export type SemId = Uint8Array;\n"));
+        assert!(dts.contains("    /** @range 0..18446744073709551615 */\n    min: number;\n"));
+        assert!(dts.contains(
+            "export type InlineRef2 =\n    | { _tag: \"named\"; value: SemId }\n    | { _tag: \
+             \"extern\"; value: ExternRef };\n"
+        ));
+    }
+}
@@ -0,0 +1,221 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Comparison of type names and definitions between two symbolic type systems.
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+
+use crate::typesys::{SymbolicSys, TypeFqn};
+use crate::SemId;
+
+/// Differences between two versions of a symbolic type system, produced by
+/// [`SymbolicSys::diff`].
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct TypeSystemDiff {
+    /// Type names present only in the new system, excluding the renamed ones.
+    pub added: Vec<TypeFqn>,
+    /// Type names present only in the old system, excluding the renamed ones.
+    pub removed: Vec<TypeFqn>,
+    /// Type names present in both systems, but pointing to different type definitions, with the
+    /// old and new semantic ids.
+    pub changed: Vec<(TypeFqn, SemId, SemId)>,
+    /// Types which definition is kept, but which are known under a different name in the new
+    /// system, with the old and new names.
+    pub renamed: Vec<(TypeFqn, TypeFqn)>,
+}
+
+impl TypeSystemDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && self.renamed.is_empty()
+    }
+
+    /// Total number of changed type names.
+    pub fn len(&self) -> usize {
+        self.added.len() + self.removed.len() + self.changed.len() + self.renamed.len()
+    }
+}
+
+/// Lists each changed type on a separate line, followed by a summary line, in the manner of
+/// `git diff --stat`.
+impl Display for TypeSystemDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, " no changes");
+        }
+        let mut lines = Vec::with_capacity(self.len());
+        lines.extend(self.added.iter().map(|fqn| (fqn.to_string(), "+ added")));
+        lines.extend(self.removed.iter().map(|fqn| (fqn.to_string(), "- removed")));
+        lines.extend(self.changed.iter().map(|(fqn, ..)| (fqn.to_string(), "~ changed")));
+        lines
+            .extend(self.renamed.iter().map(|(old, new)| (format!("{old} => {new}"), "> renamed")));
+        lines.sort();
+        let width = lines.iter().map(|(name, _)| name.len()).max().unwrap_or_default();
+        for (name, change) in lines {
+            writeln!(f, " {name:<width$} | {change}")?;
+        }
+        writeln!(
+            f,
+            " {} type(s) changed: {} added, {} removed, {} changed, {} renamed",
+            self.len(),
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len(),
+            self.renamed.len()
+        )
+    }
+}
+
+impl SymbolicSys {
+    fn named_types(&self) -> BTreeMap<&TypeFqn, SemId> {
+        self.symbols
+            .symbols
+            .iter()
+            .filter_map(|sym| sym.fqn.as_ref().map(|fqn| (fqn, sym.id)))
+            .collect()
+    }
+
+    /// Compares type names and definitions of this system with the `other` (newer) one.
+    ///
+    /// A type name which is absent in one of the systems is reported as renamed if the other
+    /// system has a new name for the type with the same semantic id; otherwise it is reported as
+    /// added or removed.
+    pub fn diff(&self, other: &SymbolicSys) -> TypeSystemDiff {
+        let old = self.named_types();
+        let new = other.named_types();
+
+        let mut diff = TypeSystemDiff::default();
+        let mut removed = BTreeMap::<SemId, Vec<&TypeFqn>>::new();
+        for (fqn, old_id) in &old {
+            match new.get(fqn) {
+                Some(new_id) if new_id != old_id => {
+                    diff.changed.push(((*fqn).clone(), *old_id, *new_id))
+                }
+                Some(_) => {}
+                None => removed.entry(*old_id).or_default().push(*fqn),
+            }
+        }
+        for (fqn, new_id) in &new {
+            if old.contains_key(fqn) {
+                continue;
+            }
+            match removed.get_mut(new_id).filter(|names| !names.is_empty()) {
+                Some(names) => diff.renamed.push((names.remove(0).clone(), (*fqn).clone())),
+                None => diff.added.push((*fqn).clone()),
+            }
+        }
+        diff.removed = removed.into_values().flatten().cloned().collect();
+        diff.removed.sort();
+        diff
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::typesys::Symbols;
+    use crate::value::test_helpers::test_system;
+    use crate::{Ty, TypeSystem};
+
+    fn sys(types: &[(Ty<SemId>, &'static str)]) -> SymbolicSys {
+        let mut sys = TypeSystem::new();
+        let mut symbols = Symbols::with([]).unwrap();
+        for (ty, fqn) in types {
+            let id = ty.sem_id_unnamed();
+            if sys.get(id).is_none() {
+                sys.insert_unchecked(id, ty.clone()).unwrap();
+            }
+            symbols.update_unchecked(id, Some(TypeFqn::from(*fqn))).unwrap();
+        }
+        SymbolicSys::new(sys, symbols)
+    }
+
+    #[test]
+    fn same() {
+        let sys = test_system();
+        let diff = sys.diff(&sys);
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), " no changes\n");
+    }
+
+    #[test]
+    fn diff() {
+        let old = sys(&[
+            (Ty::U8, "Lib.Byte"),
+            (Ty::U16, "Lib.Word"),
+            (Ty::U32, "Lib.Old"),
+            (Ty::I8, "Lib.Removed"),
+        ]);
+        let new = sys(&[
+            (Ty::U8, "Lib.Byte"),
+            (Ty::U64, "Lib.Word"),
+            (Ty::U32, "Other.New"),
+            (Ty::I16, "Lib.Added"),
+        ]);
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.added, vec![TypeFqn::from("Lib.Added")]);
+        assert_eq!(diff.removed, vec![TypeFqn::from("Lib.Removed")]);
+        assert_eq!(diff.changed, vec![(
+            TypeFqn::from("Lib.Word"),
+            Ty::<SemId>::U16.sem_id_unnamed(),
+            Ty::<SemId>::U64.sem_id_unnamed()
+        )]);
+        assert_eq!(diff.renamed, vec![(TypeFqn::from("Lib.Old"), TypeFqn::from("Other.New"))]);
+        assert_eq!(
+            diff.to_string(),
+            " Lib.Added            | + added
+ Lib.Old => Other.New | > renamed
+ Lib.Removed          | - removed
+ Lib.Word             | ~ changed
+ 4 type(s) changed: 1 added, 1 removed, 1 changed, 1 renamed
+"
+        );
+
+        let reverse = new.diff(&old);
+        assert_eq!(reverse.added, diff.removed);
+        assert_eq!(reverse.removed, diff.added);
+        assert_eq!(reverse.renamed, vec![(TypeFqn::from("Other.New"), TypeFqn::from("Lib.Old"))]);
+    }
+
+    #[test]
+    fn renamed() {
+        let old = test_system();
+        let mut new = old.clone();
+        new.rename_type("TestLib.Precision", "App.Precision").unwrap();
+        new.remove_symbol("TestLib.Precision").unwrap();
+        new.remove_symbol("TestLib.Nominal").unwrap();
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.renamed, vec![(
+            TypeFqn::from("TestLib.Precision"),
+            TypeFqn::from("App.Precision")
+        )]);
+        assert_eq!(diff.removed, vec![TypeFqn::from("TestLib.Nominal")]);
+        assert!(diff.added.is_empty());
+        assert!(diff.changed.is_empty());
+    }
+}
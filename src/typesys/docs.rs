@@ -0,0 +1,434 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generation of HTML and Markdown documentation for symbolic type systems.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use amplify::hex::ToHex;
+use encoding::{LibName, TypeName};
+
+use crate::typesys::{SymbolicSys, TypeFqn};
+use crate::{SemId, Translate, Ty};
+
+/// Number of semantic id bytes shown in the documentation.
+const SEM_ID_PREFIX: usize = 8;
+
+const CSS: &str = "\
+body { font-family: sans-serif; max-width: 60em; margin: 2em auto; padding: 0 1em; color: #222; }
+code { font-family: monospace; }
+nav ul { columns: 3; }
+section.type { border-top: 1px solid #ccc; margin-top: 1.5em; }
+dl { display: grid; grid-template-columns: max-content auto; gap: 0.2em 1em; }
+dt { font-weight: bold; }
+dd { margin: 0; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 0.2em 0.6em; text-align: left; }
+a { color: #05a; text-decoration: none; }
+";
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Format {
+    Html,
+    Markdown,
+}
+
+/// Piece of a type expression.
+enum Part {
+    Text(String),
+    Link(TypeFqn),
+}
+
+struct Table {
+    head: Vec<&'static str>,
+    rows: Vec<Vec<Vec<Part>>>,
+}
+
+fn anchor(fqn: &TypeFqn) -> String { fqn.to_string().to_lowercase().replace('.', "-") }
+
+impl Format {
+    fn escape(self, s: &str) -> String {
+        let mut escaped = String::with_capacity(s.len());
+        for c in s.chars() {
+            match (self, c) {
+                (_, '\n') => escaped.push(' '),
+                (Format::Html, '&') => escaped.push_str("&amp;"),
+                (Format::Html, '<') => escaped.push_str("&lt;"),
+                (Format::Html, '>') => escaped.push_str("&gt;"),
+                (Format::Html, '"') => escaped.push_str("&quot;"),
+                (Format::Markdown, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|') => {
+                    escaped.push('\\');
+                    escaped.push(c);
+                }
+                _ => escaped.push(c),
+            }
+        }
+        escaped
+    }
+
+    fn render(self, parts: &[Part]) -> String {
+        parts
+            .iter()
+            .map(|part| match (self, part) {
+                (_, Part::Text(s)) => self.escape(s),
+                (Format::Html, Part::Link(fqn)) => {
+                    format!("<a href=\"#{}\">{fqn}</a>", anchor(fqn))
+                }
+                (Format::Markdown, Part::Link(fqn)) => {
+                    format!("[{}](#{})", self.escape(&fqn.to_string()), anchor(fqn))
+                }
+            })
+            .collect()
+    }
+}
+
+fn cls_descr(ty: &Ty<SemId>) -> &'static str {
+    match ty {
+        Ty::Primitive(_) => "primitive",
+        Ty::UnicodeChar => "unicode character",
+        Ty::Enum(_) => "enumeration",
+        Ty::Union(_) if ty.is_option() => "optional value",
+        Ty::Union(_) => "union",
+        Ty::Struct(_) => "structure",
+        Ty::Tuple(_) => "tuple",
+        Ty::Array(_, _) => "fixed-size array",
+        Ty::List(_, _) => "list",
+        Ty::Set(_, _) => "set",
+        Ty::Map(_, _, _) => "map",
+    }
+}
+
+fn short_id(sem_id: SemId) -> String { sem_id[..SEM_ID_PREFIX].to_hex() }
+
+impl SymbolicSys {
+    fn doc_types(&self) -> BTreeMap<&LibName, BTreeMap<&TypeName, (&TypeFqn, SemId)>> {
+        let mut libs = BTreeMap::<_, BTreeMap<_, _>>::new();
+        for sym in &self.symbols.symbols {
+            if let Some(fqn) = &sym.fqn {
+                libs.entry(&fqn.lib).or_default().insert(&fqn.name, (fqn, sym.id));
+            }
+        }
+        libs
+    }
+
+    fn doc_ref(&self, sem_id: SemId, parts: &mut Vec<Part>) {
+        if let Some(fqn) = self.lookup(sem_id) {
+            parts.push(Part::Link(fqn.clone()));
+            return;
+        }
+        match self.as_types().get(sem_id) {
+            Some(ty) => self.doc_expr(ty, parts),
+            None => parts.push(Part::Text(sem_id.to_string())),
+        }
+    }
+
+    fn doc_expr(&self, ty: &Ty<SemId>, parts: &mut Vec<Part>) {
+        match ty {
+            Ty::Primitive(prim) => parts.push(Part::Text(prim.to_string())),
+            Ty::UnicodeChar => parts.push(Part::Text(s!("Unicode"))),
+            Ty::Union(_) if ty.is_option() => {
+                self.doc_ref(*ty.as_some().expect("optional"), parts);
+                parts.push(Part::Text(s!("?")));
+            }
+            Ty::Array(inner, len) => {
+                parts.push(Part::Text(s!("[")));
+                self.doc_ref(*inner, parts);
+                parts.push(Part::Text(format!(" ^ {len}]")));
+            }
+            Ty::List(inner, sizing) => {
+                parts.push(Part::Text(s!("[")));
+                self.doc_ref(*inner, parts);
+                parts.push(Part::Text(format!("{sizing}]")));
+            }
+            Ty::Set(inner, sizing) => {
+                parts.push(Part::Text(s!("{")));
+                self.doc_ref(*inner, parts);
+                parts.push(Part::Text(format!("{sizing}}}")));
+            }
+            Ty::Map(key, val, sizing) => {
+                parts.push(Part::Text(s!("{")));
+                self.doc_ref(*key, parts);
+                parts.push(Part::Text(format!(" ->{sizing} ")));
+                self.doc_ref(*val, parts);
+                parts.push(Part::Text(s!("}")));
+            }
+            Ty::Enum(_) | Ty::Union(_) | Ty::Struct(_) | Ty::Tuple(_) => {
+                let ty = ty.clone().translate(&mut (), self).expect("type system inconsistency");
+                parts.push(Part::Text(ty.to_string()));
+            }
+        }
+    }
+
+    fn doc_table(&self, fqn: &TypeFqn, ty: &Ty<SemId>) -> Option<Table> {
+        let expr = |sem_id: SemId| {
+            let mut parts = vec![];
+            self.doc_ref(sem_id, &mut parts);
+            parts
+        };
+        let text = |s: String| vec![Part::Text(s)];
+        let table = match ty {
+            Ty::Struct(fields) => {
+                let docs = fields
+                    .iter()
+                    .map(|field| self.field_doc(fqn.clone(), &field.name))
+                    .collect::<Vec<_>>();
+                let documented = docs.iter().any(Option::is_some);
+                let mut head = vec!["Field", "Type"];
+                if documented {
+                    head.push("Description");
+                }
+                let rows = fields
+                    .iter()
+                    .zip(docs)
+                    .map(|(field, doc)| {
+                        let mut row = vec![text(field.name.to_string()), expr(field.ty)];
+                        if documented {
+                            row.push(text(doc.map(ToString::to_string).unwrap_or_default()));
+                        }
+                        row
+                    })
+                    .collect();
+                Table { head, rows }
+            }
+            Ty::Tuple(fields) => Table {
+                head: vec!["#", "Type"],
+                rows: fields
+                    .iter()
+                    .enumerate()
+                    .map(|(pos, sem_id)| vec![text(pos.to_string()), expr(*sem_id)])
+                    .collect(),
+            },
+            Ty::Enum(variants) => Table {
+                head: vec!["Variant", "Tag"],
+                rows: variants
+                    .iter()
+                    .map(|var| vec![text(var.name.to_string()), text(var.tag.to_string())])
+                    .collect(),
+            },
+            Ty::Union(variants) if !ty.is_option() => Table {
+                head: vec!["Variant", "Tag", "Type"],
+                rows: variants
+                    .iter()
+                    .map(|(var, sem_id)| {
+                        vec![text(var.name.to_string()), text(var.tag.to_string()), expr(*sem_id)]
+                    })
+                    .collect(),
+            },
+            _ => return None,
+        };
+        Some(table)
+    }
+
+    /// Generates self-contained HTML page documenting all named types of the system, grouped by
+    /// their libraries. The page uses inline CSS and requires neither external resources nor
+    /// JavaScript.
+    pub fn to_html_docs(&self, title: &str) -> String {
+        let f = Format::Html;
+        let title = f.escape(title);
+        let libs = self.doc_types();
+
+        let mut html = s!("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n");
+        html.push_str("<meta charset=\"utf-8\">\n");
+        writeln!(html, "<title>{title}</title>").ok();
+        writeln!(html, "<style>\n{CSS}</style>\n</head>\n<body>").ok();
+        writeln!(html, "<h1>{title}</h1>").ok();
+        writeln!(html, "<p>Type system <code>{}</code></p>", self.id()).ok();
+
+        html.push_str("<nav>\n<ul>\n");
+        for types in libs.values() {
+            for (fqn, _) in types.values() {
+                writeln!(html, "<li><a href=\"#{}\">{fqn}</a></li>", anchor(fqn)).ok();
+            }
+        }
+        html.push_str("</ul>\n</nav>\n");
+
+        for (lib, types) in libs {
+            writeln!(html, "<h2>Library {lib}</h2>").ok();
+            for (fqn, sem_id) in types.into_values() {
+                let Some(ty) = self.as_types().get(sem_id) else {
+                    continue;
+                };
+                writeln!(html, "<section class=\"type\" id=\"{}\">", anchor(fqn)).ok();
+                writeln!(html, "<h3>{fqn}</h3>").ok();
+                if let Some(doc) = self.type_doc(fqn.clone()) {
+                    writeln!(html, "<p>{}</p>", f.escape(doc)).ok();
+                }
+                html.push_str("<dl>\n");
+                writeln!(
+                    html,
+                    "<dt>Semantic id</dt><dd><code title=\"{sem_id}\">{}</code></dd>",
+                    short_id(sem_id)
+                )
+                .ok();
+                writeln!(html, "<dt>Class</dt><dd>{}</dd>", cls_descr(ty)).ok();
+                match self.doc_table(fqn, ty) {
+                    None => {
+                        let mut parts = vec![];
+                        self.doc_expr(ty, &mut parts);
+                        writeln!(
+                            html,
+                            "<dt>Definition</dt><dd><code>{}</code></dd>",
+                            f.render(&parts)
+                        )
+                        .ok();
+                        html.push_str("</dl>\n");
+                    }
+                    Some(table) => {
+                        html.push_str("</dl>\n<table>\n<tr>");
+                        for col in table.head {
+                            write!(html, "<th>{col}</th>").ok();
+                        }
+                        html.push_str("</tr>\n");
+                        for row in table.rows {
+                            html.push_str("<tr>");
+                            for cell in row {
+                                write!(html, "<td>{}</td>", f.render(&cell)).ok();
+                            }
+                            html.push_str("</tr>\n");
+                        }
+                        html.push_str("</table>\n");
+                    }
+                }
+                html.push_str("</section>\n");
+            }
+        }
+
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    /// Generates GitHub-flavored Markdown documenting all named types of the system, grouped by
+    /// their libraries.
+    pub fn to_markdown_docs(&self) -> String {
+        let f = Format::Markdown;
+        let libs = self.doc_types();
+
+        let mut md = s!("# Type system\n\n");
+        writeln!(md, "Type system id: `{}`\n", self.id()).ok();
+
+        for types in libs.values() {
+            for (fqn, _) in types.values() {
+                writeln!(md, "- [{}](#{})", f.escape(&fqn.to_string()), anchor(fqn)).ok();
+            }
+        }
+
+        for (lib, types) in libs {
+            writeln!(md, "\n## Library {lib}").ok();
+            for (fqn, sem_id) in types.into_values() {
+                let Some(ty) = self.as_types().get(sem_id) else {
+                    continue;
+                };
+                writeln!(md, "\n<a id=\"{}\"></a>\n", anchor(fqn)).ok();
+                writeln!(md, "### {}\n", f.escape(&fqn.to_string())).ok();
+                if let Some(doc) = self.type_doc(fqn.clone()) {
+                    writeln!(md, "{}\n", f.escape(doc)).ok();
+                }
+                writeln!(md, "- Semantic id: `{}`", short_id(sem_id)).ok();
+                writeln!(md, "- Class: {}", cls_descr(ty)).ok();
+                match self.doc_table(fqn, ty) {
+                    None => {
+                        let mut parts = vec![];
+                        self.doc_expr(ty, &mut parts);
+                        writeln!(md, "- Definition: {}", f.render(&parts)).ok();
+                    }
+                    Some(table) => {
+                        writeln!(md, "\n| {} |", table.head.join(" | ")).ok();
+                        writeln!(md, "|{}", "---|".repeat(table.head.len())).ok();
+                        for row in table.rows {
+                            let cells = row.iter().map(|cell| f.render(cell)).collect::<Vec<_>>();
+                            writeln!(md, "| {} |", cells.join(" | ")).ok();
+                        }
+                    }
+                }
+            }
+        }
+        md
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, fs};
+
+    use crate::stl::{std_stl, strict_types_stl};
+    use crate::value::test_helpers::{test_system, Nominal};
+    use crate::{LibBuilder, SystemBuilder};
+
+    /// Compares `output` with the snapshot file, overwriting the snapshot instead if
+    /// `UPDATE_SNAPSHOTS` environment variable is set.
+    fn snapshot(name: &str, output: &str) {
+        let path = format!("{}/src/typesys/snapshots/{name}", env!("CARGO_MANIFEST_DIR"));
+        if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            fs::write(&path, output).unwrap();
+        }
+        let expected = fs::read_to_string(&path).unwrap();
+        assert_eq!(output, expected, "output differs from {path} snapshot");
+    }
+
+    #[test]
+    fn html() {
+        let html = test_system().to_html_docs("Test <system>");
+        assert!(html.contains("<title>Test &lt;system&gt;</title>"));
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("http"));
+        snapshot("test_system.html", &html);
+    }
+
+    #[test]
+    fn markdown() { snapshot("test_system.md", &test_system().to_markdown_docs()); }
+
+    #[test]
+    fn documented() {
+        let std = std_stl();
+        let st = strict_types_stl();
+        let lib = LibBuilder::new(libname!("TestLib"), [std.to_dependency(), st.to_dependency()])
+            .transpile_documented::<Nominal>("Nominal parameters of an asset", &[(
+                "precision",
+                "Number of <decimal> digits",
+            )])
+            .compile()
+            .unwrap();
+        let sys = SystemBuilder::new()
+            .import(lib)
+            .unwrap()
+            .import(std)
+            .unwrap()
+            .import(st)
+            .unwrap()
+            .finalize()
+            .unwrap();
+
+        let html = sys.to_html_docs("Test");
+        assert!(html.contains("<h3>TestLib.Nominal</h3>\n<p>Nominal parameters of an asset</p>\n"));
+        assert!(html.contains("<tr><th>Field</th><th>Type</th><th>Description</th></tr>\n"));
+        assert!(html.contains("<td>Number of &lt;decimal&gt; digits</td>"));
+
+        let md = sys.to_markdown_docs();
+        assert!(md.contains("### TestLib.Nominal\n\nNominal parameters of an asset\n"));
+        assert!(md.contains(
+            "| precision | [TestLib.Precision](#testlib-precision) | Number of \\<decimal\\> \
+             digits |\n"
+        ));
+        assert!(md.contains("| ticker | [StrictTypes.Ident](#stricttypes-ident) |  |\n"));
+    }
+}
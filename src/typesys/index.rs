@@ -0,0 +1,206 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Persistence of type systems in a directory, together with an index allowing to read
+//! individual types without loading the whole system.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use amplify::ByteArray;
+use encoding::{
+    DecodeError, DeserializeError, StreamReader, StrictDecode, StrictDeserialize, StrictEncode,
+    StrictReader, StrictWriter,
+};
+
+use crate::typesys::TypeSysId;
+use crate::{SemId, Ty, TypeSystem};
+
+/// Name of the file with the strict-encoded type system inside the directory used by
+/// [`TypeSystem::save_indexed`].
+pub const TYPESYS_FILE: &str = "typesys.bin";
+/// Name of the index file inside the directory used by [`TypeSystem::save_indexed`].
+pub const TYPESYS_INDEX_FILE: &str = "index.json";
+
+#[derive(Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum LoadError {
+    #[display(inner)]
+    #[from]
+    Io(io::Error),
+
+    /// invalid type system index file. Details: {0}
+    #[from]
+    Index(serde_json::Error),
+
+    #[display(inner)]
+    #[from]
+    #[from(DecodeError)]
+    Deserialize(DeserializeError),
+
+    /// type system id {data} doesn't match id {index} recorded in the index file.
+    IdMismatch { index: TypeSysId, data: TypeSysId },
+
+    /// type system file doesn't match the index file; probably it was replaced without updating
+    /// the index.
+    DataMismatch,
+}
+
+/// Index of types in a strict-encoded type system file.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+struct SysIndex {
+    id: TypeSysId,
+    /// Length of the type system file.
+    len: u64,
+    /// Byte offsets of type definitions in the type system file.
+    offsets: BTreeMap<SemId, u64>,
+}
+
+impl SysIndex {
+    fn load(dir: &Path) -> Result<Self, LoadError> {
+        let file = File::open(dir.join(TYPESYS_INDEX_FILE))?;
+        Ok(serde_json::from_reader(BufReader::new(file))?)
+    }
+}
+
+fn serialize(val: &impl StrictEncode) -> io::Result<Vec<u8>> {
+    let writer = StrictWriter::in_memory::<{ usize::MAX }>();
+    Ok(val.strict_encode(writer)?.unbox().unconfine())
+}
+
+impl TypeSystem {
+    /// Saves the strict-encoded type system into the `dir` directory (creating it, if needed),
+    /// alongside an index file with byte offsets of each type definition.
+    ///
+    /// The saved system can be loaded with [`Self::load_indexed`], and individual types can be
+    /// read with [`Self::find_indexed`].
+    pub fn save_indexed(&self, dir: &Path) -> Result<(), io::Error> {
+        let data = serialize(self)?;
+
+        let mut lens = Vec::with_capacity(self.len());
+        for ty in self.values() {
+            lens.push(serialize(ty)?.len() as u64);
+        }
+        // The system is encoded as the number of types followed by the sequence of semantic ids,
+        // each of which is followed by the type definition.
+        let mut offset = data.len() as u64 - lens.iter().map(|len| 32 + len).sum::<u64>();
+        let mut offsets = BTreeMap::new();
+        for (sem_id, len) in self.keys().zip(lens) {
+            offsets.insert(*sem_id, offset + 32);
+            offset += 32 + len;
+        }
+        let index = SysIndex {
+            id: self.id(),
+            len: data.len() as u64,
+            offsets,
+        };
+
+        fs::create_dir_all(dir)?;
+        fs::write(dir.join(TYPESYS_FILE), data)?;
+        let index = serde_json::to_vec(&index).map_err(io::Error::from)?;
+        fs::write(dir.join(TYPESYS_INDEX_FILE), index)
+    }
+
+    /// Loads type system saved with [`Self::save_indexed`], checking that it matches the index.
+    pub fn load_indexed(dir: &Path) -> Result<TypeSystem, LoadError> {
+        let index = SysIndex::load(dir)?;
+        let sys =
+            TypeSystem::strict_deserialize_from_file::<{ usize::MAX }>(dir.join(TYPESYS_FILE))?;
+        if sys.id() != index.id {
+            return Err(LoadError::IdMismatch {
+                index: index.id,
+                data: sys.id(),
+            });
+        }
+        Ok(sys)
+    }
+
+    /// Reads a single type definition from a type system saved with [`Self::save_indexed`],
+    /// without loading the rest of the system. Returns `None` if the index doesn't contain the
+    /// type.
+    ///
+    /// Since the system is not loaded, its id can't be checked against the index. Instead, the
+    /// type system file must have the length recorded in the index and the semantic id preceding
+    /// the type definition must match `sem_id`; otherwise [`LoadError::DataMismatch`] is
+    /// returned.
+    pub fn find_indexed(dir: &Path, sem_id: SemId) -> Result<Option<Ty<SemId>>, LoadError> {
+        let index = SysIndex::load(dir)?;
+        let Some(offset) = index.offsets.get(&sem_id) else {
+            return Ok(None);
+        };
+        let mut file = File::open(dir.join(TYPESYS_FILE))?;
+        if file.metadata()?.len() != index.len {
+            return Err(LoadError::DataMismatch);
+        }
+        let id_offset = offset.checked_sub(32).ok_or(LoadError::DataMismatch)?;
+        file.seek(SeekFrom::Start(id_offset))?;
+        let mut id = [0u8; 32];
+        file.read_exact(&mut id)?;
+        if SemId::from_byte_array(id) != sem_id {
+            return Err(LoadError::DataMismatch);
+        }
+        let mut reader =
+            StrictReader::with(StreamReader::new::<{ usize::MAX }>(BufReader::new(file)));
+        let ty = Ty::<SemId>::strict_decode(&mut reader).map_err(DeserializeError::from)?;
+        Ok(Some(ty))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+
+    use super::*;
+    use crate::value::test_helpers::test_system;
+
+    #[test]
+    fn save_load() {
+        let sys = test_system().into_type_system();
+        let dir = env::temp_dir().join(format!("strict-types-index-{}", std::process::id()));
+        sys.save_indexed(&dir).unwrap();
+
+        let loaded = TypeSystem::load_indexed(&dir).unwrap();
+        assert_eq!(loaded, sys);
+
+        for (sem_id, ty) in sys.as_unconfined() {
+            assert_eq!(TypeSystem::find_indexed(&dir, *sem_id).unwrap().as_ref(), Some(ty));
+        }
+        let absent = Ty::<SemId>::I128.sem_id_unnamed();
+        assert!(sys.get(absent).is_none());
+        assert_eq!(TypeSystem::find_indexed(&dir, absent).unwrap(), None);
+
+        let (sem_id, _) = sys.as_unconfined().first_key_value().unwrap();
+        let len = fs::metadata(dir.join(TYPESYS_FILE)).unwrap().len();
+        fs::write(dir.join(TYPESYS_FILE), vec![0u8; len as usize]).unwrap();
+        assert!(matches!(TypeSystem::find_indexed(&dir, *sem_id), Err(LoadError::DataMismatch)));
+
+        let other = TypeSystem::new();
+        fs::write(dir.join(TYPESYS_FILE), serialize(&other).unwrap()).unwrap();
+        assert!(matches!(TypeSystem::load_indexed(&dir), Err(LoadError::IdMismatch { .. })));
+        assert!(matches!(TypeSystem::find_indexed(&dir, *sem_id), Err(LoadError::DataMismatch)));
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(TypeSystem::load_indexed(&dir), Err(LoadError::Io(_))));
+    }
+}
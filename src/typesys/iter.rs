@@ -85,6 +85,7 @@ impl Display for TypeTree<'_> {
 #[derive(Clone, Eq, PartialEq, Debug)]
 #[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
 #[strict_type(lib = STRICT_TYPES_LIB, tags = custom)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
 pub enum NestedCase {
     #[strict_type(tag = 0x0)]
     NewType(Option<TypeFqn>),
@@ -115,6 +116,7 @@ pub struct NestedInfo<'sys> {
 #[derive(Clone, Eq, PartialEq, Debug)]
 #[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
 #[strict_type(lib = STRICT_TYPES_LIB)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TypeInfo {
     pub depth: u32,
     pub ty: Ty<SemId>,
@@ -175,7 +177,7 @@ impl<'sys> Iterator for TypeTreeIter<'sys> {
                     let rest = self.sys.get(fields[1]);
                     if let Some((first, Ty::List(rest, sizing))) = first.zip(rest) {
                         let other = self.sys.get(*rest);
-                        if first.is_char_enum() && other.map(Ty::is_char_enum).unwrap_or_default() {
+                        if first.is_ascii_char() && other.map(Ty::is_ascii_char).unwrap_or_default() {
                             let first = self.sys.symbols.lookup(fields[0]);
                             let rest = self.sys.symbols.lookup(*rest);
                             let mut sizing = *sizing;
@@ -189,7 +191,7 @@ impl<'sys> Iterator for TypeTreeIter<'sys> {
                 }
             } else if let Ty::List(inner_id, _) = ty {
                 let inner_ty = self.sys.get(*inner_id).expect("incomplete type system");
-                if inner_ty.is_char_enum() {
+                if inner_ty.is_ascii_char() {
                     let fqn = self.sys.symbols.lookup(*inner_id);
                     nested.push(NestedCase::AsciiStr(fqn.cloned()));
                 } else if inner_ty.is_byte() {
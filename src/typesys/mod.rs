@@ -25,9 +25,18 @@ mod translate;
 mod id;
 mod symbols;
 mod iter;
+mod stats;
+mod docs;
+mod diff;
+#[cfg(feature = "serde")]
+mod index;
 
+pub use diff::TypeSystemDiff;
 pub use id::TypeSysId;
+#[cfg(feature = "serde")]
+pub use index::{LoadError, TYPESYS_FILE, TYPESYS_INDEX_FILE};
 pub use iter::{NestedCase, TypeInfo, TypeTree, TypeTreeIter};
-pub use symbols::{SymbolicSys, Symbols};
-pub use translate::{Error, SystemBuilder, TypeSymbol};
-pub use type_sys::{SymTy, TypeFqn, TypeSystem, UnknownType};
+pub use stats::{SizeHistogram, SymbolicSysStats, TypeSystemStats};
+pub use symbols::{RemoveError, RenameError, SymbolicSys, Symbols};
+pub use translate::{ConflictStrategy, Error, SystemBuilder, TypeSymbol};
+pub use type_sys::{MergeError, SymTy, TypeFqn, TypeSystem, UnknownType};
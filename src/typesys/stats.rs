@@ -0,0 +1,248 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Structural metrics of type systems.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Display, Formatter};
+
+use crate::typesys::{SymbolicSys, Symbols};
+use crate::{Cls, SemId, Ty, TypeSystem};
+
+/// Number of types by the maximal size of their strict-encoded values.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct SizeHistogram {
+    /// Types with zero-sized values.
+    pub zero: usize,
+    /// Types with values of 1 to 8 bytes.
+    pub up_to_8: usize,
+    /// Types with values of 9 to 64 bytes.
+    pub up_to_64: usize,
+    /// Types with values of 65 to 512 bytes.
+    pub up_to_512: usize,
+    /// Types with values of 513 to 4096 bytes.
+    pub up_to_4096: usize,
+    /// Types with values bounded by more than 4096 bytes.
+    pub larger: usize,
+    /// Types with values of unbounded size.
+    pub unbounded: usize,
+}
+
+impl SizeHistogram {
+    fn count(&mut self, max: Option<u64>) {
+        let bucket = match max {
+            Some(0) => &mut self.zero,
+            Some(1..=8) => &mut self.up_to_8,
+            Some(9..=64) => &mut self.up_to_64,
+            Some(65..=512) => &mut self.up_to_512,
+            Some(513..=4096) => &mut self.up_to_4096,
+            Some(_) => &mut self.larger,
+            None => &mut self.unbounded,
+        };
+        *bucket += 1;
+    }
+}
+
+/// Structural metrics of a [`TypeSystem`].
+#[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct TypeSystemStats {
+    pub type_count: usize,
+    /// Number of types of each class; classes with no types are omitted.
+    pub cls_count: BTreeMap<Cls, usize>,
+    /// Total number of named fields in all structure types.
+    pub field_count: usize,
+    /// Maximal nesting depth, following references to other types.
+    pub max_depth: usize,
+    pub avg_depth: f64,
+    /// Number of types referencing themselves, directly or via other types.
+    pub recursive_count: usize,
+    pub size_histogram: SizeHistogram,
+}
+
+impl Display for TypeSystemStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "types: {}", self.type_count)?;
+        for (cls, count) in &self.cls_count {
+            writeln!(f, "  {cls}: {count}")?;
+        }
+        writeln!(f, "struct fields: {}", self.field_count)?;
+        writeln!(f, "nesting depth: max {}, avg {:.2}", self.max_depth, self.avg_depth)?;
+        writeln!(f, "recursive types: {}", self.recursive_count)?;
+        let h = &self.size_histogram;
+        writeln!(f, "value sizes:")?;
+        writeln!(f, "  0 bytes: {}", h.zero)?;
+        writeln!(f, "  1-8 bytes: {}", h.up_to_8)?;
+        writeln!(f, "  9-64 bytes: {}", h.up_to_64)?;
+        writeln!(f, "  65-512 bytes: {}", h.up_to_512)?;
+        writeln!(f, "  513-4096 bytes: {}", h.up_to_4096)?;
+        writeln!(f, "  larger: {}", h.larger)?;
+        writeln!(f, "  unbounded: {}", h.unbounded)
+    }
+}
+
+/// Structural metrics of a [`SymbolicSys`], extending [`TypeSystemStats`] with the symbol
+/// information.
+#[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct SymbolicSysStats {
+    pub types: TypeSystemStats,
+    /// Number of type names.
+    pub symbol_count: usize,
+    pub lib_count: usize,
+}
+
+impl Display for SymbolicSysStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "libraries: {}", self.lib_count)?;
+        writeln!(f, "symbols: {}", self.symbol_count)?;
+        Display::fmt(&self.types, f)
+    }
+}
+
+struct Depths<'sys> {
+    types: &'sys TypeSystem,
+    known: BTreeMap<SemId, usize>,
+    visiting: BTreeSet<SemId>,
+}
+
+impl Depths<'_> {
+    fn depth(&mut self, id: SemId) -> usize {
+        if let Some(depth) = self.known.get(&id) {
+            return *depth;
+        }
+        let Some(ty) = self.types.get(id) else {
+            return 0;
+        };
+        // Breaking potential reference cycles
+        if !self.visiting.insert(id) {
+            return 0;
+        }
+        let depth = match ty {
+            Ty::Primitive(_) | Ty::UnicodeChar | Ty::Enum(_) => 0,
+            _ => 1 + ty.iter().map(|(id, _)| self.depth(*id)).max().unwrap_or_default(),
+        };
+        self.visiting.remove(&id);
+        self.known.insert(id, depth);
+        depth
+    }
+}
+
+impl TypeSystem {
+    fn is_recursive(&self, id: SemId) -> bool {
+        let mut seen = BTreeSet::new();
+        let mut queue = vec![id];
+        while let Some(next) = queue.pop() {
+            let Some(ty) = self.get(next) else { continue };
+            for (child, _) in ty.iter() {
+                if *child == id {
+                    return true;
+                }
+                if seen.insert(*child) {
+                    queue.push(*child);
+                }
+            }
+        }
+        false
+    }
+
+    /// Computes structural metrics of the type system.
+    pub fn statistics(&self) -> TypeSystemStats {
+        let symbols = Symbols::with([]).expect("empty symbol table");
+        SymbolicSys::new(self.clone(), symbols).statistics().types
+    }
+}
+
+impl SymbolicSys {
+    /// Computes structural metrics of the type system and its symbols.
+    pub fn statistics(&self) -> SymbolicSysStats {
+        let types = self.as_types();
+        let mut stats = TypeSystemStats {
+            type_count: types.len(),
+            ..default!()
+        };
+        let mut depths = Depths {
+            types,
+            known: empty!(),
+            visiting: empty!(),
+        };
+        let mut total_depth = 0;
+        for (id, ty) in types.iter() {
+            *stats.cls_count.entry(ty.cls()).or_default() += 1;
+            if let Ty::Struct(fields) = ty {
+                stats.field_count += fields.len();
+            }
+            let depth = depths.depth(*id);
+            total_depth += depth;
+            stats.max_depth = stats.max_depth.max(depth);
+            if types.is_recursive(*id) {
+                stats.recursive_count += 1;
+            }
+            if let Some((_, max)) = self.encoded_size_range(*id) {
+                stats.size_histogram.count(max);
+            }
+        }
+        if stats.type_count > 0 {
+            stats.avg_depth = total_depth as f64 / stats.type_count as f64;
+        }
+
+        SymbolicSysStats {
+            types: stats,
+            symbol_count: self.symbols.symbols.iter().filter(|sym| sym.fqn.is_some()).count(),
+            lib_count: self.libs().len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::value::test_helpers::test_system;
+
+    #[test]
+    fn statistics() {
+        let sys = test_system();
+        let stats = sys.statistics();
+        assert_eq!(stats.lib_count, 3);
+        assert_eq!(stats.types.type_count, sys.as_types().len());
+        assert_eq!(stats.types.cls_count.values().sum::<usize>(), stats.types.type_count);
+        assert_eq!(stats.types.recursive_count, 0);
+
+        let nominal = sys.as_types().subset([sys.to_sem_id("TestLib.Nominal").unwrap()]).unwrap();
+        let stats = nominal.statistics();
+        assert_eq!(stats.type_count, 9);
+        assert_eq!(stats.cls_count[&Cls::Struct], 1);
+        assert_eq!(stats.cls_count[&Cls::Enum], 3);
+        assert_eq!(stats.cls_count.get(&Cls::Union), None);
+        assert_eq!(stats.field_count, 3);
+        assert_eq!(stats.max_depth, 4);
+        assert_eq!(stats.recursive_count, 0);
+        assert_eq!(stats.size_histogram, SizeHistogram {
+            up_to_8: 4,
+            up_to_64: 1,
+            up_to_512: 4,
+            ..default!()
+        });
+        assert!(stats.to_string().starts_with("types: 9\n  unicode: 1\n  enum: 3\n"));
+    }
+}
@@ -20,16 +20,22 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::BTreeMap;
-use std::fmt::{self, Display, Formatter};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Display, Formatter, Write};
 use std::ops::Index;
+use std::sync::OnceLock;
 
-use amplify::confinement::{self, MediumOrdSet, SmallOrdSet};
-use encoding::{StrictDeserialize, StrictSerialize, STRICT_TYPES_LIB};
+use amplify::confinement::{self, Confined, MediumOrdSet, SmallOrdSet};
+use encoding::{
+    FieldName, LibName, StrictDeserialize, StrictDumb, StrictSerialize, TypeName, STRICT_TYPES_LIB,
+};
 
-use crate::typesys::{translate, SymTy, TypeFqn, TypeSymbol, TypeSysId, TypeTree};
+use crate::layout::vesper::TypeVesper;
+use crate::layout::{MemoryLayout, SizeRange};
+use crate::typelib::Doc;
+use crate::typesys::{translate, MergeError, SymTy, TypeFqn, TypeSymbol, TypeSysId, TypeTree};
 use crate::typify::TypeSpec;
-use crate::{Dependency, SemId, Translate, Ty, TypeSystem};
+use crate::{Cls, Dependency, SemId, SystemBuilder, Translate, Ty, TypeLib, TypeLibId, TypeSystem};
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 #[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
@@ -37,7 +43,7 @@ use crate::{Dependency, SemId, Translate, Ty, TypeSystem};
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Symbols {
     libs: SmallOrdSet<Dependency>,
-    symbols: MediumOrdSet<TypeSymbol>,
+    pub(super) symbols: MediumOrdSet<TypeSymbol>,
 }
 
 impl StrictSerialize for Symbols {}
@@ -80,6 +86,91 @@ impl Symbols {
     pub fn lookup(&self, sem_id: SemId) -> Option<&TypeFqn> {
         self.symbols.iter().find(|sym| sym.id == sem_id).and_then(|sym| sym.fqn.as_ref())
     }
+
+    /// Adds a new name for a type already known under the `old` name.
+    pub fn alias(
+        &mut self,
+        old: impl Into<TypeFqn>,
+        new: impl Into<TypeFqn>,
+    ) -> Result<(), RenameError> {
+        let (old, new) = (old.into(), new.into());
+        let sem_id = *self.get(old.clone()).ok_or(RenameError::OldNotFound(old))?;
+        if self.get(new.clone()).is_some() {
+            return Err(RenameError::NewAlreadyExists(new));
+        }
+        self.symbols.push(TypeSymbol::with(sem_id, new))?;
+        Ok(())
+    }
+
+    /// Finds types with names matching a glob `pattern` in the `LibName.TypeName` form, where
+    /// `*` matches any substring and `?` matches any single character.
+    ///
+    /// Library and type names are matched independently, so `TestLib.*` finds all types of a
+    /// library and `*.Nominal` finds types with the same name in all libraries. A pattern without
+    /// a library name matches types from any library. The result is ordered by type names.
+    pub fn get_glob(&self, pattern: &str) -> Vec<(&TypeFqn, &SemId)> {
+        let (lib, name) = pattern.split_once('.').unwrap_or(("*", pattern));
+        let lib = lib.chars().collect::<Vec<_>>();
+        let name = name.chars().collect::<Vec<_>>();
+        self.get_matching(|fqn| {
+            glob_match(&lib, &fqn.lib.to_string().chars().collect::<Vec<_>>())
+                && glob_match(&name, &fqn.name.to_string().chars().collect::<Vec<_>>())
+        })
+    }
+
+    /// Finds types which fully qualified `LibName.TypeName` name matches a regular expression.
+    /// The result is ordered by type names.
+    #[cfg(feature = "regex")]
+    pub fn get_regex(&self, re: &regex::Regex) -> Vec<(&TypeFqn, &SemId)> {
+        self.get_matching(|fqn| re.is_match(&fqn.to_string()))
+    }
+
+    fn get_matching(&self, matches: impl Fn(&TypeFqn) -> bool) -> Vec<(&TypeFqn, &SemId)> {
+        let mut found = self
+            .symbols
+            .iter()
+            .filter_map(|sym| Some((sym.fqn.as_ref()?, &sym.id)))
+            .filter(|(fqn, _)| matches(fqn))
+            .collect::<Vec<_>>();
+        found.sort();
+        found
+    }
+
+    /// Removes the name from the symbol table, returning the semantic id of the type it was
+    /// pointing to.
+    pub fn remove(&mut self, fqn: impl Into<TypeFqn>) -> Option<SemId> {
+        let fqn = fqn.into();
+        let sym = self.symbols.iter().find(|sym| sym.fqn.as_ref() == Some(&fqn))?.clone();
+        self.symbols.remove(&sym).expect("symbol set has no lower bound");
+        Some(sym.id)
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum RemoveError {
+    /// library {0} is not a part of the type system.
+    UnknownLib(TypeLibId),
+
+    /// type {referenced} can't be removed since it is used by type {referrer}.
+    Referenced {
+        referenced: TypeSymbol,
+        referrer: TypeSymbol,
+    },
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum RenameError {
+    /// type `{0}` is not known to the type system.
+    OldNotFound(TypeFqn),
+
+    /// type name `{0}` is already used in the type system.
+    NewAlreadyExists(TypeFqn),
+
+    #[from]
+    #[display(inner)]
+    Confinement(confinement::Error),
 }
 
 impl Index<&'static str> for Symbols {
@@ -106,6 +197,72 @@ impl Display for Symbols {
     }
 }
 
+/// Reverse index of type references, mapping type to the types referencing it.
+///
+/// The index is not a part of the type system data, thus it is ignored in comparisons and
+/// serialization, and is lazily computed on the first use, if it wasn't computed during
+/// [`crate::SystemBuilder::finalize`].
+#[derive(Clone, Debug, Default)]
+struct ReferrerIndex(OnceLock<BTreeMap<SemId, BTreeSet<SemId>>>);
+
+impl PartialEq for ReferrerIndex {
+    fn eq(&self, _other: &Self) -> bool { true }
+}
+
+impl Eq for ReferrerIndex {}
+
+impl StrictDumb for ReferrerIndex {
+    fn strict_dumb() -> Self { default!() }
+}
+
+impl ReferrerIndex {
+    fn get(&self, types: &TypeSystem) -> &BTreeMap<SemId, BTreeSet<SemId>> {
+        self.0.get_or_init(|| {
+            let mut index = BTreeMap::<SemId, BTreeSet<SemId>>::new();
+            for (sem_id, ty) in types.as_unconfined() {
+                for (inner_id, _) in ty.type_refs() {
+                    index.entry(*inner_id).or_default().insert(*sem_id);
+                }
+            }
+            index
+        })
+    }
+
+    /// Updates the index with references of a newly added type, if the index was computed.
+    fn add(&mut self, sem_id: SemId, ty: &Ty<SemId>) {
+        let Some(index) = self.0.get_mut() else {
+            return;
+        };
+        for (inner_id, _) in ty.type_refs() {
+            index.entry(*inner_id).or_default().insert(sem_id);
+        }
+    }
+
+    /// Removes references of a removed type from the index, if the index was computed.
+    fn remove(&mut self, sem_id: SemId, ty: &Ty<SemId>) {
+        let Some(index) = self.0.get_mut() else {
+            return;
+        };
+        for (inner_id, _) in ty.type_refs() {
+            if let Some(referrers) = index.get_mut(inner_id) {
+                referrers.remove(&sem_id);
+                if referrers.is_empty() {
+                    index.remove(inner_id);
+                }
+            }
+        }
+        index.remove(&sem_id);
+    }
+}
+
+/// Documentation of the types from a single library.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub(super) struct LibDocs {
+    pub types: BTreeMap<TypeName, Doc>,
+    pub fields: BTreeMap<TypeName, BTreeMap<FieldName, Doc>>,
+}
+
 #[derive(Getters, Clone, Eq, PartialEq, Debug)]
 #[getter(prefix = "as_")]
 #[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
@@ -114,6 +271,15 @@ impl Display for Symbols {
 pub struct SymbolicSys {
     pub(super) symbols: Symbols,
     types: TypeSystem,
+    #[getter(skip)]
+    #[strict_type(skip)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    referrers: ReferrerIndex,
+    /// Type and field documentation, which is not a part of the strict encoding.
+    #[getter(skip)]
+    #[strict_type(skip, dumb = empty!())]
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "BTreeMap::is_empty"))]
+    pub(super) docs: BTreeMap<LibName, LibDocs>,
 }
 
 impl StrictSerialize for SymbolicSys {}
@@ -132,13 +298,19 @@ impl SymbolicSys {
             sym.update_unchecked(sem_id, info.orig)?;
         }
 
-        Ok(Self {
-            symbols: sym,
-            types: sys,
-        })
+        let sys = Self::new(sys, sym);
+        sys.referrers.get(&sys.types);
+        Ok(sys)
     }
 
-    pub fn new(types: TypeSystem, symbols: Symbols) -> Self { Self { symbols, types } }
+    pub fn new(types: TypeSystem, symbols: Symbols) -> Self {
+        Self {
+            symbols,
+            types,
+            referrers: default!(),
+            docs: empty!(),
+        }
+    }
 
     pub fn id(&self) -> TypeSysId { self.types.id() }
 
@@ -157,6 +329,43 @@ impl SymbolicSys {
 
     pub fn lookup(&self, sem_id: SemId) -> Option<&TypeFqn> { self.symbols.lookup(sem_id) }
 
+    /// Returns documentation of a type, if it was provided by the type library.
+    pub fn type_doc(&self, fqn: impl Into<TypeFqn>) -> Option<&Doc> {
+        let fqn = fqn.into();
+        self.docs.get(&fqn.lib)?.types.get(&fqn.name)
+    }
+
+    /// Returns documentation of a type field, if it was provided by the type library.
+    pub fn field_doc(&self, fqn: impl Into<TypeFqn>, field: &FieldName) -> Option<&Doc> {
+        let fqn = fqn.into();
+        self.docs.get(&fqn.lib)?.fields.get(&fqn.name)?.get(field)
+    }
+
+    /// Makes the type known under the `old` name available under the `new` name. The old name
+    /// remains active; use [`Self::remove_symbol`] to drop it.
+    pub fn rename_type(
+        &mut self,
+        old: impl Into<TypeFqn>,
+        new: impl Into<TypeFqn>,
+    ) -> Result<(), RenameError> {
+        self.symbols.alias(old, new)
+    }
+
+    /// Adds the `new` name for the type known under the `old` name, keeping both names active.
+    pub fn alias_type(
+        &mut self,
+        old: impl Into<TypeFqn>,
+        new: impl Into<TypeFqn>,
+    ) -> Result<(), RenameError> {
+        self.symbols.alias(old, new)
+    }
+
+    /// Removes a type name, leaving the type itself in the type system. Returns the semantic id
+    /// of the type the name was pointing to.
+    pub fn remove_symbol(&mut self, fqn: impl Into<TypeFqn>) -> Option<SemId> {
+        self.symbols.remove(fqn)
+    }
+
     pub fn to_sem_id(&self, spec: impl Into<TypeSpec>) -> Option<SemId> {
         match spec.into() {
             TypeSpec::SemId(sem_id) => Some(sem_id),
@@ -165,6 +374,290 @@ impl SymbolicSys {
     }
 
     pub fn into_type_system(self) -> TypeSystem { self.types }
+
+    /// Computes the range of byte sizes a value of the type takes when strict-encoded; see
+    /// [`crate::layout::MemoryLayout::encoded_size_range`].
+    pub fn encoded_size_range(&self, spec: impl Into<TypeSpec>) -> Option<SizeRange> {
+        self.type_tree(spec).map(|tree| tree.to_layout().encoded_size_range())
+    }
+
+    /// Returns class of the type, if the type is known.
+    pub fn type_class(&self, spec: impl Into<TypeSpec>) -> Option<Cls> {
+        self.get(spec).map(Ty::cls)
+    }
+
+    /// Constructs memory layout of the type.
+    pub fn type_layout(&self, spec: impl Into<TypeSpec>) -> Option<MemoryLayout> {
+        self.type_tree(spec).map(|tree| tree.to_layout())
+    }
+
+    /// Constructs vesper representation of the type layout.
+    pub fn type_vesper(&self, spec: impl Into<TypeSpec>) -> Option<TypeVesper> {
+        self.type_layout(spec).map(|layout| layout.to_vesper())
+    }
+
+    /// Returns displayable layout of the type, printed in vesper notation.
+    pub fn type_layout_display(&self, spec: impl Into<TypeSpec>) -> Option<impl Display> {
+        self.type_layout(spec)
+    }
+
+    /// Returns names of all libraries present in the symbol table.
+    pub fn libs(&self) -> Vec<&LibName> {
+        self.symbols
+            .symbols
+            .iter()
+            .filter_map(|sym| sym.fqn.as_ref())
+            .map(|fqn| &fqn.lib)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// Searches for types with names matching a glob `pattern`, where `*` matches any substring
+    /// and `?` matches any single character.
+    ///
+    /// The pattern is matched against the type name and against the fully qualified
+    /// `LibName.TypeName` form, so both `Nominal` and `Test*.Nominal` find `TestLib.Nominal`.
+    pub fn search(&self, pattern: &str) -> Vec<TypeFqn> {
+        let pattern = pattern.chars().collect::<Vec<_>>();
+        self.find(|fqn| {
+            glob_match(&pattern, &fqn.name.to_string().chars().collect::<Vec<_>>())
+                || glob_match(&pattern, &fqn.to_string().chars().collect::<Vec<_>>())
+        })
+    }
+
+    /// Searches for types which fully qualified `LibName.TypeName` name matches a regular
+    /// expression.
+    #[cfg(feature = "regex")]
+    pub fn search_regex(&self, pattern: &regex::Regex) -> Vec<TypeFqn> {
+        self.find(|fqn| pattern.is_match(&fqn.to_string()))
+    }
+
+    fn find(&self, matches: impl Fn(&TypeFqn) -> bool) -> Vec<TypeFqn> {
+        self.symbols
+            .symbols
+            .iter()
+            .filter_map(|sym| sym.fqn.as_ref())
+            .filter(|fqn| matches(fqn))
+            .cloned()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// Generates graphviz `.dot` directed graph of the type system, where types are vertices
+    /// and references between types are edges.
+    pub fn type_graph_dot(&self) -> String {
+        let mut dot = s!("digraph types {\n    node [shape=box];\n");
+        for (id, ty) in self.types.as_unconfined() {
+            let label = match self.lookup(*id) {
+                Some(fqn) => fqn.to_string(),
+                None => ty.cls().to_string(),
+            };
+            writeln!(dot, "    \"{id}\" [label=\"{label}\"];").ok();
+        }
+        for id in self.types.as_unconfined().keys() {
+            for inner in self.references(*id) {
+                writeln!(dot, "    \"{id}\" -> \"{inner}\";").ok();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Returns types directly referencing the type with the given id.
+    pub fn referrers(&self, sem_id: SemId) -> Vec<SemId> {
+        let index = self.referrers.get(&self.types);
+        index.get(&sem_id).map(|ids| ids.iter().copied().collect()).unwrap_or_default()
+    }
+
+    /// Returns types directly referenced by the type with the given id.
+    pub fn references(&self, sem_id: SemId) -> Vec<SemId> {
+        let Some(ty) = self.types.get(sem_id) else {
+            return vec![];
+        };
+        ty.type_refs().map(|(id, _)| *id).collect::<BTreeSet<_>>().into_iter().collect()
+    }
+
+    /// Returns all types which reference the type with the given id, directly or via other types.
+    pub fn transitive_referrers(&self, sem_id: SemId) -> Vec<SemId> {
+        self.closure(sem_id, |id| self.referrers(id))
+    }
+
+    /// Returns all types which are referenced by the type with the given id, directly or via other
+    /// types.
+    pub fn transitive_references(&self, sem_id: SemId) -> Vec<SemId> {
+        self.closure(sem_id, |id| self.references(id))
+    }
+
+    fn closure(&self, sem_id: SemId, next: impl Fn(SemId) -> Vec<SemId>) -> Vec<SemId> {
+        let mut found = BTreeSet::new();
+        let mut queue = next(sem_id);
+        while let Some(id) = queue.pop() {
+            if found.insert(id) {
+                queue.extend(next(id));
+            }
+        }
+        found.remove(&sem_id);
+        found.into_iter().collect()
+    }
+
+    /// Adds types of the library to the type system in place, without rebuilding it with
+    /// [`crate::SystemBuilder`].
+    ///
+    /// The system remains closed under type references: all library dependencies must be
+    /// already present in the system, and the library must not define types already known under
+    /// other names. Otherwise, an error is returned and the system is left unchanged. Adding a
+    /// library which is already present in the system does nothing.
+    pub fn extend(&mut self, lib: TypeLib) -> Result<(), translate::Error> {
+        let dependency = Dependency::from(&lib);
+        if let Some(present) = self.symbols.libs.iter().find(|dep| **dep == dependency) {
+            if present.id == dependency.id {
+                return Ok(());
+            }
+            return Err(translate::Error::ConflictingLib {
+                new: dependency,
+                present: present.clone(),
+            });
+        }
+        if let Some(dep) = lib
+            .dependencies
+            .iter()
+            .find(|dep| !self.symbols.libs.iter().any(|present| present.id == dep.id))
+        {
+            return Err(translate::Error::AbsentImport(dep.clone()));
+        }
+
+        let (types, docs) = SystemBuilder::new().import(lib)?.into_types();
+        for (sem_id, info) in &types {
+            if let (Some(fqn), Some(present)) = (&info.orig, self.lookup(*sem_id)) {
+                return Err(translate::Error::RepeatedType {
+                    new: TypeSymbol::with(*sem_id, fqn.clone()),
+                    present: TypeSymbol::with(*sem_id, present.clone()),
+                });
+            }
+            if let Some((unknown, _)) = info
+                .ty
+                .type_refs()
+                .find(|(id, _)| !types.contains_key(*id) && self.types.get(**id).is_none())
+            {
+                return Err(translate::Error::InnerTypeAbsent {
+                    unknown: *unknown,
+                    known: *sem_id,
+                });
+            }
+        }
+
+        for (sem_id, info) in types {
+            if self.types.get(sem_id).is_some() {
+                // The same unnamed type may be used by multiple libraries
+                continue;
+            }
+            self.referrers.add(sem_id, &info.ty);
+            self.types.insert_unchecked(sem_id, info.ty)?;
+            self.symbols.update_unchecked(sem_id, info.orig)?;
+        }
+        self.symbols.libs.push(dependency)?;
+        self.docs.extend(docs);
+        Ok(())
+    }
+
+    /// Removes library with the given id from the type system in place, together with all its
+    /// types and the unnamed types which are not used by the types of other libraries anymore.
+    ///
+    /// The system remains closed under type references: if a type of other library references a
+    /// type from the removed library, an error is returned and the system is left unchanged.
+    /// Types of the library which are also known under names from other libraries are kept.
+    pub fn remove_lib(&mut self, lib_id: TypeLibId) -> Result<(), RemoveError> {
+        let dep = self
+            .symbols
+            .libs
+            .iter()
+            .find(|dep| dep.id == lib_id)
+            .cloned()
+            .ok_or(RemoveError::UnknownLib(lib_id))?;
+        let in_lib = |sym: &TypeSymbol| sym.fqn.as_ref().is_some_and(|fqn| fqn.lib == dep.name);
+        let retained = self
+            .symbols
+            .symbols
+            .iter()
+            .filter(|sym| sym.fqn.is_some() && !in_lib(sym))
+            .map(|sym| sym.id)
+            .collect::<BTreeSet<_>>();
+        let named = self
+            .symbols
+            .symbols
+            .iter()
+            .filter(|sym| in_lib(sym) && !retained.contains(&sym.id))
+            .map(|sym| sym.id)
+            .collect::<BTreeSet<_>>();
+
+        // Unnamed types used by the removed types are removed as well, unless they are used by
+        // the retained types
+        let used = self.reachable(retained.iter().copied());
+        let mut removed = self.reachable(named.iter().copied());
+        removed
+            .retain(|id| named.contains(id) || (!used.contains(id) && self.lookup(*id).is_none()));
+
+        for sem_id in &removed {
+            if let Some(referrer) =
+                self.referrers(*sem_id).into_iter().find(|id| !removed.contains(id))
+            {
+                let symbol = |id: SemId| match self.lookup(id) {
+                    Some(fqn) => TypeSymbol::with(id, fqn.clone()),
+                    None => TypeSymbol::unnamed(id),
+                };
+                return Err(RemoveError::Referenced {
+                    referenced: symbol(*sem_id),
+                    referrer: symbol(referrer),
+                });
+            }
+        }
+
+        for sem_id in &removed {
+            if let Some(ty) = self.types.remove_unchecked(*sem_id) {
+                self.referrers.remove(*sem_id, &ty);
+            }
+        }
+        let symbols = self
+            .symbols
+            .symbols
+            .iter()
+            .filter(|sym| !in_lib(sym) && !removed.contains(&sym.id))
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        self.symbols.symbols = Confined::from_checked(symbols);
+        self.symbols.libs.remove(&dep).expect("library set has no lower bound");
+        self.docs.remove(&dep.name);
+        Ok(())
+    }
+
+    /// Returns the types and all the types they reference, directly or via other types.
+    fn reachable(&self, roots: impl IntoIterator<Item = SemId>) -> BTreeSet<SemId> {
+        roots
+            .into_iter()
+            .flat_map(|id| self.transitive_references(id).into_iter().chain([id]))
+            .collect()
+    }
+
+    /// Unites two symbolic type systems, deduplicating types with the same semantic id. If the
+    /// same type is known under different names in the systems, all the names are preserved.
+    pub fn merge(self, other: SymbolicSys) -> Result<SymbolicSys, MergeError> {
+        let types = self.types.merge(other.types)?;
+        let mut symbols = self.symbols;
+        symbols.libs.extend(other.symbols.libs)?;
+        symbols.symbols.extend(other.symbols.symbols)?;
+        Ok(SymbolicSys::new(types, symbols))
+    }
+}
+
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) => (0..=text.len()).any(|pos| glob_match(rest, &text[pos..])),
+        Some(('?', rest)) => !text.is_empty() && glob_match(rest, &text[1..]),
+        Some((c, rest)) => text.first() == Some(c) && glob_match(rest, &text[1..]),
+    }
 }
 
 impl Display for SymbolicSys {
@@ -192,3 +685,211 @@ impl armor::StrictArmor for SymbolicSys {
 
     fn armor_id(&self) -> Self::Id { self.id() }
 }
+
+#[cfg(test)]
+mod test {
+    use encoding::Sizing;
+
+    use super::*;
+    use crate::stl::{std_stl, strict_types_stl};
+    use crate::value::test_helpers::{test_system, Nominal};
+    use crate::LibBuilder;
+
+    fn sys(types: &[(Ty<SemId>, &'static str)]) -> SymbolicSys {
+        let mut sys = TypeSystem::new();
+        let mut symbols = Symbols::with([]).unwrap();
+        for (ty, fqn) in types {
+            let id = ty.sem_id_unnamed();
+            sys.insert_unchecked(id, ty.clone()).unwrap();
+            symbols.update_unchecked(id, Some(TypeFqn::from(*fqn))).unwrap();
+        }
+        SymbolicSys::new(sys, symbols)
+    }
+
+    #[test]
+    fn merge() {
+        let u8 = Ty::<SemId>::U8.sem_id_unnamed();
+        let list = Ty::<SemId>::List(u8, Sizing::U8);
+        let left = sys(&[(Ty::U8, "Left.Byte")]);
+        let right = sys(&[(Ty::U8, "Right.Octet"), (list.clone(), "Right.Bytes")]);
+
+        let merged = left.merge(right).unwrap();
+        assert_eq!(merged.as_types().count_types().into_u32(), 2);
+        assert_eq!(merged.resolve("Left.Byte"), Some(&u8));
+        assert_eq!(merged.resolve("Right.Octet"), Some(&u8));
+        assert_eq!(merged.get("Right.Bytes"), Some(&list));
+    }
+
+    #[test]
+    fn rename() {
+        let mut sys = test_system();
+        let nominal = sys.to_sem_id("TestLib.Nominal").unwrap();
+
+        sys.rename_type("TestLib.Nominal", "App.Asset").unwrap();
+        sys.alias_type("App.Asset", "App.Token").unwrap();
+        assert_eq!(sys.resolve("TestLib.Nominal"), Some(&nominal));
+        assert_eq!(sys.resolve("App.Asset"), Some(&nominal));
+        assert_eq!(sys.resolve("App.Token"), Some(&nominal));
+
+        assert_eq!(
+            sys.rename_type("App.Unknown", "App.Other"),
+            Err(RenameError::OldNotFound(TypeFqn::from("App.Unknown")))
+        );
+        assert_eq!(
+            sys.alias_type("App.Asset", "TestLib.Precision"),
+            Err(RenameError::NewAlreadyExists(TypeFqn::from("TestLib.Precision")))
+        );
+
+        assert_eq!(sys.remove_symbol("TestLib.Nominal"), Some(nominal));
+        assert_eq!(sys.remove_symbol("TestLib.Nominal"), None);
+        assert_eq!(sys.resolve("TestLib.Nominal"), None);
+        assert_eq!(sys.get("App.Asset"), sys.as_types().get(nominal));
+        assert!(sys.as_types().get(nominal).is_some());
+    }
+
+    #[test]
+    fn extend_remove() {
+        let std = std_stl();
+        let st = strict_types_stl();
+        let lib = LibBuilder::new("TestLib", [std.to_dependency(), st.to_dependency()])
+            .transpile::<Nominal>()
+            .compile()
+            .unwrap();
+        let base = SystemBuilder::new()
+            .import(std.clone())
+            .unwrap()
+            .import(st.clone())
+            .unwrap()
+            .finalize()
+            .unwrap();
+        let full = test_system();
+
+        let mut sys = base.clone();
+        let precision = full.to_sem_id("TestLib.Precision").unwrap();
+        // initialize referrers index to check it is updated
+        assert!(sys.referrers(precision).is_empty());
+        sys.extend(lib.clone()).unwrap();
+        assert_eq!(sys, full);
+        assert_eq!(sys.referrers(precision), full.referrers(precision));
+        sys.extend(lib.clone()).unwrap();
+        assert_eq!(sys, full);
+
+        let mut partial = SystemBuilder::new().import(std.clone()).unwrap().finalize().unwrap();
+        assert_eq!(
+            partial.extend(lib.clone()),
+            Err(translate::Error::AbsentImport(st.to_dependency()))
+        );
+
+        assert!(matches!(sys.remove_lib(std.id()), Err(RemoveError::Referenced { .. })));
+        assert_eq!(sys, full);
+        sys.remove_lib(lib.id()).unwrap();
+        assert_eq!(sys, base);
+        assert!(sys.referrers(precision).is_empty());
+        assert_eq!(sys.remove_lib(lib.id()), Err(RemoveError::UnknownLib(lib.id())));
+    }
+
+    #[test]
+    fn referrers() {
+        let sys = test_system();
+        let nominal = sys.to_sem_id("TestLib.Nominal").unwrap();
+        let precision = sys.to_sem_id("TestLib.Precision").unwrap();
+        let ident = sys.to_sem_id("StrictTypes.Ident").unwrap();
+        let alpha = sys.to_sem_id("Std.AlphaLodash").unwrap();
+
+        assert_eq!(sys.referrers(precision), vec![nominal]);
+        assert!(sys.referrers(nominal).is_empty());
+        assert!(sys.references(nominal).contains(&precision));
+        assert!(sys.references(nominal).contains(&ident));
+        assert!(!sys.references(nominal).contains(&alpha));
+        assert!(sys.references(precision).is_empty());
+
+        assert!(sys.transitive_references(nominal).contains(&alpha));
+        assert!(sys.transitive_referrers(alpha).contains(&nominal));
+        assert!(sys.transitive_referrers(alpha).contains(&ident));
+        assert!(!sys.transitive_referrers(alpha).contains(&alpha));
+
+        let dot = sys.type_graph_dot();
+        assert!(dot.contains(&format!("    \"{nominal}\" [label=\"TestLib.Nominal\"];\n")));
+        assert!(dot.contains(&format!("    \"{nominal}\" -> \"{precision}\";\n")));
+        assert!(!dot.contains(&format!("    \"{precision}\" -> ")));
+
+        let decoded = SymbolicSys::from_strict_serialized::<{ usize::MAX }>(
+            sys.to_strict_serialized::<{ usize::MAX }>().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(decoded, sys);
+        assert_eq!(decoded.referrers(precision), vec![nominal]);
+    }
+
+    #[test]
+    fn search() {
+        let sys = test_system();
+        let libs = sys.libs().into_iter().map(LibName::to_string).collect::<Vec<_>>();
+        assert_eq!(libs, ["Std", "StrictTypes", "TestLib"]);
+
+        assert_eq!(sys.search("Nominal"), vec![TypeFqn::from("TestLib.Nominal")]);
+        assert_eq!(sys.search("Test*.Nominal"), vec![TypeFqn::from("TestLib.Nominal")]);
+        assert_eq!(sys.search("TestLib.*"), vec![
+            TypeFqn::from("TestLib.Nominal"),
+            TypeFqn::from("TestLib.Precision")
+        ]);
+        assert_eq!(sys.search("Preci?ion"), vec![TypeFqn::from("TestLib.Precision")]);
+        assert!(sys.search("Nomina").is_empty());
+        assert!(sys.search("*").len() > 3);
+        assert!(sys.search("Std.*").iter().all(|fqn| fqn.lib == libname!("Std")));
+    }
+
+    #[test]
+    fn get_glob() {
+        let sys = test_system();
+        let symbols = &sys.symbols;
+        let nominal = sys.to_sem_id("TestLib.Nominal").unwrap();
+        let precision = sys.to_sem_id("TestLib.Precision").unwrap();
+        let names = |found: Vec<(&TypeFqn, &SemId)>| {
+            found.into_iter().map(|(fqn, _)| fqn.to_string()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(symbols.get_glob("TestLib.*"), vec![
+            (&TypeFqn::from("TestLib.Nominal"), &nominal),
+            (&TypeFqn::from("TestLib.Precision"), &precision)
+        ]);
+        assert_eq!(names(symbols.get_glob("*.Nominal")), ["TestLib.Nominal"]);
+        assert_eq!(names(symbols.get_glob("Nominal")), ["TestLib.Nominal"]);
+        assert_eq!(names(symbols.get_glob("Test*.Prec?sion")), ["TestLib.Precision"]);
+        assert_eq!(symbols.get_glob("*").len(), sys.search("*").len());
+        assert!(symbols.get_glob("*.Test*").is_empty());
+        assert!(symbols.get_glob("Std.*").iter().all(|(fqn, _)| fqn.lib == libname!("Std")));
+    }
+
+    #[test]
+    fn type_layout() {
+        let sys = test_system();
+        let tree = sys.type_tree("TestLib.Nominal").unwrap();
+        let layout = MemoryLayout::from(&tree);
+        assert_eq!(sys.type_layout("TestLib.Nominal").unwrap(), layout);
+        assert_eq!(sys.type_vesper("TestLib.Nominal").unwrap(), layout.to_vesper());
+        assert_eq!(
+            sys.type_layout_display("TestLib.Nominal").unwrap().to_string(),
+            tree.to_string()
+        );
+        assert!(sys.type_layout("TestLib.Absent").is_none());
+        assert_eq!(sys.type_class("TestLib.Nominal"), Some(Cls::Struct));
+        assert_eq!(sys.type_class("TestLib.Absent"), None);
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn search_regex() {
+        let sys = test_system();
+        let regex = regex::Regex::new("^TestLib\\.(Nom|Prec)").unwrap();
+        assert_eq!(sys.search_regex(&regex), vec![
+            TypeFqn::from("TestLib.Nominal"),
+            TypeFqn::from("TestLib.Precision")
+        ]);
+        let found = sys.symbols.get_regex(&regex);
+        assert_eq!(found.into_iter().map(|(fqn, _)| fqn.clone()).collect::<Vec<_>>(), vec![
+            TypeFqn::from("TestLib.Nominal"),
+            TypeFqn::from("TestLib.Precision")
+        ]);
+    }
+}
@@ -28,9 +28,11 @@ use encoding::{LibName, TypeName, STRICT_TYPES_LIB};
 
 use crate::ast::SemCommit;
 use crate::typelib::{ExternRef, InlineRef, InlineRef1, InlineRef2, LibSubref};
-use crate::typesys::symbols::SymbolicSys;
+use crate::typesys::symbols::{LibDocs, SymbolicSys};
 use crate::typesys::{SymTy, TypeFqn};
-use crate::{CommitConsume, Dependency, LibRef, SemId, Translate, Ty, TypeLib, TypeRef};
+use crate::{
+    CommitConsume, Dependency, LibRef, SemId, Translate, Ty, TypeLib, TypeRef, TypeSystem,
+};
 
 /// Information about type semantic id and fully qualified name, if any.
 #[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
@@ -59,7 +61,9 @@ impl SemCommit for TypeSymbol {
     fn sem_commit(&self, hasher: &mut impl CommitConsume) { self.id.sem_commit(hasher); }
 }
 
-impl TypeRef for TypeSymbol {}
+impl TypeRef for TypeSymbol {
+    fn sem_id(&self) -> Option<SemId> { Some(self.id) }
+}
 
 impl Display for TypeSymbol {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
@@ -87,55 +91,152 @@ impl Translate<TypeSymbol> for SemId {
     }
 }
 
+/// Strategy for resolving conflicts when a library imported into [`SystemBuilder`] defines a
+/// type with the same semantic id as a type already present in the system, but under a
+/// different fully qualified name.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum ConflictStrategy {
+    /// Fail with [`Error::RepeatedType`].
+    #[default]
+    Reject,
+
+    /// Keep the name of the type already present in the system.
+    KeepExisting,
+
+    /// Replace the name of the type already present in the system with the new one.
+    OverwriteWithNew,
+
+    /// Keep both names, such that the type can be resolved using any of them.
+    MergeSymbols,
+}
+
 #[derive(Clone, Eq, PartialEq, Debug, Default)]
 pub struct SystemBuilder {
     pending_deps: BTreeSet<Dependency>,
     imported_deps: BTreeSet<Dependency>,
     types: BTreeMap<SemId, SymTy>,
+    aliases: BTreeMap<SemId, BTreeSet<TypeFqn>>,
+    docs: BTreeMap<LibName, LibDocs>,
+    conflict_strategy: ConflictStrategy,
+    skip_validation: bool,
 }
 
 impl SystemBuilder {
     pub fn new() -> SystemBuilder { SystemBuilder::default() }
 
+    /// Disables check for the presence of all referenced types during [`Self::finalize`].
+    pub fn skip_validation(mut self) -> Self {
+        self.skip_validation = true;
+        self
+    }
+
+    /// Sets the strategy for resolving conflicting names of the same type imported from
+    /// different libraries.
+    pub fn with_conflict_resolution(mut self, strategy: ConflictStrategy) -> Self {
+        self.conflict_strategy = strategy;
+        self
+    }
+
     pub fn import(mut self, lib: TypeLib) -> Result<Self, Error> {
         let dependency = Dependency::from(&lib);
         self.pending_deps.remove(&dependency);
         self.imported_deps.insert(dependency);
         self.pending_deps
             .extend(lib.dependencies.into_iter().filter(|dep| !self.imported_deps.contains(dep)));
+        if !lib.type_docs.is_empty() || !lib.field_docs.is_empty() {
+            let docs = LibDocs {
+                types: lib.type_docs,
+                fields: lib.field_docs,
+            };
+            self.docs.insert(lib.name.clone(), docs);
+        }
 
         for (ty_name, ty) in lib.types {
             let id = ty.sem_id_named(&ty_name);
             let ty = ty.translate(&mut self, &())?;
             let info = SymTy::named(lib.name.clone(), ty_name.clone(), ty);
-            self.types.insert(id, info);
+            match self.types.get(&id) {
+                Some(present) if present.orig.is_some() && present.orig != info.orig => {
+                    match self.conflict_strategy {
+                        ConflictStrategy::Reject => {
+                            return Err(Error::RepeatedType {
+                                new: TypeSymbol::with(id, TypeFqn::with(lib.name, ty_name)),
+                                present: TypeSymbol {
+                                    id,
+                                    fqn: present.orig.clone(),
+                                },
+                            });
+                        }
+                        ConflictStrategy::KeepExisting => {}
+                        ConflictStrategy::OverwriteWithNew => {
+                            self.types.insert(id, info);
+                        }
+                        ConflictStrategy::MergeSymbols => {
+                            let fqn = TypeFqn::with(lib.name.clone(), ty_name);
+                            self.aliases.entry(id).or_default().insert(fqn);
+                        }
+                    }
+                }
+                _ => {
+                    self.types.insert(id, info);
+                }
+            }
         }
 
         Ok(self)
     }
 
+    /// Imports multiple libraries, for instance the ones returned by
+    /// [`TypeLib::with_dependencies`].
+    pub fn import_all(self, libs: impl IntoIterator<Item = TypeLib>) -> Result<Self, Error> {
+        libs.into_iter().try_fold(self, SystemBuilder::import)
+    }
+
     pub fn finalize(self) -> Result<SymbolicSys, Vec<Error>> {
         let mut errors = vec![];
 
         for dep in self.pending_deps {
             errors.push(Error::AbsentImport(dep));
         }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
 
-        for (sem_id, info) in &self.types {
-            for (inner_id, _) in info.ty.type_refs() {
-                if !self.types.contains_key(sem_id) {
-                    errors.push(Error::InnerTypeAbsent {
-                        unknown: *inner_id,
-                        known: *sem_id,
-                    });
-                }
-            }
+        let mut sys = SymbolicSys::with(self.imported_deps, self.types).map_err(|err| vec![err])?;
+        sys.docs = self.docs;
+        for (id, fqn) in self
+            .aliases
+            .into_iter()
+            .flat_map(|(id, fqns)| fqns.into_iter().map(move |fqn| (id, fqn)))
+        {
+            sys.symbols.update_unchecked(id, Some(fqn)).map_err(|err| vec![err])?;
         }
-        if !errors.is_empty() {
+        if self.skip_validation {
+            return Ok(sys);
+        }
+        if let Err(missing) = sys.as_types().validate_all() {
+            for (known, unknown) in missing {
+                errors.extend(
+                    unknown.into_iter().map(|unknown| Error::InnerTypeAbsent { unknown, known }),
+                );
+            }
             return Err(errors);
         }
+        Ok(sys)
+    }
+
+    /// Returns types and documentation collected by the builder, ignoring the dependencies.
+    pub(super) fn into_types(self) -> (BTreeMap<SemId, SymTy>, BTreeMap<LibName, LibDocs>) {
+        (self.types, self.docs)
+    }
 
-        SymbolicSys::with(self.imported_deps, self.types).map_err(|err| vec![err])
+    /// Returns type system with all types collected by the builder, ignoring the dependencies.
+    pub(crate) fn into_type_system(self) -> TypeSystem {
+        let mut sys = TypeSystem::new();
+        for (id, sym) in self.types {
+            sys.insert_unchecked(id, sym.ty).expect("type number is checked on library import");
+        }
+        sys
     }
 
     #[allow(clippy::multiple_bound_locations)]
@@ -237,6 +338,12 @@ pub enum Error {
     /// type with id `{0}` is not a part of the type system.
     UnknownType(SemId),
 
+    /// library {new} has the same name as library {present} already present in the type system.
+    ConflictingLib {
+        new: Dependency,
+        present: Dependency,
+    },
+
     /// type `{unknown}` referenced from `{known}` is not known; perhaps you need to import a
     /// library defining this type.
     InnerTypeAbsent { unknown: SemId, known: SemId },
@@ -248,3 +355,83 @@ pub enum Error {
     /// Too deeply nested types.
     TooDeep,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::LibBuilder;
+
+    mod a {
+        #[derive(Copy, Clone, Eq, PartialEq, Debug)]
+        #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+        #[strict_type(lib = "LibA", tags = repr, into_u8, try_from_u8)]
+        #[repr(u8)]
+        pub enum Precision {
+            #[strict_type(dumb)]
+            NoDecimals = 0,
+            OneDecimal = 1,
+        }
+    }
+
+    mod b {
+        #[derive(Copy, Clone, Eq, PartialEq, Debug)]
+        #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+        #[strict_type(lib = "LibB", tags = repr, into_u8, try_from_u8)]
+        #[repr(u8)]
+        pub enum Precision {
+            #[strict_type(dumb)]
+            NoDecimals = 0,
+            OneDecimal = 1,
+        }
+    }
+
+    fn libs() -> (TypeLib, TypeLib) {
+        let a = LibBuilder::new("LibA", []).transpile::<a::Precision>().compile().unwrap();
+        let b = LibBuilder::new("LibB", []).transpile::<b::Precision>().compile().unwrap();
+        (a, b)
+    }
+
+    fn build(strategy: ConflictStrategy) -> Result<SymbolicSys, Error> {
+        let (a, b) = libs();
+        Ok(SystemBuilder::new()
+            .with_conflict_resolution(strategy)
+            .import(a)?
+            .import(b)?
+            .finalize()
+            .unwrap())
+    }
+
+    #[test]
+    fn conflict_resolution() {
+        let (a, _) = libs();
+        let id = a.types[&tn!("Precision")].sem_id_named(&tn!("Precision"));
+        let fqn_a = TypeFqn::from("LibA.Precision");
+        let fqn_b = TypeFqn::from("LibB.Precision");
+
+        assert_eq!(build(ConflictStrategy::Reject).unwrap_err(), Error::RepeatedType {
+            new: TypeSymbol::with(id, fqn_b.clone()),
+            present: TypeSymbol::with(id, fqn_a.clone()),
+        });
+
+        let sys = build(ConflictStrategy::KeepExisting).unwrap();
+        assert_eq!(sys.resolve(fqn_a.clone()), Some(&id));
+        assert_eq!(sys.resolve(fqn_b.clone()), None);
+
+        let sys = build(ConflictStrategy::OverwriteWithNew).unwrap();
+        assert_eq!(sys.resolve(fqn_a.clone()), None);
+        assert_eq!(sys.resolve(fqn_b.clone()), Some(&id));
+
+        let sys = build(ConflictStrategy::MergeSymbols).unwrap();
+        assert_eq!(sys.resolve(fqn_a.clone()), Some(&id));
+        assert_eq!(sys.resolve(fqn_b), Some(&id));
+        assert_eq!(sys.lookup(id), Some(&fqn_a));
+        assert_eq!(sys.as_types().len(), 1);
+    }
+
+    #[test]
+    fn repeated_import() {
+        let (a, _) = libs();
+        let sys = SystemBuilder::new().import(a.clone()).unwrap().import(a).unwrap();
+        assert_eq!(sys.finalize().unwrap().as_types().len(), 1);
+    }
+}
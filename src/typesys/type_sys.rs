@@ -29,15 +29,29 @@ use std::ops::Index;
 
 use amplify::confinement::{self, Confined, MediumOrdMap};
 use amplify::num::u24;
-use encoding::{LibName, Sizing, StrictDeserialize, StrictSerialize, TypeName};
+use encoding::{
+    FieldName, LibName, Sizing, StrictDeserialize, StrictSerialize, TypeName, VariantName,
+};
 use strict_encoding::STRICT_TYPES_LIB;
 
 use crate::ast::UnnamedFields;
-use crate::{SemId, Ty};
+use crate::{Cls, SemId, Ty};
 
 #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Display, Error)]
 #[display("type with id `{0}` is not a part of the type system.")]
-pub struct UnknownType(SemId);
+pub struct UnknownType(pub(crate) SemId);
+
+#[derive(Clone, Eq, PartialEq, Debug, Display, From, Error)]
+#[display(doc_comments)]
+pub enum MergeError {
+    /// type with id `{0}` has different definitions in the merged type systems, which indicates
+    /// a semantic id collision.
+    Collision(SemId),
+
+    #[from]
+    #[display(inner)]
+    Confinement(confinement::Error),
+}
 
 #[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Display)]
 #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
@@ -123,7 +137,21 @@ impl TypeSystem {
 
     pub fn count_types(&self) -> u24 { self.0.len_u24() }
 
-    pub(super) fn insert_unchecked(
+    /// Checks that all types referenced from the system types are present in the system,
+    /// returning a map of type ids to the ids of missing types they reference otherwise.
+    pub fn validate_all(&self) -> Result<(), BTreeMap<SemId, Vec<SemId>>> {
+        let missing = self
+            .0
+            .iter()
+            .filter_map(|(id, ty)| ty.validate_refs(self).err().map(|missing| (*id, missing)))
+            .collect::<BTreeMap<_, _>>();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        Err(missing)
+    }
+
+    pub(crate) fn insert_unchecked(
         &mut self,
         sem_id: SemId,
         ty: Ty<SemId>,
@@ -131,6 +159,10 @@ impl TypeSystem {
         self.0.insert(sem_id, ty).map(|r| r.is_some())
     }
 
+    pub(crate) fn remove_unchecked(&mut self, sem_id: SemId) -> Option<Ty<SemId>> {
+        self.0.remove(&sem_id).expect("type system has no lower bound")
+    }
+
     pub fn get(&self, sem_id: SemId) -> Option<&Ty<SemId>> { self.0.get(&sem_id) }
 
     pub fn extend(&mut self, other: Self) -> Result<(), confinement::Error> {
@@ -152,6 +184,139 @@ impl TypeSystem {
         Ok(Self(Confined::from_checked(extract)))
     }
 
+    /// Unites two type systems, deduplicating types with the same semantic id.
+    pub fn merge(mut self, other: TypeSystem) -> Result<TypeSystem, MergeError> {
+        for (sem_id, ty) in other.0 {
+            match self.0.get(&sem_id) {
+                Some(present) if present != &ty => return Err(MergeError::Collision(sem_id)),
+                Some(_) => {}
+                None => {
+                    self.0.insert(sem_id, ty)?;
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    /// Constructs a type system containing only the `roots` types and types which are
+    /// transitively referenced by them. Root ids which are not known to the type system are
+    /// ignored; a missing type referenced from the roots results in an error.
+    pub fn subset(&self, roots: impl IntoIterator<Item = SemId>) -> Result<Self, UnknownType> {
+        let roots = roots.into_iter().filter(|id| self.0.contains_key(id));
+        self.extract(roots)
+    }
+
+    /// Checks whether all the types with the given ids are present in the type system.
+    pub fn contains_all(&self, ids: &[SemId]) -> bool {
+        ids.iter().all(|id| self.0.contains_key(id))
+    }
+
+    /// Returns ids of the types which are not present in the type system.
+    pub fn missing(&self, ids: &[SemId]) -> Vec<SemId> {
+        ids.iter().filter(|id| !self.0.contains_key(*id)).copied().collect()
+    }
+
+    /// Finds a type with the same structure as `ty`.
+    ///
+    /// Since semantic ids commit to the type structure, for unnamed types this is equivalent to
+    /// looking up [`Ty::sem_id_unnamed`]; named types, whose ids also commit to their names, are
+    /// found by comparing their definitions.
+    pub fn find_by_structure(&self, ty: &Ty<SemId>) -> Option<SemId> {
+        let id = ty.sem_id_unnamed();
+        if self.0.contains_key(&id) {
+            return Some(id);
+        }
+        self.0.iter().find(|(_, other)| *other == ty).map(|(id, _)| *id)
+    }
+
+    /// Returns ids of all structure types having a field with the given name.
+    pub fn find_all_structs_with_field(&self, name: &FieldName) -> Vec<SemId> {
+        self.0
+            .iter()
+            .filter(|(_, ty)| matches!(ty, Ty::Struct(fields) if fields.contains_name(name)))
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// Returns ids of all union types having a variant with the given name.
+    pub fn find_all_unions_with_variant(&self, name: &VariantName) -> Vec<SemId> {
+        self.0
+            .iter()
+            .filter(|(_, ty)| matches!(ty, Ty::Union(variants) if variants.by_name(name).is_some()))
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// Constructs a new type system where each reference to the `old` type is replaced with a
+    /// reference to `new_ref` (see [`Ty::substitute`]).
+    ///
+    /// Semantic ids of unnamed types are recomputed, and references to them are updated
+    /// accordingly. Named types keep their ids, since type names are not known to the type
+    /// system. The `old` type itself is left intact.
+    pub fn substitute_all(&self, old: SemId, new_ref: SemId) -> TypeSystem {
+        let mut renames = bmap! { old => new_ref };
+        let rename = |renames: &BTreeMap<SemId, SemId>, ty: &Ty<SemId>| {
+            ty.map_refs(&|id| renames.get(&id).copied().unwrap_or(id))
+        };
+        loop {
+            let mut changed = false;
+            for (id, ty) in &self.0 {
+                if renames.contains_key(id)
+                    || !ty.iter().any(|(r, _)| renames.contains_key(r))
+                    || ty.sem_id_unnamed() != *id
+                {
+                    continue;
+                }
+                let new_id = rename(&renames, ty).sem_id_unnamed();
+                if new_id != *id {
+                    renames.insert(*id, new_id);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        let mut sys = TypeSystem::new();
+        for (id, ty) in &self.0 {
+            let id = if *id == old { old } else { renames.get(id).copied().unwrap_or(*id) };
+            sys.insert_unchecked(id, rename(&renames, ty)).expect("type number is not increased");
+        }
+        sys
+    }
+
+    /// Detects whether the type with `sem_id` is a list of ASCII characters (see
+    /// [`Ty::is_ascii_char`]).
+    pub fn is_ascii_string(&self, sem_id: SemId) -> bool {
+        match self.find(sem_id) {
+            Some(Ty::List(id, _)) => self.find(*id).is_some_and(Ty::is_ascii_char),
+            _ => false,
+        }
+    }
+
+    /// Returns class of the type with `sem_id`, if the type is present in the type system.
+    pub fn type_class(&self, sem_id: SemId) -> Option<Cls> { self.find(sem_id).map(Ty::cls) }
+
+    /// Detects whether the type with `sem_id` is a primitive type.
+    pub fn is_primitive(&self, sem_id: SemId) -> bool {
+        self.type_class(sem_id) == Some(Cls::Primitive)
+    }
+
+    /// Detects whether the type with `sem_id` is an enumeration.
+    pub fn is_enum(&self, sem_id: SemId) -> bool { self.type_class(sem_id) == Some(Cls::Enum) }
+
+    /// Detects whether the type with `sem_id` is a union.
+    pub fn is_union(&self, sem_id: SemId) -> bool { self.type_class(sem_id) == Some(Cls::Union) }
+
+    /// Detects whether the type with `sem_id` is a structure with named fields.
+    pub fn is_struct(&self, sem_id: SemId) -> bool { self.type_class(sem_id) == Some(Cls::Struct) }
+
+    /// Detects whether the type with `sem_id` is an array, list, set or map.
+    pub fn is_collection(&self, sem_id: SemId) -> bool {
+        matches!(self.type_class(sem_id), Some(Cls::Array | Cls::List | Cls::Set | Cls::Map))
+    }
+
     pub(crate) fn rstring_sizing(
         &self,
         fields: &UnnamedFields<SemId>,
@@ -160,8 +325,9 @@ impl TypeSystem {
         let rest = self.find(rest).ok_or(UnknownType(rest))?;
         if let Ty::List(rest, sizing) = rest {
             let mut sizing = *sizing;
-            sizing.min += 1;
-            sizing.max += 1;
+            sizing.min = sizing.min.saturating_add(1);
+            // Unlimited lists stay unlimited
+            sizing.max = sizing.max.saturating_add(1);
             return Ok(Some((*rest, sizing)));
         }
         Ok(None)
@@ -176,8 +342,8 @@ impl TypeSystem {
             return Ok(false);
         };
 
-        Ok(self.find(first).ok_or(UnknownType(first))?.is_char_enum()
-            && self.find(rest).ok_or(UnknownType(rest))?.is_char_enum())
+        Ok(self.find(first).ok_or(UnknownType(first))?.is_ascii_char()
+            && self.find(rest).ok_or(UnknownType(rest))?.is_ascii_char())
     }
 }
 
@@ -209,3 +375,197 @@ impl armor::StrictArmor for TypeSystem {
 
     fn armor_id(&self) -> Self::Id { self.id() }
 }
+
+#[cfg(test)]
+mod test {
+    use amplify::ByteArray;
+
+    use super::*;
+    use crate::ast::UnnamedFields;
+
+    fn insert(sys: &mut TypeSystem, ty: Ty<SemId>) -> SemId {
+        let id = ty.sem_id_unnamed();
+        sys.insert_unchecked(id, ty).unwrap();
+        id
+    }
+
+    #[test]
+    fn subset() {
+        let mut sys = TypeSystem::new();
+        for ty in [
+            Ty::UNIT,
+            Ty::U8,
+            Ty::U16,
+            Ty::U24,
+            Ty::U32,
+            Ty::U64,
+            Ty::U128,
+            Ty::U256,
+            Ty::I8,
+            Ty::I16,
+            Ty::I32,
+            Ty::I64,
+            Ty::I128,
+            Ty::F16,
+            Ty::F32,
+            Ty::F64,
+        ] {
+            insert(&mut sys, ty);
+        }
+        let u8 = Ty::<SemId>::U8.sem_id_unnamed();
+        let u16 = Ty::<SemId>::U16.sem_id_unnamed();
+        let u32 = Ty::<SemId>::U32.sem_id_unnamed();
+        insert(&mut sys, Ty::List(u8, Sizing::U8));
+        insert(&mut sys, Ty::Set(u16, Sizing::U16));
+        insert(&mut sys, Ty::Array(u32, 4));
+        let fields = UnnamedFields::try_from(vec![u8, u16]).unwrap();
+        let tuple = insert(&mut sys, Ty::Tuple(fields));
+        assert_eq!(sys.count_types(), u24::with(20));
+
+        let unknown = Ty::<SemId>::F80.sem_id_unnamed();
+        let subset = sys.subset([tuple, unknown]).unwrap();
+        assert_eq!(subset.count_types(), u24::with(3));
+        assert!(subset.contains_all(&[tuple, u8, u16]));
+        assert_eq!(subset.missing(&[tuple, u8, u32, unknown]), vec![u32, unknown]);
+        assert_ne!(subset.id(), sys.id());
+        assert_eq!(sys.missing(&[tuple, u32, unknown]), vec![unknown]);
+
+        let dangling = insert(&mut sys, Ty::List(unknown, Sizing::U8));
+        assert_eq!(sys.subset([dangling]), Err(UnknownType(unknown)));
+    }
+
+    #[test]
+    fn merge() {
+        let mut left = TypeSystem::new();
+        let u8 = insert(&mut left, Ty::U8);
+        let u16 = insert(&mut left, Ty::U16);
+        let mut right = TypeSystem::new();
+        insert(&mut right, Ty::U16);
+        let list = insert(&mut right, Ty::List(u8, Sizing::U8));
+
+        let merged = left.clone().merge(right.clone()).unwrap();
+        assert_eq!(merged.count_types(), u24::with(3));
+        assert!(merged.contains_all(&[u8, u16, list]));
+        assert_eq!(merged, right.merge(left.clone()).unwrap());
+
+        let mut collision = TypeSystem::new();
+        collision.insert_unchecked(u8, Ty::I8).unwrap();
+        assert_eq!(left.merge(collision).unwrap_err(), MergeError::Collision(u8));
+    }
+
+    #[test]
+    fn validate_all() {
+        let mut sys = TypeSystem::new();
+        let int = insert(&mut sys, Ty::U32);
+        assert_eq!(sys.validate_all(), Ok(()));
+
+        let missing = Ty::<SemId>::U64.sem_id_unnamed();
+        let list = insert(&mut sys, Ty::List(missing, Sizing::U8));
+        let tuple =
+            insert(&mut sys, Ty::Tuple(UnnamedFields::try_from(vec![int, missing]).unwrap()));
+        assert_eq!(
+            sys.validate_all(),
+            Err(bmap! { list => vec![missing], tuple => vec![missing] })
+        );
+    }
+
+    #[test]
+    fn finalize_validation() {
+        use crate::typelib::LibRef;
+        use crate::typesys::{Error, SystemBuilder};
+        use crate::value::test_helpers::Nominal;
+        use crate::LibBuilder;
+
+        let std = crate::stl::std_stl();
+        let st = crate::stl::strict_types_stl();
+        let mut lib = LibBuilder::new("TestLib", [std.to_dependency(), st.to_dependency()])
+            .transpile::<Nominal>()
+            .compile()
+            .unwrap();
+        let missing = Ty::<LibRef>::UNIT.sem_id_named(&tn!("Missing"));
+        lib.types.insert(tn!("Broken"), Ty::List(LibRef::Named(missing), Sizing::U8)).unwrap();
+        let known = lib.types[&tn!("Broken")].sem_id_named(&tn!("Broken"));
+
+        let builder =
+            SystemBuilder::new().import(lib).unwrap().import(std).unwrap().import(st).unwrap();
+        assert_eq!(builder.clone().finalize().unwrap_err(), vec![Error::InnerTypeAbsent {
+            unknown: missing,
+            known
+        }]);
+        assert!(builder.skip_validation().finalize().is_ok());
+    }
+
+    #[test]
+    fn substitute_all() {
+        let mut sys = TypeSystem::new();
+        let u8 = insert(&mut sys, Ty::U8);
+        let u16 = insert(&mut sys, Ty::U16);
+        let list = insert(&mut sys, Ty::List(u8, Sizing::U8));
+        let array = insert(&mut sys, Ty::Array(list, 2));
+        // Ids of named types don't match their unnamed definitions
+        let named = SemId::from_byte_array([0xAA; 32]);
+        sys.insert_unchecked(named, Ty::Array(list, 4)).unwrap();
+
+        assert_eq!(Ty::List(u8, Sizing::U8).substitute(u8, u16), Ty::List(u16, Sizing::U8));
+        assert_eq!(Ty::<SemId>::U8.substitute(u8, u16), Ty::U8);
+
+        let new = sys.substitute_all(u8, u16);
+        let new_list = Ty::List(u16, Sizing::U8).sem_id_unnamed();
+        let new_array = Ty::Array(new_list, 2).sem_id_unnamed();
+        assert_eq!(new.len(), sys.len());
+        assert_eq!(new.get(list), None);
+        assert_eq!(new.get(array), None);
+        assert_eq!(new.get(new_list), Some(&Ty::List(u16, Sizing::U8)));
+        assert_eq!(new.get(new_array), Some(&Ty::Array(new_list, 2)));
+        assert_eq!(new.get(named), Some(&Ty::Array(new_list, 4)));
+        assert_eq!(new.get(u8), Some(&Ty::U8));
+        assert!(new.validate_all().is_ok());
+
+        assert_eq!(sys.substitute_all(Ty::<SemId>::U64.sem_id_unnamed(), u16), sys);
+    }
+
+    #[test]
+    fn find_by_structure() {
+        let sys = crate::value::test_helpers::test_system();
+        let types = sys.as_types();
+        let nominal = *sys.resolve("TestLib.Nominal").unwrap();
+
+        assert_eq!(types.find_by_structure(&types[nominal]), Some(nominal));
+        let u8 = Ty::<SemId>::U8;
+        let mut sys = TypeSystem::new();
+        assert_eq!(sys.find_by_structure(&u8), None);
+        let id = insert(&mut sys, u8.clone());
+        assert_eq!(sys.find_by_structure(&u8), Some(id));
+        assert_eq!(types.find_by_structure(&Ty::Array(nominal, 17)), None);
+
+        assert_eq!(types.find_all_structs_with_field(&fname!("ticker")), vec![nominal]);
+        assert!(types.find_all_structs_with_field(&fname!("missing")).is_empty());
+        let unions = types.find_all_unions_with_variant(&vname!("some"));
+        assert!(!unions.is_empty());
+        for id in unions {
+            assert!(matches!(&types[id], Ty::Union(variants) if variants.len() == 2));
+        }
+        assert!(types.find_all_unions_with_variant(&vname!("missing")).is_empty());
+    }
+
+    #[test]
+    fn type_class() {
+        let mut sys = TypeSystem::new();
+        let u8 = insert(&mut sys, Ty::U8);
+        let list = insert(&mut sys, Ty::List(u8, Sizing::U8));
+        let fields = UnnamedFields::try_from(vec![u8, list]).unwrap();
+        let tuple = insert(&mut sys, Ty::Tuple(fields));
+        let unknown = Ty::<SemId>::U16.sem_id_unnamed();
+
+        assert_eq!(sys.type_class(u8), Some(Cls::Primitive));
+        assert_eq!(sys.type_class(tuple), Some(Cls::Tuple));
+        assert_eq!(sys.type_class(unknown), None);
+        assert!(sys.is_primitive(u8));
+        assert!(!sys.is_primitive(unknown));
+        assert!(sys.is_collection(list));
+        assert!(!sys.is_collection(tuple));
+        assert!(!sys.is_struct(tuple));
+        assert!(!sys.is_enum(u8));
+        assert!(!sys.is_union(list));
+    }
+}
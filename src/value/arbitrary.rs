@@ -0,0 +1,365 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Property-based testing strategies producing strict values.
+
+use amplify::num::apfloat::{ieee, Float, FloatConvert};
+use amplify::num::u256;
+use encoding::{FieldName, Primitive, VariantName};
+use proptest::collection::{btree_map, vec};
+use proptest::prelude::*;
+use proptest::sample::select;
+
+use super::{Blob, StrictNum, StrictVal};
+use crate::{SemId, Ty, TypeRef, TypeSystem};
+
+/// Maximal nesting of the values generated for recursive types.
+const MAX_DEPTH: usize = 8;
+/// Maximal number of items generated for collection types above their minimal size.
+const MAX_EXTRA_ITEMS: u64 = 3;
+
+fn ident() -> impl Strategy<Value = String> { "[a-z][a-zA-Z0-9]{0,6}" }
+
+impl Arbitrary for Blob {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        vec(any::<u8>(), 0..16).prop_map(Blob).boxed()
+    }
+}
+
+/// Produces numbers in their canonical form, i.e. non-negative integers are always unsigned and
+/// NaN floats have no payload. Floats include both values representable by `f64` and values
+/// requiring 256-bit precision.
+impl Arbitrary for StrictNum {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        use proptest::num::f64::{INFINITE, NEGATIVE, NORMAL, POSITIVE, SUBNORMAL, ZERO};
+
+        let float = prop_oneof![
+            (POSITIVE | NEGATIVE | NORMAL | SUBNORMAL | ZERO | INFINITE),
+            Just(f64::NAN),
+            Just(-f64::NAN),
+        ];
+        prop_oneof![
+            any::<u64>().prop_map(StrictNum::Uint),
+            (i64::MIN..0).prop_map(StrictNum::Int),
+            float.prop_map(StrictNum::from),
+            NORMAL.prop_map(|f| {
+                let double = ieee::Double::from_bits(u256::from(f.to_bits()));
+                let oct: ieee::Oct = double.convert(&mut false).value;
+                StrictNum::Float((oct / ieee::Oct::from_u256(u256::from(3u8)).value).value)
+            }),
+        ]
+        .boxed()
+    }
+}
+
+/// Produces values which have unambiguous STON representation: tuples have at least two fields
+/// and sets and maps are non-empty.
+impl Arbitrary for StrictVal {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        let leaf = prop_oneof![
+            Just(StrictVal::Unit),
+            any::<StrictNum>().prop_map(StrictVal::Number),
+            prop_oneof!["(?s).{0,8}", "[a-z \"\\\\]{0,8}"].prop_map(StrictVal::String),
+            any::<Blob>().prop_map(StrictVal::Bytes),
+            ident().prop_map(|s| StrictVal::enumer(VariantName::try_from(s).unwrap())),
+        ];
+        leaf.clone()
+            .prop_recursive(3, 32, 4, move |inner| {
+                prop_oneof![
+                    vec(inner.clone(), 2..4).prop_map(StrictVal::Tuple),
+                    btree_map(ident(), inner.clone(), 1..4).prop_map(|fields| {
+                        StrictVal::Struct(
+                            fields
+                                .into_iter()
+                                .map(|(name, val)| (FieldName::try_from(name).unwrap(), val))
+                                .collect(),
+                        )
+                    }),
+                    (ident(), inner.clone()).prop_map(|(tag, val)| {
+                        StrictVal::union(VariantName::try_from(tag).unwrap(), val)
+                    }),
+                    vec(inner.clone(), 0..4).prop_map(StrictVal::List),
+                    vec(inner.clone(), 1..4).prop_map(StrictVal::Set),
+                    vec((leaf.clone(), inner), 1..4).prop_map(StrictVal::Map),
+                ]
+            })
+            .boxed()
+    }
+}
+
+impl TypeSystem {
+    /// Constructs strategy producing values of the type with the given id, in the form they have
+    /// after strict decoding. Returns `None` if the type contains primitives which can't be
+    /// decoded into strict values or if it's recursive without a non-recursive alternative.
+    pub(crate) fn value_strategy(&self, sem_id: SemId) -> Option<BoxedStrategy<StrictVal>> {
+        self.strategy_at(sem_id, 0)
+    }
+
+    fn strategy_at(&self, sem_id: SemId, depth: usize) -> Option<BoxedStrategy<StrictVal>> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        let depth = depth + 1;
+        let ty = self.find(sem_id)?;
+        Some(match ty {
+            Ty::Primitive(prim) => prim_strategy(*prim)?,
+            Ty::UnicodeChar => return None,
+
+            Ty::List(id, sizing) if self.find(*id)?.is_ascii_char() => {
+                let chars = self.chars(*id)?;
+                let (min, max) = items_range(sizing.min, sizing.max);
+                vec(select(chars), min..=max)
+                    .prop_map(|chars| StrictVal::String(chars.into_iter().collect()))
+                    .boxed()
+            }
+            Ty::Tuple(fields) if self.is_rstring(fields).ok()? => {
+                let first = self.chars(fields[0])?;
+                let (rest_id, sizing) = self.rstring_sizing(fields).ok()??;
+                let rest = self.chars(rest_id)?;
+                let (min, max) = items_range(sizing.min - 1, sizing.max - 1);
+                (select(first), vec(select(rest), min..=max))
+                    .prop_map(|(first, rest)| {
+                        StrictVal::String(Some(first).into_iter().chain(rest).collect())
+                    })
+                    .boxed()
+            }
+
+            Ty::Enum(variants) => {
+                let names = variants.iter().map(|v| v.name.clone()).collect::<Vec<_>>();
+                select(names).prop_map(StrictVal::enumer).boxed()
+            }
+            Ty::Union(variants) => {
+                let alts = variants
+                    .iter()
+                    .filter_map(|(variant, id)| {
+                        let name = variant.name.clone();
+                        let strategy = self.strategy_at(*id, depth)?;
+                        Some(strategy.prop_map(move |val| StrictVal::union(name.clone(), val)))
+                    })
+                    .collect::<Vec<_>>();
+                if alts.is_empty() {
+                    return None;
+                }
+                proptest::strategy::Union::new(alts).boxed()
+            }
+            Ty::Tuple(fields) => fields
+                .iter()
+                .map(|id| self.strategy_at(*id, depth))
+                .collect::<Option<Vec<_>>>()?
+                .prop_map(StrictVal::Tuple)
+                .boxed(),
+            Ty::Struct(fields) => {
+                let names = fields.iter().map(|field| field.name.clone()).collect::<Vec<_>>();
+                fields
+                    .iter()
+                    .map(|field| self.strategy_at(field.ty, depth))
+                    .collect::<Option<Vec<_>>>()?
+                    .prop_map(move |vals| {
+                        StrictVal::Struct(names.clone().into_iter().zip(vals).collect())
+                    })
+                    .boxed()
+            }
+
+            Ty::Array(id, len) if id.is_byte() => {
+                vec(any::<u8>(), *len as usize).prop_map(|v| StrictVal::Bytes(Blob(v))).boxed()
+            }
+            Ty::Array(id, len) => {
+                vec(self.strategy_at(*id, depth)?, *len as usize).prop_map(StrictVal::List).boxed()
+            }
+            Ty::List(id, sizing) if id.is_byte() => {
+                let (min, max) = items_range(sizing.min, sizing.max);
+                vec(any::<u8>(), min..=max).prop_map(|v| StrictVal::Bytes(Blob(v))).boxed()
+            }
+            Ty::List(id, sizing) if id.is_unicode_char() => {
+                let (min, max) = items_range(sizing.min, sizing.max);
+                vec(any::<char>(), min..=max)
+                    .prop_map(|chars| StrictVal::String(chars.into_iter().collect()))
+                    .boxed()
+            }
+            Ty::List(id, sizing) => {
+                let (min, max) = items_range(sizing.min, sizing.max);
+                let Some(item) = self.strategy_at(*id, depth) else {
+                    return (min == 0).then(|| Just(StrictVal::List(vec![])).boxed());
+                };
+                vec(item, min..=max).prop_map(StrictVal::List).boxed()
+            }
+            Ty::Set(id, sizing) => {
+                let (min, max) = items_range(sizing.min, sizing.max);
+                let Some(item) = self.strategy_at(*id, depth) else {
+                    return (min == 0).then(|| Just(StrictVal::Set(vec![])).boxed());
+                };
+                vec(item, min..=max)
+                    .prop_map(|mut items| {
+                        let mut unique = vec![];
+                        items.retain(|item| {
+                            let new = !unique.contains(item);
+                            unique.push(item.clone());
+                            new
+                        });
+                        items
+                    })
+                    .prop_filter("too few unique set items", move |items| items.len() >= min)
+                    .prop_map(StrictVal::Set)
+                    .boxed()
+            }
+            Ty::Map(key_id, id, sizing) => {
+                let (min, max) = items_range(sizing.min, sizing.max);
+                let (Some(key), Some(item)) =
+                    (self.strategy_at(*key_id, depth), self.strategy_at(*id, depth))
+                else {
+                    return (min == 0).then(|| Just(StrictVal::Map(vec![])).boxed());
+                };
+                vec((key, item), min..=max)
+                    .prop_map(|mut items| {
+                        let mut keys = vec![];
+                        items.retain(|(key, _)| {
+                            let new = !keys.contains(key);
+                            keys.push(key.clone());
+                            new
+                        });
+                        items
+                    })
+                    .prop_filter("too few unique map keys", move |items| items.len() >= min)
+                    .prop_map(StrictVal::Map)
+                    .boxed()
+            }
+        })
+    }
+
+    /// Returns characters defined by a character enum type.
+    fn chars(&self, sem_id: SemId) -> Option<Vec<char>> {
+        let Ty::Enum(variants) = self.find(sem_id)? else {
+            return None;
+        };
+        Some(variants.iter().map(|v| v.tag as char).collect())
+    }
+}
+
+fn items_range(min: u64, max: u64) -> (usize, usize) {
+    (min as usize, max.min(min + MAX_EXTRA_ITEMS) as usize)
+}
+
+fn prim_strategy(prim: Primitive) -> Option<BoxedStrategy<StrictVal>> {
+    Some(match prim {
+        Primitive::U8 => any::<u8>().prop_map(StrictVal::num).boxed(),
+        Primitive::U16 => any::<u16>().prop_map(StrictVal::num).boxed(),
+        Primitive::U24 => (0..1u32 << 24).prop_map(StrictVal::num).boxed(),
+        Primitive::U32 => any::<u32>().prop_map(StrictVal::num).boxed(),
+        Primitive::U40 => (0..1u64 << 40).prop_map(StrictVal::num).boxed(),
+        Primitive::U48 => (0..1u64 << 48).prop_map(StrictVal::num).boxed(),
+        Primitive::U56 => (0..1u64 << 56).prop_map(StrictVal::num).boxed(),
+        Primitive::U64 => any::<u64>().prop_map(StrictVal::num).boxed(),
+        Primitive::I8 => any::<i8>().prop_map(StrictVal::num).boxed(),
+        Primitive::I16 => any::<i16>().prop_map(StrictVal::num).boxed(),
+        Primitive::I32 => any::<i32>().prop_map(StrictVal::num).boxed(),
+        Primitive::I64 => any::<i64>().prop_map(StrictVal::num).boxed(),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use proptest::test_runner::{Config, TestCaseError, TestRunner};
+
+    use super::*;
+    use crate::value::test_helpers::test_system;
+
+    /// Runs the check for values of each of the types in the test type system, returning the
+    /// number of checked types.
+    fn check_all(
+        test: impl Fn(&TypeSystem, SemId, StrictVal) -> Result<(), TestCaseError>,
+    ) -> usize {
+        let sys = test_system();
+        let types = sys.as_types();
+        let mut count = 0;
+        for sem_id in types.as_unconfined().keys() {
+            let Some(strategy) = types.value_strategy(*sem_id) else {
+                continue;
+            };
+            let mut runner = TestRunner::new(Config::default());
+            runner.run(&strategy, |val| test(types, *sem_id, val)).unwrap_or_else(|err| match sys
+                .lookup(*sem_id)
+            {
+                Some(fqn) => panic!("type {fqn}: {err}"),
+                None => panic!("type {sem_id}: {err}"),
+            });
+            count += 1;
+        }
+        count
+    }
+
+    #[test]
+    fn nominal() {
+        let sys = test_system();
+        let sem_id = sys.to_sem_id("TestLib.Nominal").unwrap();
+        let strategy = sys.as_types().value_strategy(sem_id).unwrap();
+        let mut runner = TestRunner::new(Config::default());
+        runner
+            .run(&strategy, |val| {
+                let ticker = val.unwrap_struct("ticker").unwrap_string();
+                prop_assert!(ticker.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_'));
+                prop_assert!(matches!(val.unwrap_struct("precision"), StrictVal::Enum(_)));
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn encode_decode() {
+        let count = check_all(|sys, sem_id, val| {
+            let typed = sys
+                .typify(val.clone(), sem_id)
+                .map_err(|err| TestCaseError::fail(err.to_string()))?;
+            let data = sys
+                .strict_serialize_value::<{ usize::MAX }>(&typed)
+                .map_err(|err| TestCaseError::fail(err.to_string()))?;
+            let decoded = sys
+                .strict_deserialize_type(sem_id, &data)
+                .map_err(|err| TestCaseError::fail(err.to_string()))?;
+            prop_assert_eq!(decoded.val, val);
+            Ok(())
+        });
+        assert!(count > 100, "only {count} types were checked");
+    }
+
+    #[test]
+    fn typify_idempotent() {
+        check_all(|sys, sem_id, val| {
+            let typed =
+                sys.typify(val, sem_id).map_err(|err| TestCaseError::fail(err.to_string()))?;
+            let retyped = sys
+                .typify(typed.val.clone(), sem_id)
+                .map_err(|err| TestCaseError::fail(err.to_string()))?;
+            prop_assert_eq!(retyped.val, typed.val);
+            Ok(())
+        });
+    }
+}
@@ -0,0 +1,150 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Builders for programmatic construction of composite strict values.
+
+use encoding::FieldName;
+use indexmap::IndexMap;
+
+use crate::value::EnumTag;
+use crate::StrictVal;
+
+/// Builder of [`StrictVal::Struct`] values.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct StrictValBuilder {
+    fields: IndexMap<FieldName, StrictVal>,
+}
+
+impl StrictValBuilder {
+    pub fn new() -> Self { StrictValBuilder::default() }
+
+    /// Adds a field to the struct. If the field was already added, its value is replaced,
+    /// keeping the original field position.
+    pub fn field(mut self, name: impl Into<FieldName>, val: impl Into<StrictVal>) -> Self {
+        self.fields.insert(name.into(), val.into());
+        self
+    }
+
+    pub fn build(self) -> StrictVal { StrictVal::Struct(self.fields) }
+}
+
+/// Builder of [`StrictVal::Union`] values.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct StrictValUnionBuilder;
+
+impl StrictValUnionBuilder {
+    pub fn new() -> Self { StrictValUnionBuilder }
+
+    pub fn variant(self, tag: impl Into<EnumTag>, val: impl Into<StrictVal>) -> StrictVal {
+        StrictVal::union(tag, val)
+    }
+}
+
+/// Builder of [`StrictVal::List`] values.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct StrictValListBuilder {
+    items: Vec<StrictVal>,
+}
+
+impl StrictValListBuilder {
+    pub fn new() -> Self { StrictValListBuilder::default() }
+
+    pub fn push(mut self, val: impl Into<StrictVal>) -> Self {
+        self.items.push(val.into());
+        self
+    }
+
+    pub fn build(self) -> StrictVal { StrictVal::List(self.items) }
+}
+
+/// Builder of [`StrictVal::Set`] values.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct StrictValSetBuilder {
+    items: Vec<StrictVal>,
+}
+
+impl StrictValSetBuilder {
+    pub fn new() -> Self { StrictValSetBuilder::default() }
+
+    /// Adds an item to the set, unless the set already contains it.
+    pub fn insert(mut self, val: impl Into<StrictVal>) -> Self {
+        let val = val.into();
+        if !self.items.contains(&val) {
+            self.items.push(val);
+        }
+        self
+    }
+
+    pub fn build(self) -> StrictVal { StrictVal::Set(self.items) }
+}
+
+/// Builder of [`StrictVal::Map`] values.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct StrictValMapBuilder {
+    items: Vec<(StrictVal, StrictVal)>,
+}
+
+impl StrictValMapBuilder {
+    pub fn new() -> Self { StrictValMapBuilder::default() }
+
+    /// Adds an entry to the map. If the map already has the key, its value is replaced.
+    pub fn entry(mut self, key: impl Into<StrictVal>, val: impl Into<StrictVal>) -> Self {
+        let (key, val) = (key.into(), val.into());
+        match self.items.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => *v = val,
+            None => self.items.push((key, val)),
+        }
+        self
+    }
+
+    pub fn build(self) -> StrictVal { StrictVal::Map(self.items) }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn build() {
+        let strct = StrictValBuilder::new()
+            .field("name", "Some name")
+            .field(fname!("ticker"), "TICK")
+            .field("precision", 2u8)
+            .field("name", "Other name")
+            .build();
+        assert_eq!(strct, ston!(name "Other name", ticker "TICK", precision 2u8));
+        assert_eq!(strct.to_string(), r#"name "Other name", ticker "TICK", precision 2"#);
+
+        assert_eq!(StrictValUnionBuilder::new().variant("some", 5u8), StrictVal::some(5u8));
+        assert_eq!(StrictValUnionBuilder::new().variant(1, ()), StrictVal::union(1, ()));
+
+        let list = StrictValListBuilder::new().push(1u8).push(1u8).push(2u8).build();
+        assert_eq!(list, svlist!([1u8, 1u8, 2u8]));
+
+        let set = StrictValSetBuilder::new().insert(1u8).insert(1u8).insert(2u8).build();
+        assert_eq!(set, StrictVal::set([1u8, 2u8]));
+
+        let map =
+            StrictValMapBuilder::new().entry("a", 1u8).entry("b", 2u8).entry("a", 3u8).build();
+        assert_eq!(map, StrictVal::map([("a", 3u8), ("b", 2u8)]));
+    }
+}
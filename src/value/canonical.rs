@@ -0,0 +1,249 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Schema-less canonical binary encoding of strict values, used for their hashing and ordering.
+
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+use amplify::num::apfloat::Float;
+use sha2::{Digest, Sha256};
+
+use crate::value::{EnumTag, StrictNum};
+use crate::StrictVal;
+
+fn write_len(buf: &mut Vec<u8>, len: usize) {
+    let len = u32::try_from(len).expect("strict value collection exceeding 2^32 items");
+    buf.extend(len.to_le_bytes());
+}
+
+fn write_str(buf: &mut Vec<u8>, s: &[u8]) {
+    write_len(buf, s.len());
+    buf.extend(s);
+}
+
+fn write_words(buf: &mut Vec<u8>, words: &[u64]) {
+    for word in words {
+        buf.extend(word.to_le_bytes());
+    }
+}
+
+fn write_tag(buf: &mut Vec<u8>, tag: &EnumTag) {
+    match tag {
+        EnumTag::Name(name) => {
+            buf.push(0);
+            write_str(buf, name.as_str().as_bytes());
+        }
+        EnumTag::Ord(ord) => buf.extend([1, *ord]),
+    }
+}
+
+fn write_num(buf: &mut Vec<u8>, num: &StrictNum) {
+    match num {
+        StrictNum::Uint(v) => {
+            buf.push(0);
+            buf.extend(v.to_le_bytes());
+        }
+        StrictNum::BigUint(v) => {
+            buf.push(1);
+            write_words(buf, v.as_inner());
+        }
+        StrictNum::Int(v) => {
+            buf.push(2);
+            buf.extend(v.to_le_bytes());
+        }
+        StrictNum::BigInt(v) => {
+            buf.push(3);
+            write_words(buf, v.as_inner());
+        }
+        StrictNum::Float(v) => {
+            buf.push(4);
+            write_words(buf, v.to_bits().as_inner());
+        }
+    }
+}
+
+fn write_items<'a>(buf: &mut Vec<u8>, items: impl ExactSizeIterator<Item = &'a StrictVal>) {
+    write_len(buf, items.len());
+    for item in items {
+        write_val(buf, item);
+    }
+}
+
+fn write_val(buf: &mut Vec<u8>, val: &StrictVal) {
+    match val {
+        StrictVal::Unit => buf.push(0),
+        StrictVal::Number(num) => {
+            buf.push(1);
+            write_num(buf, num);
+        }
+        StrictVal::String(s) => {
+            buf.push(2);
+            write_str(buf, s.as_bytes());
+        }
+        StrictVal::Bytes(blob) => {
+            buf.push(3);
+            write_str(buf, blob.as_slice());
+        }
+        StrictVal::Struct(fields) => {
+            buf.push(4);
+            // Struct equality doesn't depend on the field order
+            let mut fields = fields.iter().collect::<Vec<_>>();
+            fields.sort_by_key(|(name, _)| *name);
+            write_len(buf, fields.len());
+            for (name, val) in fields {
+                write_str(buf, name.as_str().as_bytes());
+                write_val(buf, val);
+            }
+        }
+        StrictVal::Enum(tag) => {
+            buf.push(5);
+            write_tag(buf, tag);
+        }
+        StrictVal::Union(tag, val) => {
+            buf.push(6);
+            write_tag(buf, tag);
+            write_val(buf, val);
+        }
+        StrictVal::List(items) => {
+            buf.push(7);
+            write_items(buf, items.iter());
+        }
+        StrictVal::Set(items) => {
+            buf.push(8);
+            write_items(buf, items.iter());
+        }
+        StrictVal::Tuple(items) => {
+            buf.push(9);
+            write_items(buf, items.iter());
+        }
+        StrictVal::Map(items) => {
+            buf.push(10);
+            write_len(buf, items.len());
+            for (key, val) in items {
+                write_val(buf, key);
+                write_val(buf, val);
+            }
+        }
+    }
+}
+
+impl StrictVal {
+    /// Produces deterministic prefix-free binary encoding of the value, not requiring type
+    /// information.
+    ///
+    /// Each value starts with a single-byte variant tag, followed by the variant-specific data.
+    /// Strings, byte strings and collections are prefixed with their length as a 4-byte
+    /// little-endian integer; struct fields are ordered by their names. Two values are equal
+    /// if and only if their canonical encodings are equal.
+    pub fn to_canonical_bytes(&self) -> Vec<u8> {
+        let mut buf = vec![];
+        write_val(&mut buf, self);
+        buf
+    }
+}
+
+impl Hash for StrictVal {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let digest = Sha256::digest(self.to_canonical_bytes());
+        let mut hash = [0u8; 8];
+        hash.copy_from_slice(&digest[..8]);
+        state.write_u64(u64::from_le_bytes(hash));
+    }
+}
+
+impl PartialOrd for StrictVal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+/// Orders values by lexicographic comparison of their
+/// [canonical encoding](StrictVal::to_canonical_bytes).
+impl Ord for StrictVal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self == other {
+            return Ordering::Equal;
+        }
+        self.to_canonical_bytes().cmp(&other.to_canonical_bytes())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeSet, HashSet};
+
+    use super::*;
+
+    #[test]
+    fn canonical() {
+        assert_eq!(StrictVal::Unit.to_canonical_bytes(), [0]);
+        assert_eq!(StrictVal::num(1u8).to_canonical_bytes(), [1, 0, 1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(StrictVal::str("ab").to_canonical_bytes(), [2, 2, 0, 0, 0, b'a', b'b']);
+        assert_eq!(StrictVal::enumer(2).to_canonical_bytes(), [5, 1, 2]);
+        assert_eq!(StrictVal::list([StrictVal::Unit]).to_canonical_bytes(), [7, 1, 0, 0, 0, 0]);
+
+        assert_ne!(
+            StrictVal::num(1u8).to_canonical_bytes(),
+            StrictVal::num(1i8).to_canonical_bytes()
+        );
+        assert_ne!(
+            StrictVal::list([1u8]).to_canonical_bytes(),
+            StrictVal::set([1u8]).to_canonical_bytes()
+        );
+        assert_ne!(
+            StrictVal::tuple(["a", "b"]).to_canonical_bytes(),
+            StrictVal::tuple(["ab", ""]).to_canonical_bytes()
+        );
+
+        let a = ston!(name "Some name", ticker "TICK");
+        let b = ston!(ticker "TICK", name "Some name");
+        assert_eq!(a, b);
+        assert_eq!(a.to_canonical_bytes(), b.to_canonical_bytes());
+    }
+
+    #[test]
+    fn collections() {
+        let vals = [
+            StrictVal::Unit,
+            StrictVal::num(1u8),
+            StrictVal::num(2u8),
+            StrictVal::num(-1i8),
+            StrictVal::num(0.5f64),
+            StrictVal::str("a"),
+            StrictVal::bytes([1u8]),
+            ston!(name "Some name", ticker "TICK"),
+            StrictVal::some(1u8),
+            StrictVal::map([("a", 1u8)]),
+        ];
+        let set = vals.iter().cloned().collect::<BTreeSet<_>>();
+        assert_eq!(set.len(), vals.len());
+        assert!(set.contains(&ston!(ticker "TICK", name "Some name")));
+        assert_eq!(set.first(), Some(&StrictVal::Unit));
+
+        let hashed = vals.iter().cloned().collect::<HashSet<_>>();
+        assert_eq!(hashed.len(), vals.len());
+        assert!(hashed.contains(&ston!(ticker "TICK", name "Some name")));
+
+        assert!(StrictVal::num(1u8) < StrictVal::num(2u8));
+        assert!(StrictVal::Unit < StrictVal::num(0u8));
+        assert_eq!(StrictVal::str("a").cmp(&StrictVal::str("a")), Ordering::Equal);
+    }
+}
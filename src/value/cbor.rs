@@ -0,0 +1,285 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! CBOR representation of strict values.
+//!
+//! Values are mapped to CBOR data items as follows: unit to `null`; numbers to integers, floats
+//! or bignums (tags 2 and 3); strings to text; byte strings to bytes; enums to their variant
+//! name (or tag number); unions to an array of the variant name (or tag number) and the
+//! variant value; tuples, lists and sets to arrays; structs and maps to maps.
+
+use ciborium::value::Value;
+
+use crate::typify::{self, TypedVal};
+use crate::value::interop::{bignum_from_bytes, bignum_to_bytes, num_from_i128, ReshapeError};
+use crate::value::{Blob, EnumTag, StrictNum};
+use crate::{SemId, StrictVal, TypeSystem};
+
+const TAG_POS_BIGNUM: u64 = 2;
+const TAG_NEG_BIGNUM: u64 = 3;
+
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum CborError {
+    /// invalid CBOR data: {0}.
+    Decode(String),
+
+    /// CBOR data item {0} can't be represented as a strict value.
+    Unsupported(String),
+
+    /// invalid field or variant name `{0}`.
+    InvalidName(String),
+
+    /// type {0} is not a part of the type system.
+    TypeAbsent(SemId),
+
+    /// CBOR data doesn't match type {0}.
+    TypeMismatch(SemId),
+
+    #[from]
+    #[display(inner)]
+    Typify(typify::Error),
+}
+
+impl From<ReshapeError> for CborError {
+    fn from(err: ReshapeError) -> Self {
+        match err {
+            ReshapeError::TypeAbsent(sem_id) => CborError::TypeAbsent(sem_id),
+            ReshapeError::TypeMismatch(sem_id) => CborError::TypeMismatch(sem_id),
+            ReshapeError::InvalidName(name) => CborError::InvalidName(name),
+        }
+    }
+}
+
+fn tag_to_cbor(tag: &EnumTag) -> Value {
+    match tag {
+        EnumTag::Name(name) => Value::Text(name.to_string()),
+        EnumTag::Ord(ord) => Value::Integer((*ord).into()),
+    }
+}
+
+fn num_to_cbor(num: &StrictNum) -> Value {
+    match num {
+        StrictNum::Uint(v) => Value::Integer((*v).into()),
+        StrictNum::Int(v) => Value::Integer((*v).into()),
+        StrictNum::Float(_) => Value::Float(num.to_f64_lossy()),
+        num => match (num.to_u64(), num.to_i64(), bignum_to_bytes(num)) {
+            (Some(v), _, _) => Value::Integer(v.into()),
+            (_, Some(v), _) => Value::Integer(v.into()),
+            (_, _, Some((false, bytes))) => {
+                Value::Tag(TAG_POS_BIGNUM, Box::new(Value::Bytes(bytes)))
+            }
+            (_, _, Some((true, bytes))) => {
+                Value::Tag(TAG_NEG_BIGNUM, Box::new(Value::Bytes(bytes)))
+            }
+            (None, None, None) => unreachable!("big integers always have byte representation"),
+        },
+    }
+}
+
+fn to_cbor_value(val: &StrictVal) -> Value {
+    match val {
+        StrictVal::Unit => Value::Null,
+        StrictVal::Number(num) => num_to_cbor(num),
+        StrictVal::String(s) => Value::Text(s.clone()),
+        StrictVal::Bytes(blob) => Value::Bytes(blob.to_vec()),
+        StrictVal::Enum(tag) => tag_to_cbor(tag),
+        StrictVal::Union(tag, val) => Value::Array(vec![tag_to_cbor(tag), to_cbor_value(val)]),
+        StrictVal::Tuple(items) | StrictVal::List(items) | StrictVal::Set(items) => {
+            Value::Array(items.iter().map(to_cbor_value).collect())
+        }
+        StrictVal::Struct(fields) => Value::Map(
+            fields
+                .iter()
+                .map(|(name, val)| (Value::Text(name.to_string()), to_cbor_value(val)))
+                .collect(),
+        ),
+        StrictVal::Map(items) => Value::Map(
+            items.iter().map(|(key, val)| (to_cbor_value(key), to_cbor_value(val))).collect(),
+        ),
+    }
+}
+
+fn from_cbor_value(cbor: Value) -> Result<StrictVal, CborError> {
+    Ok(match cbor {
+        Value::Null => StrictVal::Unit,
+        Value::Bool(v) => StrictVal::bool(v),
+        Value::Integer(int) => StrictVal::Number(num_from_i128(int.into())),
+        Value::Float(v) => StrictVal::num(v),
+        Value::Text(s) => StrictVal::String(s),
+        Value::Bytes(data) => StrictVal::Bytes(Blob(data)),
+        Value::Tag(tag @ (TAG_POS_BIGNUM | TAG_NEG_BIGNUM), inner) => match *inner {
+            Value::Bytes(data) => bignum_from_bytes(tag == TAG_NEG_BIGNUM, &data)
+                .map(StrictVal::Number)
+                .ok_or_else(|| CborError::Unsupported(format!("bignum {tag} {data:02x?}")))?,
+            other => return Err(CborError::Unsupported(format!("tag {tag} {other:?}"))),
+        },
+        Value::Tag(tag, inner) => {
+            return Err(CborError::Unsupported(format!("tag {tag} {inner:?}")))
+        }
+        Value::Array(items) => {
+            StrictVal::List(items.into_iter().map(from_cbor_value).collect::<Result<_, _>>()?)
+        }
+        Value::Map(items) => StrictVal::Map(
+            items
+                .into_iter()
+                .map(|(key, val)| Ok((from_cbor_value(key)?, from_cbor_value(val)?)))
+                .collect::<Result<_, CborError>>()?,
+        ),
+        other => return Err(CborError::Unsupported(format!("{other:?}"))),
+    })
+}
+
+fn write_cbor(cbor: &Value) -> Vec<u8> {
+    let mut buf = vec![];
+    ciborium::ser::into_writer(cbor, &mut buf).expect("in-memory CBOR serialization");
+    buf
+}
+
+fn read_cbor(bytes: &[u8]) -> Result<Value, CborError> {
+    ciborium::de::from_reader(bytes).map_err(|err| CborError::Decode(err.to_string()))
+}
+
+impl StrictVal {
+    /// Encodes the value as CBOR data item.
+    pub fn to_cbor(&self) -> Vec<u8> { write_cbor(&to_cbor_value(self)) }
+
+    /// Decodes the value from CBOR data item without using type information. Since CBOR doesn't
+    /// distinguish enums, unions, tuples, sets and structs, CBOR arrays are decoded as lists, CBOR
+    /// maps as maps and text as strings; use [`TypeSystem::from_cbor`] to decode them according
+    /// to a type.
+    pub fn from_cbor(bytes: &[u8]) -> Result<StrictVal, CborError> {
+        from_cbor_value(read_cbor(bytes)?)
+    }
+}
+
+impl TypeSystem {
+    /// Encodes typed value as CBOR data item.
+    pub fn to_cbor(&self, val: &TypedVal) -> Vec<u8> { val.as_val().to_cbor() }
+
+    /// Decodes CBOR data item as a value of type `sem_id`.
+    pub fn from_cbor(&self, sem_id: SemId, bytes: &[u8]) -> Result<TypedVal, CborError> {
+        let val = self.reshape(from_cbor_value(read_cbor(bytes)?)?, sem_id)?;
+        Ok(self.typify(val, sem_id)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::num::{i1024, u1024};
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::value::test_helpers::test_system;
+
+    #[test]
+    fn variants() {
+        let vals = [
+            (StrictVal::Unit, "f6"),
+            (StrictVal::num(10u8), "0a"),
+            (StrictVal::num(-10i8), "29"),
+            (StrictVal::num(1.5f64), "f93e00"),
+            (StrictVal::str("a"), "6161"),
+            (StrictVal::bytes([1u8, 2]), "420102"),
+            (StrictVal::enumer("a"), "6161"),
+            (StrictVal::enumer(1), "01"),
+            (StrictVal::union("some", 1u8), "8264736f6d6501"),
+            (StrictVal::tuple([1u8, 2]), "820102"),
+            (StrictVal::list([1u8, 2]), "820102"),
+            (StrictVal::set([1u8, 2]), "820102"),
+            (ston!(a 1u8, b 2u8), "a2616101616202"),
+            (StrictVal::map([(1u8, 2u8)]), "a10102"),
+            (
+                StrictVal::Number(StrictNum::BigUint(u1024::from(1u128 << 64))),
+                "c249010000000000000000",
+            ),
+            (
+                StrictVal::Number(StrictNum::BigInt(i1024::from(-(1i128 << 64) - 1))),
+                "c349010000000000000000",
+            ),
+        ];
+        for (val, hex) in vals {
+            let cbor = val.to_cbor();
+            let cbor_hex = cbor.iter().map(|b| format!("{b:02x}")).collect::<String>();
+            assert_eq!(cbor_hex, hex, "{val}");
+        }
+
+        assert_eq!(StrictVal::from_cbor(&[0xf5]), Ok(StrictVal::bool(true)));
+        assert_eq!(
+            StrictVal::from_cbor(&StrictVal::union("some", 1u8).to_cbor()),
+            Ok(StrictVal::List(vec![StrictVal::str("some"), StrictVal::num(1u8)]))
+        );
+        assert!(matches!(StrictVal::from_cbor(&[0x82, 0x01]), Err(CborError::Decode(_))));
+        assert!(matches!(StrictVal::from_cbor(&[0xc1, 0x01]), Err(CborError::Unsupported(_))));
+    }
+
+    #[test]
+    fn typed() {
+        let sys = test_system();
+        let id = sys.to_sem_id("TestLib.Nominal").unwrap();
+        let val = ston!(ticker "TICK", name "Some name", precision svenum!(twoDecimals));
+        let typed = sys.typify(val, id).unwrap();
+        let types = sys.as_types();
+
+        let cbor = types.to_cbor(&typed);
+        assert_eq!(types.from_cbor(id, &cbor), Ok(typed));
+        assert!(matches!(
+            types.from_cbor(id, &StrictVal::num(1u8).to_cbor()),
+            Err(CborError::Typify(_))
+        ));
+        assert_eq!(
+            types.from_cbor(SemId::from([0u8; 32]), &cbor),
+            Err(CborError::TypeAbsent(SemId::from([0u8; 32])))
+        );
+    }
+
+    fn value() -> impl Strategy<Value = StrictVal> {
+        let leaf = prop_oneof![
+            Just(StrictVal::Unit),
+            any::<u64>().prop_map(StrictVal::num),
+            (i64::MIN..0).prop_map(StrictVal::num),
+            any::<u128>()
+                .prop_filter("bignum", |v| *v > u64::MAX as u128)
+                .prop_map(|v| StrictVal::Number(StrictNum::BigUint(u1024::from(v)))),
+            any::<i128>()
+                .prop_filter("bignum", |v| *v < i64::MIN as i128)
+                .prop_map(|v| StrictVal::Number(StrictNum::BigInt(i1024::from(v)))),
+            any::<f64>().prop_filter("not NaN", |v| !v.is_nan()).prop_map(StrictVal::num),
+            "[a-zA-Z0-9 _.,-]{0,8}".prop_map(StrictVal::String),
+            vec(any::<u8>(), 0..8).prop_map(|v| StrictVal::Bytes(Blob(v))),
+        ];
+        leaf.prop_recursive(3, 32, 4, |inner| {
+            prop_oneof![
+                vec(inner.clone(), 0..4).prop_map(StrictVal::List),
+                vec((inner.clone(), inner), 0..4).prop_map(StrictVal::Map),
+            ]
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn roundtrip(val in value()) {
+            prop_assert_eq!(StrictVal::from_cbor(&val.to_cbor()), Ok(val));
+        }
+    }
+}
@@ -22,7 +22,28 @@
 
 //! Converts strict values from/to non-STON value serialization formats (JSON, YAML, TOML etc).
 
-use crate::StrictVal;
+use crate::typify::{self, TypedVal};
+use crate::{SemId, StrictVal, TypeSystem};
+
+#[derive(Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum ConvertError {
+    /// invalid JSON: {0}
+    #[from]
+    Json(serde_json::Error),
+
+    /// invalid YAML: {0}
+    #[from]
+    Yaml(serde_yaml::Error),
+
+    /// invalid TOML: {0}
+    #[from]
+    Toml(toml::de::Error),
+
+    #[from]
+    #[display(inner)]
+    Typify(typify::Error),
+}
 
 impl From<serde_json::Value> for StrictVal {
     fn from(json: serde_json::Value) -> Self {
@@ -33,7 +54,7 @@ impl From<serde_json::Value> for StrictVal {
             Value::Bool(v) => StrictVal::bool(v),
             Value::Number(no) if no.is_u64() => StrictVal::num(no.as_u64().unwrap()),
             Value::Number(no) if no.is_i64() => StrictVal::num(no.as_i64().unwrap()),
-            Value::Number(no) if no.is_f64() => todo!(),
+            Value::Number(no) if no.is_f64() => StrictVal::num(no.as_f64().unwrap()),
             Value::Number(_) => {
                 unreachable!()
             }
@@ -46,6 +67,53 @@ impl From<serde_json::Value> for StrictVal {
     }
 }
 
+impl StrictVal {
+    /// Parses the value from a JSON string without using type information; use
+    /// [`TypeSystem::from_json_str`] to parse the value according to a type.
+    pub fn from_json_str(json: &str) -> Result<StrictVal, serde_json::Error> {
+        serde_json::from_str::<serde_json::Value>(json).map(StrictVal::from)
+    }
+
+    pub fn to_json_str(&self) -> Result<String, serde_json::Error> { serde_json::to_string(self) }
+
+    pub fn to_json_str_pretty(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Parses the value from a YAML string without using type information; use
+    /// [`TypeSystem::typify_yaml`] to parse the value according to a type.
+    pub fn from_yaml_str(yaml: &str) -> Result<StrictVal, ConvertError> {
+        Ok(serde_yaml::from_str::<serde_yaml::Value>(yaml).map(StrictVal::from)?)
+    }
+
+    /// Parses the value from a TOML string without using type information; use
+    /// [`TypeSystem::typify_toml`] to parse the value according to a type.
+    pub fn from_toml_str(toml: &str) -> Result<StrictVal, ConvertError> {
+        Ok(toml::from_str::<toml::Value>(toml).map(StrictVal::from)?)
+    }
+}
+
+impl TypeSystem {
+    /// Parses JSON string as a value of type `sem_id`.
+    pub fn from_json_str(&self, sem_id: SemId, json: &str) -> Result<TypedVal, ConvertError> {
+        Ok(self.typify(StrictVal::from_json_str(json)?, sem_id)?)
+    }
+
+    pub fn to_json_str(&self, val: &TypedVal) -> Result<String, ConvertError> {
+        Ok(val.as_val().to_json_str()?)
+    }
+
+    /// Parses YAML string as a value of type `sem_id`. Structure fields may go in any order.
+    pub fn typify_yaml(&self, yaml: &str, sem_id: SemId) -> Result<TypedVal, ConvertError> {
+        Ok(self.typify(StrictVal::from_yaml_str(yaml)?, sem_id)?)
+    }
+
+    /// Parses TOML string as a value of type `sem_id`. Structure fields may go in any order.
+    pub fn typify_toml(&self, toml: &str, sem_id: SemId) -> Result<TypedVal, ConvertError> {
+        Ok(self.typify(StrictVal::from_toml_str(toml)?, sem_id)?)
+    }
+}
+
 impl From<serde_yaml::Value> for StrictVal {
     fn from(yaml: serde_yaml::Value) -> Self {
         use serde_yaml::Value;
@@ -86,3 +154,80 @@ impl From<toml::Value> for StrictVal {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::value::test_helpers::test_system;
+
+    #[test]
+    fn json_str() {
+        assert_eq!(
+            StrictVal::from_json_str(r#"{"a": [1, -2, 1.5, null]}"#).unwrap(),
+            StrictVal::map([(
+                "a",
+                StrictVal::list([
+                    StrictVal::num(1u8),
+                    StrictVal::num(-2i8),
+                    StrictVal::num(1.5f64),
+                    StrictVal::Unit
+                ])
+            )])
+        );
+        assert!(StrictVal::from_json_str("{").is_err());
+        assert_eq!(StrictVal::list([1u8, 2]).to_json_str().unwrap(), "[1,2]");
+        assert_eq!(StrictVal::list([1u8]).to_json_str_pretty().unwrap(), "[\n  1\n]");
+    }
+
+    #[test]
+    fn typed() {
+        let sys = test_system();
+        let id = sys.to_sem_id("TestLib.Nominal").unwrap();
+        let val = ston!(ticker "TICK", name "Some name", precision svenum!(twoDecimals));
+        let typed = sys.typify(val, id).unwrap();
+        let types = sys.as_types();
+
+        let json = types.to_json_str(&typed).unwrap();
+        assert_eq!(json, r#"{"ticker":["TICK"],"name":"Some name","precision":"twoDecimals"}"#);
+        assert_eq!(types.from_json_str(id, &json).unwrap(), typed);
+        assert!(matches!(types.from_json_str(id, "1"), Err(ConvertError::Typify(_))));
+        assert!(matches!(types.from_json_str(id, "{"), Err(ConvertError::Json(_))));
+    }
+
+    #[test]
+    fn yaml_toml() {
+        let sys = test_system();
+        let id = sys.to_sem_id("TestLib.Nominal").unwrap();
+        let types = sys.as_types();
+        let expected = sys
+            .typify(ston!(ticker "TICK", name "Some name", precision svenum!(twoDecimals)), id)
+            .unwrap();
+
+        for yaml in [
+            "ticker: TICK\nname: Some name\nprecision: twoDecimals\n",
+            "precision: twoDecimals\nticker: TICK\nname: Some name\n",
+            "name: Some name\nprecision: twoDecimals\nticker: TICK\n",
+        ] {
+            assert_eq!(types.typify_yaml(yaml, id).unwrap(), expected, "{yaml}");
+        }
+        let toml = "precision = \"twoDecimals\"\nname = \"Some name\"\nticker = \"TICK\"\n";
+        assert_eq!(types.typify_toml(toml, id).unwrap(), expected);
+
+        let yaml = "ticker: TICK\nname: Some name\ndetails: Details\nprecision: twoDecimals\n";
+        assert_eq!(
+            StrictVal::from_yaml_str(yaml).unwrap(),
+            StrictVal::map([
+                ("ticker", "TICK"),
+                ("name", "Some name"),
+                ("details", "Details"),
+                ("precision", "twoDecimals")
+            ])
+        );
+        assert!(matches!(
+            types.typify_yaml(yaml, id),
+            Err(ConvertError::Typify(typify::Error::ExtraField(field))) if field.as_str() == "details"
+        ));
+        assert!(matches!(StrictVal::from_yaml_str("a: [1"), Err(ConvertError::Yaml(_))));
+        assert!(matches!(StrictVal::from_toml_str("a = "), Err(ConvertError::Toml(_))));
+    }
+}
@@ -22,6 +22,9 @@
 
 //! Reification module: reads & writes strict values from binary strict encodings.
 
+use std::fmt::{self, Display, Formatter};
+use std::io;
+
 use amplify::ascii::AsciiString;
 use amplify::confinement::{
     Confined, LargeAscii, LargeBlob, LargeString, MediumAscii, MediumBlob, MediumString,
@@ -37,6 +40,10 @@ use crate::typify::{TypeSpec, TypedVal};
 use crate::value::Blob;
 use crate::{SemId, StrictVal, Ty, TypeRef, TypeSystem};
 
+/// Maximal number of collection items for which memory is allocated before reading them, such
+/// that collection length read from a malformed data can't exhaust the memory.
+const PREALLOC_LIMIT: usize = 0x1000;
+
 #[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
 #[display(doc_comments)]
 pub enum Error {
@@ -56,6 +63,118 @@ pub enum Error {
 
     /// data provided to reify operation are not entirely consumed during deserialization.
     NotEntirelyConsumed,
+
+    /// repeated value {1} in set `{0}`.
+    RepeatedSetValue(TypeSpec, StrictVal),
+
+    /// {inner} (at byte offset {offset}).
+    AtOffset { offset: u64, inner: Box<Error> },
+
+    /// {inner} (in `{path}`).
+    WithContext { path: FieldPath, inner: Box<Error> },
+}
+
+impl Error {
+    /// Returns byte offset in the input stream at which the error has happened, if the error was
+    /// produced by one of the offset-aware deserialization methods.
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            Error::AtOffset { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+
+    /// Strips byte offset information from the error, if present.
+    pub fn without_offset(self) -> Error {
+        match self {
+            Error::AtOffset { inner, .. } => *inner,
+            err => err,
+        }
+    }
+
+    /// Returns path to the field at which the error has happened, if the error has happened
+    /// inside a nested type.
+    pub fn path(&self) -> Option<&FieldPath> {
+        match self {
+            Error::AtOffset { inner, .. } => inner.path(),
+            Error::WithContext { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Strips both byte offset and field path information from the error, returning the
+    /// original error.
+    pub fn without_context(self) -> Error {
+        match self {
+            Error::AtOffset { inner, .. } | Error::WithContext { inner, .. } => {
+                inner.without_context()
+            }
+            err => err,
+        }
+    }
+
+    /// Prepends `segment` to the path of the field at which the error has happened.
+    fn in_segment(self, segment: String) -> Error {
+        match self {
+            Error::AtOffset { offset, inner } => Error::AtOffset {
+                offset,
+                inner: Box::new(inner.in_segment(segment)),
+            },
+            Error::WithContext { mut path, inner } => {
+                path.0.insert(0, segment);
+                Error::WithContext { path, inner }
+            }
+            err => Error::WithContext {
+                path: FieldPath(vec![segment]),
+                inner: Box::new(err),
+            },
+        }
+    }
+}
+
+/// Path to a nested field, consisting of segments in dot-notation for struct and tuple fields
+/// (`.name`), bracket-notation for list, set, map and array items (`[index]`) and angle-bracket
+/// notation for union variants (`<variant>`).
+#[derive(Wrapper, Clone, Eq, PartialEq, Hash, Debug, Default, From)]
+#[wrapper(Deref)]
+pub struct FieldPath(Vec<String>);
+
+impl Display for FieldPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let path = self.0.concat();
+        f.write_str(path.strip_prefix('.').unwrap_or(&path))
+    }
+}
+
+/// Wrapper around [`ReadRaw`] implementor which counts the number of bytes consumed from the
+/// stream, such that decoding errors can report the position at which they have happened.
+#[derive(Debug)]
+pub struct ByteOffset<R: ReadRaw> {
+    inner: R,
+    offset: u64,
+}
+
+impl<R: ReadRaw> ByteOffset<R> {
+    pub fn new(inner: R) -> Self { ByteOffset { inner, offset: 0 } }
+
+    /// Number of bytes read from the underlying reader so far.
+    pub fn offset(&self) -> u64 { self.offset }
+
+    pub fn unbox(self) -> R { self.inner }
+}
+
+impl<R: ReadRaw> ReadRaw for ByteOffset<R> {
+    fn read_raw<const MAX_LEN: usize>(&mut self, len: usize) -> io::Result<Vec<u8>> {
+        let data = self.inner.read_raw::<MAX_LEN>(len)?;
+        self.offset += data.len() as u64;
+        Ok(data)
+    }
+
+    fn read_raw_array<const LEN: usize>(&mut self) -> io::Result<[u8; LEN]> {
+        let data = self.inner.read_raw_array::<LEN>()?;
+        self.offset += LEN as u64;
+        Ok(data)
+    }
 }
 
 impl SymbolicSys {
@@ -65,10 +184,25 @@ impl SymbolicSys {
         data: &[u8],
     ) -> Result<TypedVal, Error> {
         let spec = spec.into();
+        #[cfg(feature = "tracing")]
+        let _span =
+            tracing::debug_span!("strict_deserialize_type", %spec, len = data.len()).entered();
         let sem_id = self.to_sem_id(spec.clone()).ok_or(Error::TypeAbsent(spec))?;
         self.as_types().strict_deserialize_type(sem_id, data)
     }
 
+    /// Deserializes data like [`Self::strict_deserialize_type`], reporting errors together with
+    /// the byte offset at which they have happened (see [`Error::AtOffset`]).
+    pub fn strict_deserialize_type_offset_aware(
+        &self,
+        spec: impl Into<TypeSpec>,
+        data: &[u8],
+    ) -> Result<TypedVal, Error> {
+        let spec = spec.into();
+        let sem_id = self.to_sem_id(spec.clone()).ok_or(Error::TypeAbsent(spec))?;
+        self.as_types().strict_deserialize_type_offset_aware(sem_id, data)
+    }
+
     pub fn strict_read_type(
         &self,
         spec: impl Into<TypeSpec>,
@@ -81,50 +215,93 @@ impl SymbolicSys {
 }
 
 impl TypeSystem {
-    fn strict_read_list(
+    fn strict_read_list<R: ReadRaw>(
         &self,
         len: usize,
         ty: SemId,
-        d: &mut impl ReadRaw,
+        d: &mut ByteOffset<R>,
     ) -> Result<Vec<StrictVal>, Error> {
-        let mut list = Vec::with_capacity(len);
-        for _ in 0..len {
-            let item = self.strict_read_type(ty, d)?;
+        let mut list = Vec::with_capacity(len.min(PREALLOC_LIMIT));
+        for index in 0..len {
+            let item = self.read_type(ty, d).map_err(|err| err.in_segment(format!("[{index}]")))?;
             list.push(item.val);
         }
         Ok(list)
     }
 
-    fn strict_read_map(
+    fn check_set(spec: &TypeSpec, list: Vec<StrictVal>) -> Result<Vec<StrictVal>, Error> {
+        let mut sorted = list.iter().collect::<Vec<_>>();
+        sorted.sort_unstable();
+        if let Some(pair) = sorted.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(Error::RepeatedSetValue(spec.clone(), pair[0].clone()));
+        }
+        Ok(list)
+    }
+
+    fn strict_read_map<R: ReadRaw>(
         &self,
         len: usize,
         key_ty: SemId,
         ty: SemId,
-        d: &mut impl ReadRaw,
+        d: &mut ByteOffset<R>,
     ) -> Result<Vec<(StrictVal, StrictVal)>, Error> {
-        let mut list = Vec::with_capacity(len);
-        for _ in 0..len {
-            let key = self.strict_read_type(key_ty, d)?;
-            let item = self.strict_read_type(ty, d)?;
+        let mut list = Vec::with_capacity(len.min(PREALLOC_LIMIT));
+        for index in 0..len {
+            let key =
+                self.read_type(key_ty, d).map_err(|err| err.in_segment(format!("[{index}]")))?;
+            let item = self.read_type(ty, d).map_err(|err| err.in_segment(format!("[{index}]")))?;
             list.push((key.val, item.val));
         }
         Ok(list)
     }
 
     pub fn strict_deserialize_type(&self, sem_id: SemId, data: &[u8]) -> Result<TypedVal, Error> {
-        let mut cursor = StreamReader::cursor::<MAX32>(data);
-        let ty = self.strict_read_type(sem_id, &mut cursor)?;
-        if cursor.unconfine().position() as usize != data.len() {
-            return Err(Error::NotEntirelyConsumed);
+        self.strict_deserialize_type_offset_aware(sem_id, data).map_err(Error::without_offset)
+    }
+
+    /// Deserializes data like [`Self::strict_deserialize_type`], reporting errors together with
+    /// the byte offset at which they have happened (see [`Error::AtOffset`]).
+    pub fn strict_deserialize_type_offset_aware(
+        &self,
+        sem_id: SemId,
+        data: &[u8],
+    ) -> Result<TypedVal, Error> {
+        let mut reader = ByteOffset::new(StreamReader::cursor::<MAX32>(data));
+        let ty = self.read_type(sem_id, &mut reader)?;
+        if reader.offset() as usize != data.len() {
+            return Err(Error::AtOffset {
+                offset: reader.offset(),
+                inner: Box::new(Error::NotEntirelyConsumed),
+            });
         }
         Ok(ty)
     }
 
-    pub fn strict_read_type(
+    pub fn strict_read_type(&self, sem_id: SemId, d: &mut impl ReadRaw) -> Result<TypedVal, Error> {
+        self.read_type(sem_id, &mut ByteOffset::new(d)).map_err(Error::without_offset)
+    }
+
+    fn read_type<R: ReadRaw>(
+        &self,
+        sem_id: SemId,
+        d: &mut ByteOffset<R>,
+    ) -> Result<TypedVal, Error> {
+        self.read_type_inner(sem_id, d).map_err(|err| match err {
+            err @ Error::AtOffset { .. } => err,
+            err => Error::AtOffset {
+                offset: d.offset(),
+                inner: Box::new(err),
+            },
+        })
+    }
+
+    fn read_type_inner<R: ReadRaw>(
         &self,
         sem_id: SemId,
-        mut d: &mut impl ReadRaw,
+        mut d: &mut ByteOffset<R>,
     ) -> Result<TypedVal, Error> {
+        #[cfg(feature = "tracing")]
+        tracing::trace!(%sem_id, offset = d.offset(), "reading type");
         let spec = TypeSpec::from(sem_id);
         let ty = self.find(sem_id).ok_or_else(|| Error::TypeAbsent(spec.clone()))?;
 
@@ -158,16 +335,13 @@ impl TypeSystem {
                 }
             }
             Ty::UnicodeChar => {
-                todo!()
+                return Err(Error::NotImplemented(s!(
+                    "loading unicode character into a typed value"
+                )));
             }
 
             // ASCII strings:
-            Ty::List(sem_id, sizing)
-                if self
-                    .find(*sem_id)
-                    .ok_or_else(|| Error::TypeAbsent(spec.clone()))?
-                    .is_char_enum() =>
-            {
+            Ty::List(_, sizing) if self.is_ascii_string(sem_id) => {
                 if sizing.max <= u8::MAX as u64 {
                     StrictVal::String(TinyAscii::strict_decode(&mut reader)?.to_string())
                 } else if sizing.max <= u16::MAX as u64 {
@@ -218,14 +392,19 @@ impl TypeSystem {
                 let Some((variant, ty)) = variants.by_tag(tag) else {
                     return Err(DecodeError::EnumTagNotKnown(spec.to_string(), tag).into());
                 };
-                let fields = self.strict_read_type(*ty, reader.unbox())?;
+                let d = reader.unbox();
+                let fields = self
+                    .read_type(*ty, d)
+                    .map_err(|err| err.in_segment(format!("<{}>", variant.name)))?;
                 StrictVal::union(variant.name.clone(), fields.val)
             }
             Ty::Tuple(reqs) => {
                 let mut fields = Vec::with_capacity(reqs.len());
                 let d = reader.unbox();
-                for ty in reqs {
-                    let checked = self.strict_read_type(*ty, d)?;
+                for (index, ty) in reqs.iter().enumerate() {
+                    let checked = self
+                        .read_type(*ty, d)
+                        .map_err(|err| err.in_segment(format!(".{index}")))?;
                     fields.push(checked.val);
                 }
                 StrictVal::tuple(fields)
@@ -234,7 +413,9 @@ impl TypeSystem {
                 let mut fields = IndexMap::with_capacity(reqs.len());
                 let d = reader.unbox();
                 for field in reqs {
-                    let checked = self.strict_read_type(field.ty, d)?;
+                    let checked = self
+                        .read_type(field.ty, d)
+                        .map_err(|err| err.in_segment(format!(".{}", field.name)))?;
                     fields.insert(field.name.clone(), checked.val);
                 }
                 StrictVal::Struct(fields)
@@ -249,8 +430,10 @@ impl TypeSystem {
             Ty::Array(ty, len) => {
                 let mut list = Vec::<StrictVal>::with_capacity(*len as usize);
                 let d = reader.unbox();
-                for _ in 0..*len {
-                    let checked = self.strict_read_type(*ty, d)?;
+                for index in 0..*len {
+                    let checked = self
+                        .read_type(*ty, d)
+                        .map_err(|err| err.in_segment(format!("[{index}]")))?;
                     list.push(checked.val);
                 }
                 StrictVal::List(list)
@@ -273,6 +456,11 @@ impl TypeSystem {
                 let string = LargeBlob::strict_decode(&mut reader)?;
                 StrictVal::Bytes(Blob(string.release()))
             }
+            Ty::List(ty, _) if ty.is_byte() => {
+                let string =
+                    Confined::<Vec<u8>, 0, { u64::MAX as usize }>::strict_decode(&mut reader)?;
+                StrictVal::Bytes(Blob(string.release()))
+            }
 
             // Unicode strings:
             Ty::List(ty, sizing) if ty.is_unicode_char() && sizing.max <= u8::MAX as u64 => {
@@ -291,6 +479,11 @@ impl TypeSystem {
                 let string = LargeString::strict_decode(&mut reader)?;
                 StrictVal::String(string.release())
             }
+            Ty::List(ty, _) if ty.is_unicode_char() => {
+                let string =
+                    Confined::<String, 0, { u64::MAX as usize }>::strict_decode(&mut reader)?;
+                StrictVal::String(string.release())
+            }
 
             // Other lists:
             Ty::List(ty, sizing) if sizing.max <= u8::MAX as u64 => {
@@ -323,36 +516,35 @@ impl TypeSystem {
                 let list = self.strict_read_list(len as usize, *ty, d)?;
                 StrictVal::List(list)
             }
-            // TODO: Find a way to check for the uniqueness of the set values
             Ty::Set(ty, sizing) if sizing.max <= u8::MAX as u64 => {
                 let len = u8::strict_decode(&mut reader)?;
                 d = reader.unbox();
                 let list = self.strict_read_list(len as usize, *ty, d)?;
-                StrictVal::Set(list)
+                StrictVal::Set(Self::check_set(&spec, list)?)
             }
             Ty::Set(ty, sizing) if sizing.max <= u16::MAX as u64 => {
                 let len = u16::strict_decode(&mut reader)?;
                 d = reader.unbox();
                 let list = self.strict_read_list(len as usize, *ty, d)?;
-                StrictVal::Set(list)
+                StrictVal::Set(Self::check_set(&spec, list)?)
             }
             Ty::Set(ty, sizing) if sizing.max <= u24::MAX.into_u64() => {
                 let len = u24::strict_decode(&mut reader)?;
                 d = reader.unbox();
                 let list = self.strict_read_list(len.into_usize(), *ty, d)?;
-                StrictVal::Set(list)
+                StrictVal::Set(Self::check_set(&spec, list)?)
             }
             Ty::Set(ty, sizing) if sizing.max <= u32::MAX as u64 => {
                 let len = u32::strict_decode(&mut reader)?;
                 d = reader.unbox();
                 let list = self.strict_read_list(len as usize, *ty, d)?;
-                StrictVal::Set(list)
+                StrictVal::Set(Self::check_set(&spec, list)?)
             }
             Ty::Set(ty, _) => {
                 let len = u64::strict_decode(&mut reader)?;
                 d = reader.unbox();
                 let list = self.strict_read_list(len as usize, *ty, d)?;
-                StrictVal::Set(list)
+                StrictVal::Set(Self::check_set(&spec, list)?)
             }
             Ty::Map(key_id, id, sizing) if sizing.max <= u8::MAX as u64 => {
                 let len = u8::strict_decode(&mut reader)?;
@@ -395,8 +587,60 @@ impl TypeSystem {
 
 #[cfg(test)]
 mod test {
+    use amplify::confinement::{TinyOrdMap, TinyOrdSet, TinyVec};
+    use amplify::ByteArray;
+    use encoding::{StrictSerialize, TypeName};
+
     use super::super::test_helpers::*;
-    // use super::*;
+    use super::*;
+    use crate::stl::{std_stl, strict_types_stl};
+    use crate::typesys::SystemBuilder;
+    use crate::LibBuilder;
+
+    #[derive(Clone, Eq, PartialEq, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Outer {
+        middle: Middle,
+    }
+    impl StrictSerialize for Outer {}
+
+    #[derive(Clone, Eq, PartialEq, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Middle {
+        inners: TinyVec<Inner>,
+    }
+
+    #[derive(Clone, Eq, PartialEq, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Inner {
+        nominal: Nominal,
+    }
+
+    #[derive(Clone, Eq, PartialEq, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Huge {
+        items: Confined<Vec<u16>, 0, { usize::MAX }>,
+    }
+
+    #[derive(Clone, Eq, PartialEq, Debug, Default)]
+    #[derive(StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Index {
+        ids: TinyOrdMap<SemId, u32>,
+        names: TinyOrdMap<TypeName, u32>,
+    }
+    impl StrictSerialize for Index {}
+
+    #[derive(Clone, Eq, PartialEq, Debug, Default)]
+    #[derive(StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Tags {
+        tags: TinyOrdSet<u8>,
+    }
 
     #[test]
     fn typify() {
@@ -406,7 +650,131 @@ mod test {
         let checked = sys.typify(value, "TestLib.Nominal").unwrap();
         assert_eq!(
             format!("{}", checked.val),
-            r#"name "Some name", ticker "TICK", precision twoDecimals"#
+            r#"ticker "TICK", name "Some name", precision twoDecimals"#
         );
+
+        let nominal = Nominal::with("TICK", "Some name", 2);
+        let mut data = nominal.to_strict_serialized::<{ usize::MAX }>().unwrap().release();
+        let len = data.len() as u64;
+
+        data.push(0);
+        let err = sys.strict_deserialize_type_offset_aware("TestLib.Nominal", &data).unwrap_err();
+        assert_eq!(err.offset(), Some(len));
+        assert_eq!(err.without_offset(), Error::NotEntirelyConsumed);
+        data.pop();
+
+        // Corrupting `precision` enum tag, which is the last byte of the serialized data
+        *data.last_mut().unwrap() = 5;
+        let err = sys.strict_deserialize_type_offset_aware("TestLib.Nominal", &data).unwrap_err();
+        assert_eq!(err.offset(), Some(len));
+        assert!(matches!(err.without_context(), Error::Decode(DecodeError::EnumTagNotKnown(..))));
+        let err = sys.strict_deserialize_type("TestLib.Nominal", &data).unwrap_err();
+        assert_eq!(err.offset(), None);
+    }
+
+    #[test]
+    fn nested_path() {
+        let std = std_stl();
+        let st = strict_types_stl();
+        let lib = LibBuilder::new("TestLib", [std.to_dependency(), st.to_dependency()])
+            .transpile::<Outer>()
+            .compile()
+            .unwrap();
+        let sys = SystemBuilder::new()
+            .import(lib)
+            .unwrap()
+            .import(std)
+            .unwrap()
+            .import(st)
+            .unwrap()
+            .finalize()
+            .unwrap();
+
+        let inner = Inner {
+            nominal: Nominal::with("TICK", "Some name", 2),
+        };
+        let outer = Outer {
+            middle: Middle {
+                inners: TinyVec::try_from(vec![inner.clone(), inner]).unwrap(),
+            },
+        };
+        let mut data = outer.to_strict_serialized::<{ usize::MAX }>().unwrap().release();
+        // Corrupting `precision` enum tag of the second item
+        *data.last_mut().unwrap() = 5;
+
+        let err = sys.strict_deserialize_type("TestLib.Outer", &data).unwrap_err();
+        assert_eq!(err.path().unwrap().to_string(), "middle.inners[1].nominal.precision");
+        assert!(err.to_string().contains("(in `middle.inners[1].nominal.precision`)"));
+        assert!(matches!(err.without_context(), Error::Decode(DecodeError::EnumTagNotKnown(..))));
+    }
+
+    #[test]
+    fn map_keys() {
+        let std = std_stl();
+        let st = strict_types_stl();
+        let lib = LibBuilder::new("TestLib", [std.to_dependency(), st.to_dependency()])
+            .transpile::<Index>()
+            .compile()
+            .unwrap();
+        let sys = SystemBuilder::new()
+            .import(lib)
+            .unwrap()
+            .import(std)
+            .unwrap()
+            .import(st)
+            .unwrap()
+            .finalize()
+            .unwrap();
+
+        let index = Index {
+            ids: TinyOrdMap::from_iter_checked([
+                (SemId::unit(), 1),
+                (Ty::<SemId>::U32.sem_id_unnamed(), 2),
+            ]),
+            names: TinyOrdMap::from_iter_checked([(tn!("Alpha"), 3), (tn!("Beta"), 4)]),
+        };
+        let data = index.to_strict_serialized::<{ usize::MAX }>().unwrap();
+        let typed = sys.strict_deserialize_type("TestLib.Index", data.as_slice()).unwrap();
+        let StrictVal::Struct(fields) = &typed.val else {
+            panic!("not a struct")
+        };
+        let StrictVal::Map(ids) = &fields[&fname!("ids")] else {
+            panic!("not a map")
+        };
+        let unit = StrictVal::tuple([StrictVal::bytes(SemId::unit().to_byte_array())]);
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&(unit, StrictVal::num(1u32))));
+
+        let encoded = sys.as_types().strict_serialize_value::<{ usize::MAX }>(&typed).unwrap();
+        assert_eq!(encoded, data);
+    }
+
+    #[test]
+    fn malformed_len() {
+        let lib = LibBuilder::new("TestLib", []).transpile::<Huge>().compile().unwrap();
+        let sys = SystemBuilder::new().import(lib).unwrap().finalize().unwrap();
+
+        // Found by fuzzing: collection length must not be used to pre-allocate memory
+        let err = sys.strict_deserialize_type("TestLib.Huge", &[0xFF; 8]).unwrap_err();
+        assert!(matches!(err.without_context(), Error::Decode(DecodeError::Io(_))));
+        let err = sys
+            .strict_deserialize_type("TestLib.Huge", &[0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 0])
+            .unwrap_err();
+        assert!(matches!(err.without_context(), Error::Decode(DecodeError::Io(_))));
+    }
+
+    #[test]
+    fn repeated_set_value() {
+        let lib = LibBuilder::new("TestLib", []).transpile::<Tags>().compile().unwrap();
+        let sys = SystemBuilder::new().import(lib).unwrap().finalize().unwrap();
+
+        let typed = sys.strict_deserialize_type("TestLib.Tags", &[2, 7, 1]).unwrap();
+        assert_eq!(typed.val.unwrap_struct("tags").try_as_set().unwrap().len(), 2);
+
+        let err = sys.strict_deserialize_type("TestLib.Tags", &[3, 7, 1, 7]).unwrap_err();
+        assert!(matches!(
+            err.without_context(),
+            Error::RepeatedSetValue(_, val) if val == StrictVal::num(7u8)
+        ));
     }
 }
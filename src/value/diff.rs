@@ -0,0 +1,561 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Computing differences between strict values and applying them as patches.
+
+use amplify::confinement::{TinyBlob, TinyString};
+
+use crate::value::{Blob, KeyStep, Path, PathError, Step, StrictNum};
+use crate::StrictVal;
+
+/// Single change between two strict values.
+///
+/// Entries are produced by [`StrictVal::diff`] in the order they must be applied: indexes of
+/// list and set items account for all the preceding entries.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub enum DiffEntry {
+    /// Value at the path was replaced.
+    Changed {
+        path: Path,
+        from: StrictVal,
+        to: StrictVal,
+    },
+
+    /// Struct field, collection item or map entry was added. Map entries are inserted before
+    /// the first entry with a greater key.
+    Added { path: Path, val: StrictVal },
+
+    /// Struct field, collection item or map entry was removed.
+    Removed { path: Path, val: StrictVal },
+}
+
+impl DiffEntry {
+    pub fn path(&self) -> &Path {
+        match self {
+            DiffEntry::Changed { path, .. }
+            | DiffEntry::Added { path, .. }
+            | DiffEntry::Removed { path, .. } => path,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum PatchError {
+    /// invalid patch path - {0}
+    #[from]
+    Path(PathError),
+
+    /// value at `{0}` doesn't match the one expected by the patch.
+    ValueMismatch(Path),
+
+    /// value at `{0}` already exists.
+    AlreadyExists(Path),
+
+    /// patch can't add or remove the root value.
+    RootValue,
+}
+
+fn key_step(key: &StrictVal) -> Option<KeyStep> {
+    Some(match key {
+        StrictVal::Number(StrictNum::Uint(no)) => KeyStep::Number(*no as u128),
+        StrictVal::Bytes(blob) => KeyStep::TinyBlob(TinyBlob::try_from(blob.to_vec()).ok()?),
+        StrictVal::String(s) => KeyStep::TinyString(TinyString::try_from(s.clone()).ok()?),
+        _ => return None,
+    })
+}
+
+fn key_val(key: &KeyStep) -> Option<StrictVal> {
+    Some(match key {
+        KeyStep::Number(no) => StrictVal::num(u64::try_from(*no).ok()?),
+        KeyStep::TinyBlob(blob) => StrictVal::Bytes(Blob::from(blob.to_vec())),
+        KeyStep::TinyString(s) => StrictVal::String(s.to_string()),
+    })
+}
+
+fn with_step(path: &Path, step: Step) -> Path {
+    let mut path = path.clone();
+    path.push(step).expect("path exceeding 64k steps");
+    path
+}
+
+/// Edit operation for list items.
+enum Edit {
+    Keep,
+    Replace,
+    Insert,
+    Delete,
+}
+
+/// Maximal number of cells in the edit distance table; larger lists are reported as changed
+/// as a whole.
+const MAX_EDIT_CELLS: usize = 1 << 20;
+
+/// Computes minimal sequence of item edits using edit distance, where keeping equal items
+/// costs nothing and any other operation costs one. Returns `None` if the lists differ in too
+/// many items to be compared within [`MAX_EDIT_CELLS`].
+fn edits(a: &[StrictVal], b: &[StrictVal]) -> Option<Vec<Edit>> {
+    let prefix = a.iter().zip(b).take_while(|(a, b)| a == b).count();
+    let (a, b) = (&a[prefix..], &b[prefix..]);
+    let suffix = a.iter().rev().zip(b.iter().rev()).take_while(|(a, b)| a == b).count();
+    let (a, b) = (&a[..a.len() - suffix], &b[..b.len() - suffix]);
+
+    let (n, m) = (a.len(), b.len());
+    if (n + 1).saturating_mul(m + 1) > MAX_EDIT_CELLS {
+        return None;
+    }
+    let mut cost = vec![vec![0usize; m + 1]; n + 1];
+    for (i, row) in cost.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in cost[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=n {
+        for j in 1..=m {
+            let replace = cost[i - 1][j - 1] + (a[i - 1] != b[j - 1]) as usize;
+            cost[i][j] = replace.min(cost[i - 1][j] + 1).min(cost[i][j - 1] + 1);
+        }
+    }
+
+    let mut edits = Vec::with_capacity(prefix + n.max(m) + suffix);
+    edits.extend((0..suffix).map(|_| Edit::Keep));
+    let (mut i, mut j) = (n, m);
+    while i > 0 || j > 0 {
+        if i > 0 && j > 0 && a[i - 1] == b[j - 1] && cost[i][j] == cost[i - 1][j - 1] {
+            edits.push(Edit::Keep);
+            i -= 1;
+            j -= 1;
+        } else if i > 0 && cost[i][j] == cost[i - 1][j] + 1 {
+            edits.push(Edit::Delete);
+            i -= 1;
+        } else if j > 0 && cost[i][j] == cost[i][j - 1] + 1 {
+            edits.push(Edit::Insert);
+            j -= 1;
+        } else {
+            edits.push(Edit::Replace);
+            i -= 1;
+            j -= 1;
+        }
+    }
+    edits.extend((0..prefix).map(|_| Edit::Keep));
+    edits.reverse();
+    Some(edits)
+}
+
+fn diff_items(path: &Path, a: &[StrictVal], b: &[StrictVal], diff: &mut Vec<DiffEntry>) -> bool {
+    let Some(edits) = edits(a, b) else {
+        return false;
+    };
+    let (mut i, mut j) = (0usize, 0usize);
+    for edit in edits {
+        let item_path = with_step(path, Step::Index(j as u32));
+        match edit {
+            Edit::Keep => {
+                i += 1;
+                j += 1;
+            }
+            Edit::Replace => {
+                diff_at(&item_path, &a[i], &b[j], diff);
+                i += 1;
+                j += 1;
+            }
+            Edit::Insert => {
+                diff.push(DiffEntry::Added {
+                    path: item_path,
+                    val: b[j].clone(),
+                });
+                j += 1;
+            }
+            Edit::Delete => {
+                diff.push(DiffEntry::Removed {
+                    path: item_path,
+                    val: a[i].clone(),
+                });
+                i += 1;
+            }
+        }
+    }
+    true
+}
+
+/// Computes order of map keys after applying the patch removing keys of `a` absent from `b`
+/// and adding the new keys of `b` as [`StrictVal::apply_patch`] does.
+fn patched_keys(a_keys: &[KeyStep], b_keys: &[KeyStep]) -> Vec<KeyStep> {
+    let mut keys = a_keys.iter().filter(|key| b_keys.contains(key)).cloned().collect::<Vec<_>>();
+    for key in b_keys.iter().filter(|key| !a_keys.contains(key)) {
+        let pos = keys.iter().position(|k| k > key).unwrap_or(keys.len());
+        keys.insert(pos, key.clone());
+    }
+    keys
+}
+
+fn diff_map(
+    path: &Path,
+    a: &[(StrictVal, StrictVal)],
+    b: &[(StrictVal, StrictVal)],
+    diff: &mut Vec<DiffEntry>,
+) -> bool {
+    let keys = |map: &[(StrictVal, StrictVal)]| {
+        map.iter().map(|(key, _)| key_step(key)).collect::<Option<Vec<_>>>()
+    };
+    let (Some(a_keys), Some(b_keys)) = (keys(a), keys(b)) else {
+        return false;
+    };
+    // Map equality depends on the entry order, which must be reproduced by the patch
+    if patched_keys(&a_keys, &b_keys) != b_keys {
+        return false;
+    }
+
+    for (key, (_, val)) in a_keys.iter().zip(a) {
+        let key_path = with_step(path, Step::Key(key.clone()));
+        match b_keys.iter().position(|k| k == key) {
+            Some(pos) => diff_at(&key_path, val, &b[pos].1, diff),
+            None => diff.push(DiffEntry::Removed {
+                path: key_path,
+                val: val.clone(),
+            }),
+        }
+    }
+    for (key, (_, val)) in b_keys.iter().zip(b) {
+        if !a_keys.contains(key) {
+            diff.push(DiffEntry::Added {
+                path: with_step(path, Step::Key(key.clone())),
+                val: val.clone(),
+            });
+        }
+    }
+    true
+}
+
+fn diff_at(path: &Path, a: &StrictVal, b: &StrictVal, diff: &mut Vec<DiffEntry>) {
+    if a == b {
+        return;
+    }
+    match (a, b) {
+        (StrictVal::Struct(a), StrictVal::Struct(b)) => {
+            for (name, val) in a {
+                let field_path = with_step(path, Step::NamedField(name.clone()));
+                match b.get(name) {
+                    Some(other) => diff_at(&field_path, val, other, diff),
+                    None => diff.push(DiffEntry::Removed {
+                        path: field_path,
+                        val: val.clone(),
+                    }),
+                }
+            }
+            for (name, val) in b.iter().filter(|(name, _)| !a.contains_key(*name)) {
+                diff.push(DiffEntry::Added {
+                    path: with_step(path, Step::NamedField(name.clone())),
+                    val: val.clone(),
+                });
+            }
+        }
+        (StrictVal::Tuple(a), StrictVal::Tuple(b)) if a.len() == b.len() && a.len() <= 256 => {
+            for (no, (a, b)) in a.iter().zip(b).enumerate() {
+                diff_at(&with_step(path, Step::UnnamedField(no as u8)), a, b, diff);
+            }
+        }
+        (StrictVal::List(a), StrictVal::List(b)) | (StrictVal::Set(a), StrictVal::Set(b))
+            if diff_items(path, a, b, diff) => {}
+        (StrictVal::Map(a), StrictVal::Map(b)) if diff_map(path, a, b, diff) => {}
+        _ => diff.push(DiffEntry::Changed {
+            path: path.clone(),
+            from: a.clone(),
+            to: b.clone(),
+        }),
+    }
+}
+
+impl StrictVal {
+    /// Computes list of changes converting `self` into `other`.
+    ///
+    /// Structs, tuples and maps having string, byte string or unsigned integer keys are
+    /// compared field by field; lists and sets are compared item by item, producing the minimal
+    /// number of item insertions, removals and replacements. All other values are reported
+    /// as [`DiffEntry::Changed`] as a whole, as well as maps which entries can't be brought
+    /// into the order of `other` by the patch, and long lists differing in lots of items.
+    pub fn diff(&self, other: &StrictVal) -> Vec<DiffEntry> {
+        let mut diff = vec![];
+        diff_at(&Path::new(), self, other, &mut diff);
+        diff
+    }
+
+    /// Applies changes produced by [`StrictVal::diff`] to a copy of the value.
+    ///
+    /// # Errors
+    ///
+    /// If a change path doesn't exist in the value, or if the value it removes or replaces is
+    /// different from the one present at the path.
+    pub fn apply_patch(&self, patches: &[DiffEntry]) -> Result<StrictVal, PatchError> {
+        let mut val = self.clone();
+        for entry in patches {
+            val.apply_entry(entry)?;
+        }
+        Ok(val)
+    }
+
+    fn apply_entry(&mut self, entry: &DiffEntry) -> Result<(), PatchError> {
+        let path = entry.path();
+        if let DiffEntry::Changed { from, to, .. } = entry {
+            let val = path.resolve_mut(self)?;
+            if val != from {
+                return Err(PatchError::ValueMismatch(path.clone()));
+            }
+            *val = to.clone();
+            return Ok(());
+        }
+
+        let (last, parent) = path.split_last().ok_or(PatchError::RootValue)?;
+        let parent = self.at_path_mut(parent)?;
+        match (entry, parent, last) {
+            (DiffEntry::Added { val, .. }, StrictVal::Struct(fields), Step::NamedField(name)) => {
+                if fields.contains_key(name) {
+                    return Err(PatchError::AlreadyExists(path.clone()));
+                }
+                fields.insert(name.clone(), val.clone());
+            }
+            (
+                DiffEntry::Added { val, .. },
+                StrictVal::List(items) | StrictVal::Set(items),
+                Step::Index(idx),
+            ) => {
+                if *idx as usize > items.len() {
+                    return Err(PathError::CollectionIndexOutOfBounds(*idx, items.len()).into());
+                }
+                items.insert(*idx as usize, val.clone());
+            }
+            (DiffEntry::Added { val, .. }, StrictVal::Map(items), Step::Key(key)) => {
+                if items.iter().any(|(k, _)| key.has_match(k)) {
+                    return Err(PatchError::AlreadyExists(path.clone()));
+                }
+                let pos = items
+                    .iter()
+                    .position(|(k, _)| key_step(k).is_some_and(|k| k > *key))
+                    .unwrap_or(items.len());
+                let Some(key) = key_val(key) else {
+                    return Err(PatchError::ValueMismatch(path.clone()));
+                };
+                items.insert(pos, (key, val.clone()));
+            }
+            (DiffEntry::Removed { val, .. }, StrictVal::Struct(fields), Step::NamedField(name)) => {
+                match fields.get(name) {
+                    None => return Err(PathError::UnknownFieldName(name.clone()).into()),
+                    Some(v) if v != val => return Err(PatchError::ValueMismatch(path.clone())),
+                    Some(_) => {}
+                }
+                fields.shift_remove(name);
+            }
+            (
+                DiffEntry::Removed { val, .. },
+                StrictVal::List(items) | StrictVal::Set(items),
+                Step::Index(idx),
+            ) => {
+                match items.get(*idx as usize) {
+                    None => {
+                        return Err(PathError::CollectionIndexOutOfBounds(*idx, items.len()).into())
+                    }
+                    Some(v) if v != val => return Err(PatchError::ValueMismatch(path.clone())),
+                    Some(_) => {}
+                }
+                items.remove(*idx as usize);
+            }
+            (DiffEntry::Removed { val, .. }, StrictVal::Map(items), Step::Key(key)) => {
+                let Some(pos) = items.iter().position(|(k, _)| key.has_match(k)) else {
+                    return Err(PathError::UnknownKey(key.clone()).into());
+                };
+                if &items[pos].1 != val {
+                    return Err(PatchError::ValueMismatch(path.clone()));
+                }
+                items.remove(pos);
+            }
+            (_, parent, _) => {
+                return Err(PathError::TypeMismatch(last.clone(), parent.clone()).into())
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn roundtrip(a: &StrictVal, b: &StrictVal) -> Vec<DiffEntry> {
+        let patches = a.diff(b);
+        assert_eq!(a.apply_patch(&patches).unwrap(), *b);
+        assert_eq!(b.apply_patch(&b.diff(a)).unwrap(), *a);
+        patches
+    }
+
+    #[test]
+    fn diff_struct() {
+        let a = ston!(name "Some name", ticker "TICK", precision 8u8, meta svlist!([1u8, 2u8]));
+        let b = ston!(name "Other name", ticker "TICK", decimals 2u8, meta svlist!([1u8, 2u8]));
+        assert!(a.diff(&a).is_empty());
+
+        let patches = roundtrip(&a, &b);
+        assert_eq!(patches, vec![
+            DiffEntry::Changed {
+                path: Path::with(Step::NamedField(fname!("name"))),
+                from: StrictVal::str("Some name"),
+                to: StrictVal::str("Other name"),
+            },
+            DiffEntry::Removed {
+                path: Path::with(Step::NamedField(fname!("precision"))),
+                val: StrictVal::num(8u8),
+            },
+            DiffEntry::Added {
+                path: Path::with(Step::NamedField(fname!("decimals"))),
+                val: StrictVal::num(2u8),
+            },
+        ]);
+        assert_eq!(patches[0].path().to_string(), "name");
+    }
+
+    #[test]
+    fn diff_list() {
+        let a = svlist!([1u8, 2u8, 3u8, 4u8, 5u8]);
+        let b = svlist!([0u8, 1u8, 3u8, 4u8, 6u8, 7u8]);
+        let patches = roundtrip(&a, &b);
+        assert_eq!(patches.len(), 4);
+        assert_eq!(patches[0], DiffEntry::Added {
+            path: Path::with(Step::Index(0)),
+            val: StrictVal::num(0u8),
+        });
+        assert_eq!(patches[1], DiffEntry::Removed {
+            path: Path::with(Step::Index(2)),
+            val: StrictVal::num(2u8),
+        });
+
+        roundtrip(&svlist!([1u8]), &StrictVal::list(Vec::<StrictVal>::new()));
+        roundtrip(&StrictVal::set([1u8, 2u8]), &StrictVal::set([2u8, 3u8]));
+        roundtrip(&svlist!(["a", "b"]), &StrictVal::set(["a", "b"]));
+    }
+
+    #[test]
+    fn diff_nested() {
+        let a = StrictVal::list([
+            ston!(name "a", vals svlist!([1u8, 2u8])),
+            ston!(name "b", vals svlist!([3u8])),
+        ]);
+        let b = StrictVal::list([
+            ston!(name "a", vals svlist!([1u8, 2u8, 3u8])),
+            ston!(name "b", vals svlist!([3u8])),
+        ]);
+        let patches = roundtrip(&a, &b);
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].path().to_string(), "[0].vals[2]");
+
+        roundtrip(&StrictVal::tuple([1u8, 2u8]), &StrictVal::tuple([1u8, 3u8]));
+        roundtrip(&StrictVal::tuple([1u8, 2u8]), &StrictVal::tuple([1u8]));
+        roundtrip(&StrictVal::some(1u8), &StrictVal::none());
+        roundtrip(&StrictVal::num(1u8), &StrictVal::str("1"));
+    }
+
+    #[test]
+    fn diff_map() {
+        let a = StrictVal::map([("a", 1u8), ("b", 2u8)]);
+        let b = StrictVal::map([("b", 3u8), ("c", 4u8)]);
+        let patches = roundtrip(&a, &b);
+        assert_eq!(patches.len(), 3);
+        assert_eq!(patches[1].path().to_string(), r#"["b"]"#);
+
+        let a = StrictVal::map([(1u8, "a")]);
+        let b = StrictVal::map([(1u8, "b"), (2u8, "c")]);
+        assert_eq!(roundtrip(&a, &b).len(), 2);
+
+        let a = StrictVal::map([(StrictVal::enumer("a"), 1u8)]);
+        let b = StrictVal::map([(StrictVal::enumer("a"), 2u8)]);
+        assert_eq!(roundtrip(&a, &b).len(), 1);
+
+        // Same entries in a different order
+        let a = StrictVal::map([("a", 1u8), ("b", 2u8)]);
+        let b = StrictVal::map([("b", 2u8), ("a", 1u8)]);
+        assert_ne!(a, b);
+        assert!(matches!(roundtrip(&a, &b)[..], [DiffEntry::Changed { .. }]));
+
+        // Added entry which can't be placed by the key order
+        let a = StrictVal::map([("b", 2u8)]);
+        let b = StrictVal::map([("b", 2u8), ("a", 1u8)]);
+        assert!(matches!(roundtrip(&a, &b)[..], [DiffEntry::Changed { .. }]));
+        let b = StrictVal::map([("c", 3u8), ("b", 2u8), ("d", 4u8)]);
+        assert!(matches!(roundtrip(&a, &b)[..], [DiffEntry::Changed { .. }]));
+        let b = StrictVal::map([("a", 1u8), ("b", 2u8), ("c", 3u8)]);
+        assert_eq!(roundtrip(&a, &b).len(), 2);
+    }
+
+    #[test]
+    fn diff_long_list() {
+        let a = StrictVal::list((0..50_000u32).map(StrictVal::num));
+        let mut items = (0..50_000u32).map(StrictVal::num).collect::<Vec<_>>();
+        items[100] = StrictVal::num(0u8);
+        let b = StrictVal::list(items.clone());
+        let patches = roundtrip(&a, &b);
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].path().to_string(), "[100]");
+
+        items.reverse();
+        let b = StrictVal::list(items);
+        assert!(matches!(roundtrip(&a, &b)[..], [DiffEntry::Changed { .. }]));
+    }
+
+    #[test]
+    fn patch_errors() {
+        let a = StrictVal::struc([("name", "a")]);
+        let b = StrictVal::struc([("name", "b")]);
+        let patches = a.diff(&b);
+        assert_eq!(
+            b.apply_patch(&patches),
+            Err(PatchError::ValueMismatch(patches[0].path().clone()))
+        );
+        assert_eq!(
+            StrictVal::num(1u8).apply_patch(&patches),
+            Err(PatchError::Path(PathError::TypeMismatch(
+                Step::NamedField(fname!("name")),
+                StrictVal::num(1u8)
+            )))
+        );
+        assert_eq!(
+            a.apply_patch(&[DiffEntry::Added {
+                path: Path::new(),
+                val: StrictVal::num(0u8)
+            }]),
+            Err(PatchError::RootValue)
+        );
+        assert_eq!(
+            a.apply_patch(&[DiffEntry::Added {
+                path: Path::with(Step::NamedField(fname!("name"))),
+                val: StrictVal::num(0u8)
+            }]),
+            Err(PatchError::AlreadyExists(Path::with(Step::NamedField(fname!("name")))))
+        );
+        assert_eq!(
+            svlist!([1u8]).apply_patch(&[DiffEntry::Removed {
+                path: Path::with(Step::Index(1)),
+                val: StrictVal::num(1u8)
+            }]),
+            Err(PatchError::Path(PathError::CollectionIndexOutOfBounds(1, 1)))
+        );
+    }
+}
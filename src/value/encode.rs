@@ -20,15 +20,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::io;
+//! Serialization module: writes strict values into binary strict encoding and computes the
+//! size of their encoded form.
+
+use std::io::{self, Write};
 
 use amplify::confinement::{Confined, ConfinedBlob};
 use amplify::num::u24;
+use amplify::IoError;
 use encoding::{
     Primitive, SerializeError, Sizing, StrictEncode, StrictSerialize, StrictType, TypeName,
     TypedWrite, WriteRaw,
 };
 
+use crate::typesys::UnknownType;
 use crate::typify::TypedVal;
 use crate::value::{EnumTag, StrictNum};
 use crate::{SemId, StrictVal, Ty, TypeSystem};
@@ -75,7 +80,22 @@ impl TypeSystem {
         typed: &TypedVal,
         writer: &mut impl io::Write,
     ) -> Result<(), io::Error> {
-        self.strict_write_val(&typed.val, typed.orig.id, writer)
+        self.strict_write_val(&typed.val, typed.orig.id, &mut PlainWriter(writer))
+    }
+
+    /// Writes the value like [`Self::strict_write_value`], passing the data to the `writer`
+    /// in chunks via [`ChunkedWriter`]. Collection items are buffered one by one, and byte
+    /// strings are split into [`STREAMING_CHUNK_SIZE`] pieces, such that the memory used for
+    /// buffering doesn't depend on the size of the encoded value.
+    pub fn strict_write_streaming(
+        &self,
+        typed: &TypedVal,
+        writer: &mut impl io::Write,
+    ) -> Result<(), Error> {
+        let mut chunked = ChunkedWriter::new(writer);
+        self.strict_write_val(&typed.val, typed.orig.id, &mut chunked)?;
+        chunked.flush()?;
+        Ok(())
     }
 
     #[deprecated(since = "2.7.2", note = "use strict_write_value instead")]
@@ -84,14 +104,14 @@ impl TypeSystem {
         typed: &TypedVal,
         writer: &mut impl io::Write,
     ) -> Result<(), io::Error> {
-        self.strict_write_val(&typed.val, typed.orig.id, writer)
+        self.strict_write_val(&typed.val, typed.orig.id, &mut PlainWriter(writer))
     }
 
     fn strict_write_val(
         &self,
         val: &StrictVal,
         sem_id: SemId,
-        writer: &mut impl io::Write,
+        writer: &mut impl ValueWrite,
     ) -> Result<(), io::Error> {
         let ty = self.find(sem_id).expect("typified with some other TypeSystem");
         self.strict_write_ty(val, ty, writer)
@@ -101,7 +121,7 @@ impl TypeSystem {
         &self,
         val: &StrictVal,
         ty: &Ty<SemId>,
-        writer: &mut impl io::Write,
+        writer: &mut impl ValueWrite,
     ) -> Result<(), io::Error> {
         match (val, ty) {
             (StrictVal::Unit, Ty::Primitive(prim)) => {
@@ -135,12 +155,19 @@ impl TypeSystem {
             }
             (StrictVal::Bytes(vec), Ty::Array(_, len)) => {
                 debug_assert_eq!(vec.len(), *len as usize);
-                writer.write_all(vec)?;
+                writer.write_bytes(vec)?;
             }
             (StrictVal::String(s), Ty::Array(_, len)) => {
                 debug_assert_eq!(s.len(), *len as usize);
                 writer.write_all(s.as_bytes())?;
             }
+            (StrictVal::List(list), Ty::Array(sem_id, len)) => {
+                debug_assert_eq!(list.len(), *len as usize);
+                for val in list {
+                    self.strict_write_val(val, *sem_id, writer)?;
+                    writer.element_done()?;
+                }
+            }
 
             (StrictVal::Tuple(vals), Ty::Tuple(fields)) => {
                 debug_assert_eq!(vals.len(), fields.len());
@@ -184,7 +211,7 @@ impl TypeSystem {
                 let bytes_count = sizing.byte_size();
                 let le_bytes = &s.len().to_le_bytes()[0..bytes_count];
                 writer.write_all(le_bytes)?;
-                writer.write_all(s)?;
+                writer.write_bytes(s)?;
             }
             (StrictVal::List(list), Ty::List(sem_id, sizing))
             | (StrictVal::Set(list), Ty::Set(sem_id, sizing)) => {
@@ -193,6 +220,7 @@ impl TypeSystem {
                 writer.write_all(le_bytes)?;
                 for val in list {
                     self.strict_write_val(val, *sem_id, writer)?;
+                    writer.element_done()?;
                 }
             }
             (StrictVal::Map(list), Ty::Map(key_id, sem_id, sizing)) => {
@@ -202,6 +230,7 @@ impl TypeSystem {
                 for (key, val) in list {
                     self.strict_write_val(key, *key_id, writer)?;
                     self.strict_write_val(val, *sem_id, writer)?;
+                    writer.element_done()?;
                 }
             }
 
@@ -224,7 +253,202 @@ impl TypeSystem {
     }
 }
 
-trait SizingExt {
+/// Errors encoding strict values or computing their encoded length.
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum Error {
+    #[display(inner)]
+    #[from(io::Error)]
+    Io(IoError),
+
+    /// type {0} is absent from the type system.
+    TypeAbsent(SemId),
+
+    /// value `{0}` doesn't match type {1}.
+    ValueMismatch(StrictVal, SemId),
+}
+
+impl TypeSystem {
+    /// Computes the number of bytes the value takes when strict-encoded as the type `sem_id`,
+    /// without encoding the value.
+    ///
+    /// # Errors
+    ///
+    /// If the value doesn't match the type or some of the types nested into it are absent from
+    /// the type system.
+    pub fn encoded_len(&self, val: &StrictVal, sem_id: SemId) -> Result<u64, Error> {
+        let ty = self.find(sem_id).ok_or(Error::TypeAbsent(sem_id))?;
+        let mismatch = || Error::ValueMismatch(val.clone(), sem_id);
+        let prefixed = |sizing: &Sizing, len: usize| sizing.byte_size() as u64 + len as u64;
+        let all = |items: &mut dyn Iterator<Item = (&StrictVal, SemId)>| {
+            let mut len = 0u64;
+            for (val, sem_id) in items {
+                len += self.encoded_len(val, sem_id)?;
+            }
+            Ok::<_, Error>(len)
+        };
+
+        Ok(match (val, ty) {
+            (StrictVal::Unit, Ty::Primitive(prim)) if *prim == Primitive::UNIT => 0,
+            (
+                StrictVal::Number(
+                    StrictNum::Uint(_)
+                    | StrictNum::BigUint(_)
+                    | StrictNum::Int(_)
+                    | StrictNum::BigInt(_),
+                ),
+                Ty::Primitive(prim),
+            ) => prim.byte_size() as u64,
+
+            (StrictVal::String(s), Ty::UnicodeChar) => s.len() as u64,
+            (StrictVal::Bytes(vec), Ty::Array(_, len)) if vec.len() == *len as usize => *len as u64,
+            (StrictVal::String(s), Ty::Array(_, len)) if s.len() == *len as usize => *len as u64,
+            (StrictVal::List(list), Ty::Array(sem_id, len)) if list.len() == *len as usize => {
+                all(&mut list.iter().map(|val| (val, *sem_id)))?
+            }
+
+            (StrictVal::Tuple(vals), Ty::Tuple(fields)) if vals.len() == fields.len() => {
+                all(&mut vals.iter().zip(fields.iter().copied()))?
+            }
+            (StrictVal::Struct(vals), Ty::Struct(fields)) if vals.len() == fields.len() => {
+                let mut len = 0u64;
+                for field in fields {
+                    let val = vals.get(&field.name).ok_or_else(mismatch)?;
+                    len += self.encoded_len(val, field.ty)?;
+                }
+                len
+            }
+            (StrictVal::Enum(EnumTag::Ord(tag)), Ty::Enum(variants)) if variants.has_tag(*tag) => 1,
+            (StrictVal::Enum(EnumTag::Name(tag)), Ty::Enum(variants))
+                if variants.tag_by_name(tag).is_some() =>
+            {
+                1
+            }
+            (StrictVal::Union(EnumTag::Ord(tag), val), Ty::Union(variants)) => {
+                let sem_id = variants.ty_by_tag(*tag).ok_or_else(mismatch)?;
+                1 + self.encoded_len(val, *sem_id)?
+            }
+            (StrictVal::Union(EnumTag::Name(tag), val), Ty::Union(variants)) => {
+                let (_, sem_id) = variants.by_name(tag).ok_or_else(mismatch)?;
+                1 + self.encoded_len(val, *sem_id)?
+            }
+
+            (StrictVal::String(s), Ty::List(_, sizing)) => prefixed(sizing, s.len()),
+            (StrictVal::Bytes(s), Ty::List(_, sizing)) => prefixed(sizing, s.len()),
+            (StrictVal::List(list), Ty::List(sem_id, sizing))
+            | (StrictVal::Set(list), Ty::Set(sem_id, sizing)) => {
+                prefixed(sizing, 0) + all(&mut list.iter().map(|val| (val, *sem_id)))?
+            }
+            (StrictVal::Map(list), Ty::Map(key_id, sem_id, sizing)) => {
+                prefixed(sizing, 0)
+                    + all(&mut list.iter().flat_map(|(key, val)| [(key, *key_id), (val, *sem_id)]))?
+            }
+
+            (StrictVal::String(s), Ty::Tuple(fields)) if s.is_ascii() => {
+                let absent = |err: UnknownType| Error::TypeAbsent(err.0);
+                if !self.is_rstring(fields).map_err(absent)? {
+                    return Err(mismatch());
+                }
+                let (_, sizing) =
+                    self.rstring_sizing(fields).map_err(absent)?.ok_or_else(mismatch)?;
+                prefixed(&sizing, s.len())
+            }
+
+            _ => return Err(mismatch()),
+        })
+    }
+}
+
+/// Size of chunks used by [`TypeSystem::strict_write_streaming`].
+pub const STREAMING_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Writer receiving strict-encoded values, which is notified about the boundaries of
+/// collection items.
+trait ValueWrite: io::Write {
+    /// Called after each of collection items or byte string chunks is written.
+    fn element_done(&mut self) -> io::Result<()>;
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        for chunk in bytes.chunks(STREAMING_CHUNK_SIZE) {
+            self.write_all(chunk)?;
+            self.element_done()?;
+        }
+        Ok(())
+    }
+}
+
+struct PlainWriter<'w, W: io::Write>(&'w mut W);
+
+impl<W: io::Write> io::Write for PlainWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.write(buf) }
+    fn flush(&mut self) -> io::Result<()> { self.0.flush() }
+}
+
+impl<W: io::Write> ValueWrite for PlainWriter<'_, W> {
+    fn element_done(&mut self) -> io::Result<()> { Ok(()) }
+    fn write_bytes(&mut self, bytes: &[u8]) -> io::Result<()> { self.0.write_all(bytes) }
+}
+
+/// Writer adapter buffering the data until the buffer reaches the chunk size and a complete
+/// element is written (see [`ChunkedWriter::end_element`]); only then the buffer is passed to
+/// the inner writer.
+#[derive(Debug)]
+pub struct ChunkedWriter<W: io::Write> {
+    inner: W,
+    buf: Vec<u8>,
+    chunk_size: usize,
+}
+
+impl<W: io::Write> ChunkedWriter<W> {
+    pub fn new(inner: W) -> Self { Self::with_chunk_size(inner, STREAMING_CHUNK_SIZE) }
+
+    pub fn with_chunk_size(inner: W, chunk_size: usize) -> Self {
+        ChunkedWriter {
+            inner,
+            buf: Vec::with_capacity(chunk_size),
+            chunk_size,
+        }
+    }
+
+    /// Marks the end of a complete element, passing the buffered data to the inner writer if
+    /// the buffer has reached the chunk size.
+    pub fn end_element(&mut self) -> io::Result<()> {
+        if self.buf.len() >= self.chunk_size {
+            self.write_buf()?;
+        }
+        Ok(())
+    }
+
+    /// Writes all the buffered data and returns the inner writer.
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.flush()?;
+        Ok(self.inner)
+    }
+
+    fn write_buf(&mut self) -> io::Result<()> {
+        self.inner.write_all(&self.buf)?;
+        self.buf.clear();
+        Ok(())
+    }
+}
+
+impl<W: io::Write> io::Write for ChunkedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_buf()?;
+        self.inner.flush()
+    }
+}
+
+impl<W: io::Write> ValueWrite for ChunkedWriter<W> {
+    fn element_done(&mut self) -> io::Result<()> { self.end_element() }
+}
+
+pub(crate) trait SizingExt {
     fn byte_size(&self) -> usize;
 }
 
@@ -239,3 +463,222 @@ impl SizingExt for Sizing {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use amplify::confinement::{LargeVec, MediumBlob};
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::stl::{std_stl, strict_types_stl};
+    use crate::typesys::{SymbolicSys, SystemBuilder};
+    use crate::value::test_helpers::{test_system, Nominal};
+    use crate::LibBuilder;
+
+    #[derive(Clone, Eq, PartialEq, Debug, Default)]
+    #[derive(StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Bulk {
+        nums: LargeVec<u32>,
+        data: MediumBlob,
+    }
+
+    impl StrictSerialize for Bulk {}
+
+    #[derive(Clone, Eq, PartialEq, Debug, Default)]
+    #[derive(StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib")]
+    struct Triple {
+        items: [u16; 3],
+    }
+
+    impl StrictSerialize for Triple {}
+
+    fn bulk_system() -> SymbolicSys {
+        let std = std_stl();
+        let st = strict_types_stl();
+        let lib = LibBuilder::new("TestLib", [std.to_dependency(), st.to_dependency()])
+            .transpile::<Bulk>()
+            .compile()
+            .unwrap();
+        SystemBuilder::new().import(lib).unwrap().import(std).unwrap().finalize().unwrap()
+    }
+
+    /// Writer recording the number and size of the writes.
+    #[derive(Default)]
+    struct Recorder {
+        data: Vec<u8>,
+        writes: usize,
+        max_write: usize,
+    }
+
+    impl io::Write for Recorder {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.data.extend_from_slice(buf);
+            self.writes += 1;
+            self.max_write = self.max_write.max(buf.len());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    }
+
+    #[test]
+    fn streaming() {
+        let sys = bulk_system();
+        let bulk = Bulk {
+            nums: LargeVec::try_from((0..100_000u32).collect::<Vec<_>>()).unwrap(),
+            data: MediumBlob::try_from(vec![0xA5; 200_000]).unwrap(),
+        };
+        let val = StrictVal::struc([
+            ("nums", StrictVal::list(bulk.nums.iter().copied())),
+            ("data", StrictVal::bytes(&bulk.data)),
+        ]);
+        let typed = sys.typify(val, "TestLib.Bulk").unwrap();
+
+        let mut recorder = Recorder::default();
+        sys.as_types().strict_write_streaming(&typed, &mut recorder).unwrap();
+        assert_eq!(recorder.data.len(), 4 + 100_000 * 4 + 3 + 200_000);
+        assert_eq!(recorder.data, bulk.to_strict_serialized::<{ usize::MAX }>().unwrap().release());
+        assert!(recorder.writes > 1);
+        assert!(recorder.max_write < 2 * STREAMING_CHUNK_SIZE);
+
+        let mut buf = vec![];
+        sys.as_types().strict_write_value(&typed, &mut buf).unwrap();
+        assert_eq!(buf, recorder.data);
+    }
+
+    #[test]
+    fn encoded_len() {
+        let nominal = test_system();
+        let types = nominal.as_types();
+        let value = ston!(ticker "TICK", name "Some name", precision svenum!(2));
+        let typed = nominal.typify(value, "TestLib.Nominal").unwrap();
+        let sem_id = typed.as_orig().id;
+        let mut buf = vec![];
+        types.strict_write_value(&typed, &mut buf).unwrap();
+        assert_eq!(types.encoded_len(typed.as_val(), sem_id), Ok(buf.len() as u64));
+
+        let mut wrong = typed.as_val().clone();
+        if let StrictVal::Struct(fields) = &mut wrong {
+            fields.insert(fname!("precision"), StrictVal::str("x"));
+        }
+        let precision = nominal.to_sem_id("TestLib.Precision").unwrap();
+        assert_eq!(
+            types.encoded_len(&wrong, sem_id),
+            Err(Error::ValueMismatch(StrictVal::str("x"), precision))
+        );
+
+        let sys = bulk_system();
+        for (count, size) in [(0usize, 0usize), (1, 1), (255, 256), (1000, 70_000)] {
+            let val = StrictVal::struc([
+                ("nums", StrictVal::list(0..count as u32)),
+                ("data", StrictVal::bytes(vec![1u8; size])),
+            ]);
+            let typed = sys.typify(val, "TestLib.Bulk").unwrap();
+            let mut buf = vec![];
+            sys.as_types().strict_write_value(&typed, &mut buf).unwrap();
+            assert_eq!(
+                sys.as_types().encoded_len(typed.as_val(), typed.as_orig().id),
+                Ok(buf.len() as u64)
+            );
+            assert_eq!(buf.len() as u64, 4 + count as u64 * 4 + 3 + size as u64);
+        }
+
+        let reordered = StrictVal::struc([
+            ("data", StrictVal::bytes(vec![1u8; 10])),
+            ("nums", StrictVal::list(0..5u32)),
+        ]);
+        let bulk_id = sys.to_sem_id("TestLib.Bulk").unwrap();
+        assert_eq!(sys.as_types().encoded_len(&reordered, bulk_id), Ok(4 + 5 * 4 + 3 + 10));
+        let renamed = StrictVal::struc([
+            ("nums", StrictVal::list(0..5u32)),
+            ("blob", StrictVal::bytes(vec![1u8; 10])),
+        ]);
+        assert_eq!(
+            sys.as_types().encoded_len(&renamed, bulk_id),
+            Err(Error::ValueMismatch(renamed.clone(), bulk_id))
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn encoded_len_bulk(nums in vec(any::<u32>(), 0..300), data in vec(any::<u8>(), 0..300)) {
+            let sys = bulk_system();
+            let val = StrictVal::struc([
+                ("nums", StrictVal::list(nums.iter().copied())),
+                ("data", StrictVal::bytes(data.clone())),
+            ]);
+            let typed = sys.typify(val, "TestLib.Bulk").unwrap();
+            let mut buf = vec![];
+            sys.as_types().strict_write_value(&typed, &mut buf).unwrap();
+            prop_assert_eq!(
+                sys.as_types().encoded_len(typed.as_val(), typed.as_orig().id),
+                Ok(buf.len() as u64)
+            );
+            let bulk = Bulk {
+                nums: LargeVec::try_from(nums).unwrap(),
+                data: MediumBlob::try_from(data).unwrap(),
+            };
+            prop_assert_eq!(buf, bulk.to_strict_serialized::<{ usize::MAX }>().unwrap().release());
+        }
+
+        #[test]
+        fn encoded_len_nominal(
+            ticker in "[A-Za-z][A-Za-z0-9_]{0,15}",
+            name in "[ -~]{1,32}",
+            precision in 0u8..3,
+        ) {
+            let sys = test_system();
+            let val = ston!(ticker ticker.as_str(), name name.as_str(), precision StrictVal::enumer(precision));
+            let typed = sys.typify(val, "TestLib.Nominal").unwrap();
+            let mut buf = vec![];
+            sys.as_types().strict_write_value(&typed, &mut buf).unwrap();
+            prop_assert_eq!(
+                sys.as_types().encoded_len(typed.as_val(), typed.as_orig().id),
+                Ok(buf.len() as u64)
+            );
+            let nominal = Nominal {
+                ticker: ticker.try_into().unwrap(),
+                name: Confined::try_from(name).unwrap(),
+                precision: precision.try_into().unwrap(),
+            };
+            prop_assert_eq!(
+                buf,
+                nominal.to_strict_serialized::<{ usize::MAX }>().unwrap().release()
+            );
+        }
+    }
+
+    #[test]
+    fn array() {
+        let lib = LibBuilder::new("TestLib", []).transpile::<Triple>().compile().unwrap();
+        let sys = SystemBuilder::new().import(lib).unwrap().finalize().unwrap();
+        let triple = Triple {
+            items: [1, 2, 0x300],
+        };
+        let typed = sys
+            .typify(StrictVal::struc([("items", svlist!([1u16, 2, 0x300]))]), "TestLib.Triple")
+            .unwrap();
+        let mut buf = vec![];
+        sys.as_types().strict_write_value(&typed, &mut buf).unwrap();
+        assert_eq!(buf, triple.to_strict_serialized::<{ usize::MAX }>().unwrap().release());
+        assert_eq!(sys.as_types().encoded_len(typed.as_val(), typed.as_orig().id), Ok(6));
+    }
+
+    #[test]
+    fn chunked_writer() {
+        let mut writer = ChunkedWriter::with_chunk_size(Recorder::default(), 4);
+        writer.write_all(&[1, 2, 3]).unwrap();
+        writer.end_element().unwrap();
+        assert!(writer.inner.data.is_empty());
+        writer.write_all(&[4, 5]).unwrap();
+        writer.end_element().unwrap();
+        assert_eq!(writer.inner.data, [1, 2, 3, 4, 5]);
+        writer.write_all(&[6]).unwrap();
+        let recorder = writer.into_inner().unwrap();
+        assert_eq!(recorder.data, [1, 2, 3, 4, 5, 6]);
+        assert_eq!(recorder.writes, 2);
+    }
+}
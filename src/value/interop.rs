@@ -0,0 +1,163 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers shared by conversions of strict values into self-describing binary formats (CBOR,
+//! MessagePack).
+
+use amplify::num::{i1024, u1024};
+use encoding::{FieldName, VariantName};
+
+use crate::value::{EnumTag, StrictNum};
+use crate::{SemId, StrictVal, Ty, TypeSystem};
+
+/// Big-endian byte representation of a big integer magnitude, as used by the bignum extensions
+/// of the formats. Negative numbers `n` are represented as `-1 - n`.
+pub(crate) fn bignum_to_bytes(num: &StrictNum) -> Option<(bool, Vec<u8>)> {
+    let (neg, num) = match num {
+        StrictNum::BigUint(v) => (false, *v),
+        StrictNum::BigInt(v) if *v >= i1024::ZERO => (false, u1024::from_inner(*v.as_inner())),
+        // Bitwise inversion of `n` is equal to `-1 - n`
+        StrictNum::BigInt(v) => (true, !u1024::from_inner(*v.as_inner())),
+        _ => return None,
+    };
+    let bytes = num.as_inner().iter().rev().flat_map(|word| word.to_be_bytes()).collect::<Vec<_>>();
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    Some((neg, bytes[start..].to_vec()))
+}
+
+pub(crate) fn bignum_from_bytes(neg: bool, bytes: &[u8]) -> Option<StrictNum> {
+    if bytes.len() > 128 {
+        return None;
+    }
+    let mut buf = [0u8; 128];
+    buf[128 - bytes.len()..].copy_from_slice(bytes);
+    let mut words = [0u64; 16];
+    for (word, chunk) in words.iter_mut().rev().zip(buf.chunks_exact(8)) {
+        *word = u64::from_be_bytes(chunk.try_into().expect("fixed chunk size"));
+    }
+    let num = u1024::from_inner(words);
+    match neg {
+        false => Some(StrictNum::BigUint(num)),
+        true if words[15] >> 63 == 1 => None,
+        true => Some(StrictNum::BigInt(!i1024::from_inner(words))),
+    }
+}
+
+/// Converts integer decoded from a binary format into a strict number, using the smallest
+/// fitting variant.
+pub(crate) fn num_from_i128(int: i128) -> StrictNum {
+    if let Ok(v) = u64::try_from(int) {
+        StrictNum::Uint(v)
+    } else if let Ok(v) = i64::try_from(int) {
+        StrictNum::Int(v)
+    } else if int >= 0 {
+        StrictNum::BigUint(u1024::from(int as u128))
+    } else {
+        StrictNum::BigInt(i1024::from(int))
+    }
+}
+
+pub(crate) enum ReshapeError {
+    TypeAbsent(SemId),
+    TypeMismatch(SemId),
+    InvalidName(String),
+}
+
+fn tag_from_val(val: StrictVal, sem_id: SemId) -> Result<EnumTag, ReshapeError> {
+    match val {
+        StrictVal::String(name) => VariantName::try_from(name.clone())
+            .map(EnumTag::Name)
+            .map_err(|_| ReshapeError::InvalidName(name)),
+        StrictVal::Number(StrictNum::Uint(ord)) => {
+            u8::try_from(ord).map(EnumTag::Ord).map_err(|_| ReshapeError::TypeMismatch(sem_id))
+        }
+        _ => Err(ReshapeError::TypeMismatch(sem_id)),
+    }
+}
+
+impl TypeSystem {
+    /// Converts the value decoded from a self-describing format, which represents enums as strings
+    /// or numbers, unions, tuples and sets as lists and structs as maps, into the value matching
+    /// type `sem_id`.
+    pub(crate) fn reshape(&self, val: StrictVal, sem_id: SemId) -> Result<StrictVal, ReshapeError> {
+        let ty = self.get(sem_id).ok_or(ReshapeError::TypeAbsent(sem_id))?;
+        let reshape_all = |items: Vec<StrictVal>, ty: SemId| {
+            items.into_iter().map(|item| self.reshape(item, ty)).collect::<Result<Vec<_>, _>>()
+        };
+        Ok(match (ty, val) {
+            (Ty::Enum(_), tag @ (StrictVal::String(_) | StrictVal::Number(_))) => {
+                StrictVal::Enum(tag_from_val(tag, sem_id)?)
+            }
+            (Ty::Union(variants), StrictVal::List(items)) => {
+                let [tag, val] = <[StrictVal; 2]>::try_from(items)
+                    .map_err(|_| ReshapeError::TypeMismatch(sem_id))?;
+                let tag = tag_from_val(tag, sem_id)?;
+                let ty = match &tag {
+                    EnumTag::Name(name) => variants.ty_by_name(name),
+                    EnumTag::Ord(ord) => variants.ty_by_tag(*ord),
+                };
+                let ty = ty.ok_or(ReshapeError::TypeMismatch(sem_id))?;
+                StrictVal::Union(tag, Box::new(self.reshape(val, *ty)?))
+            }
+            (Ty::Tuple(fields), StrictVal::List(items)) => {
+                if items.len() != fields.len() {
+                    return Err(ReshapeError::TypeMismatch(sem_id));
+                }
+                StrictVal::Tuple(
+                    items
+                        .into_iter()
+                        .zip(fields.iter())
+                        .map(|(item, ty)| self.reshape(item, *ty))
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            (Ty::Struct(fields), StrictVal::Map(items)) => StrictVal::Struct(
+                items
+                    .into_iter()
+                    .map(|(key, val)| {
+                        let StrictVal::String(name) = key else {
+                            return Err(ReshapeError::TypeMismatch(sem_id));
+                        };
+                        let name = FieldName::try_from(name.clone())
+                            .map_err(|_| ReshapeError::InvalidName(name))?;
+                        let ty =
+                            fields.ty_by_name(&name).ok_or(ReshapeError::TypeMismatch(sem_id))?;
+                        Ok((name, self.reshape(val, *ty)?))
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            (Ty::Array(ty, _) | Ty::List(ty, _), StrictVal::List(items)) => {
+                StrictVal::List(reshape_all(items, *ty)?)
+            }
+            (Ty::Set(ty, _), StrictVal::List(items)) => StrictVal::Set(reshape_all(items, *ty)?),
+            (Ty::Map(key_ty, val_ty, _), StrictVal::Map(items)) => StrictVal::Map(
+                items
+                    .into_iter()
+                    .map(|(key, val)| {
+                        Ok((self.reshape(key, *key_ty)?, self.reshape(val, *val_ty)?))
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            (_, val) => val,
+        })
+    }
+}
@@ -31,24 +31,59 @@
 #[macro_use]
 mod val;
 mod path;
+mod diff;
+mod visit;
+mod canonical;
+mod build;
 pub mod ston;
+#[cfg(feature = "ston-parse")]
+pub mod parse;
 pub mod typify;
 pub mod decode;
 #[cfg(feature = "serde")]
 pub mod convert;
-mod encode;
+pub mod encode;
+#[cfg(any(feature = "cbor", feature = "msgpack"))]
+mod interop;
+#[cfg(feature = "cbor")]
+mod cbor;
+#[cfg(feature = "msgpack")]
+mod msgpack;
+#[cfg(feature = "xml")]
+mod xml;
+#[cfg(test)]
+mod arbitrary;
 
+pub use build::{
+    StrictValBuilder, StrictValListBuilder, StrictValMapBuilder, StrictValSetBuilder,
+    StrictValUnionBuilder,
+};
+#[cfg(feature = "cbor")]
+pub use cbor::CborError;
+#[cfg(feature = "serde")]
+pub use convert::ConvertError;
+pub use diff::{DiffEntry, PatchError};
+pub(crate) use encode::SizingExt;
+pub use encode::{ChunkedWriter, STREAMING_CHUNK_SIZE};
+#[cfg(feature = "msgpack")]
+pub use msgpack::MsgPackError;
 pub use path::{KeyStep, Path, PathError, Step};
-pub use val::{Blob, EnumTag, StrictNum, StrictVal};
+pub use val::{Blob, EnumTag, NumParseError, StrictNum, StrictVal};
+pub use visit::{CountingVisitor, StrictValVisitor, VisitResult};
+#[cfg(feature = "xml")]
+pub use xml::{XmlError, XML_MAX_DEPTH};
 
 #[cfg(test)]
-mod test_helpers {
+pub(crate) mod test_helpers {
+    use std::collections::HashMap;
+
     use amplify::confinement::{Confined, NonEmptyString};
     use encoding::{Ident, StrictDeserialize, StrictSerialize};
 
     use crate::stl::{std_stl, strict_types_stl};
     use crate::typesys::{SymbolicSys, SystemBuilder};
-    use crate::LibBuilder;
+    use crate::typify::TypifyOptions;
+    use crate::{LibBuilder, StrictVal};
 
     #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
     #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
@@ -100,4 +135,14 @@ mod test_helpers {
             .finalize()
             .unwrap()
     }
+
+    /// Typify options making `Nominal.precision` field optional.
+    pub fn test_defaults(sys: &SymbolicSys) -> TypifyOptions {
+        let mut opts = TypifyOptions::default();
+        opts.field_defaults.insert(
+            sys.to_sem_id("TestLib.Nominal").unwrap(),
+            HashMap::from([(fname!("precision"), StrictVal::enumer("noDecimals"))]),
+        );
+        opts
+    }
 }
@@ -0,0 +1,311 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! MessagePack representation of strict values.
+//!
+//! Values are mapped to MessagePack objects as follows: unit to `nil`; numbers to integers,
+//! floats or bignum extensions (types 2 and 3, holding big-endian magnitude); strings to
+//! strings; byte strings to binaries; enums to their variant name (or tag number); unions to an
+//! array of the variant name (or tag number) and the variant value; tuples, lists and sets to
+//! arrays; structs and maps to maps. Integers use the most compact MessagePack encoding.
+
+use rmpv::Value;
+
+use crate::typify::{self, TypedVal};
+use crate::value::interop::{bignum_from_bytes, bignum_to_bytes, num_from_i128, ReshapeError};
+use crate::value::{Blob, EnumTag, StrictNum};
+use crate::{SemId, StrictVal, TypeSystem};
+
+const EXT_POS_BIGNUM: i8 = 2;
+const EXT_NEG_BIGNUM: i8 = 3;
+
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum MsgPackError {
+    /// invalid MessagePack data: {0}.
+    Decode(String),
+
+    /// MessagePack object {0} can't be represented as a strict value.
+    Unsupported(String),
+
+    /// invalid field or variant name `{0}`.
+    InvalidName(String),
+
+    /// type {0} is not a part of the type system.
+    TypeAbsent(SemId),
+
+    /// MessagePack data doesn't match type {0}.
+    TypeMismatch(SemId),
+
+    #[from]
+    #[display(inner)]
+    Typify(typify::Error),
+}
+
+impl From<ReshapeError> for MsgPackError {
+    fn from(err: ReshapeError) -> Self {
+        match err {
+            ReshapeError::TypeAbsent(sem_id) => MsgPackError::TypeAbsent(sem_id),
+            ReshapeError::TypeMismatch(sem_id) => MsgPackError::TypeMismatch(sem_id),
+            ReshapeError::InvalidName(name) => MsgPackError::InvalidName(name),
+        }
+    }
+}
+
+fn tag_to_msgpack(tag: &EnumTag) -> Value {
+    match tag {
+        EnumTag::Name(name) => Value::from(name.to_string()),
+        EnumTag::Ord(ord) => Value::from(*ord),
+    }
+}
+
+fn num_to_msgpack(num: &StrictNum) -> Value {
+    match num {
+        StrictNum::Uint(v) => Value::from(*v),
+        StrictNum::Int(v) => Value::from(*v),
+        StrictNum::Float(_) => Value::F64(num.to_f64_lossy()),
+        num => match (num.to_u64(), num.to_i64(), bignum_to_bytes(num)) {
+            (Some(v), _, _) => Value::from(v),
+            (_, Some(v), _) => Value::from(v),
+            (_, _, Some((false, bytes))) => Value::Ext(EXT_POS_BIGNUM, bytes),
+            (_, _, Some((true, bytes))) => Value::Ext(EXT_NEG_BIGNUM, bytes),
+            (None, None, None) => unreachable!("big integers always have byte representation"),
+        },
+    }
+}
+
+fn to_msgpack_value(val: &StrictVal) -> Value {
+    match val {
+        StrictVal::Unit => Value::Nil,
+        StrictVal::Number(num) => num_to_msgpack(num),
+        StrictVal::String(s) => Value::from(s.as_str()),
+        StrictVal::Bytes(blob) => Value::Binary(blob.to_vec()),
+        StrictVal::Enum(tag) => tag_to_msgpack(tag),
+        StrictVal::Union(tag, val) => {
+            Value::Array(vec![tag_to_msgpack(tag), to_msgpack_value(val)])
+        }
+        StrictVal::Tuple(items) | StrictVal::List(items) | StrictVal::Set(items) => {
+            Value::Array(items.iter().map(to_msgpack_value).collect())
+        }
+        StrictVal::Struct(fields) => Value::Map(
+            fields
+                .iter()
+                .map(|(name, val)| (Value::from(name.to_string()), to_msgpack_value(val)))
+                .collect(),
+        ),
+        StrictVal::Map(items) => Value::Map(
+            items.iter().map(|(key, val)| (to_msgpack_value(key), to_msgpack_value(val))).collect(),
+        ),
+    }
+}
+
+fn from_msgpack_value(msgpack: Value) -> Result<StrictVal, MsgPackError> {
+    Ok(match msgpack {
+        Value::Nil => StrictVal::Unit,
+        Value::Boolean(v) => StrictVal::bool(v),
+        Value::Integer(int) => match (int.as_u64(), int.as_i64()) {
+            (Some(v), _) => StrictVal::num(v),
+            (_, Some(v)) => StrictVal::Number(num_from_i128(v as i128)),
+            (None, None) => unreachable!("MessagePack integers fit into 64 bits"),
+        },
+        Value::F32(v) => StrictVal::num(v as f64),
+        Value::F64(v) => StrictVal::num(v),
+        Value::String(s) => match s.into_str() {
+            Some(s) => StrictVal::String(s),
+            None => return Err(MsgPackError::Unsupported(s!("non-UTF-8 string"))),
+        },
+        Value::Binary(data) => StrictVal::Bytes(Blob(data)),
+        Value::Ext(ty @ (EXT_POS_BIGNUM | EXT_NEG_BIGNUM), data) => {
+            bignum_from_bytes(ty == EXT_NEG_BIGNUM, &data)
+                .map(StrictVal::Number)
+                .ok_or_else(|| MsgPackError::Unsupported(format!("bignum {ty} {data:02x?}")))?
+        }
+        Value::Ext(ty, data) => {
+            return Err(MsgPackError::Unsupported(format!("extension {ty} {data:02x?}")))
+        }
+        Value::Array(items) => {
+            StrictVal::List(items.into_iter().map(from_msgpack_value).collect::<Result<_, _>>()?)
+        }
+        Value::Map(items) => StrictVal::Map(
+            items
+                .into_iter()
+                .map(|(key, val)| Ok((from_msgpack_value(key)?, from_msgpack_value(val)?)))
+                .collect::<Result<_, MsgPackError>>()?,
+        ),
+    })
+}
+
+fn write_msgpack(msgpack: &Value) -> Vec<u8> {
+    let mut buf = vec![];
+    rmpv::encode::write_value(&mut buf, msgpack).expect("in-memory MessagePack serialization");
+    buf
+}
+
+fn read_msgpack(mut bytes: &[u8]) -> Result<Value, MsgPackError> {
+    let val = rmpv::decode::read_value(&mut bytes)
+        .map_err(|err| MsgPackError::Decode(err.to_string()))?;
+    if !bytes.is_empty() {
+        return Err(MsgPackError::Decode(format!("{} bytes after the end of data", bytes.len())));
+    }
+    Ok(val)
+}
+
+impl StrictVal {
+    /// Encodes the value as MessagePack object.
+    pub fn to_msgpack(&self) -> Vec<u8> { write_msgpack(&to_msgpack_value(self)) }
+
+    /// Decodes the value from MessagePack object without using type information. Since
+    /// MessagePack doesn't distinguish enums, unions, tuples, sets and structs, arrays are
+    /// decoded as lists, maps as maps and strings as strings; use [`TypeSystem::from_msgpack`] to
+    /// decode them according to a type.
+    pub fn from_msgpack(bytes: &[u8]) -> Result<StrictVal, MsgPackError> {
+        from_msgpack_value(read_msgpack(bytes)?)
+    }
+}
+
+impl TypeSystem {
+    /// Encodes typed value as MessagePack object.
+    pub fn to_msgpack(&self, val: &TypedVal) -> Vec<u8> { val.as_val().to_msgpack() }
+
+    /// Decodes MessagePack object as a value of type `sem_id`.
+    pub fn from_msgpack(&self, sem_id: SemId, bytes: &[u8]) -> Result<TypedVal, MsgPackError> {
+        let val = self.reshape(from_msgpack_value(read_msgpack(bytes)?)?, sem_id)?;
+        Ok(self.typify(val, sem_id)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::num::{i1024, u1024};
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::value::test_helpers::test_system;
+
+    #[test]
+    fn variants() {
+        let vals = [
+            (StrictVal::Unit, "c0"),
+            (StrictVal::num(10u8), "0a"),
+            (StrictVal::num(200u8), "ccc8"),
+            (StrictVal::num(1000u16), "cd03e8"),
+            (StrictVal::num(-10i8), "f6"),
+            (StrictVal::num(-200i16), "d1ff38"),
+            (StrictVal::num(1.5f64), "cb3ff8000000000000"),
+            (StrictVal::str("a"), "a161"),
+            (StrictVal::bytes([1u8, 2]), "c4020102"),
+            (StrictVal::enumer("a"), "a161"),
+            (StrictVal::enumer(1), "01"),
+            (StrictVal::union("some", 1u8), "92a4736f6d6501"),
+            (StrictVal::tuple([1u8, 2]), "920102"),
+            (StrictVal::list([1u8, 2]), "920102"),
+            (StrictVal::set([1u8, 2]), "920102"),
+            (ston!(a 1u8, b 2u8), "82a16101a16202"),
+            (StrictVal::map([(1u8, 2u8)]), "810102"),
+            (
+                StrictVal::Number(StrictNum::BigUint(u1024::from(1u128 << 64))),
+                "c70902010000000000000000",
+            ),
+            (
+                StrictVal::Number(StrictNum::BigInt(i1024::from(-(1i128 << 64) - 1))),
+                "c70903010000000000000000",
+            ),
+        ];
+        for (val, hex) in vals {
+            let msgpack = val.to_msgpack();
+            let msgpack_hex = msgpack.iter().map(|b| format!("{b:02x}")).collect::<String>();
+            assert_eq!(msgpack_hex, hex, "{val}");
+        }
+
+        assert_eq!(StrictVal::from_msgpack(&[0xc3]), Ok(StrictVal::bool(true)));
+        assert_eq!(StrictVal::from_msgpack(&[0xcd, 0x00, 0x01]), Ok(StrictVal::num(1u64)));
+        assert_eq!(StrictVal::from_msgpack(&[0xd0, 0xff]), Ok(StrictVal::num(-1i64)));
+        assert_eq!(StrictVal::from_msgpack(&[0xca, 0x3f, 0xc0, 0, 0]), Ok(StrictVal::num(1.5f64)));
+        assert_eq!(
+            StrictVal::from_msgpack(&StrictVal::union("some", 1u8).to_msgpack()),
+            Ok(StrictVal::List(vec![StrictVal::str("some"), StrictVal::num(1u8)]))
+        );
+        assert!(matches!(StrictVal::from_msgpack(&[0x92, 0x01]), Err(MsgPackError::Decode(_))));
+        assert!(matches!(StrictVal::from_msgpack(&[0x01, 0x01]), Err(MsgPackError::Decode(_))));
+        assert!(matches!(
+            StrictVal::from_msgpack(&[0xd4, 0x01, 0x00]),
+            Err(MsgPackError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn typed() {
+        let sys = test_system();
+        let id = sys.to_sem_id("TestLib.Nominal").unwrap();
+        let val = ston!(ticker "TICK", name "Some name", precision svenum!(twoDecimals));
+        let typed = sys.typify(val, id).unwrap();
+        let types = sys.as_types();
+
+        let msgpack = types.to_msgpack(&typed);
+        assert_eq!(types.from_msgpack(id, &msgpack), Ok(typed));
+        assert!(matches!(
+            types.from_msgpack(id, &StrictVal::num(1u8).to_msgpack()),
+            Err(MsgPackError::Typify(_))
+        ));
+        assert_eq!(
+            types.from_msgpack(SemId::from([0u8; 32]), &msgpack),
+            Err(MsgPackError::TypeAbsent(SemId::from([0u8; 32])))
+        );
+    }
+
+    fn value() -> impl Strategy<Value = StrictVal> {
+        let leaf = prop_oneof![
+            Just(StrictVal::Unit),
+            any::<u8>().prop_map(StrictVal::num),
+            any::<u16>().prop_map(StrictVal::num),
+            any::<u32>().prop_map(StrictVal::num),
+            any::<u64>().prop_map(StrictVal::num),
+            (i8::MIN..0).prop_map(StrictVal::num),
+            (i16::MIN..0).prop_map(StrictVal::num),
+            (i32::MIN..0).prop_map(StrictVal::num),
+            (i64::MIN..0).prop_map(StrictVal::num),
+            any::<u128>()
+                .prop_filter("bignum", |v| *v > u64::MAX as u128)
+                .prop_map(|v| StrictVal::Number(StrictNum::BigUint(u1024::from(v)))),
+            any::<i128>()
+                .prop_filter("bignum", |v| *v < i64::MIN as i128)
+                .prop_map(|v| StrictVal::Number(StrictNum::BigInt(i1024::from(v)))),
+            any::<f64>().prop_filter("not NaN", |v| !v.is_nan()).prop_map(StrictVal::num),
+            "[a-zA-Z0-9 _.,-]{0,8}".prop_map(StrictVal::String),
+            vec(any::<u8>(), 0..8).prop_map(|v| StrictVal::Bytes(Blob(v))),
+        ];
+        leaf.prop_recursive(3, 32, 4, |inner| {
+            prop_oneof![
+                vec(inner.clone(), 0..4).prop_map(StrictVal::List),
+                vec((inner.clone(), inner), 0..4).prop_map(StrictVal::Map),
+            ]
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn roundtrip(val in value()) {
+            prop_assert_eq!(StrictVal::from_msgpack(&val.to_msgpack()), Ok(val));
+        }
+    }
+}
@@ -0,0 +1,546 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parser reading strict type object notation (STON) produced by [`StrictVal`] `Display`
+//! implementation back into strict values.
+
+use std::str::FromStr;
+
+use amplify::hex::FromHex;
+use encoding::{FieldName, VariantName};
+use indexmap::IndexMap;
+
+use super::{Blob, EnumTag, StrictNum, StrictVal};
+
+/// Maximal nesting depth of values in STON text accepted by the parser.
+pub const STON_MAX_DEPTH: usize = 128;
+
+/// Error parsing STON text, providing the position at which it has happened.
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display("line {line}, column {column}: {kind}")]
+pub struct ParseError {
+    /// Line number, starting from 1.
+    pub line: usize,
+    /// Column number in characters, starting from 1.
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Display)]
+#[display(doc_comments)]
+pub enum ParseErrorKind {
+    /// unexpected end of the input; {0} was expected.
+    UnexpectedEnd(&'static str),
+
+    /// unexpected character `{0}`; {1} was expected.
+    UnexpectedChar(char, &'static str),
+
+    /// invalid number `{0}`.
+    InvalidNumber(String),
+
+    /// invalid hex-encoded byte string `{0}`.
+    InvalidHex(String),
+
+    /// invalid identifier `{0}`.
+    InvalidIdent(String),
+
+    /// repeated field name `{0}`.
+    RepeatedField(FieldName),
+
+    /// named and unnamed fields can't be mixed in the same value.
+    MixedFields,
+
+    /// values are nested deeper than {0} levels.
+    TooDeep(usize),
+}
+
+impl FromStr for StrictVal {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            src: s,
+            pos: 0,
+            depth: 0,
+        };
+        let val = parser.parse_seq()?;
+        parser.skip_ws();
+        match parser.peek() {
+            None => Ok(val),
+            Some(ch) => Err(parser.err(ParseErrorKind::UnexpectedChar(ch, "end of the input"))),
+        }
+    }
+}
+
+struct Parser<'s> {
+    src: &'s str,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn rest(&self) -> &str { &self.src[self.pos..] }
+
+    fn peek(&self) -> Option<char> { self.rest().chars().next() }
+
+    fn bump(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.pos += ch.len_utf8();
+        Some(ch)
+    }
+
+    /// Skips whitespaces together with `/* ... */` and `// ...` comments. Unterminated block
+    /// comment consumes the rest of the input.
+    fn skip_ws(&mut self) {
+        loop {
+            let rest = self.rest();
+            if rest.starts_with("/*") {
+                self.pos += rest.find("*/").map(|pos| pos + 2).unwrap_or(rest.len());
+            } else if rest.starts_with("//") {
+                self.pos += rest.find('\n').unwrap_or(rest.len());
+            } else if self.peek().is_some_and(char::is_whitespace) {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn err(&self, kind: ParseErrorKind) -> ParseError {
+        let consumed = &self.src[..self.pos];
+        let line = consumed.matches('\n').count() + 1;
+        let column = consumed.rsplit('\n').next().unwrap_or_default().chars().count() + 1;
+        ParseError { line, column, kind }
+    }
+
+    fn unexpected(&self, expected: &'static str) -> ParseError {
+        match self.peek() {
+            None => self.err(ParseErrorKind::UnexpectedEnd(expected)),
+            Some(ch) => self.err(ParseErrorKind::UnexpectedChar(ch, expected)),
+        }
+    }
+
+    fn expect(&mut self, ch: char, expected: &'static str) -> Result<(), ParseError> {
+        self.skip_ws();
+        if self.peek() != Some(ch) {
+            return Err(self.unexpected(expected));
+        }
+        self.bump();
+        Ok(())
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &str {
+        let start = self.pos;
+        while self.peek().is_some_and(&f) {
+            self.bump();
+        }
+        &self.src[start..self.pos]
+    }
+
+    /// Returns length of the number starting at the current position, and whether it is a float
+    /// number. Float numbers are written in scientific notation, as signed `inf` or as signed
+    /// `NaN`; a dot not followed by a fractional part and an exponent separates union tag.
+    fn number_len(&self) -> (usize, bool) {
+        let rest = self.rest().as_bytes();
+        let digits = |from: usize| rest[from..].iter().take_while(|b| b.is_ascii_digit()).count();
+        let sign = usize::from(matches!(rest.first(), Some(b'-' | b'+')));
+        for special in [&b"inf"[..], b"NaN"] {
+            if sign > 0 && rest[sign..].starts_with(special) {
+                return (sign + special.len(), true);
+            }
+        }
+        let int = sign + digits(sign);
+        let mut end = int;
+        if rest.get(end) == Some(&b'.') && digits(end + 1) > 0 {
+            end += 1 + digits(end + 1);
+        }
+        if rest.get(end) != Some(&b'e') {
+            return (int, false);
+        }
+        end += 1;
+        if rest.get(end) == Some(&b'-') {
+            end += 1;
+        }
+        (end + digits(end), true)
+    }
+
+    fn is_value_start(&self) -> bool {
+        let rest = self.rest();
+        match rest.chars().next() {
+            Some('-') => !rest.starts_with("->"),
+            Some(ch) => {
+                matches!(ch, '~' | '(' | '"' | '[' | '{' | '+') || ch.is_ascii_alphanumeric()
+            }
+            None => false,
+        }
+    }
+
+    /// Parses comma-separated sequence of values, which is either a single value, a tuple or a
+    /// structure.
+    fn parse_seq(&mut self) -> Result<StrictVal, ParseError> {
+        let mut items = vec![];
+        loop {
+            self.skip_ws();
+            let start = self.pos;
+            items.push((self.parse_item()?, start));
+            self.skip_ws();
+            if self.peek() != Some(',') {
+                break;
+            }
+            self.bump();
+        }
+        if items.len() == 1 && items[0].0 .0.is_none() {
+            return Ok(items.remove(0).0 .1);
+        }
+        if items.iter().all(|((name, _), _)| name.is_none()) {
+            return Ok(StrictVal::Tuple(items.into_iter().map(|((_, val), _)| val).collect()));
+        }
+        let mut fields = IndexMap::with_capacity(items.len());
+        for ((name, val), pos) in items {
+            let Some(name) = name else {
+                self.pos = pos;
+                return Err(self.err(ParseErrorKind::MixedFields));
+            };
+            if fields.contains_key(&name) {
+                self.pos = pos;
+                return Err(self.err(ParseErrorKind::RepeatedField(name)));
+            }
+            fields.insert(name, val);
+        }
+        Ok(StrictVal::Struct(fields))
+    }
+
+    /// Parses either a named structure field or a value.
+    fn parse_item(&mut self) -> Result<(Option<FieldName>, StrictVal), ParseError> {
+        self.skip_ws();
+        let start = self.pos;
+        if self.peek().is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_') {
+            let ident = self.take_while(|ch| ch.is_ascii_alphanumeric() || ch == '_').to_owned();
+            if self.peek().is_some_and(char::is_whitespace) || self.rest().starts_with("/*") {
+                self.skip_ws();
+                if self.is_value_start() {
+                    let name = FieldName::try_from(ident.clone()).map_err(|_| {
+                        self.pos = start;
+                        self.err(ParseErrorKind::InvalidIdent(ident))
+                    })?;
+                    return Ok((Some(name), self.parse_value()?));
+                }
+            }
+            self.pos = start;
+        }
+        Ok((None, self.parse_value()?))
+    }
+
+    /// Parses a value, which may be followed by union variant tags.
+    fn parse_value(&mut self) -> Result<StrictVal, ParseError> {
+        let mut val = self.parse_atom()?;
+        while self.peek() == Some('.') {
+            self.bump();
+            let tag = if self.peek().is_some_and(|ch| ch.is_ascii_digit()) {
+                let start = self.pos;
+                let num = self.take_while(|ch| ch.is_ascii_digit()).to_owned();
+                EnumTag::Ord(num.parse().map_err(|_| {
+                    self.pos = start;
+                    self.err(ParseErrorKind::InvalidNumber(num))
+                })?)
+            } else {
+                EnumTag::Name(self.parse_ident()?)
+            };
+            val = StrictVal::Union(tag, Box::new(val));
+        }
+        Ok(val)
+    }
+
+    fn parse_ident(&mut self) -> Result<VariantName, ParseError> {
+        let start = self.pos;
+        if !self.peek().is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_') {
+            return Err(self.unexpected("identifier"));
+        }
+        let ident = self.take_while(|ch| ch.is_ascii_alphanumeric() || ch == '_').to_owned();
+        VariantName::try_from(ident.clone()).map_err(|_| {
+            self.pos = start;
+            self.err(ParseErrorKind::InvalidIdent(ident))
+        })
+    }
+
+    /// Parses a value which is not followed by union variant tags, limiting its nesting depth to
+    /// [`STON_MAX_DEPTH`].
+    fn parse_atom(&mut self) -> Result<StrictVal, ParseError> {
+        self.skip_ws();
+        if self.depth >= STON_MAX_DEPTH {
+            return Err(self.err(ParseErrorKind::TooDeep(STON_MAX_DEPTH)));
+        }
+        self.depth += 1;
+        let res = self.parse_term();
+        self.depth -= 1;
+        res
+    }
+
+    fn parse_term(&mut self) -> Result<StrictVal, ParseError> {
+        let start = self.pos;
+        match self.peek() {
+            Some('~') => {
+                self.bump();
+                Ok(StrictVal::none())
+            }
+            Some('(') => {
+                self.bump();
+                self.skip_ws();
+                if self.peek() == Some(',') {
+                    self.bump();
+                    self.expect(')', "closing parenthesis")?;
+                    return Ok(StrictVal::Unit);
+                }
+                let val = self.parse_seq()?;
+                self.expect(')', "closing parenthesis")?;
+                Ok(val)
+            }
+            Some('"') => {
+                self.bump();
+                let mut s = String::new();
+                loop {
+                    match self.bump() {
+                        Some('"') => break,
+                        Some('\\') => match self.bump() {
+                            Some(ch) if ch == '"' || ch == '\\' => s.push(ch),
+                            Some(ch) => {
+                                self.pos -= ch.len_utf8();
+                                return Err(self.unexpected("escaped quote or backslash"));
+                            }
+                            None => return Err(self.unexpected("escaped character")),
+                        },
+                        Some(ch) => s.push(ch),
+                        None => return Err(self.unexpected("closing quote")),
+                    }
+                }
+                Ok(StrictVal::String(s))
+            }
+            Some('0') if self.rest().starts_with("0x") => {
+                self.pos += 2;
+                let hex = self.take_while(|ch| ch.is_ascii_alphanumeric()).to_owned();
+                let blob = Blob::from_hex(&hex).map_err(|_| {
+                    self.pos = start;
+                    self.err(ParseErrorKind::InvalidHex(hex))
+                })?;
+                Ok(StrictVal::Bytes(blob))
+            }
+            Some(ch) if ch == '-' || ch == '+' || ch.is_ascii_digit() => {
+                let (len, float) = self.number_len();
+                let num = self.rest()[..len].to_owned();
+                self.pos += len;
+                let res = match ch {
+                    _ if float => StrictNum::from_float_str(&num),
+                    // Negative zero is the same as zero, which is unsigned
+                    '-' => num.parse::<i64>().ok().map(|v| match v {
+                        0 => StrictNum::Uint(0),
+                        v => StrictNum::Int(v),
+                    }),
+                    _ => num.parse::<u64>().map(StrictNum::Uint).ok(),
+                };
+                res.map(StrictVal::Number).ok_or_else(|| {
+                    self.pos = start;
+                    self.err(ParseErrorKind::InvalidNumber(num))
+                })
+            }
+            Some('[') => {
+                self.bump();
+                let items = self.parse_list(']')?;
+                Ok(StrictVal::List(items))
+            }
+            Some('{') => {
+                self.bump();
+                self.skip_ws();
+                if self.peek() == Some('}') {
+                    self.bump();
+                    return Ok(StrictVal::Set(vec![]));
+                }
+                let key = self.parse_value()?;
+                self.skip_ws();
+                if !self.rest().starts_with("->") {
+                    let mut items = vec![key];
+                    if self.peek() == Some(',') {
+                        self.bump();
+                        items.extend(self.parse_list('}')?);
+                    } else {
+                        self.expect('}', "closing brace")?;
+                    }
+                    return Ok(StrictVal::Set(items));
+                }
+                let mut items = vec![];
+                let mut key = key;
+                loop {
+                    self.skip_ws();
+                    if !self.rest().starts_with("->") {
+                        return Err(self.unexpected("`->`"));
+                    }
+                    self.pos += 2;
+                    items.push((key, self.parse_value()?));
+                    self.skip_ws();
+                    match self.peek() {
+                        Some(',') => {
+                            self.bump();
+                            key = self.parse_value()?;
+                        }
+                        Some('}') => {
+                            self.bump();
+                            break;
+                        }
+                        _ => return Err(self.unexpected("comma or closing brace")),
+                    }
+                }
+                Ok(StrictVal::Map(items))
+            }
+            Some(ch) if ch.is_ascii_alphabetic() || ch == '_' => {
+                Ok(StrictVal::Enum(EnumTag::Name(self.parse_ident()?)))
+            }
+            _ => Err(self.unexpected("value")),
+        }
+    }
+
+    /// Parses comma-separated values terminated with `end` character.
+    fn parse_list(&mut self, end: char) -> Result<Vec<StrictVal>, ParseError> {
+        let mut items = vec![];
+        self.skip_ws();
+        if self.peek() == Some(end) {
+            self.bump();
+            return Ok(items);
+        }
+        loop {
+            items.push(self.parse_value()?);
+            self.skip_ws();
+            match self.peek() {
+                Some(',') => {
+                    self.bump();
+                }
+                Some(ch) if ch == end => {
+                    self.bump();
+                    return Ok(items);
+                }
+                _ => return Err(self.unexpected("comma or closing bracket")),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn roundtrip(val in any::<StrictVal>()) {
+            prop_assert_eq!(val.to_string().parse::<StrictVal>(), Ok(val));
+        }
+    }
+
+    #[test]
+    fn parse() {
+        let val = ston!(name "Some name", ticker "TICK", precision svenum!(twoDecimals), data svlist!([0u8, 1, 2]), tuple ston!(a 15u8, b "text"));
+        assert_eq!(val.to_string().parse::<StrictVal>(), Ok(val));
+
+        assert_eq!("~".parse::<StrictVal>(), Ok(StrictVal::none()));
+        assert_eq!("(,)".parse::<StrictVal>(), Ok(StrictVal::Unit));
+        assert_eq!("0x00ff".parse::<StrictVal>(), Ok(StrictVal::Bytes(Blob(vec![0, 0xff]))));
+        assert_eq!("5.1".parse::<StrictVal>(), Ok(StrictVal::union(1, 5u8)));
+        assert_eq!(r#""value".some"#.parse::<StrictVal>(), Ok(StrictVal::some("value")));
+        for float in [1.5, -2.5e-3, 0.0, -0.0, 1e300, f64::INFINITY, f64::NEG_INFINITY, f64::NAN] {
+            let val = StrictVal::num(float);
+            assert_eq!(val.to_string().parse::<StrictVal>(), Ok(val));
+        }
+        assert_eq!(StrictVal::num(-2.5e-3).to_string(), "-2.5e-3");
+        assert_eq!(StrictVal::num(f64::INFINITY).to_string(), "+inf");
+        assert_eq!("1.5e0.2".parse::<StrictVal>(), Ok(StrictVal::union(2, StrictVal::num(1.5))));
+        assert_eq!("-1".parse::<StrictVal>(), Ok(StrictVal::num(-1i64)));
+        assert_eq!("-00".parse::<StrictVal>(), Ok(StrictVal::num(0u8)));
+        assert_eq!(
+            "1e".parse::<StrictVal>().unwrap_err().kind,
+            ParseErrorKind::InvalidNumber(s!("1e"))
+        );
+        let quoted = StrictVal::String(s!(r#"say "hi" \ bye"#));
+        assert_eq!(quoted.to_string(), r#""say \"hi\" \\ bye""#);
+        assert_eq!(quoted.to_string().parse::<StrictVal>(), Ok(quoted));
+        assert_eq!(
+            r#""\n""#.parse::<StrictVal>().unwrap_err().kind,
+            ParseErrorKind::UnexpectedChar('n', "escaped quote or backslash")
+        );
+        assert_eq!(
+            "{1 -> a, 2 -> b}".parse::<StrictVal>(),
+            Ok(StrictVal::Map(vec![
+                (StrictVal::num(1u8), StrictVal::enumer("a")),
+                (StrictVal::num(2u8), StrictVal::enumer("b"))
+            ]))
+        );
+    }
+
+    #[test]
+    fn comments() {
+        let s = "// Nominal value
+            /* TestLib.Nominal.ticker */ ticker \"TICK\", /* name */ name \"Some name\",
+            precision /* twoDecimals */ 2 // trailing comment";
+        assert_eq!(
+            s.parse::<StrictVal>(),
+            Ok(ston!(ticker "TICK", name "Some name", precision 2u8))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let err = "a 1,\n  b (1, 2".parse::<StrictVal>().unwrap_err();
+        assert_eq!((err.line, err.column), (2, 10));
+        assert_eq!(err.kind, ParseErrorKind::UnexpectedEnd("closing parenthesis"));
+
+        let err = "a 1, b 2, a 3".parse::<StrictVal>().unwrap_err();
+        assert_eq!((err.line, err.column), (1, 11));
+        assert_eq!(err.kind, ParseErrorKind::RepeatedField(fname!("a")));
+
+        let err = "a 1, 2".parse::<StrictVal>().unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::MixedFields);
+
+        let err = "[1, 2".parse::<StrictVal>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 1, column 6: unexpected end of the input; comma or closing bracket was expected."
+        );
+
+        let err = "{1 -> 2 é".parse::<StrictVal>().unwrap_err();
+        assert_eq!((err.line, err.column), (1, 9));
+        assert_eq!(err.kind, ParseErrorKind::UnexpectedChar('é', "comma or closing brace"));
+        let err = "{1 -> 2 //é".parse::<StrictVal>().unwrap_err();
+        assert_eq!((err.line, err.column), (1, 12));
+        assert_eq!(err.kind, ParseErrorKind::UnexpectedEnd("comma or closing brace"));
+        let err = "{1 -> 2, 3 -> 4 é".parse::<StrictVal>().unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::UnexpectedChar('é', "comma or closing brace"));
+    }
+
+    #[test]
+    fn depth_limit() {
+        let nested = |depth: usize| format!("{}1{}", "[".repeat(depth - 1), "]".repeat(depth - 1));
+        assert!(nested(STON_MAX_DEPTH).parse::<StrictVal>().is_ok());
+        let err = nested(STON_MAX_DEPTH + 1).parse::<StrictVal>().unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::TooDeep(STON_MAX_DEPTH));
+        let err = "(".repeat(200_000).parse::<StrictVal>().unwrap_err();
+        assert_eq!((err.line, err.column), (1, STON_MAX_DEPTH + 1));
+        assert_eq!(err.kind, ParseErrorKind::TooDeep(STON_MAX_DEPTH));
+    }
+}
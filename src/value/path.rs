@@ -23,12 +23,13 @@
 //! Path accessors into strict values.
 
 use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
 
 use amplify::confinement::{SmallVec, TinyBlob, TinyString};
 use encoding::{FieldName, STRICT_TYPES_LIB};
 
 use crate::value::{EnumTag, StrictNum};
-use crate::StrictVal;
+use crate::{Cls, SemId, StrictVal, Ty, TypeSystem};
 
 // TODO: Convert into `StrictKey` and use in `StrictVal::Map` for key value repr.
 #[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, From)]
@@ -88,25 +89,40 @@ impl Display for KeyStep {
     }
 }
 
-#[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Display, From)]
+#[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, From)]
 #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
 #[strict_type(lib = STRICT_TYPES_LIB, tags = order, dumb = Self::UnnamedField(strict_dumb!()))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
 pub enum Step {
-    #[display(".{0}")]
     #[from]
     NamedField(FieldName),
 
-    #[display(".{0}")]
     #[from]
     UnnamedField(u8),
 
-    #[display("[{0}]")]
     Index(u32),
 
-    #[display("{{{0}}}")]
     #[from]
     Key(KeyStep),
+
+    /// Wildcard matching all elements of a collection.
+    All,
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::NamedField(name) => write!(f, ".{name}"),
+            Step::UnnamedField(no) => write!(f, ".{no}"),
+            Step::Index(idx) => write!(f, "[{idx}]"),
+            Step::Key(KeyStep::Number(num)) => write!(f, "{{{num}}}"),
+            Step::Key(KeyStep::TinyString(s)) => {
+                write!(f, "[\"{}\"]", s.replace('\\', "\\\\").replace('"', "\\\""))
+            }
+            Step::Key(key) => write!(f, "[{key}]"),
+            Step::All => f.write_str("[*]"),
+        }
+    }
 }
 
 #[derive(Wrapper, WrapperMut, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Default, From)]
@@ -132,16 +148,210 @@ impl<'path> IntoIterator for &'path Path {
     fn into_iter(self) -> Self::IntoIter { self.0.iter() }
 }
 
+/// Path expressions start with a field name and continue with the steps: `.field` for named
+/// and `.0` for unnamed fields, `[0]` for collection items, `[*]` for all collection items,
+/// `["key"]` and `[0hAB]` for string and byte string map keys and `{0}` for numeric map keys,
+/// for instance `foo.bar[0].baz` or `data["key"].val`.
 impl Display for Path {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        for step in self {
-            Display::fmt(step, f)?;
+        for (pos, step) in self.iter().enumerate() {
+            match step {
+                Step::NamedField(name) if pos == 0 => Display::fmt(name, f)?,
+                Step::UnnamedField(no) if pos == 0 => Display::fmt(no, f)?,
+                step => Display::fmt(step, f)?,
+            }
         }
         Ok(())
     }
 }
 
-// TODO: Implement FromStr for value Path expressions
+struct PathParser<'s> {
+    expr: &'s str,
+    pos: usize,
+}
+
+impl PathParser<'_> {
+    fn err(&self) -> PathError { PathError::InvalidExpr(self.expr.to_owned(), self.pos) }
+
+    fn peek(&self) -> Option<char> { self.expr[self.pos..].chars().next() }
+
+    fn expect(&mut self, c: char) -> Result<(), PathError> {
+        if self.peek() != Some(c) {
+            return Err(self.err());
+        }
+        self.pos += c.len_utf8();
+        Ok(())
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &str {
+        let start = self.pos;
+        while let Some(c) = self.peek().filter(|c| f(*c)) {
+            self.pos += c.len_utf8();
+        }
+        &self.expr[start..self.pos]
+    }
+
+    fn field(&mut self) -> Result<Step, PathError> {
+        let start = self.pos;
+        if self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            let no = self.take_while(|c| c.is_ascii_digit());
+            return no.parse().map(Step::UnnamedField).map_err(|_| {
+                self.pos = start;
+                self.err()
+            });
+        }
+        let name = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+        FieldName::try_from(name.to_owned()).map(Step::NamedField).map_err(|_| {
+            self.pos = start;
+            self.err()
+        })
+    }
+
+    fn bracket(&mut self) -> Result<Step, PathError> {
+        self.expect('[')?;
+        let start = self.pos;
+        let step = match self.peek() {
+            Some('*') => {
+                self.pos += 1;
+                Step::All
+            }
+            Some('"') => {
+                self.pos += 1;
+                let mut s = String::new();
+                loop {
+                    let c = self.peek().ok_or_else(|| self.err())?;
+                    self.pos += c.len_utf8();
+                    match c {
+                        '"' => break,
+                        '\\' => {
+                            let c = self.peek().filter(|c| *c == '"' || *c == '\\');
+                            let c = c.ok_or_else(|| self.err())?;
+                            self.pos += 1;
+                            s.push(c);
+                        }
+                        c => s.push(c),
+                    }
+                }
+                let s = TinyString::try_from(s).map_err(|_| {
+                    self.pos = start;
+                    self.err()
+                })?;
+                Step::Key(KeyStep::TinyString(s))
+            }
+            Some('0') if self.expr[self.pos..].starts_with("0h") => {
+                self.pos += 2;
+                let hex = self.take_while(|c| c.is_ascii_hexdigit());
+                let blob = (hex.len() % 2 == 0)
+                    .then(|| {
+                        (0..hex.len())
+                            .step_by(2)
+                            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+                            .collect::<Result<Vec<_>, _>>()
+                            .ok()
+                    })
+                    .flatten()
+                    .and_then(|data| TinyBlob::try_from(data).ok());
+                let blob = blob.ok_or_else(|| {
+                    self.pos = start;
+                    self.err()
+                })?;
+                Step::Key(KeyStep::TinyBlob(blob))
+            }
+            _ => {
+                let idx = self.take_while(|c| c.is_ascii_digit()).parse().map_err(|_| {
+                    self.pos = start;
+                    self.err()
+                })?;
+                Step::Index(idx)
+            }
+        };
+        self.expect(']')?;
+        Ok(step)
+    }
+
+    fn key(&mut self) -> Result<Step, PathError> {
+        self.expect('{')?;
+        let start = self.pos;
+        let num = self.take_while(|c| c.is_ascii_digit()).parse().map_err(|_| {
+            self.pos = start;
+            self.err()
+        })?;
+        self.expect('}')?;
+        Ok(Step::Key(KeyStep::Number(num)))
+    }
+
+    fn parse(mut self) -> Result<Path, PathError> {
+        let mut path = Path::new();
+        let push = |path: &mut Path, step, pos| {
+            path.push(step).map_err(|_| PathError::InvalidExpr(self.expr.to_owned(), pos))
+        };
+        if self.peek().is_some_and(|c| c.is_ascii_alphanumeric() || c == '_') {
+            push(&mut path, self.field()?, self.pos)?;
+        }
+        while let Some(c) = self.peek() {
+            let step = match c {
+                '.' => {
+                    self.pos += 1;
+                    self.field()?
+                }
+                '[' => self.bracket()?,
+                '{' => self.key()?,
+                _ => return Err(self.err()),
+            };
+            push(&mut path, step, self.pos)?;
+        }
+        Ok(path)
+    }
+}
+
+impl FromStr for Path {
+    type Err = PathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> { PathParser { expr: s, pos: 0 }.parse() }
+}
+
+impl Path {
+    /// Returns mutable reference to the value nested in `val` at this path.
+    pub fn resolve_mut<'a>(&self, val: &'a mut StrictVal) -> Result<&'a mut StrictVal, PathError> {
+        val.at_path_mut(self)
+    }
+
+    /// Replaces the value nested in `val` at this path with `new_val`, returning the replaced
+    /// value.
+    pub fn replace_at(
+        &self,
+        val: &mut StrictVal,
+        new_val: StrictVal,
+    ) -> Result<StrictVal, PathError> {
+        Ok(std::mem::replace(self.resolve_mut(val)?, new_val))
+    }
+
+    /// Checks that each of the path steps can be applied to the type at the step position.
+    pub fn validate(&self, ty: &Ty<SemId>, sys: &TypeSystem) -> Result<(), PathError> {
+        let mut ty = ty;
+        for step in self {
+            let next = match (ty, step) {
+                (Ty::Struct(fields), Step::NamedField(name)) => fields
+                    .ty_by_name(name)
+                    .ok_or_else(|| PathError::UnknownFieldName(name.clone()))?,
+                (Ty::Tuple(fields), Step::UnnamedField(no)) => {
+                    fields.ty_by_pos(*no).ok_or(PathError::FieldNoOutOfBounds(*no, fields.len()))?
+                }
+                (Ty::Array(_, len), Step::Index(idx)) if *idx >= *len as u32 => {
+                    return Err(PathError::CollectionIndexOutOfBounds(*idx, *len as usize));
+                }
+                (
+                    Ty::Array(item, _) | Ty::List(item, _) | Ty::Set(item, _),
+                    Step::Index(_) | Step::All,
+                ) => item,
+                (Ty::Map(_, val, _), Step::Key(_) | Step::All) => val,
+                (ty, step) => return Err(PathError::StepMismatch(step.clone(), ty.cls())),
+            };
+            ty = sys.get(*next).ok_or(PathError::UnknownType(*next))?;
+        }
+        Ok(())
+    }
+}
 
 #[derive(Clone, PartialEq, Eq, Debug, Display, Error)]
 #[display(doc_comments)]
@@ -156,6 +366,12 @@ pub enum PathError {
     UnknownKey(KeyStep),
     /// path doesn't match value at step {0}.
     TypeMismatch(Step, StrictVal),
+    /// path step `{0}` can't be applied to a type of class {1}.
+    StepMismatch(Step, Cls),
+    /// type {0} referenced by the path is not a part of the type system.
+    UnknownType(SemId),
+    /// invalid path expression `{0}` at position {1}.
+    InvalidExpr(String, usize),
 }
 
 impl StrictVal {
@@ -164,32 +380,192 @@ impl StrictVal {
         path: impl IntoIterator<Item = &'p Step>,
     ) -> Result<&StrictVal, PathError> {
         let mut iter = path.into_iter();
-        match (self, iter.next()) {
-            (val, None) => Ok(val),
-            (StrictVal::Tuple(fields), Some(Step::UnnamedField(no)))
-                if *no as usize >= fields.len() =>
-            {
-                Err(PathError::FieldNoOutOfBounds(*no, fields.len()))
+        let Some(step) = iter.next() else {
+            return Ok(self);
+        };
+        let val = match (self, step) {
+            (StrictVal::Tuple(fields), Step::UnnamedField(no)) if *no as usize >= fields.len() => {
+                return Err(PathError::FieldNoOutOfBounds(*no, fields.len()));
             }
-            (StrictVal::Tuple(fields), Some(Step::UnnamedField(no))) => Ok(&fields[*no as usize]),
-            (StrictVal::Struct(fields), Some(Step::NamedField(name))) => {
-                fields.get(name).ok_or(PathError::UnknownFieldName(name.clone()))
+            (StrictVal::Tuple(fields), Step::UnnamedField(no)) => &fields[*no as usize],
+            (StrictVal::Struct(fields), Step::NamedField(name)) => {
+                fields.get(name).ok_or(PathError::UnknownFieldName(name.clone()))?
             }
-            (StrictVal::List(items) | StrictVal::Set(items), Some(Step::Index(idx)))
+            (StrictVal::List(items) | StrictVal::Set(items), Step::Index(idx))
                 if *idx as usize >= items.len() =>
             {
-                Err(PathError::CollectionIndexOutOfBounds(*idx, items.len()))
+                return Err(PathError::CollectionIndexOutOfBounds(*idx, items.len()));
             }
-            (StrictVal::List(items) | StrictVal::Set(items), Some(Step::Index(idx))) => {
-                Ok(&items[*idx as usize])
+            (StrictVal::List(items) | StrictVal::Set(items), Step::Index(idx)) => {
+                &items[*idx as usize]
             }
-            (StrictVal::Map(items), Some(Step::Key(idx))) => items
+            (StrictVal::Map(items), Step::Key(idx)) => items
                 .iter()
                 .find(|(key, _)| idx.has_match(key))
                 .map(|(_, val)| val)
-                .ok_or(PathError::UnknownKey(idx.clone())),
+                .ok_or(PathError::UnknownKey(idx.clone()))?,
 
-            (_, Some(step)) => Err(PathError::TypeMismatch(step.clone(), self.clone())),
+            (_, step) => return Err(PathError::TypeMismatch(step.clone(), self.clone())),
+        };
+        val.at_path(iter)
+    }
+
+    /// Returns mutable reference to the value nested at the path.
+    pub fn at_path_mut<'p>(
+        &mut self,
+        path: impl IntoIterator<Item = &'p Step>,
+    ) -> Result<&mut StrictVal, PathError> {
+        let mut iter = path.into_iter();
+        let Some(step) = iter.next() else {
+            return Ok(self);
+        };
+        let val = match (self, step) {
+            (StrictVal::Tuple(fields), Step::UnnamedField(no)) => {
+                let len = fields.len();
+                fields.get_mut(*no as usize).ok_or(PathError::FieldNoOutOfBounds(*no, len))?
+            }
+            (StrictVal::Struct(fields), Step::NamedField(name)) => {
+                fields.get_mut(name).ok_or(PathError::UnknownFieldName(name.clone()))?
+            }
+            (StrictVal::List(items) | StrictVal::Set(items), Step::Index(idx)) => {
+                let len = items.len();
+                items
+                    .get_mut(*idx as usize)
+                    .ok_or(PathError::CollectionIndexOutOfBounds(*idx, len))?
+            }
+            (StrictVal::Map(items), Step::Key(key)) => items
+                .iter_mut()
+                .find(|(k, _)| key.has_match(k))
+                .map(|(_, val)| val)
+                .ok_or(PathError::UnknownKey(key.clone()))?,
+            (val, step) => return Err(PathError::TypeMismatch(step.clone(), val.clone())),
+        };
+        val.at_path_mut(iter)
+    }
+
+    /// Returns all values matching the path, where [`Step::All`] matches each of the collection,
+    /// tuple or map items. Positions not present in the value are skipped.
+    pub fn get_all(&self, path: &Path) -> Vec<&StrictVal> {
+        let mut vals = vec![self];
+        for step in path {
+            vals = vals
+                .into_iter()
+                .flat_map(|val| match (val, step) {
+                    (
+                        StrictVal::List(items) | StrictVal::Set(items) | StrictVal::Tuple(items),
+                        Step::All,
+                    ) => items.iter().collect(),
+                    (StrictVal::Map(items), Step::All) => items.iter().map(|(_, v)| v).collect(),
+                    (val, step) => val.at_path([step]).into_iter().collect::<Vec<_>>(),
+                })
+                .collect();
         }
+        vals
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::value::test_helpers::test_system;
+
+    #[test]
+    fn parse_display() {
+        for expr in [
+            "",
+            "foo",
+            "foo.bar[0].baz",
+            r#"data["key"].val"#,
+            "items[*]",
+            "items[*].name",
+            "0.1[2]",
+            "map{5}.value",
+            "map[0hABCD]",
+            r#"map["a \"quoted\" \\ key"]"#,
+        ] {
+            let path = Path::from_str(expr).unwrap();
+            assert_eq!(path.to_string(), expr);
+        }
+
+        let path = Path::from_str(r#"foo.bar[0]["key"][*]"#).unwrap();
+        assert_eq!(path.iter().cloned().collect::<Vec<_>>(), vec![
+            Step::NamedField(fname!("foo")),
+            Step::NamedField(fname!("bar")),
+            Step::Index(0),
+            Step::Key(KeyStep::TinyString(tiny_s!("key"))),
+            Step::All,
+        ]);
+        assert_eq!(Path::from_str(".foo").unwrap(), Path::from_str("foo").unwrap());
+
+        for (expr, pos) in
+            [("foo..bar", 4), ("foo[", 4), ("foo[1", 5), ("foo]", 3), ("foo[0h1]", 4), ("a.256", 2)]
+        {
+            assert_eq!(
+                Path::from_str(expr),
+                Err(PathError::InvalidExpr(expr.to_owned(), pos)),
+                "{expr}"
+            );
+        }
+    }
+
+    #[test]
+    fn get() {
+        let val = ston!(
+            name "Some name",
+            items svlist!([ston!(name "a"), ston!(name "b")]),
+            data StrictVal::map([("key", ston!(val 1u8, other 2u8))])
+        );
+        let get = |expr| val.at_path(&Path::from_str(expr).unwrap()).cloned();
+        assert_eq!(get(r#"data["key"].val"#), Ok(StrictVal::num(1u8)));
+        assert_eq!(get("items[1]"), Ok(ston!(name "b")));
+        assert!(matches!(get("items[2]"), Err(PathError::CollectionIndexOutOfBounds(2, 2))));
+
+        let all = val.get_all(&Path::from_str("items[*]").unwrap());
+        assert_eq!(all, vec![&ston!(name "a"), &ston!(name "b")]);
+        let all = val.get_all(&Path::from_str("data[*].other").unwrap());
+        assert_eq!(all, vec![&StrictVal::num(2u8)]);
+        assert!(val.get_all(&Path::from_str("name[*]").unwrap()).is_empty());
+    }
+
+    #[test]
+    fn resolve_mut() {
+        let mut val = ston!(
+            name "Some name",
+            items svlist!([ston!(name "a", id 1u8), ston!(name "b", id 2u8)]),
+            pair StrictVal::tuple([1u8, 2u8]),
+            data StrictVal::map([("key", ston!(val 1u8, other 2u8))])
+        );
+
+        let path = Path::from_str("items[1].name").unwrap();
+        *path.resolve_mut(&mut val).unwrap() = StrictVal::str("c");
+        assert_eq!(val.at_path(&path), Ok(&StrictVal::str("c")));
+
+        let path = Path::from_str(r#"data["key"].val"#).unwrap();
+        assert_eq!(path.replace_at(&mut val, StrictVal::num(5u8)), Ok(StrictVal::num(1u8)));
+        assert_eq!(val.at_path(&path), Ok(&StrictVal::num(5u8)));
+
+        let path = Path::from_str("pair.1").unwrap();
+        assert_eq!(path.replace_at(&mut val, StrictVal::num(3u8)), Ok(StrictVal::num(2u8)));
+        assert_eq!(val.unwrap_struct("pair"), &StrictVal::tuple([1u8, 3u8]));
+
+        let path = Path::from_str("pair.2").unwrap();
+        assert_eq!(path.resolve_mut(&mut val), Err(PathError::FieldNoOutOfBounds(2, 2)));
+        let path = Path::from_str("name.0").unwrap();
+        assert!(matches!(path.resolve_mut(&mut val), Err(PathError::TypeMismatch(..))));
+    }
+
+    #[test]
+    fn validate() {
+        let sys = test_system();
+        let types = sys.as_types();
+        let nominal = sys.get("TestLib.Nominal").unwrap();
+        let validate = |expr| Path::from_str(expr).unwrap().validate(nominal, types);
+        assert_eq!(validate(""), Ok(()));
+        assert_eq!(validate("ticker"), Ok(()));
+        assert_eq!(validate("unknown"), Err(PathError::UnknownFieldName(fname!("unknown"))));
+        assert_eq!(
+            validate("precision.0"),
+            Err(PathError::StepMismatch(Step::UnnamedField(0), Cls::Enum))
+        );
     }
 }
@@ -27,7 +27,9 @@ use std::fmt::{self, Display, Formatter};
 use amplify::hex::ToHex;
 
 use super::StrictVal;
+use crate::typesys::{SymbolicSys, TypeFqn};
 use crate::value::EnumTag;
+use crate::{typify, SemId, Ty, TypeSystem};
 
 impl StrictVal {
     fn needs_parenthesis(&self) -> bool {
@@ -45,127 +47,368 @@ impl StrictVal {
             StrictVal::Tuple(_) | StrictVal::Struct(_) => true,
         }
     }
+
+    /// Detects optional value which is absent, displayed with `~` shorthand.
+    fn is_ston_none(&self) -> bool {
+        matches!(self, StrictVal::Union(tag, content)
+            if (*tag == EnumTag::Ord(0) || *tag == EnumTag::Name(vname!("none")))
+                && **content == StrictVal::Unit)
+    }
 }
 
 impl Display for StrictVal {
+    /// Formats value in STON notation. The alternate flag (`{:#}`) produces multi-line output,
+    /// with the width (if given) used as the maximum line width.
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        // TODO: Add nested formatting with `#` and `width`
-        match self {
-            StrictVal::Unit => f.write_str("(,)"),
-            StrictVal::Number(n) => Display::fmt(n, f),
-            StrictVal::String(s) => write!(f, r#""{s}""#),
-            StrictVal::Bytes(blob) => write!(f, "0x{}", blob.to_hex()),
+        let mut formatter = StonFormatter::default().compact(!f.alternate());
+        if let Some(width) = f.width() {
+            formatter = formatter.max_line_width(width);
+        }
+        f.write_str(&formatter.format(self))
+    }
+}
+
+/// Configurable formatter producing STON representation of strict values.
+///
+/// By default, the formatter produces compact single-line output, identical to the output of
+/// [`StrictVal`] `Display` implementation.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct StonFormatter {
+    indent: usize,
+    max_line_width: usize,
+    compact: bool,
+    comments: bool,
+}
+
+impl Default for StonFormatter {
+    fn default() -> Self {
+        StonFormatter {
+            indent: 4,
+            max_line_width: 80,
+            compact: true,
+            comments: false,
+        }
+    }
+}
+
+impl StonFormatter {
+    pub fn new() -> Self { Self::default() }
+
+    /// Number of spaces used for each nesting level in multi-line output.
+    pub fn indent(mut self, n: usize) -> Self {
+        self.indent = n;
+        self
+    }
+
+    /// Maximum line width in multi-line output; values which don't fit are split across lines.
+    pub fn max_line_width(mut self, n: usize) -> Self {
+        self.max_line_width = n;
+        self
+    }
+
+    /// Whether to put the whole value into a single line.
+    pub fn compact(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+
+    /// Whether to annotate structure fields with comments naming them. Comments are produced only
+    /// by [`Self::format_typed`], which knows the name of the formatted type.
+    pub fn comments(mut self, comments: bool) -> Self {
+        self.comments = comments;
+        self
+    }
+
+    pub fn format(&self, val: &StrictVal) -> String { self.render(val, 0, None) }
+
+    /// Formats value of a type with the given name, adding comments to the structure fields if
+    /// [`Self::comments`] is enabled.
+    pub fn format_typed(&self, val: &StrictVal, fqn: &TypeFqn) -> String {
+        self.render(val, 0, Some(&fqn.to_string()))
+    }
+
+    fn pad(&self, depth: usize) -> String { " ".repeat(self.indent * depth) }
+
+    fn fits(&self, line: &str, depth: usize) -> bool {
+        self.compact
+            || (!line.contains('\n') && line.len() + self.indent * depth <= self.max_line_width)
+    }
+
+    fn render(&self, val: &StrictVal, depth: usize, path: Option<&str>) -> String {
+        match val {
+            StrictVal::Unit => s!("(,)"),
+            StrictVal::Number(n) => n.to_string(),
+            StrictVal::String(s) => {
+                format!(r#""{}""#, s.replace('\\', r"\\").replace('"', r#"\""#))
+            }
+            StrictVal::Bytes(blob) => format!("0x{}", blob.to_hex()),
             StrictVal::Tuple(fields) => {
-                let mut iter = fields.iter();
-                while let Some(field) = iter.next() {
-                    if field.needs_parenthesis() {
-                        f.write_str("(")?;
-                    }
-                    Display::fmt(field, f)?;
-                    if field.needs_parenthesis() {
-                        f.write_str(")")?;
-                    }
-                    if iter.len() > 0 {
-                        f.write_str(", ")?;
-                    }
-                }
-                Ok(())
+                let parts = fields.iter().map(|fval| self.render_nested(fval, depth, path));
+                self.render_seq(parts.collect(), depth)
             }
             StrictVal::Struct(fields) => {
-                let mut iter = fields.iter();
-                while let Some((fname, fval)) = iter.next() {
-                    write!(f, "{fname} ")?;
-                    if fval.needs_parenthesis() {
-                        f.write_str("(")?;
-                    }
-                    Display::fmt(fval, f)?;
-                    if fval.needs_parenthesis() {
-                        f.write_str(")")?;
-                    }
-                    if iter.len() > 0 {
-                        f.write_str(", ")?;
-                    }
-                }
-                Ok(())
-            }
-            StrictVal::Enum(tag) => Display::fmt(tag, f),
-            StrictVal::Union(tag, content)
-                if (*tag == EnumTag::Ord(0) || *tag == EnumTag::Name(vname!("none")))
-                    && **content == StrictVal::Unit =>
-            {
-                f.write_str("~")
+                let parts = fields.iter().map(|(fname, fval)| {
+                    let path = path.map(|path| format!("{path}.{fname}"));
+                    let comment = match (&path, self.comments) {
+                        (Some(path), true) => format!("/* {path} */ "),
+                        _ => none!(),
+                    };
+                    let fval = self.render_nested(fval, depth, path.as_deref());
+                    format!("{comment}{fname} {fval}")
+                });
+                self.render_seq(parts.collect(), depth)
             }
+            StrictVal::Enum(tag) => tag.to_string(),
+            StrictVal::Union(..) if val.is_ston_none() => s!("~"),
             StrictVal::Union(tag, content) => {
-                if content.needs_parenthesis() {
-                    f.write_str("(")?;
-                }
-                Display::fmt(content, f)?;
-                if content.needs_parenthesis() {
-                    f.write_str(")")?;
-                }
-                f.write_str(".")?;
-                Display::fmt(tag, f)?;
-                Ok(())
+                format!("{}.{tag}", self.render_nested(content, depth, path))
             }
             StrictVal::List(items) => {
-                let mut iter = items.iter();
-                f.write_str("[")?;
-                while let Some(item) = iter.next() {
-                    if item.needs_parenthesis() {
-                        f.write_str("(")?;
-                    }
-                    Display::fmt(item, f)?;
-                    if item.needs_parenthesis() {
-                        f.write_str(")")?;
-                    }
-                    if iter.len() > 0 {
-                        f.write_str(", ")?;
-                    }
-                }
-                f.write_str("]")
+                let parts = items.iter().map(|item| self.render_nested(item, depth + 1, path));
+                self.render_collection("[", "]", parts.collect(), depth)
             }
             StrictVal::Set(items) => {
-                let mut iter = items.iter();
-                f.write_str("{")?;
-                while let Some(item) = iter.next() {
-                    if item.needs_parenthesis() {
-                        f.write_str("(")?;
-                    }
-                    Display::fmt(item, f)?;
-                    if item.needs_parenthesis() {
-                        f.write_str(")")?;
-                    }
-                    if iter.len() > 0 {
-                        f.write_str(", ")?;
-                    }
-                }
-                f.write_str("}")
+                let parts = items.iter().map(|item| self.render_nested(item, depth + 1, path));
+                self.render_collection("{", "}", parts.collect(), depth)
             }
             StrictVal::Map(items) => {
-                let mut iter = items.iter();
-                f.write_str("{")?;
-                while let Some((fname, fval)) = iter.next() {
-                    write!(f, "{fname} -> ")?;
-                    if fval.needs_parenthesis() {
-                        f.write_str("(")?;
-                    }
-                    Display::fmt(fval, f)?;
-                    if fval.needs_parenthesis() {
-                        f.write_str(")")?;
-                    }
-                    if iter.len() > 0 {
-                        f.write_str(", ")?;
-                    }
+                let parts = items.iter().map(|(key, val)| {
+                    let key = self.render(key, depth + 1, path);
+                    let val = self.render_nested(val, depth + 1, path);
+                    format!("{key} -> {val}")
+                });
+                self.render_collection("{", "}", parts.collect(), depth)
+            }
+        }
+    }
+
+    fn render_nested(&self, val: &StrictVal, depth: usize, path: Option<&str>) -> String {
+        if !val.needs_parenthesis() {
+            return self.render(val, depth, path);
+        }
+        let inner = self.render(val, depth + 1, path);
+        if inner.contains('\n') {
+            format!("(\n{}{inner}\n{})", self.pad(depth + 1), self.pad(depth))
+        } else {
+            format!("({inner})")
+        }
+    }
+
+    fn render_seq(&self, parts: Vec<String>, depth: usize) -> String {
+        let line = parts.join(", ");
+        if self.fits(&line, depth) {
+            return line;
+        }
+        parts.join(&format!(",\n{}", self.pad(depth)))
+    }
+
+    fn render_collection(
+        &self,
+        open: &str,
+        close: &str,
+        parts: Vec<String>,
+        depth: usize,
+    ) -> String {
+        let line = format!("{open}{}{close}", parts.join(", "));
+        if parts.is_empty() || self.fits(&line, depth) {
+            return line;
+        }
+        let pad = self.pad(depth + 1);
+        format!("{open}\n{pad}{}\n{}{close}", parts.join(&format!(",\n{pad}")), self.pad(depth))
+    }
+}
+
+/// Displays a strict value in compact STON notation, annotating values of named types with
+/// comments containing the type names. Produced by [`SymbolicSys::display_typed`].
+///
+/// Since annotations are comments, the output can be parsed back into the same value.
+#[derive(Copy, Clone, Debug)]
+pub struct StrictValDisplay<'sys> {
+    val: &'sys StrictVal,
+    sys: &'sys SymbolicSys,
+    sem_id: SemId,
+}
+
+impl Display for StrictValDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(self.val, Some(self.sem_id)))
+    }
+}
+
+impl StrictValDisplay<'_> {
+    fn render(&self, val: &StrictVal, sem_id: Option<SemId>) -> String {
+        let ty = sem_id.and_then(|id| self.sys.as_types().get(id));
+        let body = match (val, ty) {
+            (StrictVal::Tuple(fields), ty) => {
+                let ty_of = |pos: usize| match ty {
+                    Some(Ty::Tuple(tys)) => tys.iter().nth(pos).copied(),
+                    _ => None,
+                };
+                let parts = fields.iter().enumerate().map(|(pos, fval)| {
+                    let id = ty_of(pos);
+                    self.render_nested(fval, id)
+                });
+                parts.collect::<Vec<_>>().join(", ")
+            }
+            (StrictVal::Struct(fields), ty) => {
+                let parts = fields.iter().map(|(fname, fval)| {
+                    let id = match ty {
+                        Some(Ty::Struct(tys)) => tys.ty_by_name(fname).copied(),
+                        _ => None,
+                    };
+                    format!("{fname} {}", self.render_nested(fval, id))
+                });
+                parts.collect::<Vec<_>>().join(", ")
+            }
+            (StrictVal::Union(tag, content), Some(Ty::Union(variants))) if !val.is_ston_none() => {
+                let id = match tag {
+                    EnumTag::Name(name) => variants.ty_by_name(name),
+                    EnumTag::Ord(ord) => variants.ty_by_tag(*ord),
+                };
+                format!("{}.{tag}", self.render_nested(content, id.copied()))
+            }
+            (StrictVal::List(items), Some(Ty::List(id, _) | Ty::Array(id, _))) => {
+                let parts = items.iter().map(|item| self.render_nested(item, Some(*id)));
+                format!("[{}]", parts.collect::<Vec<_>>().join(", "))
+            }
+            (StrictVal::Set(items), Some(Ty::Set(id, _))) => {
+                let parts = items.iter().map(|item| self.render_nested(item, Some(*id)));
+                format!("{{{}}}", parts.collect::<Vec<_>>().join(", "))
+            }
+            (StrictVal::Map(items), Some(Ty::Map(key_id, id, _))) => {
+                let parts = items.iter().map(|(key, item)| {
+                    let key = self.render(key, Some(*key_id));
+                    format!("{key} -> {}", self.render_nested(item, Some(*id)))
+                });
+                format!("{{{}}}", parts.collect::<Vec<_>>().join(", "))
+            }
+            (val, _) => val.to_string(),
+        };
+        match sem_id.and_then(|id| self.sys.lookup(id)) {
+            Some(fqn) => format!("/* {fqn} */ {body}"),
+            None => body,
+        }
+    }
+
+    fn render_nested(&self, val: &StrictVal, sem_id: Option<SemId>) -> String {
+        let inner = self.render(val, sem_id);
+        if val.needs_parenthesis() {
+            format!("({inner})")
+        } else {
+            inner
+        }
+    }
+}
+
+impl SymbolicSys {
+    /// Returns a value wrapper displaying the value of the `sem_id` type with annotations
+    /// naming the types of the value and its nested values; see [`StrictValDisplay`].
+    pub fn display_typed<'a>(&'a self, val: &'a StrictVal, sem_id: SemId) -> StrictValDisplay<'a> {
+        StrictValDisplay {
+            val,
+            sys: self,
+            sem_id,
+        }
+    }
+
+    /// Formats the value of the `sem_id` type in a human-readable form, using the type
+    /// information. Unlike STON, the output is not intended for parsing.
+    ///
+    /// Enums are shown by their variant names; union variants as `variantName { content }` (or
+    /// just `variantName` for unit variants); optional values as `some(value)` or `none`;
+    /// byte strings as `<N bytes: hex>`; structures as `{ field: value, .. }`; newtypes as their
+    /// inner values. Other values are shown in STON notation.
+    ///
+    /// # Errors
+    ///
+    /// If the value doesn't match the type (see [`TypeSystem::typify`]).
+    pub fn format_typed(&self, val: &StrictVal, sem_id: SemId) -> Result<String, typify::Error> {
+        let typed = self.as_types().typify(val.clone(), sem_id)?;
+        Ok(format_typed(self.as_types(), typed.as_val(), sem_id))
+    }
+}
+
+/// Formats value which was checked to match the type; see [`SymbolicSys::format_typed`].
+fn format_typed(types: &TypeSystem, val: &StrictVal, sem_id: SemId) -> String {
+    let ty = &types[sem_id];
+    let join = |parts: Vec<String>| parts.join(", ");
+    match (val, ty) {
+        (StrictVal::Union(_, content), ty) if ty.is_option() => {
+            if val.is_ston_none() {
+                return s!("none");
+            }
+            let id = *ty.as_some().expect("checked option");
+            format!("some({})", format_typed(types, content, id))
+        }
+        (StrictVal::Enum(tag), Ty::Enum(variants)) => match tag {
+            EnumTag::Name(name) => name.to_string(),
+            EnumTag::Ord(ord) => variants
+                .name_by_tag(*ord)
+                .map(|name| name.to_string())
+                .unwrap_or_else(|| ord.to_string()),
+        },
+        (StrictVal::Union(tag, content), Ty::Union(variants)) => {
+            let (name, id) = match tag {
+                EnumTag::Name(name) => (name.clone(), variants.ty_by_name(name)),
+                EnumTag::Ord(ord) => match variants.by_tag(*ord) {
+                    Some((variant, id)) => (variant.name.clone(), Some(id)),
+                    None => return val.to_string(),
+                },
+            };
+            match (content.as_ref(), id) {
+                (StrictVal::Unit, _) | (_, None) => name.to_string(),
+                (content @ StrictVal::Struct(_), Some(id)) => {
+                    format!("{name} {}", format_typed(types, content, *id))
+                }
+                (content, Some(id)) => {
+                    format!("{name} {{ {} }}", format_typed(types, content, *id))
                 }
-                f.write_str("}")
             }
         }
+        (StrictVal::Bytes(blob), _) => format!("<{} bytes: {}>", blob.len(), blob.to_hex()),
+        (StrictVal::Tuple(fields), Ty::Tuple(tys)) if fields.len() == 1 && tys.len() == 1 => {
+            format_typed(types, &fields[0], tys[0])
+        }
+        (StrictVal::Tuple(fields), Ty::Tuple(tys)) => {
+            let parts = fields.iter().zip(tys.iter());
+            format!("({})", join(parts.map(|(fval, id)| format_typed(types, fval, *id)).collect()))
+        }
+        (StrictVal::Struct(fields), Ty::Struct(tys)) => {
+            if fields.is_empty() {
+                return s!("{}");
+            }
+            let parts = fields.iter().map(|(fname, fval)| match tys.ty_by_name(fname) {
+                Some(id) => format!("{fname}: {}", format_typed(types, fval, *id)),
+                None => format!("{fname}: {fval}"),
+            });
+            format!("{{ {} }}", join(parts.collect()))
+        }
+        (StrictVal::List(items), Ty::List(id, _) | Ty::Array(id, _)) => {
+            format!("[{}]", join(items.iter().map(|item| format_typed(types, item, *id)).collect()))
+        }
+        (StrictVal::Set(items), Ty::Set(id, _)) => {
+            format!(
+                "{{{}}}",
+                join(items.iter().map(|item| format_typed(types, item, *id)).collect())
+            )
+        }
+        (StrictVal::Map(items), Ty::Map(key_id, id, _)) => {
+            let parts = items.iter().map(|(key, item)| {
+                format!(
+                    "{} -> {}",
+                    format_typed(types, key, *key_id),
+                    format_typed(types, item, *id)
+                )
+            });
+            format!("{{{}}}", join(parts.collect()))
+        }
+        (val, _) => val.to_string(),
     }
 }
 
 #[cfg(test)]
 mod test {
-    //use super::*;
+    use super::*;
 
     #[test]
     fn simple() {
@@ -197,6 +440,118 @@ mod test {
         )
     }
 
+    #[test]
+    fn pretty() {
+        let strct = ston!(name "Some name", ticker "TICK", precision svenum!(8), data svlist!([0u8, 1, 2, 3, 4]), tuple ston!(a 15u8, b "text"));
+        assert_eq!(StonFormatter::new().format(&strct), strct.to_string());
+        assert_eq!(
+            StonFormatter::new().compact(false).max_line_width(30).indent(2).format(&strct),
+            r#"name "Some name",
+ticker "TICK",
+precision 8,
+data [0, 1, 2, 3, 4],
+tuple (a 15, b "text")"#
+        );
+        assert_eq!(
+            format!("{strct:#12}"),
+            r#"name "Some name",
+ticker "TICK",
+precision 8,
+data [
+    0,
+    1,
+    2,
+    3,
+    4
+],
+tuple (
+    a 15,
+    b "text"
+)"#
+        );
+
+        let fqn = TypeFqn::from("TestLib.Nominal");
+        let formatter = StonFormatter::new().comments(true);
+        assert_eq!(
+            formatter.format_typed(&ston!(ticker "TICK", inner ston!(a 15u8, b 0u8)), &fqn),
+            "/* TestLib.Nominal.ticker */ ticker \"TICK\", /* TestLib.Nominal.inner */ inner (/* \
+             TestLib.Nominal.inner.a */ a 15, /* TestLib.Nominal.inner.b */ b 0)"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "ston-parse")]
+    fn pretty_parse() {
+        let strct = ston!(name "Some name", list svlist!([ston!(a 1u8, b svsome!(2u8)), ston!(a 3u8, b svnone!())]), tuple ston!(svenum!(e), svsome!("text")));
+        let fqn = TypeFqn::from("TestLib.Nominal");
+        let formatter = StonFormatter::new().compact(false).max_line_width(10).comments(true);
+        assert_eq!(formatter.format_typed(&strct, &fqn).parse::<StrictVal>(), Ok(strct));
+    }
+
+    #[test]
+    fn typed() {
+        use encoding::StrictSerialize;
+
+        use crate::value::test_helpers::{test_system, Nominal};
+
+        let sys = test_system();
+        let data = Nominal::with("TICK", "Some name", 2).to_strict_serialized::<0xFF>().unwrap();
+        let typed = sys.strict_deserialize_type("TestLib.Nominal", data.as_slice()).unwrap();
+        let display = sys.display_typed(typed.as_val(), typed.as_orig().id).to_string();
+        assert_eq!(
+            display,
+            r#"/* TestLib.Nominal */ ticker /* StrictTypes.Ident */ "TICK", name "Some name", precision /* TestLib.Precision */ twoDecimals"#
+        );
+        #[cfg(feature = "ston-parse")]
+        assert_eq!(display.parse::<StrictVal>(), typed.as_val().to_string().parse());
+    }
+
+    #[test]
+    fn format_typed() {
+        use crate::typesys::Symbols;
+        use crate::value::test_helpers::test_system;
+
+        let sys = test_system();
+        let nominal = sys.to_sem_id("TestLib.Nominal").unwrap();
+        let val = ston!(ticker "TICK", name "Some name", precision svenum!(2));
+        assert_eq!(
+            sys.format_typed(&val, nominal).unwrap(),
+            r#"{ ticker: "TICK", name: "Some name", precision: twoDecimals }"#
+        );
+        assert!(sys.format_typed(&StrictVal::Unit, nominal).is_err());
+
+        let mut types = sys.as_types().clone();
+        let mut insert = |ty: Ty<SemId>| {
+            let id = ty.sem_id_unnamed();
+            types.insert_unchecked(id, ty).unwrap();
+            id
+        };
+        let unit = insert(Ty::UNIT);
+        let u8 = insert(Ty::U8);
+        let byte = insert(Ty::BYTE);
+        let bytes = insert(Ty::Array(byte, 4));
+        let option = insert(Ty::Union(variants!("none" => 0 => unit, "some" => 1 => u8)));
+        let point = insert(Ty::Struct(fields!("x" => u8, "y" => u8)));
+        let shape = insert(Ty::Union(
+            variants!("empty" => 0 => unit, "point" => 1 => point, "size" => 2 => u8),
+        ));
+        let sys = SymbolicSys::new(types, Symbols::with([]).unwrap());
+
+        let format = |val: StrictVal, sem_id: SemId| sys.format_typed(&val, sem_id).unwrap();
+        assert_eq!(
+            format(StrictVal::bytes([0xde, 0xad, 0xbe, 0xef]), bytes),
+            "<4 bytes: deadbeef>"
+        );
+        assert_eq!(format(svnone!(), option), "none");
+        assert_eq!(format(svsome!(5u8), option), "some(5)");
+        assert_eq!(format(StrictVal::union(0, ()), shape), "empty");
+        assert_eq!(
+            format(StrictVal::union("point", ston!(x 1u8, y 2u8)), shape),
+            "point { x: 1, y: 2 }"
+        );
+        assert_eq!(format(StrictVal::union(2, 3u8), shape), "size { 3 }");
+    }
+
     #[test]
     #[cfg(feature = "serde")]
     fn serde() {
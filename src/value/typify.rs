@@ -22,6 +22,9 @@
 
 //! Checks strict values against provied strict type specification.
 
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+
 use amplify::ascii::{AsAsciiStrError, AsciiString};
 use amplify::confinement::NonEmptyOrdSet;
 use amplify::Wrapper;
@@ -29,11 +32,14 @@ use encoding::{FieldName, InvalidRString, Primitive, Sizing, VariantName};
 use indexmap::IndexMap;
 
 use super::{Blob, StrictVal};
-use crate::ast::EnumVariants;
+use crate::ast::{EnumVariants, NamedFields};
 use crate::typesys::{SymbolicSys, TypeFqn, TypeSymbol};
 use crate::value::{EnumTag, StrictNum};
 use crate::{SemId, Ty, TypeRef, TypeSystem};
 
+/// Maximal number of types which [`SymbolicSys::infer_type`] tries to typify a value with.
+pub const INFER_TYPE_MAX_CANDIDATES: usize = 64;
+
 #[derive(Clone, Eq, PartialEq, Hash, Debug, From, Display)]
 #[display(inner)]
 pub enum TypeSpec {
@@ -88,6 +94,12 @@ pub enum Error {
     /// unexpected field `{0}`.
     ExtraField(FieldName),
 
+    /// required field `{0}` is missing.
+    MissingField(FieldName),
+
+    /// field `{0}` is provided out of the order defined by the type.
+    FieldOrderMismatch(FieldName),
+
     /// value `{value}` doesn't match type requirements `{expected}`.
     TypeMismatch {
         value: StrictVal,
@@ -101,6 +113,10 @@ pub enum Error {
     /// invalid enum tag `{0}`; allowed variants are {1}.
     EnumTagInvalid(EnumTag, EnumVariants),
 
+    /// enum tag `{0}` ambiguously matches multiple variants of {1} when compared
+    /// case-insensitively.
+    AmbiguousVariant(String, EnumVariants),
+
     /// invalid union tag `{0}`; allowed variants are {1}.
     UnionTagInvalid(EnumTag, EnumVariants),
 
@@ -109,6 +125,70 @@ pub enum Error {
 
     /// invalid optional structure {0}.
     InvalidOptional(StrictVal),
+
+    /// default value for field `{0}` doesn't match type requirements: {1}
+    InvalidDefault(FieldName, Box<Error>),
+}
+
+/// Options controlling how relaxed [`TypeSystem::typify_with_options`] is towards the provided
+/// values.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct TypifyOptions {
+    /// Match enum variant names ignoring their ASCII case if no exact match was found.
+    pub case_insensitive_enums: bool,
+    /// Require structure fields to be given in the order defined by the type.
+    pub strict_field_order: bool,
+    /// Drop structure fields which are not defined by the type instead of failing.
+    pub allow_extra_fields: bool,
+    /// Values used for structure fields which are not provided, keyed by the semantic id of the
+    /// structure type. Names of types may be resolved into ids with [`SymbolicSys::to_sem_id`].
+    pub field_defaults: HashMap<SemId, HashMap<FieldName, StrictVal>>,
+}
+
+struct TypifyCtx<'a> {
+    opts: &'a TypifyOptions,
+    /// Present if errors are collected instead of failing on the first one.
+    errors: Option<RefCell<Vec<Error>>>,
+}
+
+impl<'a> TypifyCtx<'a> {
+    fn new(opts: &'a TypifyOptions) -> Self { TypifyCtx { opts, errors: None } }
+
+    fn default_for(&self, sem_id: SemId, field: &FieldName) -> Option<&StrictVal> {
+        self.opts.field_defaults.get(&sem_id).and_then(|fields| fields.get(field))
+    }
+
+    /// If errors are collected, stores the error and returns `None`, such that the check may
+    /// proceed with the next item; otherwise returns the error.
+    fn recover<T>(&self, res: Result<T, Error>) -> Result<Option<T>, Error> {
+        match (res, &self.errors) {
+            (Ok(val), _) => Ok(Some(val)),
+            (Err(err), Some(errors)) => {
+                errors.borrow_mut().push(err);
+                Ok(None)
+            }
+            (Err(err), None) => Err(err),
+        }
+    }
+}
+
+trait VariantMatch {
+    fn by_name_case_insensitive(&self, name: &str) -> Result<Option<&VariantName>, Error>;
+}
+
+impl VariantMatch for EnumVariants {
+    fn by_name_case_insensitive(&self, name: &str) -> Result<Option<&VariantName>, Error> {
+        let folded = name.to_ascii_lowercase();
+        let mut matches = self
+            .iter()
+            .map(|variant| &variant.name)
+            .filter(|vname| vname.as_str().to_ascii_lowercase() == folded);
+        let found = matches.next();
+        if matches.next().is_some() {
+            return Err(Error::AmbiguousVariant(name.to_owned(), self.clone()));
+        }
+        Ok(found)
+    }
 }
 
 trait PrimitiveValue {
@@ -128,9 +208,94 @@ impl PrimitiveValue for Primitive {
 impl SymbolicSys {
     pub fn typify(&self, val: StrictVal, spec: impl Into<TypeSpec>) -> Result<TypedVal, Error> {
         let spec = spec.into();
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("typify", %spec).entered();
         let sem_id = self.to_sem_id(spec.clone()).ok_or(Error::TypeAbsent(spec))?;
         self.as_types().typify(val, sem_id)
     }
+
+    pub fn typify_with_options(
+        &self,
+        val: StrictVal,
+        spec: impl Into<TypeSpec>,
+        opts: &TypifyOptions,
+    ) -> Result<TypedVal, Error> {
+        let spec = spec.into();
+        let sem_id = self.to_sem_id(spec.clone()).ok_or(Error::TypeAbsent(spec))?;
+        self.as_types().typify_with_options(val, sem_id, opts)
+    }
+
+    pub fn typify_all(
+        &self,
+        val: StrictVal,
+        spec: impl Into<TypeSpec>,
+    ) -> Result<TypedVal, Vec<Error>> {
+        let spec = spec.into();
+        let sem_id = self.to_sem_id(spec.clone()).ok_or(vec![Error::TypeAbsent(spec)])?;
+        self.as_types().typify_all(val, sem_id)
+    }
+
+    /// Finds named types which the value may represent, i.e. for which [`Self::typify`]
+    /// succeeds. Unnamed types (like inline collections) are not checked.
+    ///
+    /// Types are pre-filtered with [`TypeSystem::type_matches`], and only the
+    /// [`INFER_TYPE_MAX_CANDIDATES`] most specific of the remaining types are typified, so the
+    /// list may be incomplete for values matching lots of types structurally.
+    ///
+    /// The types are ordered by their specificity, see [`Self::infer_type_within`].
+    pub fn infer_type(&self, val: &StrictVal) -> Vec<TypeSpec> {
+        self.find_compatible_type_by_name(val, "*")
+            .into_iter()
+            .take(INFER_TYPE_MAX_CANDIDATES)
+            .map(TypeSpec::from)
+            .filter(|spec| self.typify(val.clone(), spec.clone()).is_ok())
+            .collect()
+    }
+
+    /// Finds which of the `candidates` types the value may represent, i.e. for which
+    /// [`Self::typify`] succeeds. Types not passing [`TypeSystem::type_matches`] check are
+    /// skipped without typification.
+    ///
+    /// The types are ordered by their specificity: types with a bounded encoded size go before
+    /// unbounded ones, and types with narrower size ranges go first. Types with equal
+    /// specificity keep the order in which they were provided.
+    pub fn infer_type_within(&self, val: &StrictVal, candidates: &[TypeSpec]) -> Vec<TypeSpec> {
+        let types = self.as_types();
+        let mut matches = candidates
+            .iter()
+            .filter(|spec| {
+                self.to_sem_id((*spec).clone()).is_some_and(|id| types.type_matches(val, id))
+            })
+            .filter(|spec| self.typify(val.clone(), (*spec).clone()).is_ok())
+            .map(|spec| {
+                let specificity = match self.encoded_size_range(spec.clone()) {
+                    Some((min, Some(max))) => (false, max - min),
+                    _ => (true, 0),
+                };
+                (specificity, spec.clone())
+            })
+            .collect::<Vec<_>>();
+        matches.sort_by_key(|(specificity, _)| *specificity);
+        matches.into_iter().map(|(_, spec)| spec).collect()
+    }
+
+    /// Finds types with names matching a glob `pattern` (see [`Self::search`]) which the value
+    /// may represent according to [`TypeSystem::type_matches`].
+    ///
+    /// The types are ordered by their specificity, like in [`TypeSystem::find_compatible_type`].
+    pub fn find_compatible_type_by_name(&self, val: &StrictVal, pattern: &str) -> Vec<TypeFqn> {
+        let types = self.as_types();
+        let mut matches = self
+            .search(pattern)
+            .into_iter()
+            .filter_map(|fqn| {
+                let sem_id = self.to_sem_id(fqn.clone())?;
+                types.type_matches(val, sem_id).then(|| (types.specificity(sem_id), fqn))
+            })
+            .collect::<Vec<_>>();
+        matches.sort_by_key(|(specificity, _)| *specificity);
+        matches.into_iter().map(|(_, fqn)| fqn).collect()
+    }
 }
 
 impl TypeSystem {
@@ -139,6 +304,128 @@ impl TypeSystem {
     }
 
     pub fn typify(&self, val: StrictVal, sem_id: SemId) -> Result<TypedVal, Error> {
+        self.typify_with_options(val, sem_id, &TypifyOptions::default())
+    }
+
+    pub fn typify_with_options(
+        &self,
+        val: StrictVal,
+        sem_id: SemId,
+        opts: &TypifyOptions,
+    ) -> Result<TypedVal, Error> {
+        self.typify_inner(val, sem_id, &TypifyCtx::new(opts))
+    }
+
+    /// Checks the value like [`Self::typify`], but instead of failing on the first error
+    /// continues with the remaining structure fields and collection items, returning all found
+    /// errors.
+    pub fn typify_all(&self, val: StrictVal, sem_id: SemId) -> Result<TypedVal, Vec<Error>> {
+        let opts = TypifyOptions::default();
+        let ctx = TypifyCtx {
+            errors: Some(empty!()),
+            ..TypifyCtx::new(&opts)
+        };
+        let res = self.typify_inner(val, sem_id, &ctx);
+        let mut errors = ctx.errors.map(RefCell::into_inner).unwrap_or_default();
+        match res {
+            Ok(checked) if errors.is_empty() => Ok(checked),
+            Ok(_) => Err(errors),
+            Err(err) => {
+                errors.push(err);
+                Err(errors)
+            }
+        }
+    }
+
+    /// Performs a quick structural check of whether the value may represent the type with
+    /// `sem_id`, for use as a pre-filter before [`Self::typify`].
+    ///
+    /// Only the kind of the value, the number of collection items and the names of structure
+    /// fields are checked; nested values are not looked into and nothing is cloned. Thus, `false`
+    /// means that `typify` will fail, but `true` does not guarantee that it will succeed.
+    pub fn type_matches(&self, val: &StrictVal, sem_id: SemId) -> bool {
+        let Some(ty) = self.find(sem_id) else {
+            return false;
+        };
+        match (val, ty) {
+            (StrictVal::Unit, Ty::Primitive(prim)) => *prim == Primitive::UNIT,
+            (StrictVal::Number(_), Ty::Primitive(prim)) => *prim != Primitive::UNIT,
+            (StrictVal::String(s), Ty::UnicodeChar) => s.len() == 1,
+
+            (StrictVal::Enum(_) | StrictVal::String(_), Ty::Enum(_)) => true,
+            (StrictVal::Number(StrictNum::Uint(tag)), Ty::Enum(_)) => *tag < 0x100,
+            (_, ty @ Ty::Union(_)) if ty.is_option() => true,
+            (StrictVal::Union(EnumTag::Name(name), _), Ty::Union(variants)) => {
+                variants.ty_by_name(name).is_some()
+            }
+            (StrictVal::Union(EnumTag::Ord(tag), _), Ty::Union(variants)) => {
+                variants.ty_by_tag(*tag).is_some()
+            }
+
+            (StrictVal::Tuple(s), Ty::Tuple(fields)) => s.len() == fields.len(),
+            (StrictVal::List(_), Ty::Tuple(_)) => true,
+            (StrictVal::String(s), Ty::Tuple(fields)) if s.is_ascii() && fields.len() == 2 => true,
+            (val, Ty::Tuple(fields)) if fields.len() == 1 => self.type_matches(val, fields[0]),
+            (StrictVal::Struct(s), Ty::Struct(fields)) => {
+                s.keys().all(|fname| fields.ty_by_name(fname).is_some())
+            }
+            (StrictVal::Map(s), Ty::Struct(fields)) => s.iter().all(|(fname, _)| {
+                matches!(fname, StrictVal::String(fname)
+                    if fields.iter().any(|field| field.name.as_str() == fname))
+            }),
+
+            (StrictVal::Bytes(s), Ty::Array(id, len)) => id.is_byte() && s.len() <= *len as usize,
+            (StrictVal::String(s), Ty::Array(id, len)) => {
+                (s.is_ascii() || id.is_unicode_char()) && s.len() <= *len as usize
+            }
+            (StrictVal::List(s), Ty::Array(_, len)) => s.len() <= *len as usize,
+            (StrictVal::Bytes(s), Ty::List(id, sizing)) => id.is_byte() && sizing.check(s.len()),
+            (StrictVal::String(s), Ty::List(id, sizing)) => {
+                (id.is_unicode_char()
+                    || id.is_byte()
+                    || (s.is_ascii() && self.is_ascii_string(sem_id)))
+                    && sizing.check(s.len())
+            }
+            (StrictVal::List(s), Ty::List(_, sizing)) | (StrictVal::Set(s), Ty::Set(_, sizing)) => {
+                sizing.check(s.len())
+            }
+            (StrictVal::Map(s), Ty::Map(_, _, sizing)) => sizing.check(s.len()),
+
+            _ => false,
+        }
+    }
+
+    /// Selects the `candidates` which the value may represent according to
+    /// [`Self::type_matches`].
+    ///
+    /// The types are ordered by their specificity: types with a bounded encoded size go before
+    /// unbounded ones, and types with narrower size ranges go first. Types with equal specificity
+    /// keep the order in which they were provided.
+    pub fn find_compatible_type(&self, val: &StrictVal, candidates: &[SemId]) -> Vec<SemId> {
+        let mut matches = candidates
+            .iter()
+            .filter(|sem_id| self.type_matches(val, **sem_id))
+            .map(|sem_id| (self.specificity(*sem_id), *sem_id))
+            .collect::<Vec<_>>();
+        matches.sort_by_key(|(specificity, _)| *specificity);
+        matches.into_iter().map(|(_, sem_id)| sem_id).collect()
+    }
+
+    /// Sorting key putting types with bounded and narrower encoded size ranges first.
+    fn specificity(&self, sem_id: SemId) -> (bool, u64) {
+        match self.find(sem_id).map(|ty| ty.encoded_size_range(self)) {
+            Some((min, Some(max))) => (false, max - min),
+            _ => (true, 0),
+        }
+    }
+
+    fn typify_inner(
+        &self,
+        val: StrictVal,
+        sem_id: SemId,
+        ctx: &TypifyCtx,
+    ) -> Result<TypedVal, Error> {
+        let opts = ctx.opts;
         let spec = TypeSpec::from(sem_id);
         let ty = self.find(sem_id).ok_or_else(|| Error::TypeAbsent(spec.clone()))?;
         let val = match (val, ty) {
@@ -238,24 +525,63 @@ impl TypeSystem {
             (StrictVal::String(s), Ty::List(id, _)) if id.is_byte() => {
                 StrictVal::Bytes(Blob(s.into_bytes()))
             }
-            (StrictVal::String(s), Ty::List(_, _)) if s.is_ascii() => {
+            (StrictVal::String(s), Ty::List(id, _)) if self.is_ascii_string(sem_id) => {
                 AsciiString::from_ascii(s.as_bytes()).map_err(|err| err.ascii_error())?;
+                let Some(Ty::Enum(variants)) = self.find(*id) else {
+                    unreachable!("checked by is_ascii_string")
+                };
+                if let Some(ch) = s.bytes().find(|ch| !variants.has_tag(*ch)) {
+                    return Err(Error::EnumTagInvalid(EnumTag::Ord(ch), variants.clone()));
+                }
                 StrictVal::String(s)
             }
+            // ASCII strings given as lists of characters
+            (StrictVal::List(s), Ty::List(id, _)) if self.is_ascii_string(sem_id) => {
+                let Some(Ty::Enum(variants)) = self.find(*id) else {
+                    unreachable!("checked by is_ascii_string")
+                };
+                let mut new = String::with_capacity(s.len());
+                for item in s {
+                    let Some(checked) = ctx.recover(self.typify_inner(item, *id, ctx))? else {
+                        continue;
+                    };
+                    let tag = match &checked.val {
+                        StrictVal::Enum(EnumTag::Name(name)) => variants.tag_by_name(name),
+                        StrictVal::Enum(EnumTag::Ord(tag)) => Some(*tag),
+                        // Single characters are typified as strings
+                        StrictVal::String(ch) => ch.bytes().next(),
+                        _ => None,
+                    };
+                    let Some(tag) = tag else {
+                        ctx.recover::<()>(Err(Error::TypeMismatch {
+                            value: checked.val,
+                            expected: Ty::Enum(variants.clone()),
+                        }))?;
+                        continue;
+                    };
+                    new.push(tag as char);
+                }
+                StrictVal::String(new)
+            }
             (StrictVal::List(s), Ty::List(id, _)) => {
                 let mut new = Vec::with_capacity(s.len());
                 for item in s {
-                    let checked = self.typify(item, *id)?;
-                    new.push(checked.val);
+                    if let Some(checked) = ctx.recover(self.typify_inner(item, *id, ctx))? {
+                        new.push(checked.val);
+                    }
                 }
                 StrictVal::List(new)
             }
             (StrictVal::Set(s), Ty::Set(id, _)) => {
                 let mut new = Vec::with_capacity(s.len());
+                let mut seen = BTreeSet::new();
                 for item in s {
-                    let checked = self.typify(item, *id)?;
-                    if new.contains(&checked.val) {
-                        return Err(Error::RepeatedSetValue(spec, checked.val));
+                    let Some(checked) = ctx.recover(self.typify_inner(item, *id, ctx))? else {
+                        continue;
+                    };
+                    if !seen.insert(checked.val.clone()) {
+                        ctx.recover::<()>(Err(Error::RepeatedSetValue(spec.clone(), checked.val)))?;
+                        continue;
                     }
                     new.push(checked.val);
                 }
@@ -263,11 +589,19 @@ impl TypeSystem {
             }
             (StrictVal::Map(s), Ty::Map(key_id, id, _)) => {
                 let mut new = Vec::<(StrictVal, StrictVal)>::with_capacity(s.len());
+                let mut seen = BTreeSet::new();
                 for (key, item) in s {
-                    let checked_key = self.typify(key, *key_id)?;
-                    let checked_val = self.typify(item, *id)?;
-                    if new.iter().any(|(k, _)| k == &checked_key.val) {
-                        return Err(Error::RepeatedKeyValue(spec, checked_key.val));
+                    let checked_key = ctx.recover(self.typify_inner(key, *key_id, ctx))?;
+                    let checked_val = ctx.recover(self.typify_inner(item, *id, ctx))?;
+                    let (Some(checked_key), Some(checked_val)) = (checked_key, checked_val) else {
+                        continue;
+                    };
+                    if !seen.insert(checked_key.val.clone()) {
+                        ctx.recover::<()>(Err(Error::RepeatedKeyValue(
+                            spec.clone(),
+                            checked_key.val,
+                        )))?;
+                        continue;
                     }
                     new.push((checked_key.val, checked_val.val));
                 }
@@ -277,7 +611,11 @@ impl TypeSystem {
             // Enums:
             (StrictVal::Enum(tag), Ty::Enum(variants)) => {
                 let vname = match &tag {
-                    EnumTag::Name(name) => variants.tag_by_name(name).map(|_| name),
+                    EnumTag::Name(name) if variants.has_name(name) => Some(name),
+                    EnumTag::Name(name) if opts.case_insensitive_enums => {
+                        variants.by_name_case_insensitive(name.as_str())?
+                    }
+                    EnumTag::Name(_) => None,
                     EnumTag::Ord(ord) => variants.name_by_tag(*ord),
                 };
                 match vname {
@@ -286,16 +624,24 @@ impl TypeSystem {
                 }
             }
             (StrictVal::String(s), Ty::Enum(variants)) => {
-                if let Ok(vname) = VariantName::try_from(s.clone()) {
-                    if !variants.has_name(&vname) {
+                let exact = VariantName::try_from(s.clone());
+                let folded = match (&exact, opts.case_insensitive_enums) {
+                    (Ok(vname), _) if variants.has_name(vname) => None,
+                    (_, true) => variants.by_name_case_insensitive(&s)?,
+                    (_, false) => None,
+                };
+                match (exact, folded) {
+                    (Ok(vname), _) if variants.has_name(&vname) => StrictVal::enumer(vname),
+                    (_, Some(vname)) => StrictVal::enumer(vname.clone()),
+                    (Ok(vname), None) => {
                         return Err(Error::EnumTagInvalid(vname.into(), variants.clone()));
                     }
-                    StrictVal::enumer(vname)
-                } else {
-                    return Err(Error::TypeMismatch {
-                        value: StrictVal::String(s),
-                        expected: ty.clone(),
-                    });
+                    (Err(_), None) => {
+                        return Err(Error::TypeMismatch {
+                            value: StrictVal::String(s),
+                            expected: ty.clone(),
+                        });
+                    }
                 }
             }
             (StrictVal::Number(StrictNum::Uint(tag)), Ty::Enum(variants)) if tag < 0x100 => {
@@ -316,7 +662,7 @@ impl TypeSystem {
                         NonEmptyOrdSet::from_iter_checked(vars_req.keys().cloned()).into(),
                     ));
                 };
-                let checked = self.typify(*content, *id)?;
+                let checked = self.typify_inner(*content, *id, ctx)?;
                 StrictVal::Union(tag, Box::new(checked.val))
             }
 
@@ -330,50 +676,29 @@ impl TypeSystem {
                     found: fields.len(),
                 });
             }
-            (StrictVal::Struct(fields), Ty::Struct(fields_req))
-                if fields.len() != fields_req.len() =>
-            {
-                return Err(Error::FieldNumberMismatch {
-                    spec,
-                    expected: fields_req.len(),
-                    found: fields.len(),
-                });
-            }
 
             // Check specific field types:
             (StrictVal::Tuple(s) | StrictVal::List(s), Ty::Tuple(fields_req)) => {
                 let mut new = Vec::with_capacity(s.len());
                 for (item, id) in s.into_iter().zip(fields_req) {
-                    let checked = self.typify(item, *id)?;
-                    new.push(checked.val);
+                    if let Some(checked) = ctx.recover(self.typify_inner(item, *id, ctx))? {
+                        new.push(checked.val);
+                    }
                 }
                 StrictVal::Tuple(new)
             }
             (StrictVal::Struct(s), Ty::Struct(fields_req)) => {
-                let mut new = IndexMap::with_capacity(s.len());
-                for (fname, item) in s.into_iter() {
-                    let Some(field) = fields_req.ty_by_name(&fname) else {
-                        return Err(Error::ExtraField(fname));
-                    };
-                    let checked = self.typify(item, *field)?;
-                    new.insert(fname, checked.val);
-                }
-                StrictVal::Struct(new)
+                StrictVal::Struct(self.typify_fields(s, sem_id, fields_req, ctx)?)
             }
             (StrictVal::Map(s), Ty::Struct(fields_req)) => {
-                let mut new = IndexMap::with_capacity(s.len());
+                let mut fields = IndexMap::with_capacity(s.len());
                 for (fname, item) in s.into_iter() {
                     let StrictVal::String(fname) = fname else {
                         return Err(Error::MapNotStructure);
                     };
-                    let fname = FieldName::try_from(fname)?;
-                    let Some(field) = fields_req.ty_by_name(&fname) else {
-                        return Err(Error::ExtraField(fname));
-                    };
-                    let checked = self.typify(item, *field)?;
-                    new.insert(fname, checked.val);
+                    fields.insert(FieldName::try_from(fname)?, item);
                 }
-                StrictVal::Struct(new)
+                StrictVal::Struct(self.typify_fields(fields, sem_id, fields_req, ctx)?)
             }
 
             // Optional
@@ -388,7 +713,8 @@ impl TypeSystem {
                 if tuple[0] == StrictVal::String(s!("none")) && tuple[1] == StrictVal::Unit {
                     StrictVal::union("none", StrictVal::Unit)
                 } else if tuple[0] == StrictVal::String(s!("some")) {
-                    let inner = self.typify(tuple.pop().expect("two elements"), content)?.val;
+                    let inner =
+                        self.typify_inner(tuple.pop().expect("two elements"), content, ctx)?.val;
                     StrictVal::union("some", inner)
                 } else {
                     return Err(Error::InvalidOptional(StrictVal::Tuple(tuple)));
@@ -397,13 +723,13 @@ impl TypeSystem {
             (val, ty @ Ty::Union(fields)) if ty.is_option() => {
                 // this is `Some`
                 let content = *fields.ty_by_tag(1).expect("optional always have `Some`");
-                let inner = self.typify(val, content)?.val;
+                let inner = self.typify_inner(val, content, ctx)?.val;
                 StrictVal::union("some", inner)
             }
 
             // Newtype wrapper
             (val, Ty::Tuple(fields)) if fields.len() == 1 => {
-                let inner = self.typify(val, fields[0])?.val;
+                let inner = self.typify_inner(val, fields[0], ctx)?.val;
                 StrictVal::Tuple(vec![inner])
             }
 
@@ -419,6 +745,52 @@ impl TypeSystem {
             val,
         })
     }
+
+    /// Checks structure fields, which may be provided in any order, returning them in the order
+    /// defined by the type.
+    fn typify_fields(
+        &self,
+        mut fields: IndexMap<FieldName, StrictVal>,
+        sem_id: SemId,
+        fields_req: &NamedFields<SemId>,
+        ctx: &TypifyCtx,
+    ) -> Result<IndexMap<FieldName, StrictVal>, Error> {
+        let opts = ctx.opts;
+        if opts.allow_extra_fields {
+            fields.retain(|fname, _| fields_req.ty_by_name(fname).is_some());
+        } else {
+            for fname in fields.keys().filter(|fname| fields_req.ty_by_name(fname).is_none()) {
+                ctx.recover::<()>(Err(Error::ExtraField(fname.clone())))?;
+            }
+        }
+        if opts.strict_field_order {
+            // Fields absent from the input (and thus taken from defaults) don't affect the order
+            let provided = fields_req.iter().filter(|field| fields.contains_key(&field.name));
+            let known = fields.keys().filter(|fname| fields_req.ty_by_name(fname).is_some());
+            if let Some((fname, _)) =
+                known.zip(provided).find(|(fname, field)| **fname != field.name)
+            {
+                ctx.recover::<()>(Err(Error::FieldOrderMismatch(fname.clone())))?;
+            }
+        }
+        let mut new = IndexMap::with_capacity(fields_req.len());
+        for field in fields_req {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(%sem_id, field = %field.name, "typifying field");
+            let res = match (fields.shift_remove(&field.name), ctx.default_for(sem_id, &field.name))
+            {
+                (Some(item), _) => self.typify_inner(item, field.ty, ctx),
+                (None, Some(item)) => self
+                    .typify_inner(item.clone(), field.ty, ctx)
+                    .map_err(|err| Error::InvalidDefault(field.name.clone(), Box::new(err))),
+                (None, None) => Err(Error::MissingField(field.name.clone())),
+            };
+            if let Some(checked) = ctx.recover(res)? {
+                new.insert(field.name.clone(), checked.val);
+            }
+        }
+        Ok(new)
+    }
 }
 
 #[cfg(test)]
@@ -427,7 +799,9 @@ mod test {
     use encoding::{StreamReader, StrictSerialize};
 
     use super::super::test_helpers::*;
-    // use super::*;
+    use super::*;
+    use crate::typesys::SystemBuilder;
+    use crate::LibBuilder;
 
     #[test]
     fn load() {
@@ -441,4 +815,270 @@ mod test {
         let loaded = sys.strict_read_type("TestLib.Nominal", &mut reader).unwrap();
         assert_eq!(loaded.val, value);
     }
+
+    #[test]
+    fn ascii_string() {
+        let sys = test_system();
+        let alpha = sys.to_sem_id("Std.AlphaNumLodash").unwrap();
+        let mut types = sys.as_types().clone();
+        let mut insert = |ty: Ty<SemId>| {
+            let id = ty.sem_id_unnamed();
+            types.insert_unchecked(id, ty).unwrap();
+            id
+        };
+        let u16 = insert(Ty::U16);
+        let ascii = insert(Ty::List(alpha, Sizing::U8));
+        let numbers = insert(Ty::List(u16, Sizing::U8));
+
+        assert!(types[alpha].is_ascii_char());
+        assert!(!Ty::<SemId>::UnicodeChar.is_ascii_char());
+        assert!(types.is_ascii_string(ascii));
+        assert!(!types.is_ascii_string(numbers));
+        assert!(!types.is_ascii_string(alpha));
+
+        let val = StrictVal::String(s!("some_1"));
+        assert!(types.type_matches(&val, ascii));
+        assert_eq!(types.typify(val.clone(), ascii).unwrap().val, val);
+        assert!(!types.type_matches(&val, numbers));
+        assert!(types.typify(val, numbers).is_err());
+        assert_eq!(
+            types.typify(StrictVal::String(s!("no-dash")), ascii).unwrap_err(),
+            Error::EnumTagInvalid(EnumTag::Ord(b'-'), match &types[alpha] {
+                Ty::Enum(variants) => variants.clone(),
+                _ => unreachable!(),
+            })
+        );
+
+        let list = StrictVal::list([svenum!(_A), svenum!(one), svenum!(b'_')]);
+        assert_eq!(types.typify(list, ascii).unwrap().val, StrictVal::String(s!("A1_")));
+        let list = StrictVal::list(["A", "b"]);
+        assert_eq!(types.typify(list, ascii).unwrap().val, StrictVal::String(s!("Ab")));
+    }
+
+    #[test]
+    fn type_matches() {
+        let sys = test_system();
+        let types = sys.as_types();
+        let nominal = sys.to_sem_id("TestLib.Nominal").unwrap();
+        let precision = sys.to_sem_id("TestLib.Precision").unwrap();
+
+        let value = ston!(ticker "TICK", name "Some name", precision svenum!(2));
+        assert!(types.type_matches(&value, nominal));
+        assert!(sys.typify(value, "TestLib.Nominal").is_ok());
+        // Partial structure passes the check, but not the typification
+        let value = ston!(ticker "TICK", name "Some name");
+        assert!(types.type_matches(&value, nominal));
+        assert!(sys.typify(value, "TestLib.Nominal").is_err());
+
+        assert!(!types.type_matches(&ston!(ticker "TICK", unknown 1u8), nominal));
+        assert!(!types.type_matches(&StrictVal::Unit, nominal));
+        assert!(!types.type_matches(&StrictVal::from(5u8), SemId::unit()));
+        assert!(types.type_matches(&svenum!(twoDecimals), precision));
+        assert!(types.type_matches(&StrictVal::from(2u8), precision));
+        assert!(!types.type_matches(&StrictVal::list([1u8]), precision));
+    }
+
+    #[test]
+    fn find_compatible_type() {
+        let sys = test_system();
+        let types = sys.as_types();
+        let nominal = sys.to_sem_id("TestLib.Nominal").unwrap();
+        let precision = sys.to_sem_id("TestLib.Precision").unwrap();
+        let ident = sys.to_sem_id("StrictTypes.Ident").unwrap();
+
+        let value = StrictVal::from("twoDecimals");
+        assert_eq!(types.find_compatible_type(&value, &[nominal, ident, precision]), vec![
+            precision, ident
+        ]);
+        assert_eq!(sys.find_compatible_type_by_name(&value, "TestLib.*"), vec![TypeFqn::from(
+            "TestLib.Precision"
+        )]);
+        let value = ston!(ticker "TICK", name "Some name");
+        assert_eq!(sys.find_compatible_type_by_name(&value, "*"), vec![TypeFqn::from(
+            "TestLib.Nominal"
+        )]);
+        assert!(sys.find_compatible_type_by_name(&value, "Std.*").is_empty());
+    }
+
+    #[test]
+    fn struct_field_order() {
+        let sys = test_system();
+        let expected = r#"ticker "TICK", name "Some name", precision twoDecimals"#;
+
+        let value = ston!(ticker "TICK", name "Some name", precision svenum!(2));
+        let checked = sys.typify(value, "TestLib.Nominal").unwrap();
+        assert_eq!(checked.val.to_string(), expected);
+
+        let value = ston!(precision svenum!(2), name "Some name", ticker "TICK");
+        let checked = sys.typify(value, "TestLib.Nominal").unwrap();
+        assert_eq!(checked.val.to_string(), expected);
+
+        let value = ston!(name "Some name", extra 1u8, ticker "TICK", precision svenum!(2));
+        let err = sys.typify(value, "TestLib.Nominal").unwrap_err();
+        assert_eq!(err, Error::ExtraField(fname!("extra")));
+
+        let value = ston!(name "Some name", ticker "TICK");
+        let err = sys.typify(value, "TestLib.Nominal").unwrap_err();
+        assert_eq!(err, Error::MissingField(fname!("precision")));
+    }
+
+    #[derive(Copy, Clone, Eq, PartialEq, Debug)]
+    #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "TestLib", tags = repr, into_u8, try_from_u8)]
+    #[repr(u8)]
+    enum Casing {
+        #[strict_type(dumb)]
+        LowerCase = 0,
+        Lowercase = 1,
+    }
+
+    #[test]
+    fn case_insensitive_enums() {
+        let sys = test_system();
+        let opts = TypifyOptions {
+            case_insensitive_enums: true,
+            ..default!()
+        };
+
+        let value = ston!(ticker "TICK", name "Some name", precision "twodecimals");
+        let err = sys.typify(value.clone(), "TestLib.Nominal").unwrap_err();
+        assert!(matches!(err, Error::EnumTagInvalid(..)));
+        let checked = sys.typify_with_options(value, "TestLib.Nominal", &opts).unwrap();
+        assert_eq!(
+            checked.val.to_string(),
+            r#"ticker "TICK", name "Some name", precision twoDecimals"#
+        );
+
+        let value = ston!(ticker "TICK", name "Some name", precision "TWODECIMALS");
+        assert!(sys.typify_with_options(value, "TestLib.Nominal", &opts).is_ok());
+
+        let lib = LibBuilder::new("TestLib", []).transpile::<Casing>().compile().unwrap();
+        let sys = SystemBuilder::new().import(lib).unwrap().finalize().unwrap();
+        let checked =
+            sys.typify_with_options(StrictVal::from("lowercase"), "TestLib.Casing", &opts).unwrap();
+        assert_eq!(checked.val, StrictVal::enumer("lowercase"));
+        let err = sys
+            .typify_with_options(StrictVal::from("LOWERCASE"), "TestLib.Casing", &opts)
+            .unwrap_err();
+        assert!(matches!(err, Error::AmbiguousVariant(..)));
+    }
+
+    #[test]
+    fn field_defaults() {
+        let sys = test_system();
+        let mut opts = test_defaults(&sys);
+        let nominal = sys.to_sem_id("TestLib.Nominal").unwrap();
+
+        let value = ston!(ticker "TICK", name "Some name");
+        let err = sys.typify(value.clone(), "TestLib.Nominal").unwrap_err();
+        assert_eq!(err, Error::MissingField(fname!("precision")));
+        let checked = sys.typify_with_options(value.clone(), "TestLib.Nominal", &opts).unwrap();
+        assert_eq!(
+            checked.val.to_string(),
+            r#"ticker "TICK", name "Some name", precision noDecimals"#
+        );
+        let checked = sys.as_types().typify_with_options(value.clone(), nominal, &opts).unwrap();
+        assert_eq!(
+            checked.val.to_string(),
+            r#"ticker "TICK", name "Some name", precision noDecimals"#
+        );
+
+        let value2 = ston!(ticker "TICK", name "Some name", precision svenum!(1));
+        let checked = sys.typify_with_options(value2, "TestLib.Nominal", &opts).unwrap();
+        assert_eq!(
+            checked.val.to_string(),
+            r#"ticker "TICK", name "Some name", precision oneDecimal"#
+        );
+
+        opts.field_defaults
+            .get_mut(&nominal)
+            .unwrap()
+            .insert(fname!("precision"), StrictVal::enumer("manyDecimals"));
+        let err = sys.typify_with_options(value, "TestLib.Nominal", &opts).unwrap_err();
+        assert!(matches!(err, Error::InvalidDefault(fname, _) if fname == fname!("precision")));
+    }
+
+    #[test]
+    fn field_order_defaults() {
+        let sys = test_system();
+        let mut opts = test_defaults(&sys);
+        opts.strict_field_order = true;
+        opts.field_defaults
+            .get_mut(&sys.to_sem_id("TestLib.Nominal").unwrap())
+            .unwrap()
+            .insert(fname!("name"), StrictVal::str("Default name"));
+
+        let value = ston!(ticker "TICK", precision svenum!(twoDecimals));
+        let checked = sys.typify_with_options(value, "TestLib.Nominal", &opts).unwrap();
+        assert_eq!(
+            checked.val.to_string(),
+            r#"ticker "TICK", name "Default name", precision twoDecimals"#
+        );
+
+        let value = ston!(precision svenum!(twoDecimals), ticker "TICK");
+        let err = sys.typify_with_options(value, "TestLib.Nominal", &opts).unwrap_err();
+        assert_eq!(err, Error::FieldOrderMismatch(fname!("precision")));
+
+        let opts = TypifyOptions {
+            strict_field_order: true,
+            ..default!()
+        };
+        let value = ston!(ticker "TICK", precision svenum!(twoDecimals));
+        let err = sys.typify_with_options(value, "TestLib.Nominal", &opts).unwrap_err();
+        assert_eq!(err, Error::MissingField(fname!("name")));
+    }
+
+    #[test]
+    fn typify_all() {
+        let sys = test_system();
+        let value = ston!(ticker 5u8, name "", precision "manyDecimals");
+        let errors = sys.typify_all(value.clone(), "TestLib.Nominal").unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(errors[0], Error::TypeMismatch { .. }));
+        assert!(matches!(errors[1], Error::OutOfBounds(..)));
+        assert!(matches!(errors[2], Error::EnumTagInvalid(..)));
+        assert_eq!(sys.typify(value, "TestLib.Nominal").unwrap_err(), errors[0]);
+
+        let value = ston!(ticker "TICK", extra 1u8, precision "twoDecimals");
+        let errors = sys.typify_all(value, "TestLib.Nominal").unwrap_err();
+        assert_eq!(errors, vec![
+            Error::ExtraField(fname!("extra")),
+            Error::MissingField(fname!("name"))
+        ]);
+
+        let value = ston!(ticker "TICK", name "Some name", precision "twoDecimals");
+        assert!(sys.typify_all(value, "TestLib.Nominal").is_ok());
+    }
+
+    #[test]
+    fn infer_type() {
+        let sys = test_system();
+        let value = ston!(ticker "TICK", name "Some name", precision svenum!(2));
+        assert_eq!(sys.infer_type(&value), vec![TypeSpec::from("TestLib.Nominal")]);
+        assert!(sys.infer_type(&ston!(ticker "TICK")).is_empty());
+
+        let value = svenum!(2);
+        let types = sys.infer_type(&value);
+        assert!(types.contains(&TypeSpec::from("TestLib.Precision")));
+        assert!(!types.contains(&TypeSpec::from("Std.U1")));
+        for spec in types {
+            assert!(sys.typify(value.clone(), spec).is_ok());
+        }
+
+        let candidates = [
+            TypeSpec::from("TestLib.Nominal"),
+            TypeSpec::from("StrictTypes.Ident"),
+            TypeSpec::from("StrictTypes.SemId"),
+        ];
+        assert_eq!(sys.infer_type_within(&svstr!("TICK"), &candidates), vec![
+            TypeSpec::from("StrictTypes.SemId"),
+            TypeSpec::from("StrictTypes.Ident")
+        ]);
+        assert!(sys.infer_type_within(&svstr!("TICK"), &[]).is_empty());
+
+        let value = svnum!(1u8);
+        let types = sys.infer_type(&value);
+        assert!(!types.is_empty());
+        assert!(types.len() <= INFER_TYPE_MAX_CANDIDATES);
+    }
 }
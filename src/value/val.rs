@@ -22,11 +22,14 @@
 
 //! Strict value core types.
 
-use std::fmt::Debug;
+use std::cmp::Ordering;
+use std::fmt::{self, Debug, Display, Formatter, LowerExp};
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
 
 use amplify::hex::ToHex;
-// use amplify::num::apfloat::ieee;
-use amplify::num::{i1024, u1024, u24, u40, u48, u56};
+use amplify::num::apfloat::{ieee, Float, FloatConvert, Round};
+use amplify::num::{i1024, u1024, u24, u256, u40, u48, u56};
 use amplify::Wrapper;
 use encoding::{FieldName, StrictEnum, VariantName};
 use indexmap::IndexMap;
@@ -172,8 +175,11 @@ macro_rules! svtable {
     };
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Display, From)]
-#[display(inner)]
+/// Number kept in strict value.
+///
+/// Numbers are ordered by their value (see [`StrictNum::total_cmp`]), thus the same value
+/// represented by different variants is ordered consistently with other numbers.
+#[derive(Copy, Clone, Debug, From)]
 #[cfg_attr(
     feature = "serde",
     derive(Serialize, Deserialize),
@@ -210,24 +216,269 @@ pub enum StrictNum {
     //#[from(i512)]
     #[from]
     BigInt(i1024),
-    // TODO: Do conversion of number types in to amplify_num
-    /*
-    #[from(half::bf16)]
-    #[from(ieee::Half)]
-    #[from(ieee::Single)]
-    #[from(ieee::Double)]
-    #[from(ieee::Quad)]
-    #[from(ieee::Oct]
-    #[from(f32)]
-    #[from(f64)]
+
+    #[from]
+    #[cfg_attr(feature = "serde", serde(with = "serde_float"))]
     Float(ieee::Oct),
-    */
-    // TODO: Addnon-zero
+}
+
+macro_rules! impl_from_float {
+    ($($ty:ty),+) => {$(
+        impl From<$ty> for StrictNum {
+            fn from(f: $ty) -> Self { StrictNum::Float(f.convert(&mut false).value) }
+        }
+    )+};
+}
+impl_from_float!(ieee::Half, ieee::Single, ieee::Double, ieee::Quad);
+
+impl From<f32> for StrictNum {
+    fn from(f: f32) -> Self { StrictNum::from(ieee::Single::from_bits(u256::from(f.to_bits()))) }
+}
+
+impl From<f64> for StrictNum {
+    fn from(f: f64) -> Self { StrictNum::from_f64(f) }
+}
+
+impl PartialEq for StrictNum {
+    fn eq(&self, other: &Self) -> bool { self.total_cmp(other) == Ordering::Equal }
+}
+
+impl Eq for StrictNum {}
+
+impl Hash for StrictNum {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.variant_no().hash(state);
+        match self {
+            StrictNum::Uint(v) => v.hash(state),
+            StrictNum::BigUint(v) => v.hash(state),
+            StrictNum::Int(v) => v.hash(state),
+            StrictNum::BigInt(v) => v.hash(state),
+            StrictNum::Float(v) => v.to_bits().hash(state),
+        }
+    }
+}
+
+/// Displays integers in decimal notation, and floats in scientific notation with the number of
+/// significant digits allowing lossless round-trip (see [`StrictNum::from_float_str`]).
+/// Infinities and NaN are always prefixed with a sign, so they can't be confused with
+/// identifiers.
+impl Display for StrictNum {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            StrictNum::Uint(v) => Display::fmt(v, f),
+            StrictNum::BigUint(v) => Display::fmt(v, f),
+            StrictNum::Int(v) => Display::fmt(v, f),
+            StrictNum::BigInt(v) => Display::fmt(v, f),
+            StrictNum::Float(v) => {
+                let sign = if v.is_negative() { "-" } else { "+" };
+                if v.is_nan() {
+                    return write!(f, "{sign}NaN");
+                }
+                if v.is_infinite() {
+                    return write!(f, "{sign}inf");
+                }
+                if let Some(double) = oct_to_f64(*v) {
+                    return LowerExp::fmt(&double, f);
+                }
+                f.write_str(&oct_to_sci(*v))
+            }
+        }
+    }
+}
+
+/// Maximal number of significant digits in the shortest representation of `f64` numbers.
+/// Numbers written with more digits are parsed as 256-bit floats.
+const F64_DIGITS: usize = 17;
+
+/// Converts the float into `f64`, if it is exactly representable by it.
+fn oct_to_f64(f: ieee::Oct) -> Option<f64> {
+    let mut loses_info = false;
+    let double: ieee::Double = f.convert(&mut loses_info).value;
+    if loses_info {
+        return None;
+    }
+    Some(f64::from_bits(double.to_bits().low_u64()))
+}
+
+/// Formats the float, which is not exactly representable by `f64`, in scientific notation using
+/// the smallest number of significant digits allowing lossless round-trip, but no less than
+/// [`F64_DIGITS`] + 1, so the parser can distinguish it from `f64` numbers.
+fn oct_to_sci(f: ieee::Oct) -> String {
+    // Default precision of `ieee::Oct` display is enough for a lossless round-trip
+    let (neg, digits, exp) = decompose_decimal(&format!("{f}"));
+    let sign = if neg { "-" } else { "" };
+    let render = |len: usize| {
+        let (digits, exp) = round_digits(&digits, exp, len);
+        format!("{sign}{}.{}e{exp}", &digits[..1], &digits[1..])
+    };
+    (F64_DIGITS + 1..digits.len())
+        .map(render)
+        .find(|s| ieee::Oct::from_str(s).is_ok_and(|parsed| parsed.bitwise_eq(f)))
+        .unwrap_or_else(|| render(digits.len().max(F64_DIGITS + 1)))
+}
+
+/// Rounds significant decimal digits half-up to the given length, padding them with zeros if
+/// required. Returns the digits together with the adjusted decimal exponent.
+fn round_digits(digits: &str, exp: i64, len: usize) -> (String, i64) {
+    let mut res = digits.as_bytes()[..len.min(digits.len())].to_vec();
+    res.resize(len, b'0');
+    if digits.as_bytes().get(len).is_some_and(|d| *d >= b'5') {
+        match res.iter().rposition(|d| *d != b'9') {
+            Some(pos) => {
+                res[pos] += 1;
+                res[pos + 1..].fill(b'0');
+            }
+            None => {
+                res.fill(b'0');
+                res[0] = b'1';
+                return (String::from_utf8(res).expect("ASCII digits"), exp + 1);
+            }
+        }
+    }
+    (String::from_utf8(res).expect("ASCII digits"), exp)
+}
+
+/// Splits finite decimal number, written either in plain or scientific notation, into its sign,
+/// significant digits and decimal exponent of the first digit.
+fn decompose_decimal(s: &str) -> (bool, String, i64) {
+    let (neg, s) = match s.strip_prefix('-') {
+        Some(s) => (true, s),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (mantissa, exp) = s.split_once(['e', 'E']).unwrap_or((s, "0"));
+    let exp = exp.parse::<i64>().unwrap_or_default();
+    let (int, fract) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let all = format!("{int}{fract}");
+    let digits = all.trim_start_matches('0');
+    if digits.is_empty() {
+        return (neg, s!("0"), 0);
+    }
+    let leading = (all.len() - digits.len()) as i64;
+    let digits = digits.trim_end_matches('0');
+    (neg, digits.to_owned(), exp + int.len() as i64 - leading - 1)
+}
+
+/// Serializes floats as numbers if they are exactly representable by `f64`, and as strings
+/// otherwise.
+#[cfg(feature = "serde")]
+mod serde_float {
+    use serde::de::Error;
+    use serde::{Deserializer, Serializer};
+
+    use super::*;
+
+    pub fn serialize<S: Serializer>(f: &ieee::Oct, serializer: S) -> Result<S::Ok, S::Error> {
+        match oct_to_f64(*f) {
+            Some(double) if double.is_finite() => serializer.serialize_f64(double),
+            _ => serializer.serialize_str(&StrictNum::Float(*f).to_string()),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ieee::Oct, D::Error> {
+        struct Visitor;
+        impl serde::de::Visitor<'_> for Visitor {
+            type Value = StrictNum;
+
+            fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+                f.write_str("floating-point number or its string representation")
+            }
+
+            fn visit_f64<E: Error>(self, v: f64) -> Result<Self::Value, E> {
+                Ok(StrictNum::from_f64(v))
+            }
+
+            fn visit_u64<E: Error>(self, v: u64) -> Result<Self::Value, E> {
+                Ok(StrictNum::from_f64(v as f64))
+            }
+
+            fn visit_i64<E: Error>(self, v: i64) -> Result<Self::Value, E> {
+                Ok(StrictNum::from_f64(v as f64))
+            }
+
+            fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+                StrictNum::from_float_str(v)
+                    .ok_or_else(|| E::custom(format!("invalid floating-point number `{v}`")))
+            }
+        }
+        match deserializer.deserialize_any(Visitor)? {
+            StrictNum::Float(f) => Ok(f),
+            _ => unreachable!("visitor produces only floats"),
+        }
+    }
 }
 
 // TODO: Do conversion of number types in to amplify_num
 
 impl StrictNum {
+    pub fn from_f64(f: f64) -> StrictNum {
+        StrictNum::from(ieee::Double::from_bits(u256::from(f.to_bits())))
+    }
+
+    /// Parses floating-point number written in the notation produced by [`Display`]: in plain
+    /// or scientific notation, or as a signed `inf` or `NaN`.
+    ///
+    /// Numbers with no more than 17 significant digits are parsed as `f64` numbers, while numbers
+    /// with more digits are rounded to the nearest 256-bit float.
+    pub fn from_float_str(s: &str) -> Option<StrictNum> {
+        let (neg, abs) = match s.strip_prefix('-') {
+            Some(abs) => (true, abs),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let special = match abs {
+            "inf" => Some(f64::INFINITY),
+            "NaN" => Some(f64::NAN),
+            _ => None,
+        };
+        if let Some(f) = special {
+            return Some(StrictNum::from_f64(if neg { -f } else { f }));
+        }
+        let mantissa = abs.split_once(['e', 'E']).map(|(m, _)| m).unwrap_or(abs);
+        if mantissa.is_empty() || !mantissa.chars().all(|c| c.is_ascii_digit() || c == '.') {
+            return None;
+        }
+        let digits = mantissa.chars().filter(char::is_ascii_digit).skip_while(|c| *c == '0');
+        if digits.count() <= F64_DIGITS {
+            return s.parse::<f64>().ok().map(StrictNum::from_f64);
+        }
+        let f = ieee::Oct::from_str(abs).ok()?;
+        Some(StrictNum::Float(if neg { -f } else { f }))
+    }
+
+    /// Converts the number into `f64`, rounding it to the nearest representable value.
+    pub fn to_f64_lossy(&self) -> f64 {
+        fn words_to_f64(words: &[u64]) -> f64 {
+            words.iter().rev().fold(0.0, |acc, word| acc * 2f64.powi(64) + *word as f64)
+        }
+        match self {
+            StrictNum::Uint(v) => *v as f64,
+            StrictNum::BigUint(v) => words_to_f64(v.as_inner()),
+            StrictNum::Int(v) => *v as f64,
+            StrictNum::BigInt(v) if v.is_negative() => -words_to_f64(v.abs().as_inner()),
+            StrictNum::BigInt(v) => words_to_f64(v.as_inner()),
+            StrictNum::Float(v) => {
+                let double: ieee::Double = v.convert(&mut false).value;
+                f64::from_bits(double.to_bits().low_u64())
+            }
+        }
+    }
+
+    /// Converts the number into a 256-bit float, rounding it to the nearest representable value.
+    fn to_oct(self) -> ieee::Oct {
+        let (neg, val) = match self {
+            StrictNum::Float(f) => return f,
+            int => int.to_parts().expect("integer number"),
+        };
+        let f = val.as_inner().chunks(4).rev().fold(ieee::Oct::ZERO, |acc, words| {
+            let words = u256::from_inner([words[0], words[1], words[2], words[3]]);
+            (acc.scalbn(256) + ieee::Oct::from_u256(words).value).value
+        });
+        if neg {
+            -f
+        } else {
+            f
+        }
+    }
+
     pub fn unwrap_uint<N: TryFrom<u64>>(self) -> N
     where N::Error: Debug {
         let StrictNum::Uint(v) = self else {
@@ -237,6 +488,325 @@ impl StrictNum {
     }
 }
 
+/// Error parsing [`StrictNum`] from a string with a given radix.
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum NumParseError {
+    /// unsupported radix {0}; only 2, 8, 10 and 16 are allowed.
+    UnsupportedRadix(u32),
+
+    /// number string contains no digits.
+    Empty,
+
+    /// invalid digit `{0}` for the number radix.
+    InvalidDigit(char),
+
+    /// number is too large to be represented.
+    Overflow,
+}
+
+/// Sign (`true` for negative numbers) and absolute value of an integer.
+pub(super) type Parts = (bool, u1024);
+
+fn neg_u1024(val: u1024) -> u1024 { (!val).wrapping_add(u1024::ONE) }
+
+fn u1024_to_u128(val: u1024) -> Option<u128> {
+    let words = val.as_inner();
+    if words[2..].iter().any(|word| *word != 0) {
+        return None;
+    }
+    Some(words[0] as u128 | (words[1] as u128) << 64)
+}
+
+/// Adds two integers; returns sign of the result and its absolute value, if it doesn't
+/// overflow.
+fn add_parts((a_neg, a): Parts, (b_neg, b): Parts) -> (bool, Option<u1024>) {
+    if a_neg == b_neg {
+        (a_neg, a.checked_add(b))
+    } else if a >= b {
+        (a_neg && a != b, a.checked_sub(b))
+    } else {
+        (b_neg, b.checked_sub(a))
+    }
+}
+
+fn mul_parts((a_neg, a): Parts, (b_neg, b): Parts) -> (bool, Option<u1024>) {
+    let zero = a == u1024::ZERO || b == u1024::ZERO;
+    (a_neg != b_neg && !zero, a.checked_mul(b))
+}
+
+fn cmp_parts((a_neg, a): Parts, (b_neg, b): Parts) -> Ordering {
+    let a_neg = a_neg && a != u1024::ZERO;
+    let b_neg = b_neg && b != u1024::ZERO;
+    match (a_neg, b_neg) {
+        (false, false) => a.cmp(&b),
+        (true, true) => b.cmp(&a),
+        (neg, _) => {
+            if neg {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            }
+        }
+    }
+}
+
+/// Number of explicitly stored mantissa bits in `ieee::Oct`.
+const OCT_MANTISSA_BITS: i64 = 236;
+/// Exponent bias of `ieee::Oct`.
+const OCT_EXP_BIAS: i64 = 262143;
+
+/// Converts finite float with no fractional part into an integer without loss of precision.
+/// Returns `None` if the value doesn't fit into 1024 bits.
+fn oct_to_parts(f: ieee::Oct) -> Option<Parts> {
+    let bits = f.to_bits();
+    let exp = ((bits >> OCT_MANTISSA_BITS as usize) & u256::from(0x7FFFFu32)).low_u64() as i64;
+    let mantissa = bits & ((u256::ONE << OCT_MANTISSA_BITS as usize) - u256::ONE);
+    let (mantissa, shift) = match exp {
+        0 => (mantissa, 1 - OCT_EXP_BIAS - OCT_MANTISSA_BITS),
+        _ => (
+            mantissa | (u256::ONE << OCT_MANTISSA_BITS as usize),
+            exp - OCT_EXP_BIAS - OCT_MANTISSA_BITS,
+        ),
+    };
+    let mantissa = match shift {
+        0.. => mantissa,
+        ..=-256 => u256::ZERO,
+        _ => mantissa >> shift.unsigned_abs() as usize,
+    };
+    let shift = shift.max(0) as u64;
+    if mantissa != u256::ZERO && 256 - mantissa.leading_zeros() as u64 + shift > 1024 {
+        return None;
+    }
+    let mut words = [0u64; 16];
+    words[..4].copy_from_slice(mantissa.as_inner());
+    Some((f.is_negative(), u1024::from_inner(words) << shift as usize))
+}
+
+/// Maps the float bits into an unsigned key, which ordering follows IEEE 754-2008
+/// `totalOrder` predicate.
+fn oct_order_key(f: ieee::Oct) -> u256 {
+    let bits = f.to_bits();
+    if f.is_negative() {
+        !bits
+    } else {
+        bits ^ (u256::ONE << 255)
+    }
+}
+
+impl PartialOrd for StrictNum {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl Ord for StrictNum {
+    fn cmp(&self, other: &Self) -> Ordering { self.total_cmp(other) }
+}
+
+impl StrictNum {
+    fn is_signed(&self) -> bool { matches!(self, StrictNum::Int(_) | StrictNum::BigInt(_)) }
+
+    fn variant_no(&self) -> u8 {
+        match self {
+            StrictNum::Uint(_) => 0,
+            StrictNum::BigUint(_) => 1,
+            StrictNum::Int(_) => 2,
+            StrictNum::BigInt(_) => 3,
+            StrictNum::Float(_) => 4,
+        }
+    }
+
+    /// Compares integer number with a float without loss of precision.
+    fn cmp_float(&self, f: ieee::Oct) -> Ordering {
+        let int = self.to_parts().expect("integer number");
+        if f.is_nan() || f.is_infinite() {
+            return if f.is_negative() { Ordering::Greater } else { Ordering::Less };
+        }
+        let floor = f.round_to_integral(Round::TowardNegative).value;
+        let Some(floor_parts) = oct_to_parts(floor) else {
+            // Integers never exceed 1024 bits
+            return if f.is_negative() { Ordering::Greater } else { Ordering::Less };
+        };
+        match cmp_parts(int, floor_parts) {
+            Ordering::Equal if floor != f => Ordering::Less,
+            ord => ord,
+        }
+    }
+
+    /// Compares numbers by their value, following IEEE 754-2008 `totalOrder` predicate for
+    /// floats: negative NaNs go before all other numbers and positive NaNs (including the
+    /// default NaN) go after them.
+    ///
+    /// Integers are compared with each other and with floats without loss of precision. Equal
+    /// values represented by different variants are ordered by the variant (unsigned integers
+    /// go first, followed by signed integers and floats), making the ordering consistent with
+    /// the equality.
+    pub fn total_cmp(&self, other: &StrictNum) -> Ordering {
+        match (self, other) {
+            (StrictNum::Float(a), StrictNum::Float(b)) => oct_order_key(*a).cmp(&oct_order_key(*b)),
+            (StrictNum::Float(a), b) => b.cmp_float(*a).reverse(),
+            (a, StrictNum::Float(b)) => a.cmp_float(*b),
+            (a, b) => cmp_parts(
+                a.to_parts().expect("integer number"),
+                b.to_parts().expect("integer number"),
+            ),
+        }
+        .then_with(|| self.variant_no().cmp(&other.variant_no()))
+    }
+
+    /// Returns sign and absolute value for integer numbers and floats with no fractional
+    /// part not exceeding 1024 bits.
+    pub(super) fn to_parts(self) -> Option<Parts> {
+        Some(match self {
+            StrictNum::Uint(v) => (false, u1024::from(v)),
+            StrictNum::BigUint(v) => (false, v),
+            StrictNum::Int(v) => (v < 0, u1024::from(v.unsigned_abs())),
+            StrictNum::BigInt(v) => {
+                let val = u1024::from_inner(*v.as_inner());
+                if v.is_negative() {
+                    (true, neg_u1024(val))
+                } else {
+                    (false, val)
+                }
+            }
+            StrictNum::Float(v) if v.is_finite() && v.is_integer() => return oct_to_parts(v),
+            StrictNum::Float(_) => return None,
+        })
+    }
+
+    /// Constructs the smallest representation of an integer. Non-negative numbers are
+    /// represented with signed variants if `signed` is set and the value fits them.
+    fn from_parts((neg, val): Parts, signed: bool) -> Option<StrictNum> {
+        let small = u1024_to_u128(val);
+        if neg {
+            return match small {
+                Some(v) if v <= 1 << 63 => Some(StrictNum::Int((v as i128).wrapping_neg() as i64)),
+                _ if val <= u1024::ONE << 1023 => {
+                    Some(StrictNum::BigInt(i1024::from_inner(*neg_u1024(val).as_inner())))
+                }
+                _ => None,
+            };
+        }
+        Some(match small {
+            Some(v) if signed && v <= i64::MAX as u128 => StrictNum::Int(v as i64),
+            Some(v) if !signed && v <= u64::MAX as u128 => StrictNum::Uint(v as u64),
+            _ if signed && val < u1024::ONE << 1023 => {
+                StrictNum::BigInt(i1024::from_inner(*val.as_inner()))
+            }
+            _ => StrictNum::BigUint(val),
+        })
+    }
+
+    /// Performs an arithmetic operation, returning either the resulting number or, in case
+    /// of an overflow, sign of the result.
+    fn arithmetic(
+        self,
+        other: StrictNum,
+        int_op: fn(Parts, Parts) -> (bool, Option<u1024>),
+        float_op: fn(ieee::Oct, ieee::Oct) -> ieee::Oct,
+    ) -> Result<StrictNum, bool> {
+        let signed = self.is_signed() || other.is_signed();
+        let (a, b) = match (self, other) {
+            (StrictNum::Float(_), _) | (_, StrictNum::Float(_)) => {
+                return Ok(StrictNum::Float(float_op(self.to_oct(), other.to_oct())));
+            }
+            (a, b) => {
+                (a.to_parts().expect("integer number"), b.to_parts().expect("integer number"))
+            }
+        };
+        let (neg, val) = int_op(a, b);
+        val.and_then(|val| Self::from_parts((neg, val), signed)).ok_or(neg)
+    }
+
+    fn saturate(res: Result<StrictNum, bool>) -> StrictNum {
+        res.unwrap_or_else(|neg| match neg {
+            true => StrictNum::BigInt(i1024::MIN),
+            false => StrictNum::BigUint(u1024::MAX),
+        })
+    }
+
+    /// Adds two numbers, promoting the result into a larger integer variant if required.
+    /// Returns `None` if the result doesn't fit into 1024 bits.
+    ///
+    /// Operations involving floats are performed in 256-bit precision and always succeed.
+    pub fn checked_add(self, other: StrictNum) -> Option<StrictNum> {
+        self.arithmetic(other, add_parts, |a, b| (a + b).value).ok()
+    }
+
+    /// Subtracts `other` from the number, promoting the result into a larger or signed
+    /// integer variant if required. Returns `None` if the result doesn't fit into 1024 bits.
+    pub fn checked_sub(self, other: StrictNum) -> Option<StrictNum> {
+        self.arithmetic(other, |a, (neg, b)| add_parts(a, (!neg, b)), |a, b| (a - b).value).ok()
+    }
+
+    /// Multiplies two numbers, promoting the result into a larger integer variant if
+    /// required. Returns `None` if the result doesn't fit into 1024 bits.
+    pub fn checked_mul(self, other: StrictNum) -> Option<StrictNum> {
+        self.arithmetic(other, mul_parts, |a, b| (a * b).value).ok()
+    }
+
+    /// Adds two numbers, saturating at [`u1024::MAX`] or [`i1024::MIN`] on overflow.
+    pub fn saturating_add(self, other: StrictNum) -> StrictNum {
+        Self::saturate(self.arithmetic(other, add_parts, |a, b| (a + b).value))
+    }
+
+    /// Subtracts `other` from the number, saturating at [`u1024::MAX`] or [`i1024::MIN`]
+    /// on overflow.
+    pub fn saturating_sub(self, other: StrictNum) -> StrictNum {
+        Self::saturate(self.arithmetic(
+            other,
+            |a, (neg, b)| add_parts(a, (!neg, b)),
+            |a, b| (a - b).value,
+        ))
+    }
+
+    /// Converts the number into `u64`, if it is non-negative integer fitting into it.
+    pub fn to_u64(&self) -> Option<u64> { self.to_u128().and_then(|v| v.try_into().ok()) }
+
+    /// Converts the number into `u128`, if it is non-negative integer fitting into it.
+    pub fn to_u128(&self) -> Option<u128> {
+        match self.to_parts()? {
+            (false, val) => u1024_to_u128(val),
+            (true, val) if val == u1024::ZERO => Some(0),
+            (true, _) => None,
+        }
+    }
+
+    /// Converts the number into `i64`, if it is an integer fitting into it.
+    pub fn to_i64(&self) -> Option<i64> {
+        let (neg, val) = self.to_parts()?;
+        let val = u1024_to_u128(val)?;
+        match neg {
+            true if val <= 1 << 63 => Some((val as i128).wrapping_neg() as i64),
+            false => val.try_into().ok(),
+            true => None,
+        }
+    }
+
+    /// Parses integer number written in base 2, 8, 10 or 16, with an optional sign prefix.
+    pub fn from_str_radix(s: &str, radix: u32) -> Result<StrictNum, NumParseError> {
+        if ![2, 8, 10, 16].contains(&radix) {
+            return Err(NumParseError::UnsupportedRadix(radix));
+        }
+        let (neg, digits) = match s.as_bytes().first() {
+            Some(b'-') => (true, &s[1..]),
+            Some(b'+') => (false, &s[1..]),
+            _ => (false, s),
+        };
+        if digits.is_empty() {
+            return Err(NumParseError::Empty);
+        }
+        let mut val = u1024::ZERO;
+        for c in digits.chars() {
+            let digit = c.to_digit(radix).ok_or(NumParseError::InvalidDigit(c))?;
+            val = val
+                .checked_mul(radix as u64)
+                .and_then(|val| val.checked_add(digit as u64))
+                .ok_or(NumParseError::Overflow)?;
+        }
+        Self::from_parts((neg && val != u1024::ZERO, val), neg).ok_or(NumParseError::Overflow)
+    }
+}
+
 /// A tag specifying enum or union variant used in strict value representation.
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Display, From)]
 #[display(inner)]
@@ -281,9 +851,33 @@ impl ToHex for Blob {
     fn to_hex(&self) -> String { self.0.to_hex() }
 }
 
+impl Blob {
+    /// Constructs blob from a hex-encoded string.
+    pub fn from_hex_str(s: &str) -> Result<Blob, amplify::hex::Error> {
+        use amplify::hex::FromHex;
+        Blob::from_hex(s)
+    }
+
+    /// Returns hex-encoded representation of the blob, matching its `Display` output.
+    pub fn to_hex_str(&self) -> String { self.to_hex() }
+
+    /// Constructs blob from a string in the standard base64 encoding with padding.
+    #[cfg(feature = "base64")]
+    pub fn from_base64(s: &str) -> Result<Blob, base64::DecodeError> {
+        use base64::Engine;
+        base64::engine::general_purpose::STANDARD.decode(s).map(Blob)
+    }
+
+    /// Returns representation of the blob in the standard base64 encoding with padding.
+    #[cfg(feature = "base64")]
+    pub fn to_base64(&self) -> String {
+        use base64::Engine;
+        base64::engine::general_purpose::STANDARD.encode(&self.0)
+    }
+}
+
 #[cfg(feature = "serde")]
 mod _serde {
-    use amplify::hex::FromHex;
     use serde::de::Error;
     use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
@@ -304,8 +898,12 @@ mod _serde {
         fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
         where D: Deserializer<'de> {
             if deserializer.is_human_readable() {
-                let hex = String::deserialize(deserializer)?;
-                Blob::from_hex(&hex).map_err(D::Error::custom)
+                // Human-readable formats use hex, but base64 is accepted as well since it is
+                // common for binary data in REST APIs and configuration files
+                let s = String::deserialize(deserializer)?;
+                Blob::from_hex_str(&s)
+                    .or_else(|err| Blob::from_base64(&s).map_err(|_| err))
+                    .map_err(D::Error::custom)
             } else {
                 Vec::<u8>::deserialize(deserializer).map(Blob)
             }
@@ -381,9 +979,8 @@ pub enum StrictVal {
     // TODO: Use confined collection
     Tuple(Vec<StrictVal>),
 
-    // May be used to represent structures.
-    // it is not a hash/tree map since StrictVal doesn't implement Hash
-    // TODO: Create dedicated key type and convert to a HashMap
+    // May be used to represent structures. Keys preserve the order of the map in the encoded
+    // data; since `StrictVal` is `Ord`, they may be collected into a `BTreeMap` when needed.
     Map(Vec<(StrictVal, StrictVal)>),
 }
 
@@ -425,7 +1022,10 @@ impl StrictVal {
         StrictVal::Map(items.into_iter().map(|(n, v)| (n.into(), v.into())).collect())
     }
 
-    pub fn skip_wrapper(&self) -> &StrictVal {
+    pub fn skip_wrapper(&self) -> &StrictVal { self.flatten_wrappers() }
+
+    /// Strips all newtype layers (single-field tuples) from the value.
+    pub fn flatten_wrappers(&self) -> &StrictVal {
         let mut me = self;
         loop {
             match me {
@@ -435,6 +1035,22 @@ impl StrictVal {
         }
     }
 
+    /// Strips all newtype layers (single-field tuples) from the value, taking its ownership.
+    pub fn into_flattened(self) -> StrictVal {
+        let mut me = self;
+        loop {
+            match me {
+                StrictVal::Tuple(mut fields) if fields.len() == 1 => me = fields.remove(0),
+                _ => return me,
+            }
+        }
+    }
+
+    /// Wraps the value into `n` newtype layers (single-field tuples).
+    pub fn wrap_n_times(self, n: usize) -> StrictVal {
+        (0..n).fold(self, |val, _| StrictVal::newtype(val))
+    }
+
     pub fn unwrap_option(&self) -> Option<&StrictVal> {
         let StrictVal::Union(tag, content) = self.skip_wrapper() else {
             panic!("StrictVal expected to be a number but holds non-numeric value `{self}`");
@@ -558,6 +1174,62 @@ impl StrictVal {
             .map(|(_, v)| v)
             .unwrap_or_else(|| panic!("StrictVal map doesn't have key {key}"))
     }
+
+    pub fn try_as_num(&self) -> Option<&StrictNum> {
+        match self.skip_wrapper() {
+            StrictVal::Number(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn try_as_string(&self) -> Option<&str> {
+        match self.skip_wrapper() {
+            StrictVal::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn try_as_bytes(&self) -> Option<&[u8]> {
+        match self.skip_wrapper() {
+            StrictVal::Bytes(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn try_as_tuple(&self, no: u16) -> Option<&StrictVal> {
+        match self.skip_wrapper() {
+            StrictVal::Tuple(v) => v.get(no as usize),
+            _ => None,
+        }
+    }
+
+    pub fn try_as_struct(&self, field: &FieldName) -> Option<&StrictVal> {
+        match self.skip_wrapper() {
+            StrictVal::Struct(v) => v.get(field),
+            _ => None,
+        }
+    }
+
+    pub fn try_as_list(&self) -> Option<&[StrictVal]> {
+        match self.skip_wrapper() {
+            StrictVal::List(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn try_as_set(&self) -> Option<&[StrictVal]> {
+        match self.skip_wrapper() {
+            StrictVal::Set(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn try_as_map(&self) -> Option<&[(StrictVal, StrictVal)]> {
+        match self.skip_wrapper() {
+            StrictVal::Map(v) => Some(v),
+            _ => None,
+        }
+    }
 }
 
 impl<T: Into<StrictVal>> From<Option<T>> for StrictVal {
@@ -571,7 +1243,7 @@ impl<T: Into<StrictVal>> From<Option<T>> for StrictVal {
 
 #[cfg(test)]
 mod test {
-    // use super::*;
+    use super::*;
 
     #[test]
     fn construct() {
@@ -588,4 +1260,281 @@ mod test {
             r#"Struct({FieldName("name"): String("Some name"), FieldName("ticker"): String("TICK"), FieldName("precision"): Number(Uint(8))})"#
         )
     }
+
+    #[test]
+    fn try_as() {
+        let num = svnewtype!(StrictVal::num(5u8));
+        assert_eq!(num.try_as_num(), Some(&StrictNum::Uint(5)));
+        assert_eq!(num.try_as_string(), None);
+        assert_eq!(svstr!("some").try_as_string(), Some("some"));
+        assert_eq!(StrictVal::bytes([1u8, 2]).try_as_bytes(), Some(&[1u8, 2][..]));
+        assert_eq!(svstr!("some").try_as_bytes(), None);
+
+        let tuple = StrictVal::tuple([1u8, 2u8]);
+        assert_eq!(tuple.try_as_tuple(1), Some(&StrictVal::num(2u8)));
+        assert_eq!(tuple.try_as_tuple(2), None);
+        assert_eq!(tuple.try_as_list(), None);
+
+        let strct = ston!(name "Some name", ticker "TICK");
+        assert_eq!(strct.try_as_struct(&fname!("ticker")), Some(&svstr!("TICK")));
+        assert_eq!(strct.try_as_struct(&fname!("precision")), None);
+        assert_eq!(strct.try_as_tuple(0), None);
+
+        let list = svlist!([1u8, 2u8]);
+        assert_eq!(list.try_as_list().map(<[_]>::len), Some(2));
+        assert_eq!(list.try_as_set(), None);
+        assert_eq!(StrictVal::set([1u8]).try_as_set(), Some(&[StrictVal::num(1u8)][..]));
+
+        let map = StrictVal::map([("a", 1u8)]);
+        assert_eq!(map.try_as_map(), Some(&[(svstr!("a"), StrictVal::num(1u8))][..]));
+        assert_eq!(list.try_as_map(), None);
+    }
+
+    #[test]
+    fn wrappers() {
+        let val = StrictVal::Unit.wrap_n_times(3);
+        assert_eq!(val, svnewtype!(svnewtype!(svnewtype!(StrictVal::Unit))));
+        assert_eq!(val.flatten_wrappers(), &StrictVal::Unit);
+        assert_eq!(val.into_flattened(), StrictVal::Unit);
+
+        let strct = ston!(name "Some name", ticker "TICK");
+        for n in 0..4 {
+            let wrapped = strct.clone().wrap_n_times(n);
+            assert_eq!(wrapped.flatten_wrappers(), &strct);
+            assert_eq!(wrapped.into_flattened(), strct);
+        }
+
+        let pair = StrictVal::tuple([1u8, 2u8]).wrap_n_times(2);
+        assert_eq!(pair.flatten_wrappers(), &StrictVal::tuple([1u8, 2u8]));
+    }
+
+    #[test]
+    fn float() {
+        let nums = [
+            StrictNum::from(f64::NEG_INFINITY),
+            StrictNum::from(-1.5f64),
+            StrictNum::from(-0.0f64),
+            StrictNum::from(0.0f64),
+            StrictNum::from(0.1f64),
+            StrictNum::from(1e300f64),
+            StrictNum::from(f64::INFINITY),
+            StrictNum::from(f64::NAN),
+        ];
+        assert!(nums.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(StrictNum::from(f64::NAN), StrictNum::from(f64::NAN));
+        assert!(StrictNum::Int(-1) > StrictNum::from_f64(-2.0));
+
+        assert_eq!(StrictNum::from(0.1f64).to_string(), "1e-1");
+        assert_eq!(StrictNum::from(1.5f32).to_string(), "1.5e0");
+        for num in nums {
+            assert_eq!(StrictNum::from_float_str(&num.to_string()), Some(num));
+        }
+
+        assert_eq!(StrictNum::Uint(3).to_f64_lossy(), 3.0);
+        assert_eq!(StrictNum::Int(-3).to_f64_lossy(), -3.0);
+        assert_eq!(StrictNum::BigUint(u1024::from(5u64)).to_f64_lossy(), 5.0);
+        assert_eq!(StrictNum::BigInt(i1024::from(-5i64)).to_f64_lossy(), -5.0);
+        assert_eq!(StrictNum::from_f64(2.5).to_f64_lossy(), 2.5);
+    }
+
+    fn third() -> StrictNum {
+        let one = ieee::Oct::from_u256(u256::from(1u8)).value;
+        StrictNum::Float((one / ieee::Oct::from_u256(u256::from(3u8)).value).value)
+    }
+
+    #[test]
+    fn float_precision() {
+        let third = third();
+        assert_ne!(third, StrictNum::from_f64(1.0 / 3.0));
+        assert!(third > StrictNum::from_f64(0.333_333_333_333_333_3));
+        assert_eq!(third.to_f64_lossy(), 1.0 / 3.0);
+
+        let s = third.to_string();
+        assert!(s.starts_with("3.33333333333333333"), "{s}");
+        assert!(s.ends_with("e-1"), "{s}");
+        assert_eq!(StrictNum::from_float_str(&s), Some(third));
+        assert_eq!(
+            StrictNum::from_float_str("3.0000000000000000e-1"),
+            Some(StrictNum::from_f64(0.3))
+        );
+        let oct = StrictNum::from_float_str("3.00000000000000000e-1").unwrap();
+        assert_ne!(oct, StrictNum::from_f64(0.3));
+        assert_eq!(oct.to_string(), "3.00000000000000000e-1");
+        assert_eq!(StrictNum::from_float_str("1e"), None);
+        assert_eq!(StrictNum::from_float_str("inf"), Some(StrictNum::from_f64(f64::INFINITY)));
+
+        assert_eq!(
+            third.checked_mul(StrictNum::Uint(3)).unwrap().checked_sub(StrictNum::Uint(1)),
+            Some(StrictNum::from_f64(0.0))
+        );
+        let big = StrictNum::BigUint(u1024::ONE << 1000);
+        assert_eq!(big.checked_add(StrictNum::from_f64(0.0)).unwrap().to_parts(), big.to_parts());
+        let huge = StrictNum::from_float_str("1.00000000000000000e400").unwrap();
+        assert_eq!(huge.to_string(), "1.00000000000000000e400");
+        assert!(StrictNum::BigUint(u1024::MAX) < huge);
+        assert!(
+            StrictNum::BigInt(i1024::MIN) > StrictNum::from_f64(0.0).checked_sub(huge).unwrap()
+        );
+    }
+
+    #[test]
+    fn arithmetic() {
+        let max = StrictNum::Uint(u64::MAX);
+        let big = max.checked_add(StrictNum::Uint(1)).unwrap();
+        assert_eq!(big, StrictNum::BigUint(u1024::from(u64::MAX as u128 + 1)));
+        assert_eq!(big.checked_sub(StrictNum::Uint(1)), Some(max));
+        assert_eq!(StrictNum::Uint(1).checked_sub(StrictNum::Uint(3)), Some(StrictNum::Int(-2)));
+        assert_eq!(StrictNum::Int(-2).checked_add(StrictNum::Uint(5)), Some(StrictNum::Int(3)));
+        assert_eq!(StrictNum::Int(-4).checked_mul(StrictNum::Int(-3)), Some(StrictNum::Int(12)));
+        assert_eq!(StrictNum::Int(-4).checked_mul(StrictNum::Uint(0)), Some(StrictNum::Int(0)));
+
+        let min = StrictNum::Int(i64::MIN);
+        let big_neg = min.checked_sub(StrictNum::Uint(1)).unwrap();
+        assert_eq!(big_neg, StrictNum::BigInt(i1024::from(i64::MIN as i128 - 1)));
+        assert_eq!(big_neg.checked_add(StrictNum::Int(1)), Some(min));
+        assert_eq!(
+            min.checked_mul(StrictNum::Int(-1)),
+            Some(StrictNum::BigInt(i1024::from(1i128 << 63)))
+        );
+
+        let huge = StrictNum::BigUint(u1024::MAX);
+        assert_eq!(huge.checked_add(StrictNum::Uint(1)), None);
+        assert_eq!(huge.checked_mul(StrictNum::Uint(2)), None);
+        assert_eq!(huge.saturating_add(StrictNum::Uint(1)), huge);
+        assert_eq!(
+            StrictNum::BigInt(i1024::MIN).saturating_sub(StrictNum::Uint(1)),
+            StrictNum::BigInt(i1024::MIN)
+        );
+        assert_eq!(StrictNum::Uint(0).saturating_sub(huge), StrictNum::BigInt(i1024::MIN));
+        assert_eq!(StrictNum::Uint(2).saturating_sub(StrictNum::Uint(1)), StrictNum::Uint(1));
+
+        assert_eq!(
+            StrictNum::from_f64(0.5).checked_add(StrictNum::Uint(1)),
+            Some(StrictNum::from_f64(1.5))
+        );
+    }
+
+    #[test]
+    fn conversion() {
+        assert_eq!(StrictNum::Uint(u64::MAX).to_u64(), Some(u64::MAX));
+        assert_eq!(StrictNum::Uint(u64::MAX).to_i64(), None);
+        assert_eq!(StrictNum::Int(-1).to_u64(), None);
+        assert_eq!(StrictNum::Int(-1).to_i64(), Some(-1));
+        assert_eq!(StrictNum::Int(i64::MIN).to_i64(), Some(i64::MIN));
+        assert_eq!(StrictNum::BigUint(u1024::from(u128::MAX)).to_u128(), Some(u128::MAX));
+        assert_eq!(StrictNum::BigUint(u1024::from(u128::MAX)).to_u64(), None);
+        assert_eq!(StrictNum::BigUint(u1024::MAX).to_u128(), None);
+        assert_eq!(StrictNum::BigInt(i1024::from(-5i64)).to_i64(), Some(-5));
+        assert_eq!(StrictNum::BigInt(i1024::from(5i64)).to_u64(), Some(5));
+        assert_eq!(StrictNum::BigInt(i1024::MIN).to_i64(), None);
+        assert_eq!(StrictNum::from_f64(3.0).to_u64(), Some(3));
+        assert_eq!(StrictNum::from_f64(-3.0).to_i64(), Some(-3));
+        assert_eq!(StrictNum::from_f64(3.5).to_u64(), None);
+        assert_eq!(StrictNum::from_f64(f64::NAN).to_u64(), None);
+    }
+
+    #[test]
+    fn from_str_radix() {
+        assert_eq!(StrictNum::from_str_radix("1010", 2), Ok(StrictNum::Uint(10)));
+        assert_eq!(StrictNum::from_str_radix("-17", 8), Ok(StrictNum::Int(-15)));
+        assert_eq!(StrictNum::from_str_radix("+42", 10), Ok(StrictNum::Uint(42)));
+        assert_eq!(StrictNum::from_str_radix("-0", 10), Ok(StrictNum::Int(0)));
+        assert_eq!(StrictNum::from_str_radix("fF", 16), Ok(StrictNum::Uint(255)));
+        assert_eq!(
+            StrictNum::from_str_radix("10000000000000000", 16),
+            Ok(StrictNum::BigUint(u1024::from(1u128 << 64)))
+        );
+        assert_eq!(
+            StrictNum::from_str_radix("-8000000000000001", 16),
+            Ok(StrictNum::BigInt(i1024::from(i64::MIN as i128 - 1)))
+        );
+        assert_eq!(
+            StrictNum::from_str_radix(&"f".repeat(256), 16),
+            Ok(StrictNum::BigUint(u1024::MAX))
+        );
+        assert_eq!(
+            StrictNum::from_str_radix(&format!("-8{}", "0".repeat(255)), 16),
+            Ok(StrictNum::BigInt(i1024::MIN))
+        );
+        assert_eq!(
+            StrictNum::from_str_radix(&format!("-8{}1", "0".repeat(254)), 16),
+            Err(NumParseError::Overflow)
+        );
+        assert_eq!(StrictNum::from_str_radix(&"f".repeat(257), 16), Err(NumParseError::Overflow));
+        assert_eq!(StrictNum::from_str_radix("12", 3), Err(NumParseError::UnsupportedRadix(3)));
+        assert_eq!(StrictNum::from_str_radix("-", 10), Err(NumParseError::Empty));
+        assert_eq!(StrictNum::from_str_radix("102", 2), Err(NumParseError::InvalidDigit('2')));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn float_serde() {
+        let floats = [0.5, -1e-300, f64::MAX, f64::INFINITY, f64::NEG_INFINITY, f64::NAN];
+        for num in floats.map(StrictNum::from_f64).into_iter().chain([third()]) {
+            let json = serde_json::to_string(&num).unwrap();
+            assert_eq!(serde_json::from_str::<StrictNum>(&json).unwrap(), num);
+        }
+        assert_eq!(serde_json::to_string(&StrictNum::from_f64(0.5)).unwrap(), "0.5");
+        assert_eq!(serde_json::to_string(&StrictNum::from_f64(f64::NAN)).unwrap(), r#""+NaN""#);
+        assert_eq!(serde_json::to_string(&third()).unwrap(), format!(r#""{}""#, third()));
+        assert_eq!(serde_json::to_string(&StrictNum::Uint(5)).unwrap(), "5");
+    }
+
+    #[test]
+    #[cfg(feature = "base64")]
+    fn blob_encodings() {
+        let blob = Blob(vec![0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(blob.to_hex_str(), "deadbeef");
+        assert_eq!(Blob::from_hex_str("deadbeef"), Ok(blob.clone()));
+        assert!(Blob::from_hex_str("3q2+7w==").is_err());
+        assert_eq!(blob.to_base64(), "3q2+7w==");
+        assert_eq!(Blob::from_base64("3q2+7w=="), Ok(blob.clone()));
+        assert!(Blob::from_base64("deadbeef!").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn blob_serde() {
+        let blob = Blob(vec![0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(serde_json::to_string(&blob).unwrap(), r#""deadbeef""#);
+        assert_eq!(serde_json::from_str::<Blob>(r#""deadbeef""#).unwrap(), blob);
+        assert_eq!(serde_json::from_str::<Blob>(r#""3q2+7w==""#).unwrap(), blob);
+        assert!(serde_json::from_str::<Blob>(r#""not a blob""#).is_err());
+    }
+
+    #[test]
+    fn total_cmp() {
+        let nums = [
+            StrictNum::from_f64(-f64::NAN),
+            StrictNum::from_f64(f64::NEG_INFINITY),
+            StrictNum::BigInt(i1024::MIN),
+            StrictNum::from_f64(-1e300),
+            StrictNum::Int(i64::MIN),
+            StrictNum::Int(-2),
+            StrictNum::from_f64(-2.0),
+            StrictNum::from_f64(-1.5),
+            StrictNum::Uint(0),
+            StrictNum::Int(0),
+            StrictNum::from_f64(-0.0),
+            StrictNum::from_f64(0.0),
+            StrictNum::from_f64(0.5),
+            StrictNum::Uint(1),
+            StrictNum::Uint(u64::MAX),
+            StrictNum::from_f64(u64::MAX as f64),
+            StrictNum::BigUint(u1024::from(u64::MAX) + u1024::from(2u8)),
+            StrictNum::from_f64(1e300),
+            StrictNum::BigUint(u1024::MAX),
+            StrictNum::from_f64(f64::INFINITY),
+            StrictNum::from_f64(f64::NAN),
+        ];
+        for (i, a) in nums.iter().enumerate() {
+            for (j, b) in nums.iter().enumerate() {
+                assert_eq!(a.total_cmp(b), i.cmp(&j), "{a:?} vs {b:?}");
+            }
+        }
+        let mut sorted = nums;
+        sorted.reverse();
+        sorted.sort();
+        assert_eq!(sorted, nums);
+    }
 }
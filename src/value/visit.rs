@@ -0,0 +1,233 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generic depth-first traversal of strict values.
+
+use encoding::FieldName;
+use indexmap::IndexMap;
+
+use crate::value::{Blob, EnumTag, StrictNum};
+use crate::StrictVal;
+
+/// Result of visiting a value, telling whether the traversal should proceed.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum VisitResult {
+    #[default]
+    Continue,
+    Stop,
+}
+
+/// Visitor called by [`StrictVal::visit`] for each of the value tree nodes.
+///
+/// Composite values (unions, tuples, structs and collections) are visited before their
+/// nested values. All methods do nothing by default.
+pub trait StrictValVisitor {
+    fn visit_unit(&mut self) -> VisitResult { VisitResult::Continue }
+
+    fn visit_num(&mut self, _num: &StrictNum) -> VisitResult { VisitResult::Continue }
+
+    fn visit_string(&mut self, _s: &str) -> VisitResult { VisitResult::Continue }
+
+    fn visit_bytes(&mut self, _bytes: &Blob) -> VisitResult { VisitResult::Continue }
+
+    fn visit_enum(&mut self, _tag: &EnumTag) -> VisitResult { VisitResult::Continue }
+
+    fn visit_union(&mut self, _tag: &EnumTag, _val: &StrictVal) -> VisitResult {
+        VisitResult::Continue
+    }
+
+    fn visit_tuple(&mut self, _fields: &[StrictVal]) -> VisitResult { VisitResult::Continue }
+
+    fn visit_struct(&mut self, _fields: &IndexMap<FieldName, StrictVal>) -> VisitResult {
+        VisitResult::Continue
+    }
+
+    fn visit_list(&mut self, _items: &[StrictVal]) -> VisitResult { VisitResult::Continue }
+
+    fn visit_set(&mut self, _items: &[StrictVal]) -> VisitResult { VisitResult::Continue }
+
+    fn visit_map(&mut self, _items: &[(StrictVal, StrictVal)]) -> VisitResult {
+        VisitResult::Continue
+    }
+}
+
+/// Visitor counting the number of leaf (non-composite) values, including map keys.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct CountingVisitor {
+    pub leaves: usize,
+}
+
+impl CountingVisitor {
+    pub fn new() -> Self { CountingVisitor::default() }
+
+    fn leaf(&mut self) -> VisitResult {
+        self.leaves += 1;
+        VisitResult::Continue
+    }
+}
+
+impl StrictValVisitor for CountingVisitor {
+    fn visit_unit(&mut self) -> VisitResult { self.leaf() }
+    fn visit_num(&mut self, _num: &StrictNum) -> VisitResult { self.leaf() }
+    fn visit_string(&mut self, _s: &str) -> VisitResult { self.leaf() }
+    fn visit_bytes(&mut self, _bytes: &Blob) -> VisitResult { self.leaf() }
+    fn visit_enum(&mut self, _tag: &EnumTag) -> VisitResult { self.leaf() }
+}
+
+impl StrictVal {
+    /// Walks the value tree depth-first, calling the visitor method matching each of the
+    /// visited values. Returns [`VisitResult::Stop`] if the visitor has stopped the traversal.
+    pub fn visit(&self, visitor: &mut dyn StrictValVisitor) -> VisitResult {
+        let res = match self {
+            StrictVal::Unit => visitor.visit_unit(),
+            StrictVal::Number(num) => visitor.visit_num(num),
+            StrictVal::String(s) => visitor.visit_string(s),
+            StrictVal::Bytes(bytes) => visitor.visit_bytes(bytes),
+            StrictVal::Enum(tag) => visitor.visit_enum(tag),
+            StrictVal::Union(tag, val) => visitor.visit_union(tag, val),
+            StrictVal::Tuple(fields) => visitor.visit_tuple(fields),
+            StrictVal::Struct(fields) => visitor.visit_struct(fields),
+            StrictVal::List(items) => visitor.visit_list(items),
+            StrictVal::Set(items) => visitor.visit_set(items),
+            StrictVal::Map(items) => visitor.visit_map(items),
+        };
+        if res == VisitResult::Stop {
+            return res;
+        }
+        let visit_all = |vals: &mut dyn Iterator<Item = &StrictVal>,
+                         visitor: &mut dyn StrictValVisitor| {
+            for val in vals {
+                if val.visit(visitor) == VisitResult::Stop {
+                    return VisitResult::Stop;
+                }
+            }
+            VisitResult::Continue
+        };
+        match self {
+            StrictVal::Union(_, val) => val.visit(visitor),
+            StrictVal::Tuple(items) | StrictVal::List(items) | StrictVal::Set(items) => {
+                visit_all(&mut items.iter(), visitor)
+            }
+            StrictVal::Struct(fields) => visit_all(&mut fields.values(), visitor),
+            StrictVal::Map(items) => {
+                visit_all(&mut items.iter().flat_map(|(key, val)| [key, val]), visitor)
+            }
+            _ => VisitResult::Continue,
+        }
+    }
+
+    /// Walks the value tree depth-first, calling `f` for each of the values before its nested
+    /// values. If `f` returns `false`, values nested into the current one are not visited.
+    pub fn transform(&mut self, mut f: impl FnMut(&mut StrictVal) -> bool) {
+        self.transform_with(&mut f)
+    }
+
+    fn transform_with(&mut self, f: &mut impl FnMut(&mut StrictVal) -> bool) {
+        if !f(self) {
+            return;
+        }
+        match self {
+            StrictVal::Union(_, val) => val.transform_with(f),
+            StrictVal::Tuple(items) | StrictVal::List(items) | StrictVal::Set(items) => {
+                items.iter_mut().for_each(|val| val.transform_with(f))
+            }
+            StrictVal::Struct(fields) => fields.values_mut().for_each(|val| val.transform_with(f)),
+            StrictVal::Map(items) => items.iter_mut().for_each(|(key, val)| {
+                key.transform_with(f);
+                val.transform_with(f);
+            }),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn value() -> StrictVal {
+        ston!(
+            name "Some name",
+            tags svlist!(["a", "b"]),
+            meta StrictVal::map([(1u8, StrictVal::some(2u8)), (3u8, StrictVal::none())]),
+            id StrictVal::bytes([0u8; 4])
+        )
+    }
+
+    #[test]
+    fn count() {
+        let mut counter = CountingVisitor::new();
+        assert_eq!(value().visit(&mut counter), VisitResult::Continue);
+        assert_eq!(counter.leaves, 8);
+    }
+
+    #[test]
+    fn stop() {
+        struct FindStr(Vec<String>);
+        impl StrictValVisitor for FindStr {
+            fn visit_string(&mut self, s: &str) -> VisitResult {
+                self.0.push(s.to_owned());
+                if s == "a" {
+                    VisitResult::Stop
+                } else {
+                    VisitResult::Continue
+                }
+            }
+        }
+
+        let mut visitor = FindStr(vec![]);
+        assert_eq!(value().visit(&mut visitor), VisitResult::Stop);
+        assert_eq!(visitor.0, ["Some name", "a"]);
+    }
+
+    #[test]
+    fn transform() {
+        let mut val = value();
+        val.transform(|val| {
+            if let StrictVal::String(s) = val {
+                *s = s.to_uppercase();
+            }
+            !matches!(val, StrictVal::Map(_))
+        });
+        assert_eq!(
+            val,
+            ston!(
+                name "SOME NAME",
+                tags svlist!(["A", "B"]),
+                meta StrictVal::map([(1u8, StrictVal::some(2u8)), (3u8, StrictVal::none())]),
+                id StrictVal::bytes([0u8; 4])
+            )
+        );
+
+        let mut val = value();
+        val.transform(|val| {
+            if let StrictVal::Number(num) = val {
+                *num = num.checked_add(StrictNum::Uint(1)).unwrap();
+            }
+            true
+        });
+        assert_eq!(
+            val.unwrap_struct("meta"),
+            &StrictVal::map([(2u8, StrictVal::some(3u8)), (4u8, StrictVal::none())])
+        );
+    }
+}
@@ -0,0 +1,522 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! XML representation of strict values.
+//!
+//! Each value is represented by an element named after the structure field or union variant
+//! holding it, `item` for tuple, list and set items, `entry` for map entries, or the root tag.
+//! The kind of the value is given by the `type` attribute, which is omitted only for the unit
+//! value, represented by an empty element. Numbers, strings, byte strings (in base64) and enum
+//! variant names (or tags) are kept as the element text. Union elements contain a single element
+//! named after the variant (or a `tag` element with the variant tag in `ord` attribute); struct
+//! elements contain an element per field. Map entry elements hold the value, with the key put
+//! into `key` and `key-type` attributes; non-scalar keys are written as `key` and `value` child
+//! elements of the entry instead.
+
+use std::fmt::Write;
+use std::str::FromStr;
+
+use encoding::{FieldName, VariantName};
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use crate::value::{Blob, EnumTag, StrictNum};
+use crate::StrictVal;
+
+const ATTR_TYPE: &str = "type";
+const ATTR_KEY: &str = "key";
+const ATTR_KEY_TYPE: &str = "key-type";
+const ATTR_ORD: &str = "ord";
+
+/// Maximal nesting depth of elements in XML documents accepted by [`StrictVal::from_xml`].
+pub const XML_MAX_DEPTH: usize = 128;
+
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum XmlError {
+    /// invalid XML data: {0}.
+    Parse(String),
+
+    /// XML document contains no elements.
+    NoRoot,
+
+    /// XML document contains more than one root element.
+    MultipleRoots,
+
+    /// element `{0}` has unknown value type `{1}`.
+    UnknownType(String, String),
+
+    /// content of element `{0}` doesn't match its value type.
+    InvalidContent(String),
+
+    /// invalid field or variant name `{0}`.
+    InvalidName(String),
+
+    /// elements are nested deeper than {0} levels.
+    TooDeep(usize),
+}
+
+fn kind(val: &StrictVal) -> Option<&'static str> {
+    Some(match val {
+        StrictVal::Unit => return None,
+        StrictVal::Number(_) => "number",
+        StrictVal::String(_) => "string",
+        StrictVal::Bytes(_) => "bytes",
+        StrictVal::Enum(_) => "enum",
+        StrictVal::Union(_, _) => "union",
+        StrictVal::Tuple(_) => "tuple",
+        StrictVal::List(_) => "list",
+        StrictVal::Set(_) => "set",
+        StrictVal::Struct(_) => "struct",
+        StrictVal::Map(_) => "map",
+    })
+}
+
+fn scalar_text(val: &StrictVal) -> Option<String> {
+    match val {
+        StrictVal::Unit => Some(s!("")),
+        // Big integers are written as sign and hex absolute value, since they are displayed in
+        // two's complement form
+        StrictVal::Number(num @ (StrictNum::BigUint(_) | StrictNum::BigInt(_))) => {
+            let (neg, val) = num.to_parts().expect("integer number");
+            Some(format!("{}{val}", if neg { "-" } else { "" }))
+        }
+        StrictVal::Number(num) => Some(num.to_string()),
+        StrictVal::String(s) => Some(s.clone()),
+        StrictVal::Bytes(blob) => Some(blob.to_base64()),
+        StrictVal::Enum(tag) => Some(tag.to_string()),
+        _ => None,
+    }
+}
+
+fn write_element(xml: &mut String, name: &str, attrs: &str, val: &StrictVal) {
+    write!(xml, "<{name}{attrs}").expect("writing to string");
+    if let Some(kind) = kind(val) {
+        write!(xml, " {ATTR_TYPE}=\"{kind}\"").expect("writing to string");
+    }
+    if let Some(text) = scalar_text(val) {
+        if text.is_empty() {
+            xml.push_str("/>");
+        } else {
+            write!(xml, ">{}</{name}>", escape(text.as_str())).expect("writing to string");
+        }
+        return;
+    }
+    xml.push('>');
+    match val {
+        StrictVal::Union(EnumTag::Name(vname), val) => write_element(xml, vname.as_str(), "", val),
+        StrictVal::Union(EnumTag::Ord(ord), val) => {
+            write_element(xml, "tag", &format!(" {ATTR_ORD}=\"{ord}\""), val)
+        }
+        StrictVal::Tuple(items) | StrictVal::List(items) | StrictVal::Set(items) => {
+            for item in items {
+                write_element(xml, "item", "", item);
+            }
+        }
+        StrictVal::Struct(fields) => {
+            for (fname, val) in fields {
+                write_element(xml, fname.as_str(), "", val);
+            }
+        }
+        StrictVal::Map(entries) => {
+            for (key, val) in entries {
+                match scalar_text(key) {
+                    Some(text) => {
+                        let mut attrs = format!(" {ATTR_KEY}=\"{}\"", escape(text.as_str()));
+                        if let Some(kind) = kind(key) {
+                            write!(attrs, " {ATTR_KEY_TYPE}=\"{kind}\"")
+                                .expect("writing to string");
+                        }
+                        write_element(xml, "entry", &attrs, val);
+                    }
+                    None => {
+                        xml.push_str("<entry>");
+                        write_element(xml, "key", "", key);
+                        write_element(xml, "value", "", val);
+                        xml.push_str("</entry>");
+                    }
+                }
+            }
+        }
+        _ => unreachable!("scalar values are written as element text"),
+    }
+    write!(xml, "</{name}>").expect("writing to string");
+}
+
+/// XML element parsed from the document.
+struct Element {
+    name: String,
+    attrs: Vec<(String, String)>,
+    text: String,
+    children: Vec<Element>,
+}
+
+impl Element {
+    fn with(start: &BytesStart) -> Result<Self, XmlError> {
+        let parse_err = |err: quick_xml::Error| XmlError::Parse(err.to_string());
+        let name = String::from_utf8_lossy(start.name().as_ref()).into_owned();
+        let mut attrs = vec![];
+        for attr in start.attributes() {
+            let attr = attr.map_err(|err| XmlError::Parse(err.to_string()))?;
+            let key = String::from_utf8_lossy(attr.key.as_ref()).into_owned();
+            attrs.push((key, attr.unescape_value().map_err(parse_err)?.into_owned()));
+        }
+        Ok(Element {
+            name,
+            attrs,
+            text: none!(),
+            children: none!(),
+        })
+    }
+
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs.iter().find(|(key, _)| key == name).map(|(_, val)| val.as_str())
+    }
+
+    fn invalid(&self) -> XmlError { XmlError::InvalidContent(self.name.clone()) }
+
+    fn child(&self, name: &str) -> Result<&Element, XmlError> {
+        self.children.iter().find(|child| child.name == name).ok_or_else(|| self.invalid())
+    }
+
+    fn to_val(&self) -> Result<StrictVal, XmlError> {
+        let Some(kind) = self.attr(ATTR_TYPE) else {
+            if !self.children.is_empty() || !self.text.trim().is_empty() {
+                return Err(self.invalid());
+            }
+            return Ok(StrictVal::Unit);
+        };
+        if let Some(val) = self.scalar(kind, &self.text)? {
+            if !self.children.is_empty() {
+                return Err(self.invalid());
+            }
+            return Ok(val);
+        }
+        if !matches!(kind, "union" | "tuple" | "list" | "set" | "struct" | "map") {
+            return Err(XmlError::UnknownType(self.name.clone(), kind.to_owned()));
+        }
+        if !self.text.trim().is_empty() {
+            return Err(self.invalid());
+        }
+        let items = || self.children.iter().map(Element::to_val).collect::<Result<_, _>>();
+        Ok(match kind {
+            "union" => {
+                let [variant] = self.children.as_slice() else {
+                    return Err(self.invalid());
+                };
+                let tag = match variant.attr(ATTR_ORD) {
+                    Some(ord) if variant.name == "tag" => {
+                        EnumTag::Ord(ord.parse().map_err(|_| variant.invalid())?)
+                    }
+                    _ => EnumTag::Name(
+                        VariantName::try_from(variant.name.clone())
+                            .map_err(|_| XmlError::InvalidName(variant.name.clone()))?,
+                    ),
+                };
+                StrictVal::Union(tag, Box::new(variant.to_val()?))
+            }
+            "tuple" => StrictVal::Tuple(items()?),
+            "list" => StrictVal::List(items()?),
+            "set" => StrictVal::Set(items()?),
+            "struct" => StrictVal::Struct(
+                self.children
+                    .iter()
+                    .map(|field| {
+                        let fname = FieldName::try_from(field.name.clone())
+                            .map_err(|_| XmlError::InvalidName(field.name.clone()))?;
+                        Ok((fname, field.to_val()?))
+                    })
+                    .collect::<Result<_, XmlError>>()?,
+            ),
+            "map" => StrictVal::Map(
+                self.children
+                    .iter()
+                    .map(|entry| match entry.attr(ATTR_KEY) {
+                        Some(key) => {
+                            let key = match entry.attr(ATTR_KEY_TYPE) {
+                                None if key.is_empty() => StrictVal::Unit,
+                                None => return Err(entry.invalid()),
+                                Some(kind) => {
+                                    entry.scalar(kind, key)?.ok_or_else(|| entry.invalid())?
+                                }
+                            };
+                            Ok((key, entry.to_val()?))
+                        }
+                        None => {
+                            Ok((entry.child("key")?.to_val()?, entry.child("value")?.to_val()?))
+                        }
+                    })
+                    .collect::<Result<_, XmlError>>()?,
+            ),
+            _ => unreachable!("value kind is checked above"),
+        })
+    }
+
+    /// Parses text of a scalar value; returns `None` for compound value kinds.
+    fn scalar(&self, kind: &str, text: &str) -> Result<Option<StrictVal>, XmlError> {
+        Ok(Some(match kind {
+            "number" => {
+                let text = text.trim();
+                let (sign, digits) = text.strip_prefix('-').map(|d| ("-", d)).unwrap_or(("", text));
+                let num = match digits.strip_prefix("0x") {
+                    Some(hex) => StrictNum::from_str_radix(&format!("{sign}{hex}"), 16).ok(),
+                    None => StrictNum::from_str_radix(text, 10)
+                        .ok()
+                        .or_else(|| StrictNum::from_float_str(text)),
+                };
+                StrictVal::Number(num.ok_or_else(|| self.invalid())?)
+            }
+            "string" => StrictVal::String(text.to_owned()),
+            "bytes" => {
+                StrictVal::Bytes(Blob::from_base64(text.trim()).map_err(|_| self.invalid())?)
+            }
+            "enum" => {
+                let text = text.trim();
+                match u8::from_str(text) {
+                    Ok(ord) => StrictVal::Enum(EnumTag::Ord(ord)),
+                    Err(_) => StrictVal::Enum(EnumTag::Name(
+                        VariantName::try_from(text.to_owned())
+                            .map_err(|_| XmlError::InvalidName(text.to_owned()))?,
+                    )),
+                }
+            }
+            _ => return Ok(None),
+        }))
+    }
+}
+
+fn parse_tree(xml: &str) -> Result<Element, XmlError> {
+    let mut reader = Reader::from_str(xml);
+    let mut stack = Vec::<Element>::new();
+    let mut root = None;
+    let mut complete = |stack: &mut Vec<Element>, elem: Element| match stack.last_mut() {
+        Some(parent) => {
+            parent.children.push(elem);
+            Ok(())
+        }
+        None if root.is_some() => Err(XmlError::MultipleRoots),
+        None => {
+            root = Some(elem);
+            Ok(())
+        }
+    };
+    loop {
+        let event = reader.read_event().map_err(|err| XmlError::Parse(err.to_string()))?;
+        if matches!(event, Event::Start(_) | Event::Empty(_)) && stack.len() >= XML_MAX_DEPTH {
+            return Err(XmlError::TooDeep(XML_MAX_DEPTH));
+        }
+        match event {
+            Event::Start(start) => stack.push(Element::with(&start)?),
+            Event::Empty(start) => complete(&mut stack, Element::with(&start)?)?,
+            Event::End(end) => {
+                let elem = stack.pop().ok_or_else(|| {
+                    XmlError::Parse(format!(
+                        "unexpected closing tag `{}`",
+                        String::from_utf8_lossy(end.name().as_ref())
+                    ))
+                })?;
+                complete(&mut stack, elem)?;
+            }
+            Event::Text(text) => {
+                let text = text.unescape().map_err(|err| XmlError::Parse(err.to_string()))?;
+                match stack.last_mut() {
+                    Some(elem) => elem.text.push_str(&text),
+                    None if text.trim().is_empty() => {}
+                    None => return Err(XmlError::Parse(s!("text outside of the root element"))),
+                }
+            }
+            Event::CData(data) => {
+                let data = data.decode().map_err(|err| XmlError::Parse(err.to_string()))?;
+                let elem = stack.last_mut().ok_or_else(|| {
+                    XmlError::Parse(s!("character data outside of the root element"))
+                })?;
+                elem.text.push_str(&data);
+            }
+            Event::Eof => break,
+            // Declarations, comments and processing instructions carry no data
+            _ => {}
+        }
+    }
+    if let Some(elem) = stack.pop() {
+        return Err(XmlError::Parse(format!("element `{}` is not closed", elem.name)));
+    }
+    root.ok_or(XmlError::NoRoot)
+}
+
+impl StrictVal {
+    /// Represents the value as an XML element named `root_tag`.
+    pub fn to_xml(&self, root_tag: &str) -> String {
+        let mut xml = String::new();
+        write_element(&mut xml, root_tag, "", self);
+        xml
+    }
+
+    /// Parses the value from its XML representation produced by [`StrictVal::to_xml`]. The name
+    /// of the root element is ignored. Documents with elements nested deeper than
+    /// [`XML_MAX_DEPTH`] levels are rejected.
+    pub fn from_xml(xml: &str) -> Result<StrictVal, XmlError> { parse_tree(xml)?.to_val() }
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::num::{i1024, u1024};
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn variants() {
+        let vals = [
+            (StrictVal::Unit, "<v/>"),
+            (StrictVal::num(10u8), r#"<v type="number">10</v>"#),
+            (StrictVal::num(-10i8), r#"<v type="number">-10</v>"#),
+            (StrictVal::num(1.5f64), r#"<v type="number">1.5e0</v>"#),
+            (StrictVal::str("a<b"), r#"<v type="string">a&lt;b</v>"#),
+            (StrictVal::str(""), r#"<v type="string"/>"#),
+            (StrictVal::bytes([1u8, 2]), r#"<v type="bytes">AQI=</v>"#),
+            (StrictVal::enumer("a"), r#"<v type="enum">a</v>"#),
+            (StrictVal::enumer(1), r#"<v type="enum">1</v>"#),
+            (StrictVal::union("some", 1u8), r#"<v type="union"><some type="number">1</some></v>"#),
+            (StrictVal::union(2, ()), r#"<v type="union"><tag ord="2"/></v>"#),
+            (
+                StrictVal::list([1u8, 2]),
+                r#"<v type="list"><item type="number">1</item><item type="number">2</item></v>"#,
+            ),
+            (StrictVal::tuple([()]), r#"<v type="tuple"><item/></v>"#),
+            (
+                ston!(a 1u8, b "x"),
+                r#"<v type="struct"><a type="number">1</a><b type="string">x</b></v>"#,
+            ),
+            (
+                StrictVal::map([("k", 2u8)]),
+                r#"<v type="map"><entry key="k" key-type="string" type="number">2</entry></v>"#,
+            ),
+            (
+                StrictVal::Map(vec![(StrictVal::tuple([1u8]), StrictVal::Unit)]),
+                r#"<v type="map"><entry><key type="tuple"><item type="number">1</item></key><value/></entry></v>"#,
+            ),
+        ];
+        for (val, xml) in vals {
+            assert_eq!(val.to_xml("v"), xml, "{val}");
+            assert_eq!(StrictVal::from_xml(xml), Ok(val));
+        }
+    }
+
+    #[test]
+    fn parse() {
+        let xml = r#"<?xml version="1.0"?>
+            <!-- comment -->
+            <value type="struct">
+                <name type="string"><![CDATA[a <b>]]></name>
+                <items type="set">
+                    <item type="number"> 5 </item>
+                </items>
+            </value>
+        "#;
+        assert_eq!(
+            StrictVal::from_xml(xml),
+            Ok(StrictVal::struc([
+                ("name", StrictVal::str("a <b>")),
+                ("items", StrictVal::Set(vec![StrictVal::num(5u8)]))
+            ]))
+        );
+
+        assert_eq!(StrictVal::from_xml(""), Err(XmlError::NoRoot));
+        assert_eq!(StrictVal::from_xml("<a/><b/>"), Err(XmlError::MultipleRoots));
+        assert!(matches!(StrictVal::from_xml("<a>"), Err(XmlError::Parse(_))));
+        assert!(matches!(StrictVal::from_xml("<a></b>"), Err(XmlError::Parse(_))));
+        assert_eq!(
+            StrictVal::from_xml(r#"<a type="float">1</a>"#),
+            Err(XmlError::UnknownType(s!("a"), s!("float")))
+        );
+        assert_eq!(
+            StrictVal::from_xml(r#"<a type="number">x</a>"#),
+            Err(XmlError::InvalidContent(s!("a")))
+        );
+        assert_eq!(StrictVal::from_xml("<a>text</a>"), Err(XmlError::InvalidContent(s!("a"))));
+        assert_eq!(
+            StrictVal::from_xml(r#"<a type="enum">-x</a>"#),
+            Err(XmlError::InvalidName(s!("-x")))
+        );
+    }
+
+    #[test]
+    fn too_deep() {
+        let nested = |depth: usize| {
+            let open = r#"<item type="list">"#.repeat(depth - 1);
+            let close = "</item>".repeat(depth - 1);
+            format!(r#"{open}<item type="list"/>{close}"#)
+        };
+        assert!(StrictVal::from_xml(&nested(XML_MAX_DEPTH)).is_ok());
+        assert_eq!(
+            StrictVal::from_xml(&nested(XML_MAX_DEPTH + 1)),
+            Err(XmlError::TooDeep(XML_MAX_DEPTH))
+        );
+        assert_eq!(StrictVal::from_xml(&nested(100_000)), Err(XmlError::TooDeep(XML_MAX_DEPTH)));
+    }
+
+    fn value() -> impl Strategy<Value = StrictVal> {
+        let leaf = prop_oneof![
+            Just(StrictVal::Unit),
+            any::<u64>().prop_map(StrictVal::num),
+            (i64::MIN..0).prop_map(StrictVal::num),
+            any::<u128>()
+                .prop_filter("bignum", |v| *v > u64::MAX as u128)
+                .prop_map(|v| StrictVal::Number(StrictNum::BigUint(u1024::from(v)))),
+            any::<i128>()
+                .prop_filter("bignum", |v| *v < i64::MIN as i128)
+                .prop_map(|v| StrictVal::Number(StrictNum::BigInt(i1024::from(v)))),
+            any::<f64>().prop_filter("not NaN", |v| !v.is_nan()).prop_map(StrictVal::num),
+            "[a-zA-Z0-9 _.,<>&'\"-]{0,8}".prop_map(StrictVal::String),
+            vec(any::<u8>(), 0..8).prop_map(|v| StrictVal::Bytes(Blob(v))),
+            "[a-z][a-zA-Z0-9]{0,6}"
+                .prop_map(|s| StrictVal::enumer(VariantName::try_from(s).unwrap())),
+            any::<u8>().prop_map(StrictVal::enumer),
+        ];
+        leaf.prop_recursive(3, 32, 4, |inner| {
+            prop_oneof![
+                vec(inner.clone(), 0..4).prop_map(StrictVal::List),
+                vec(inner.clone(), 0..4).prop_map(StrictVal::Set),
+                vec(inner.clone(), 0..4).prop_map(StrictVal::Tuple),
+                (any::<u8>(), inner.clone()).prop_map(|(tag, val)| StrictVal::union(tag, val)),
+                vec(("[a-z][a-zA-Z0-9]{0,6}", inner.clone()), 0..4).prop_map(|fields| {
+                    StrictVal::Struct(
+                        fields
+                            .into_iter()
+                            .map(|(name, val)| (FieldName::try_from(name).unwrap(), val))
+                            .collect(),
+                    )
+                }),
+                vec((inner.clone(), inner), 0..4).prop_map(StrictVal::Map),
+            ]
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn roundtrip(val in value()) {
+            prop_assert_eq!(StrictVal::from_xml(&val.to_xml("value")), Ok(val));
+        }
+    }
+}